@@ -0,0 +1,339 @@
+//! Human-in-the-loop approval for dangerous tool calls.
+//!
+//! An `ApprovalPolicy` flags calls by tool name, argument pattern or a write
+//! outside the workspace. The runtime pauses on a flagged call, emits
+//! `StreamEvent::ApprovalRequired` and waits here until the chat frontend
+//! calls `resolve` (inline keyboard / Discord button) or the timeout passes.
+//! Unanswered requests are denied. Only the turn's own user, in the turn's
+//! chat, can answer its requests.
+
+use crate::tools::diff::{self, Change};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tokio_util::sync::CancellationToken;
+
+/// Which calls need approval
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApprovalPolicy {
+    pub rules: Vec<ApprovalRule>,
+    /// Seconds to wait for a decision before denying (0 = default of 120)
+    pub timeout_secs: u64,
+    /// Who may answer this turn's requests; set per turn, never from config
+    #[serde(skip)]
+    pub requester: Requester,
+}
+
+/// The user a turn runs for and the chat it runs in (Telegram or Discord ids)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Requester {
+    pub user_id: String,
+    pub chat_id: String,
+}
+
+impl Requester {
+    pub fn new(user_id: impl ToString, chat_id: impl ToString) -> Self {
+        Self { user_id: user_id.to_string(), chat_id: chat_id.to_string() }
+    }
+}
+
+/// One approval rule; a call must match every field that is set
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApprovalRule {
+    /// Tool name, or "*" for any tool
+    pub tool: String,
+    /// Case-insensitive substring of the call's arguments, e.g. "git push"
+    pub matches: Option<String>,
+    /// Only when the call writes outside the workspace: its `path` argument,
+    /// or any file its `diff` touches
+    pub outside_workspace: bool,
+}
+
+/// A paused call waiting for the user
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApprovalRequest {
+    pub id: String,
+    pub tool: String,
+    pub args_summary: String,
+    /// Why the call was flagged, for the prompt shown to the user
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApprovalDecision {
+    Approved,
+    Denied,
+    TimedOut,
+    /// Nobody can answer (no chat listening, or the turn was cancelled)
+    Unavailable,
+}
+
+impl ApprovalDecision {
+    /// Note recorded in the call's tool result (and so in session history)
+    pub fn note(&self, timeout: Duration) -> String {
+        match self {
+            ApprovalDecision::Approved => "Approved by the user.".to_string(),
+            ApprovalDecision::Denied => "Denied by the user — the call was not executed.".to_string(),
+            ApprovalDecision::TimedOut => format!(
+                "No approval within {}s — the call was not executed.",
+                timeout.as_secs()
+            ),
+            ApprovalDecision::Unavailable => {
+                "This call needs user approval, which is not available here — it was not executed.".to_string()
+            }
+        }
+    }
+}
+
+impl ApprovalPolicy {
+    /// This policy with requests answerable only by `user_id` in `chat_id`
+    pub fn with_requester(mut self, user_id: impl ToString, chat_id: impl ToString) -> Self {
+        self.requester = Requester::new(user_id, chat_id);
+        self
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(if self.timeout_secs == 0 { 120 } else { self.timeout_secs })
+    }
+
+    /// Reason the call needs approval, if any rule matches
+    pub fn check(&self, tool: &str, args: &serde_json::Value, workspace_dir: &str) -> Option<String> {
+        self.rules.iter().find_map(|rule| rule.check(tool, args, workspace_dir))
+    }
+}
+
+impl ApprovalRule {
+    fn check(&self, tool: &str, args: &serde_json::Value, workspace_dir: &str) -> Option<String> {
+        if self.tool != "*" && self.tool != tool {
+            return None;
+        }
+        let mut reasons = Vec::new();
+        if let Some(ref pattern) = self.matches {
+            if !args.to_string().to_lowercase().contains(&pattern.to_lowercase()) {
+                return None;
+            }
+            reasons.push(format!("matches '{}'", pattern));
+        }
+        if self.outside_workspace {
+            match target_paths(args) {
+                Some(paths) => {
+                    let outside: Vec<String> =
+                        paths.into_iter().filter(|p| is_outside(workspace_dir, p)).collect();
+                    if outside.is_empty() {
+                        return None;
+                    }
+                    reasons.extend(outside.iter().map(|p| format!("{} is outside the workspace", p)));
+                }
+                None => reasons.push("the files its diff touches could not be determined".to_string()),
+            }
+        }
+        if reasons.is_empty() {
+            Some(format!("{} always needs approval", tool))
+        } else {
+            Some(reasons.join(", "))
+        }
+    }
+}
+
+/// Files a call writes: every file its `diff` touches (which takes precedence,
+/// as in the patch tool), else its `path`. None when the diff cannot be parsed.
+fn target_paths(args: &serde_json::Value) -> Option<Vec<String>> {
+    if let Some(diff_text) = args.get("diff").and_then(|v| v.as_str()) {
+        let patches = diff::parse(diff_text).ok()?;
+        let mut paths = Vec::new();
+        for patch in patches {
+            if let Change::Update { move_to: Some(to), .. } = patch.change {
+                paths.push(to);
+            }
+            paths.push(patch.path);
+        }
+        return Some(paths);
+    }
+    Some(args.get("path").and_then(|v| v.as_str()).map(str::to_string).into_iter().collect())
+}
+
+/// Whether `path` (absolute or relative to the workspace) leaves the workspace,
+/// compared lexically since the target may not exist yet
+fn is_outside(workspace_dir: &str, path: &str) -> bool {
+    let mut resolved = PathBuf::new();
+    for part in Path::new(workspace_dir).join(path).components() {
+        match part {
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            Component::CurDir => {}
+            other => resolved.push(other),
+        }
+    }
+    !resolved.starts_with(workspace_dir)
+}
+
+/// A call waiting for a decision
+struct Pending {
+    requester: Requester,
+    tx: oneshot::Sender<bool>,
+}
+
+/// Calls waiting for a decision, by request id
+fn pending() -> &'static Mutex<HashMap<String, Pending>> {
+    static PENDING: OnceLock<Mutex<HashMap<String, Pending>>> = OnceLock::new();
+    PENDING.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Outcome of a button press
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    Answered,
+    /// Timed out, already answered or never existed
+    Expired,
+    /// Pressed by someone other than the requester, or in another chat; still pending
+    NotRequester,
+}
+
+/// Open a request answerable by `requester`; the returned receiver yields the decision
+pub fn register(
+    tool: &str,
+    args_summary: &str,
+    reason: &str,
+    requester: &Requester,
+) -> (ApprovalRequest, oneshot::Receiver<bool>) {
+    let (tx, rx) = oneshot::channel();
+    // Short enough for Telegram's 64-byte callback data
+    let id = uuid::Uuid::new_v4().simple().to_string()[..12].to_string();
+    pending().lock().unwrap().insert(id.clone(), Pending { requester: requester.clone(), tx });
+    let request = ApprovalRequest {
+        id,
+        tool: tool.to_string(),
+        args_summary: args_summary.to_string(),
+        reason: reason.to_string(),
+    };
+    (request, rx)
+}
+
+/// Answer a pending request on behalf of `by`; only its requester can
+pub fn resolve(id: &str, approved: bool, by: &Requester) -> Resolution {
+    let mut pending = pending().lock().unwrap();
+    match pending.get(id) {
+        Some(p) if p.requester != *by => return Resolution::NotRequester,
+        Some(_) => {}
+        None => return Resolution::Expired,
+    }
+    match pending.remove(id).map(|p| p.tx.send(approved)) {
+        Some(Ok(())) => Resolution::Answered,
+        _ => Resolution::Expired,
+    }
+}
+
+/// Deny a pending request without a user (e.g. the prompt could not be shown)
+pub fn cancel(id: &str) {
+    if let Some(p) = pending().lock().unwrap().remove(id) {
+        let _ = p.tx.send(false);
+    }
+}
+
+pub fn is_pending(id: &str) -> bool {
+    pending().lock().unwrap().contains_key(id)
+}
+
+/// Wait for the user's decision on `id`, denying after `timeout` or on cancellation
+pub async fn wait(
+    id: &str,
+    rx: oneshot::Receiver<bool>,
+    timeout: Duration,
+    cancel_token: Option<&CancellationToken>,
+) -> ApprovalDecision {
+    let cancelled = async {
+        match cancel_token {
+            Some(ct) => ct.cancelled().await,
+            None => std::future::pending().await,
+        }
+    };
+    let decision = tokio::select! {
+        answer = tokio::time::timeout(timeout, rx) => match answer {
+            Ok(Ok(true)) => ApprovalDecision::Approved,
+            Ok(Ok(false)) => ApprovalDecision::Denied,
+            Ok(Err(_)) => ApprovalDecision::Unavailable,
+            Err(_) => ApprovalDecision::TimedOut,
+        },
+        _ = cancelled => ApprovalDecision::Unavailable,
+    };
+    pending().lock().unwrap().remove(id);
+    decision
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn policy() -> ApprovalPolicy {
+        serde_json::from_value(json!({
+            "rules": [
+                { "tool": "exec", "matches": "git push" },
+                { "tool": "write", "outside_workspace": true },
+                { "tool": "patch", "outside_workspace": true },
+                { "tool": "delete_everything" }
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_policy_matches() {
+        let policy = policy();
+        let ws = "/home/u/.openclaw/workspace";
+        assert!(policy.check("exec", &json!({"command": "git push origin main"}), ws).is_some());
+        assert!(policy.check("exec", &json!({"command": "GIT PUSH -f"}), ws).is_some());
+        assert!(policy.check("exec", &json!({"command": "git status"}), ws).is_none());
+        assert!(policy.check("write", &json!({"path": "notes/todo.md"}), ws).is_none());
+        assert!(policy.check("write", &json!({"path": "../../.bashrc"}), ws).is_some());
+        assert!(policy.check("write", &json!({"path": "/tmp/x"}), ws).is_some());
+        assert!(policy.check("delete_everything", &json!({}), ws).is_some());
+        assert!(policy.check("read", &json!({"path": "/etc/hosts"}), ws).is_none());
+
+        // Diff-mode patches carry their targets in the diff, not in `path`
+        let inside = "--- a/notes.md\n+++ b/notes.md\n@@ -1 +1 @@\n-old\n+new\n";
+        assert!(policy.check("patch", &json!({"diff": inside}), ws).is_none());
+        let outside = format!("{}--- /tmp/evil.sh\n+++ /tmp/evil.sh\n@@ -1 +1 @@\n-old\n+new\n", inside);
+        let reason = policy.check("patch", &json!({"path": "notes.md", "diff": outside}), ws).unwrap();
+        assert_eq!(reason, "/tmp/evil.sh is outside the workspace");
+        let moved = "*** Begin Patch\n*** Update File: notes.md\n*** Move to: ../../.bashrc\n@@\n-old\n+new\n*** End Patch\n";
+        let reason = policy.check("patch", &json!({"diff": moved}), ws).unwrap();
+        assert_eq!(reason, "../../.bashrc is outside the workspace");
+        assert!(policy.check("patch", &json!({"diff": "not a diff"}), ws).is_some());
+        assert_eq!(policy.timeout(), Duration::from_secs(120));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_and_timeout() {
+        let owner = Requester::new(42, -100);
+        let (req, rx) = register("exec", "git push", "matches 'git push'", &owner);
+        assert!(is_pending(&req.id));
+        assert_eq!(resolve(&req.id, true, &owner), Resolution::Answered);
+        assert_eq!(resolve(&req.id, false, &owner), Resolution::Expired); // already answered
+        assert_eq!(wait(&req.id, rx, Duration::from_secs(1), None).await, ApprovalDecision::Approved);
+
+        let (req, rx) = register("exec", "git push", "matches 'git push'", &owner);
+        let decision = wait(&req.id, rx, Duration::from_millis(20), None).await;
+        assert_eq!(decision, ApprovalDecision::TimedOut);
+        assert!(!is_pending(&req.id));
+    }
+
+    #[tokio::test]
+    async fn test_only_requester_can_resolve() {
+        let owner = Requester::new(42, -100);
+        let (req, rx) = register("exec", "git push", "matches 'git push'", &owner);
+        assert_eq!(resolve(&req.id, true, &Requester::new(7, -100)), Resolution::NotRequester);
+        assert_eq!(resolve(&req.id, true, &Requester::new(42, 42)), Resolution::NotRequester);
+        assert!(is_pending(&req.id));
+
+        cancel(&req.id);
+        assert!(!is_pending(&req.id));
+        assert_eq!(wait(&req.id, rx, Duration::from_secs(1), None).await, ApprovalDecision::Denied);
+    }
+}
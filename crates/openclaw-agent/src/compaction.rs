@@ -0,0 +1,236 @@
+//! Context compaction: summarize old history instead of dropping it.
+//!
+//! When a session's unsummarized history outgrows the budget in
+//! `agents.defaults.compaction`, the oldest span is folded into a rolling
+//! summary (stored on the session row in Postgres) and only the recent tail
+//! is sent verbatim. Later compactions merge the previous summary with the
+//! newly dropped span, so the summary is updated incrementally.
+
+use anyhow::Result;
+use openclaw_core::config::{AgentDefaults, CompactionConfig};
+use tracing::warn;
+
+use crate::llm::{Completion, LlmProvider, Message, Role, UsageStats};
+use crate::tokenizer::TokenCounter;
+
+/// Max characters of any one message quoted to the summarizer
+const MAX_QUOTED_CHARS: usize = 600;
+
+/// Heading for the summary when it is appended to the system prompt
+pub const SUMMARY_HEADING: &str = "## Earlier in this conversation\n\
+(Summary of older messages that are no longer shown verbatim.)";
+
+/// How much history a turn may carry verbatim
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HistoryBudget {
+    pub max_messages: usize,
+    pub max_tokens: usize,
+}
+
+/// Compaction settings for a turn, read from openclaw-manual.json
+pub struct Settings {
+    pub config: CompactionConfig,
+    /// Budget sized to the turn's model
+    pub budget: HistoryBudget,
+    /// The configured summarizer, if any (otherwise the turn's own provider)
+    pub summarizer: Option<Box<dyn LlmProvider>>,
+}
+
+/// Load compaction settings, sizing the history budget to `model`'s context window
+pub fn load_settings(model: &str) -> Settings {
+    let manual = crate::llm::fallback::load_manual_config().ok();
+    let config = manual
+        .as_ref()
+        .and_then(|c| c.pointer("/agents/defaults"))
+        .and_then(|d| serde_json::from_value::<AgentDefaults>(d.clone()).ok())
+        .unwrap_or_default()
+        .compaction_config();
+    let window = manual
+        .as_ref()
+        .and_then(|c| openclaw_core::models::context_window(c, model));
+    let budget = HistoryBudget {
+        max_messages: config.max_history_messages,
+        max_tokens: config.history_tokens(window),
+    };
+    let summarizer = config.model.as_deref().zip(manual.as_ref()).and_then(|(spec, manual)| {
+        crate::llm::fallback::build_spec_provider(manual, spec)
+            .map_err(|e| warn!("Compaction model unavailable, using the turn's provider: {}", e))
+            .ok()
+    });
+    Settings { config, budget, summarizer }
+}
+
+impl Settings {
+    /// Provider that writes summaries: the configured summarizer, else the
+    /// turn's provider without a recording wrapper, so cassettes hold only the turn
+    pub fn summarizer_for<'a>(&'a self, provider: &'a dyn LlmProvider) -> &'a dyn LlmProvider {
+        self.summarizer
+            .as_deref()
+            .or_else(|| provider.unrecorded())
+            .unwrap_or(provider)
+    }
+}
+
+/// Whether the unsummarized history exceeds the budget
+pub fn over_budget(messages: &[Message], budget: &HistoryBudget, counter: &dyn TokenCounter) -> bool {
+    messages.len() > budget.max_messages || counter.count_messages(messages) > budget.max_tokens
+}
+
+/// Index that splits `messages` into a span to summarize and a tail to keep.
+/// The tail gets half the budget, so the next few turns don't compact again,
+/// and starts on a user message where possible so no tool exchange is split.
+pub fn split_point(messages: &[Message], budget: &HistoryBudget, counter: &dyn TokenCounter) -> usize {
+    let max_messages = budget.max_messages / 2;
+    let mut tokens_left = budget.max_tokens / 2;
+    let mut cut = messages.len();
+    while cut > 0 && messages.len() - cut < max_messages {
+        let tokens = counter.count_message(&messages[cut - 1]);
+        if tokens > tokens_left {
+            break;
+        }
+        tokens_left -= tokens;
+        cut -= 1;
+    }
+
+    if let Some(offset) = messages[cut..].iter().position(|m| matches!(m.role, Role::User)) {
+        return cut + offset;
+    }
+    // One long tool exchange: at least don't start on orphaned tool results
+    while cut < messages.len() && matches!(messages[cut].role, Role::Tool) {
+        cut += 1;
+    }
+    cut
+}
+
+/// Render messages as a plain transcript for the summarizer
+pub fn transcript(messages: &[Message]) -> String {
+    let quote = |text: &str| {
+        let text = text.trim();
+        if text.chars().count() > MAX_QUOTED_CHARS {
+            let head: String = text.chars().take(MAX_QUOTED_CHARS).collect();
+            format!("{}…", head)
+        } else {
+            text.to_string()
+        }
+    };
+
+    let mut out = String::new();
+    for msg in messages {
+        let content = msg.content.as_deref().unwrap_or("");
+        let line = match msg.role {
+            Role::System => continue,
+            Role::User => format!("User: {}", quote(content)),
+            Role::Assistant => match msg.tool_calls {
+                Some(ref calls) => {
+                    let names: Vec<String> = calls
+                        .iter()
+                        .map(|c| format!("{}({})", c.function.name, quote(&c.function.arguments)))
+                        .collect();
+                    format!("Assistant called: {}", names.join(", "))
+                }
+                None => format!("Assistant: {}", quote(content)),
+            },
+            Role::Tool => format!("Tool result: {}", quote(content)),
+        };
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out
+}
+
+/// Ask `provider` to merge `previous` (if any) with `span` into an updated summary
+pub async fn summarize(
+    provider: &dyn LlmProvider,
+    previous: Option<&str>,
+    span: &[Message],
+    config: &CompactionConfig,
+) -> Result<(String, UsageStats)> {
+    let instructions = format!(
+        "You maintain the running summary of a long conversation between a user and an AI \
+         assistant. Merge the earlier summary (if any) with the new messages into one updated \
+         summary. Keep decisions, facts about the user, open tasks, names, file paths, commands \
+         and their outcomes; drop greetings and small talk. Write concise bullet points, at most \
+         {} words. Reply with the summary only.",
+        config.summary_max_words
+    );
+    let request = format!(
+        "Earlier summary:\n{}\n\nNew messages:\n{}",
+        previous.unwrap_or("(none)"),
+        transcript(span)
+    );
+
+    let (completion, usage) = provider
+        .complete(&[Message::system(&instructions), Message::user(&request)], &[])
+        .await?;
+    match completion {
+        Completion::Text { content, .. } if !content.trim().is_empty() => Ok((content.trim().to_string(), usage)),
+        Completion::Text { .. } => anyhow::bail!("summarizer returned an empty summary"),
+        Completion::ToolCalls { .. } => anyhow::bail!("summarizer answered with tool calls"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::{FunctionCall, ToolCall};
+    use crate::tokenizer::HeuristicCounter;
+
+    fn budget(max_messages: usize, max_tokens: usize) -> HistoryBudget {
+        HistoryBudget { max_messages, max_tokens }
+    }
+
+    fn exchange(i: usize) -> Vec<Message> {
+        vec![Message::user(&format!("question {}", i)), Message::assistant(&format!("answer {}", i))]
+    }
+
+    #[test]
+    fn test_over_budget() {
+        let msgs: Vec<Message> = (0..5).flat_map(exchange).collect();
+        assert!(!over_budget(&msgs, &budget(10, 12000), &HeuristicCounter));
+        assert!(over_budget(&msgs, &budget(8, 12000), &HeuristicCounter));
+        assert!(over_budget(&[Message::user(&"x".repeat(4000))], &budget(40, 500), &HeuristicCounter));
+    }
+
+    #[test]
+    fn test_split_keeps_half_budget_from_a_user_message() {
+        let msgs: Vec<Message> = (0..10).flat_map(exchange).collect();
+        let cut = split_point(&msgs, &budget(10, 12000), &HeuristicCounter);
+        // Half of 10 messages is 5; the tail is rounded forward to the next user message
+        assert_eq!(cut, 16);
+        assert!(matches!(msgs[cut].role, Role::User));
+    }
+
+    #[test]
+    fn test_split_skips_orphaned_tool_results() {
+        let call = ToolCall {
+            id: "1".into(),
+            call_type: "function".into(),
+            function: FunctionCall { name: "exec".into(), arguments: "{}".into() },
+        };
+        let mut msgs = vec![Message::user("run the build"), Message::assistant_tool_calls(vec![call], None)];
+        msgs.extend((0..6).map(|i| Message::tool_result("1", &format!("line {}", i))));
+        // The tail would start mid-exchange, so the whole exchange is summarized
+        assert_eq!(split_point(&msgs, &budget(4, 12000), &HeuristicCounter), msgs.len());
+    }
+
+    #[test]
+    fn test_transcript() {
+        let call = ToolCall {
+            id: "1".into(),
+            call_type: "function".into(),
+            function: FunctionCall { name: "read".into(), arguments: r#"{"path":"notes.md"}"#.into() },
+        };
+        let msgs = vec![
+            Message::system("ignored"),
+            Message::user("what's in my notes?"),
+            Message::assistant_tool_calls(vec![call], None),
+            Message::tool_result("1", &"n".repeat(1000)),
+            Message::assistant("Your notes list three tasks."),
+        ];
+        let text = transcript(&msgs);
+        assert!(text.starts_with("User: what's in my notes?\n"));
+        assert!(text.contains("Assistant called: read({\"path\":\"notes.md\"})"));
+        assert!(text.contains(&format!("Tool result: {}…", "n".repeat(MAX_QUOTED_CHARS))));
+        assert!(!text.contains("ignored"));
+    }
+}
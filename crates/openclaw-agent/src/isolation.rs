@@ -0,0 +1,282 @@
+//! Isolation backends for the shell commands of `exec` and `process`.
+//!
+//! With `ExecBackend::Namespace` a command runs in fresh Linux user, mount,
+//! pid, ipc and uts namespaces (plus a network namespace with only loopback
+//! when `SandboxPolicy.network_allowed` is false). The workspace and the
+//! policy's `write_allow` dirs stay writable, every other mount is read-only
+//! and `/tmp` is private. bubblewrap (`bwrap`) is used when installed,
+//! otherwise util-linux `unshare` with a small mount script; the script runs
+//! as root of the new user namespace, so it drops every capability with
+//! `setpriv` before the command starts (else the command could simply remount
+//! `/` read-write). If neither backend is available the command is refused
+//! rather than run on the host.
+
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+use crate::sandbox::{ExecBackend, ExecLimits, SandboxPolicy};
+
+/// Mount setup run inside `unshare` before the command.
+/// Arguments: private-tmp flag (1/0), the `setpriv` path, the command, then the
+/// writable dirs. The final `cd` re-enters the working directory through the
+/// new bind mount; the command runs without capabilities.
+const UNSHARE_SETUP: &str = r#"set -e
+private_tmp="$1"; setpriv="$2"; cmd="$3"; shift 3
+if [ "$private_tmp" = 1 ]; then mount -t tmpfs -o mode=1777 tmpfs /tmp; fi
+for d in "$@"; do mount --bind "$d" "$d"; done
+writable=" $* "
+if [ "$private_tmp" = 1 ]; then writable="$writable/tmp "; fi
+mount -o remount,bind,ro /
+while read -r _ _ _ _ mp _; do
+  case "$writable" in *" $mp "*) continue ;; esac
+  mount -o remount,bind,ro "$mp" 2>/dev/null || true
+done < /proc/self/mountinfo
+cd "$(pwd)"
+set +e
+exec "$setpriv" --inh-caps=-all --bounding-set=-all --no-new-privs sh -c "$cmd""#;
+
+/// `sh -c command` in the workspace, isolated and limited as the policy says
+pub fn shell_command(policy: &SandboxPolicy, command: &str, workspace_dir: &str) -> Result<Command> {
+    let mut cmd = match policy.exec_backend {
+        ExecBackend::Host => {
+            let mut cmd = Command::new("sh");
+            cmd.arg("-c").arg(command);
+            cmd
+        }
+        ExecBackend::Namespace => namespace_command(policy, command, workspace_dir)?,
+    };
+    cmd.current_dir(workspace_dir);
+    apply_limits(&mut cmd, &policy.exec_limits);
+    Ok(cmd)
+}
+
+fn namespace_command(policy: &SandboxPolicy, command: &str, workspace_dir: &str) -> Result<Command> {
+    if !cfg!(target_os = "linux") {
+        anyhow::bail!("Namespace isolation is only available on Linux");
+    }
+    let writable = writable_dirs(policy, workspace_dir);
+    // Writable dirs under /tmp would be hidden by a private /tmp, so keep the host's
+    let private_tmp = !writable.iter().any(|d| d.starts_with("/tmp"));
+    let mut writable: Vec<String> = writable.iter().map(|d| d.to_string_lossy().to_string()).collect();
+    if !private_tmp && !writable.iter().any(|d| d == "/tmp") {
+        writable.push("/tmp".to_string());
+    }
+
+    if let Some(bwrap) = find_program("bwrap") {
+        let mut cmd = Command::new(bwrap);
+        cmd.args(bwrap_args(policy, &writable, private_tmp, workspace_dir)).arg("sh").arg("-c").arg(command);
+        return Ok(cmd);
+    }
+    if let (Some(unshare), Some(setpriv)) = (find_program("unshare"), find_program("setpriv")) {
+        let mut cmd = Command::new(unshare);
+        cmd.args(unshare_args(policy))
+            .args(["sh", "-c", UNSHARE_SETUP, "openclaw-sandbox"])
+            .arg(if private_tmp { "1" } else { "0" })
+            .arg(setpriv)
+            .arg(command)
+            .args(&writable);
+        return Ok(cmd);
+    }
+    anyhow::bail!(
+        "Namespace isolation needs bubblewrap (bwrap) or util-linux unshare and setpriv on PATH; \
+         refusing to run on the host"
+    )
+}
+
+/// The workspace plus existing `write_allow` dirs, canonical and without duplicates
+fn writable_dirs(policy: &SandboxPolicy, workspace_dir: &str) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    for dir in std::iter::once(workspace_dir).chain(policy.write_allow.iter().map(String::as_str)) {
+        if let Ok(dir) = Path::new(dir).canonicalize() {
+            if !dirs.contains(&dir) {
+                dirs.push(dir);
+            }
+        }
+    }
+    dirs
+}
+
+fn bwrap_args(policy: &SandboxPolicy, writable: &[String], private_tmp: bool, workspace_dir: &str) -> Vec<String> {
+    let mut args: Vec<String> = [
+        "--die-with-parent",
+        "--new-session",
+        "--unshare-user",
+        "--unshare-pid",
+        "--unshare-ipc",
+        "--unshare-uts",
+        "--unshare-cgroup-try",
+        "--ro-bind",
+        "/",
+        "/",
+        "--dev",
+        "/dev",
+        "--proc",
+        "/proc",
+    ]
+    .iter()
+    .map(|a| a.to_string())
+    .collect();
+    if !policy.network_allowed {
+        args.push("--unshare-net".to_string());
+    }
+    if private_tmp {
+        args.extend(["--tmpfs".to_string(), "/tmp".to_string()]);
+    }
+    for dir in writable {
+        args.extend(["--bind".to_string(), dir.clone(), dir.clone()]);
+    }
+    args.extend(["--chdir".to_string(), workspace_dir.to_string()]);
+    args
+}
+
+fn unshare_args(policy: &SandboxPolicy) -> Vec<String> {
+    let mut args: Vec<String> = ["--map-root-user", "--mount", "--pid", "--ipc", "--uts", "--kill-child", "--mount-proc"]
+        .iter()
+        .map(|a| a.to_string())
+        .collect();
+    if !policy.network_allowed {
+        args.push("--net".to_string());
+    }
+    args
+}
+
+/// Resolve a program name on PATH
+pub(crate) fn find_program(name: &str) -> Option<PathBuf> {
+    std::env::var_os("PATH").and_then(|paths| {
+        std::env::split_paths(&paths)
+            .map(|dir| dir.join(name))
+            .find(|path| path.is_file())
+    })
+}
+
+/// Set rlimits in the child before it execs; they carry over into the sandbox
+fn apply_limits(cmd: &mut Command, limits: &ExecLimits) {
+    if *limits == ExecLimits::default() {
+        return;
+    }
+    #[cfg(target_os = "linux")]
+    {
+        let settings: Vec<_> = [
+            (libc::RLIMIT_CPU, limits.cpu_secs),
+            (libc::RLIMIT_AS, limits.memory_mb.map(|mb| mb.saturating_mul(1024 * 1024))),
+            (libc::RLIMIT_NPROC, limits.max_pids),
+        ]
+        .into_iter()
+        .filter_map(|(resource, value)| value.map(|v| (resource, v)))
+        .collect();
+        // SAFETY: the closure only calls setrlimit, which is async-signal-safe,
+        // and allocates nothing between fork and exec
+        unsafe {
+            cmd.pre_exec(move || {
+                for &(resource, value) in &settings {
+                    let limit = libc::rlimit { rlim_cur: value, rlim_max: value };
+                    if libc::setrlimit(resource, &limit) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                }
+                Ok(())
+            });
+        }
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = cmd;
+        tracing::warn!("exec_limits are only applied on Linux");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn namespace_policy(network_allowed: bool) -> SandboxPolicy {
+        SandboxPolicy { exec_backend: ExecBackend::Namespace, network_allowed, ..Default::default() }
+    }
+
+    /// Scratch dir outside the workspace for probe files. Not under /tmp, which
+    /// stays writable in the sandbox when the workspace lives there.
+    fn outside_dir() -> tempfile::TempDir {
+        tempfile::Builder::new().prefix(".isolation-probe-").tempdir_in(env!("CARGO_MANIFEST_DIR")).unwrap()
+    }
+
+    /// Whether unprivileged namespaces work on this machine (they don't in many CI containers)
+    fn namespaces_available() -> bool {
+        find_program("bwrap").is_some()
+            || std::process::Command::new("unshare")
+                .args(["--map-root-user", "--mount", "true"])
+                .status()
+                .is_ok_and(|s| s.success())
+    }
+
+    #[test]
+    fn test_bwrap_args() {
+        let writable = vec!["/home/me/ws".to_string()];
+        let args = bwrap_args(&namespace_policy(false), &writable, true, "/home/me/ws");
+        let joined = args.join(" ");
+        assert!(joined.contains("--ro-bind / /"));
+        assert!(joined.contains("--unshare-net"));
+        assert!(joined.contains("--tmpfs /tmp"));
+        assert!(joined.contains("--bind /home/me/ws /home/me/ws"));
+        assert!(joined.ends_with("--chdir /home/me/ws"));
+
+        let online = bwrap_args(&namespace_policy(true), &writable, false, "/home/me/ws");
+        assert!(!online.iter().any(|a| a == "--unshare-net" || a == "--tmpfs"));
+        assert!(unshare_args(&namespace_policy(false)).contains(&"--net".to_string()));
+        assert!(!unshare_args(&namespace_policy(true)).contains(&"--net".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_host_backend_applies_limits() {
+        let policy = SandboxPolicy {
+            exec_limits: ExecLimits { cpu_secs: Some(7), ..Default::default() },
+            ..Default::default()
+        };
+        let output = shell_command(&policy, "ulimit -t", "/tmp").unwrap().output().await.unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "7");
+    }
+
+    #[tokio::test]
+    async fn test_namespace_backend_confines_writes() {
+        if !namespaces_available() {
+            eprintln!("skipping: user namespaces unavailable");
+            return;
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let ws = dir.path().to_str().unwrap();
+        let probe = outside_dir();
+        let outside = probe.path().join("escaped");
+        let script = format!(
+            "echo inside > ok.txt && touch '{}' 2>/dev/null; echo pid=$$",
+            outside.display()
+        );
+        let output = shell_command(&namespace_policy(false), &script, ws).unwrap().output().await.unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+
+        assert_eq!(std::fs::read_to_string(dir.path().join("ok.txt")).unwrap(), "inside\n");
+        assert!(!outside.exists(), "write outside the workspace escaped the sandbox");
+        // A fresh pid namespace: the shell is one of its first processes
+        let pid: u32 = stdout.trim().trim_start_matches("pid=").parse().unwrap();
+        assert!(pid < 10, "expected a new pid namespace, got pid {}", pid);
+    }
+
+    #[tokio::test]
+    async fn test_namespace_backend_cannot_remount_root() {
+        if !namespaces_available() {
+            eprintln!("skipping: user namespaces unavailable");
+            return;
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let ws = dir.path().to_str().unwrap();
+        let probe = outside_dir();
+        let outside = probe.path().join("escaped");
+        let script = format!(
+            "mount -o remount,bind,rw / 2>/dev/null && echo remounted; touch '{}' 2>/dev/null; true",
+            outside.display()
+        );
+        let output = shell_command(&namespace_policy(false), &script, ws).unwrap().output().await.unwrap();
+
+        assert!(!String::from_utf8_lossy(&output.stdout).contains("remounted"), "the sandbox could remount /");
+        assert!(!outside.exists(), "write outside the workspace escaped the sandbox");
+    }
+}
@@ -1,9 +1,16 @@
+pub mod approval;
+pub mod compaction;
+pub mod isolation;
 pub mod llm;
 pub mod llm_log;
 pub mod loop_detection;
+pub mod memory;
+pub mod replay;
 pub mod runtime;
 pub mod sandbox;
 pub mod subagent;
+pub mod tokenizer;
+pub mod tool_policy;
 pub mod tools;
 pub mod watchdog;
 pub mod workspace;
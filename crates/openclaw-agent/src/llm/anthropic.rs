@@ -1,21 +1,35 @@
 use anyhow::{Context, Result};
 use async_trait::async_trait;
 use futures_util::StreamExt;
+use openclaw_core::models::GenerationParams;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
 use tokio::sync::mpsc;
 use tracing::debug;
 
 use super::streaming::StreamEvent;
+use super::structured;
 use super::{Completion, FunctionCall, LlmProvider, Message, Role, ToolCall, ToolDefinition, UsageStats};
 
 const ANTHROPIC_API_URL: &str = "https://api.anthropic.com/v1/messages";
 const ANTHROPIC_VERSION: &str = "2023-06-01";
+/// Max remembered thinking blocks (keyed by tool call ID) before the cache is reset
+const MAX_THINKING_CACHE: usize = 256;
 
 pub struct AnthropicProvider {
     client: reqwest::Client,
     api_key: String,
     model: String,
     max_tokens: u32,
+    /// Extended thinking budget (None = thinking disabled)
+    thinking_budget: Option<u32>,
+    /// Whether to place cache_control breakpoints on the system prompt and tools
+    prompt_caching: bool,
+    params: GenerationParams,
+    /// Signed thinking blocks from tool-use responses, keyed by the first tool call ID.
+    /// Anthropic requires these to be echoed back verbatim with the tool results.
+    thinking_blocks: Mutex<HashMap<String, Vec<ContentBlock>>>,
 }
 
 impl AnthropicProvider {
@@ -25,13 +39,133 @@ impl AnthropicProvider {
             api_key: api_key.to_string(),
             model: model.to_string(),
             max_tokens: 4096,
+            thinking_budget: None,
+            prompt_caching: true,
+            params: GenerationParams::default(),
+            thinking_blocks: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Apply per-model generation params. `reasoningEffort` enables extended thinking
+    /// with a matching budget unless an explicit budget was already set.
+    pub fn with_params(mut self, params: GenerationParams) -> Self {
+        if let Some(max_tokens) = params.max_tokens {
+            self.max_tokens = max_tokens;
+        }
+        if self.thinking_budget.is_none() {
+            self.thinking_budget = params.reasoning_effort.as_deref().and_then(thinking_budget_for_effort);
+        }
+        self.params = params;
+        self
+    }
+
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = max_tokens;
+        self
+    }
+
+    /// Enable extended thinking with the given token budget.
+    /// `max_tokens` is raised above the budget if needed (the API requires budget < max_tokens).
+    pub fn with_thinking(mut self, budget_tokens: u32) -> Self {
+        self.thinking_budget = Some(budget_tokens);
+        self
+    }
+
+    pub fn with_prompt_caching(mut self, enabled: bool) -> Self {
+        self.prompt_caching = enabled;
+        self
+    }
+
+    fn build_request(&self, messages: &[Message], tools: &[ToolDefinition], stream: bool) -> AnthropicRequest {
+        let (system, anthropic_msgs) = {
+            let cache = self.thinking_blocks.lock().unwrap();
+            convert_messages(messages, &cache)
+        };
+
+        let mut max_tokens = self.max_tokens;
+        let thinking = self.thinking_budget.map(|budget| {
+            if budget >= max_tokens {
+                max_tokens = budget + self.max_tokens;
+            }
+            ThinkingConfig {
+                config_type: "enabled".to_string(),
+                budget_tokens: budget,
+            }
+        });
+
+        // Extended thinking rejects a custom temperature
+        let temperature = if thinking.is_some() { None } else { self.params.temperature };
+
+        AnthropicRequest {
+            model: self.model.clone(),
+            max_tokens,
+            messages: anthropic_msgs,
+            system: system.map(|s| build_system_blocks(&s, self.prompt_caching)),
+            tools: convert_tools(tools, self.prompt_caching),
+            tool_choice: None,
+            thinking,
+            temperature,
+            top_p: self.params.top_p,
+            stop_sequences: self.params.stop.clone(),
+            stream: if stream { Some(true) } else { None },
+            extra: self.params.extra.clone(),
+        }
+    }
+
+    /// Request forcing a single `structured_output` tool call shaped by `schema`
+    fn build_structured_request(&self, messages: &[Message], schema: &serde_json::Value) -> AnthropicRequest {
+        let tool = ToolDefinition {
+            tool_type: "function".to_string(),
+            function: super::FunctionDefinition {
+                name: structured::STRUCTURED_TOOL_NAME.to_string(),
+                description: "Return the final answer as structured data matching the input schema."
+                    .to_string(),
+                parameters: schema.clone(),
+            },
+        };
+        let mut request = self.build_request(messages, std::slice::from_ref(&tool), false);
+        // Forced tool use is not allowed together with extended thinking
+        request.thinking = None;
+        request.temperature = self.params.temperature;
+        request.tool_choice = Some(serde_json::json!({
+            "type": "tool",
+            "name": structured::STRUCTURED_TOOL_NAME,
+        }));
+        request
+    }
+
+    /// Remember the signed thinking blocks behind a tool-use response so the next
+    /// round (assistant tool calls + tool results) can replay them.
+    fn remember_thinking(&self, calls: &[ToolCall], blocks: Vec<ContentBlock>) {
+        let Some(first) = calls.first() else { return };
+        if blocks.is_empty() {
+            return;
+        }
+        if let Ok(mut cache) = self.thinking_blocks.lock() {
+            if cache.len() >= MAX_THINKING_CACHE {
+                cache.clear();
+            }
+            cache.insert(first.id.clone(), blocks);
         }
     }
 }
 
+/// Map an OpenAI-style reasoning effort onto an extended thinking budget
+pub(super) fn thinking_budget_for_effort(effort: &str) -> Option<u32> {
+    match effort {
+        "low" => Some(1024),
+        "medium" => Some(4096),
+        "high" => Some(16384),
+        _ => None,
+    }
+}
+
 // ── Convert internal messages to Anthropic format ──
 
-fn convert_messages(messages: &[Message]) -> (Option<String>, Vec<AnthropicMessage>) {
+fn convert_messages(
+    messages: &[Message],
+    thinking_blocks: &HashMap<String, Vec<ContentBlock>>,
+) -> (Option<String>, Vec<AnthropicMessage>) {
     let mut system_prompt = None;
     let mut anthropic_msgs = Vec::new();
 
@@ -76,8 +210,13 @@ fn convert_messages(messages: &[Message]) -> (Option<String>, Vec<AnthropicMessa
             }
             Role::Assistant => {
                 if let Some(ref tool_calls) = msg.tool_calls {
-                    // Assistant message with tool_use blocks
-                    let mut blocks: Vec<ContentBlock> = Vec::new();
+                    // Assistant message with tool_use blocks, preceded by any
+                    // signed thinking blocks that produced them
+                    let mut blocks: Vec<ContentBlock> = tool_calls
+                        .first()
+                        .and_then(|tc| thinking_blocks.get(&tc.id))
+                        .cloned()
+                        .unwrap_or_default();
                     if let Some(ref content) = msg.content {
                         if !content.is_empty() {
                             blocks.push(ContentBlock::Text { text: content.clone() });
@@ -109,7 +248,7 @@ fn convert_messages(messages: &[Message]) -> (Option<String>, Vec<AnthropicMessa
                 let content = msg.content.clone().unwrap_or_default();
                 let block = ContentBlock::ToolResult {
                     tool_use_id: tool_call_id,
-                    content: content,
+                    content,
                 };
                 anthropic_msgs.push(AnthropicMessage {
                     role: "user".to_string(),
@@ -122,15 +261,47 @@ fn convert_messages(messages: &[Message]) -> (Option<String>, Vec<AnthropicMessa
     (system_prompt, anthropic_msgs)
 }
 
-fn convert_tools(tools: &[ToolDefinition]) -> Vec<AnthropicTool> {
-    tools
+/// Convert tool definitions. With caching enabled, the last tool carries a cache
+/// breakpoint so the whole (static) tool list is cached as one prefix.
+fn convert_tools(tools: &[ToolDefinition], caching: bool) -> Vec<AnthropicTool> {
+    let mut converted: Vec<AnthropicTool> = tools
         .iter()
         .map(|t| AnthropicTool {
             name: t.function.name.clone(),
             description: t.function.description.clone(),
             input_schema: t.function.parameters.clone(),
+            cache_control: None,
         })
-        .collect()
+        .collect();
+    if caching {
+        if let Some(last) = converted.last_mut() {
+            last.cache_control = Some(CacheControl::ephemeral());
+        }
+    }
+    converted
+}
+
+/// Split the system prompt into a cacheable base and the per-request runtime
+/// section (current date/time), which would otherwise invalidate the cache every minute.
+fn build_system_blocks(system: &str, caching: bool) -> Vec<SystemBlock> {
+    let (base, runtime) = match system.find(crate::workspace::RUNTIME_MARKER) {
+        Some(idx) if idx > 0 => (system[..idx].trim_end(), Some(&system[idx..])),
+        _ => (system, None),
+    };
+
+    let mut blocks = vec![SystemBlock {
+        block_type: "text".to_string(),
+        text: base.to_string(),
+        cache_control: if caching { Some(CacheControl::ephemeral()) } else { None },
+    }];
+    if let Some(runtime) = runtime {
+        blocks.push(SystemBlock {
+            block_type: "text".to_string(),
+            text: runtime.to_string(),
+            cache_control: None,
+        });
+    }
+    blocks
 }
 
 // ── Anthropic API types ──
@@ -141,11 +312,54 @@ struct AnthropicRequest {
     max_tokens: u32,
     messages: Vec<AnthropicMessage>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    system: Option<String>,
+    system: Option<Vec<SystemBlock>>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     tools: Vec<AnthropicTool>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thinking: Option<ThinkingConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop_sequences: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     stream: Option<bool>,
+    /// Provider-specific fields from `params.extra` (e.g. `top_k`, `metadata`)
+    #[serde(flatten)]
+    extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Serialize)]
+struct ThinkingConfig {
+    #[serde(rename = "type")]
+    config_type: String,
+    budget_tokens: u32,
+}
+
+#[derive(Serialize, Clone)]
+struct CacheControl {
+    #[serde(rename = "type")]
+    cache_type: String,
+}
+
+impl CacheControl {
+    fn ephemeral() -> Self {
+        Self {
+            cache_type: "ephemeral".to_string(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct SystemBlock {
+    #[serde(rename = "type")]
+    block_type: String,
+    text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cache_control: Option<CacheControl>,
 }
 
 #[derive(Serialize)]
@@ -161,9 +375,13 @@ enum AnthropicContent {
     Blocks(Vec<ContentBlock>),
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Clone)]
 #[serde(tag = "type")]
 enum ContentBlock {
+    #[serde(rename = "thinking")]
+    Thinking { thinking: String, signature: String },
+    #[serde(rename = "redacted_thinking")]
+    RedactedThinking { data: String },
     #[serde(rename = "text")]
     Text { text: String },
     #[serde(rename = "image")]
@@ -181,7 +399,7 @@ enum ContentBlock {
     },
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Clone)]
 struct ImageSource {
     #[serde(rename = "type")]
     source_type: String,
@@ -190,7 +408,7 @@ struct ImageSource {
 }
 
 /// Parse a data URL like "data:image/jpeg;base64,/9j/4AAQ..." into (media_type, base64_data)
-fn parse_data_url(url: &str) -> Option<(String, String)> {
+pub(super) fn parse_data_url(url: &str) -> Option<(String, String)> {
     let url = url.strip_prefix("data:")?;
     let (header, data) = url.split_once(",")?;
     let media_type = header.strip_suffix(";base64")?.to_string();
@@ -202,6 +420,8 @@ struct AnthropicTool {
     name: String,
     description: String,
     input_schema: serde_json::Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cache_control: Option<CacheControl>,
 }
 
 // ── Response types ──
@@ -218,6 +438,14 @@ struct AnthropicResponse {
 #[derive(Deserialize)]
 #[serde(tag = "type")]
 enum ResponseBlock {
+    #[serde(rename = "thinking")]
+    Thinking {
+        thinking: String,
+        #[serde(default)]
+        signature: String,
+    },
+    #[serde(rename = "redacted_thinking")]
+    RedactedThinking { data: String },
     #[serde(rename = "text")]
     Text { text: String },
     #[serde(rename = "tool_use")]
@@ -234,6 +462,25 @@ struct AnthropicUsage {
     input_tokens: u32,
     #[serde(default)]
     output_tokens: u32,
+    #[serde(default)]
+    cache_creation_input_tokens: u32,
+    #[serde(default)]
+    cache_read_input_tokens: u32,
+}
+
+impl AnthropicUsage {
+    /// Anthropic reports uncached input separately from cache reads/writes;
+    /// our prompt_tokens is the full prompt size (matching OpenAI semantics).
+    fn to_usage_stats(&self) -> UsageStats {
+        let prompt_tokens = self.input_tokens + self.cache_creation_input_tokens + self.cache_read_input_tokens;
+        UsageStats {
+            prompt_tokens,
+            completion_tokens: self.output_tokens,
+            total_tokens: prompt_tokens + self.output_tokens,
+            cache_read_tokens: self.cache_read_input_tokens,
+            cache_write_tokens: self.cache_creation_input_tokens,
+        }
+    }
 }
 
 // ── Streaming event types ──
@@ -252,23 +499,32 @@ struct StreamEventData {
     usage: Option<AnthropicUsage>,
     #[serde(default)]
     message: Option<serde_json::Value>,
+    #[serde(default)]
+    error: Option<serde_json::Value>,
 }
 
-fn process_response(resp: AnthropicResponse) -> (Completion, UsageStats) {
+/// Convert a non-streaming response. Also returns the thinking blocks to replay
+/// alongside any tool calls.
+fn process_response(resp: AnthropicResponse) -> (Completion, UsageStats, Vec<ContentBlock>) {
     let usage = resp
         .usage
-        .map(|u| UsageStats {
-            prompt_tokens: u.input_tokens,
-            completion_tokens: u.output_tokens,
-            total_tokens: u.input_tokens + u.output_tokens,
-        })
+        .map(|u| u.to_usage_stats())
         .unwrap_or_default();
 
     let mut text_parts = Vec::new();
+    let mut reasoning_parts = Vec::new();
+    let mut thinking_blocks = Vec::new();
     let mut tool_calls = Vec::new();
 
     for block in resp.content {
         match block {
+            ResponseBlock::Thinking { thinking, signature } => {
+                reasoning_parts.push(thinking.clone());
+                thinking_blocks.push(ContentBlock::Thinking { thinking, signature });
+            }
+            ResponseBlock::RedactedThinking { data } => {
+                thinking_blocks.push(ContentBlock::RedactedThinking { data });
+            }
             ResponseBlock::Text { text } => text_parts.push(text),
             ResponseBlock::ToolUse { id, name, input } => {
                 tool_calls.push(ToolCall {
@@ -283,21 +539,29 @@ fn process_response(resp: AnthropicResponse) -> (Completion, UsageStats) {
         }
     }
 
+    let reasoning = if reasoning_parts.is_empty() {
+        None
+    } else {
+        Some(reasoning_parts.join("\n\n"))
+    };
+
     if !tool_calls.is_empty() {
         (
             Completion::ToolCalls {
                 calls: tool_calls,
-                reasoning: None,
+                reasoning,
             },
             usage,
+            thinking_blocks,
         )
     } else {
         (
             Completion::Text {
                 content: text_parts.join(""),
-                reasoning: None,
+                reasoning,
             },
             usage,
+            thinking_blocks,
         )
     }
 }
@@ -313,27 +577,51 @@ impl LlmProvider for AnthropicProvider {
         messages: &[Message],
         tools: &[ToolDefinition],
     ) -> Result<(Completion, UsageStats)> {
-        let (system, anthropic_msgs) = convert_messages(messages);
-        let anthropic_tools = convert_tools(tools);
+        let request = self.build_request(messages, tools, false);
+        self.send(messages, &request).await
+    }
 
-        let request = AnthropicRequest {
-            model: self.model.clone(),
-            max_tokens: self.max_tokens,
-            messages: anthropic_msgs,
-            system,
-            tools: anthropic_tools,
-            stream: None,
-        };
+    /// Forces a single `structured_output` tool call whose input schema is the requested schema.
+    async fn complete_structured(
+        &self,
+        messages: &[Message],
+        schema: &serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        let request = self.build_structured_request(messages, schema);
+        match self.send(messages, &request).await {
+            Ok((Completion::ToolCalls { calls, .. }, _)) => {
+                let args = calls
+                    .iter()
+                    .find(|c| c.function.name == structured::STRUCTURED_TOOL_NAME)
+                    .map(|c| c.function.arguments.as_str())
+                    .unwrap_or("");
+                if let Ok(value) = serde_json::from_str::<serde_json::Value>(args) {
+                    let errors = structured::validate(&value, schema);
+                    if errors.is_empty() {
+                        return Ok(value);
+                    }
+                    tracing::warn!("{}: structured tool input invalid: {}", self.model, errors.join("; "));
+                }
+            }
+            Ok(_) => tracing::warn!("{}: model ignored forced structured tool", self.model),
+            Err(e) => return Err(e),
+        }
+        structured::complete_with_repair(self, messages, schema).await
+    }
+
+    async fn complete_streaming(
+        &self,
+        messages: &[Message],
+        tools: &[ToolDefinition],
+        event_tx: mpsc::UnboundedSender<StreamEvent>,
+    ) -> Result<(Completion, UsageStats)> {
+        let request = self.build_request(messages, tools, true);
 
         let t_start = std::time::Instant::now();
         let mut log_entry = crate::llm_log::LlmLogEntry::new(&self.model);
         log_entry.messages_count = messages.len();
-        log_entry.streaming = false;
-        log_entry.request_tokens_est = messages
-            .iter()
-            .map(|m| m.content.as_deref().unwrap_or("").len() as u32 / 4)
-            .sum::<u32>()
-            .max(1);
+        log_entry.streaming = true;
+        log_entry.request_tokens_est = crate::tokenizer::for_model(&self.model).count_messages(messages) as u32;
 
         let response = self
             .client
@@ -344,7 +632,7 @@ impl LlmProvider for AnthropicProvider {
             .json(&request)
             .send()
             .await
-            .context("Failed to send request to Anthropic")?;
+            .context("Failed to send streaming request to Anthropic")?;
 
         let status = response.status();
         if !status.is_success() {
@@ -352,20 +640,60 @@ impl LlmProvider for AnthropicProvider {
             log_entry.latency_ms = t_start.elapsed().as_millis() as u64;
             log_entry.error = Some(format!("HTTP {}: {}", status, &body[..body.len().min(500)]));
             crate::llm_log::record(log_entry);
-            anyhow::bail!("Anthropic API returned {}: {}", status, body);
+            anyhow::bail!("Anthropic streaming API returned {}: {}", status, body);
         }
 
-        let api_response: AnthropicResponse = response
-            .json()
-            .await
-            .context("Failed to parse Anthropic response")?;
+        let mut acc = StreamAccumulator::default();
+
+        let mut stream = response.bytes_stream();
+        let mut buffer = String::new();
 
-        let (completion, usage) = process_response(api_response);
+        let streamed: Result<()> = async {
+            while let Some(chunk) = stream.next().await {
+                let chunk = chunk.context("Anthropic stream read error")?;
+                buffer.push_str(&String::from_utf8_lossy(&chunk));
+
+                while let Some(line_end) = buffer.find('\n') {
+                    let line = buffer[..line_end].trim().to_string();
+                    buffer = buffer[line_end + 1..].to_string();
+
+                    if line.is_empty() || line.starts_with("event:") {
+                        continue;
+                    }
+
+                    if let Some(json_str) = line.strip_prefix("data: ") {
+                        if let Ok(evt) = serde_json::from_str::<StreamEventData>(json_str) {
+                            acc.handle_event(evt, &event_tx);
+                        }
+                    }
+                }
+            }
+            acc.check_complete()
+        }
+        .await;
+
+        // A truncated stream is logged without usage so a retry elsewhere isn't double-counted
+        if let Err(e) = streamed {
+            log_entry.latency_ms = t_start.elapsed().as_millis() as u64;
+            log_entry.error = Some(format!("{:#}", e));
+            crate::llm_log::record(log_entry);
+            return Err(e);
+        }
+
+        // NOTE: Do NOT send StreamEvent::Done here. The runtime controls Done
+        // because there may be tool calls to execute after the LLM stream ends.
+
+        let (completion, usage, thinking_blocks) = acc.finish(messages, &self.model);
+        if let Completion::ToolCalls { ref calls, .. } = completion {
+            self.remember_thinking(calls, thinking_blocks);
+        }
 
         log_entry.latency_ms = t_start.elapsed().as_millis() as u64;
         log_entry.usage_prompt_tokens = usage.prompt_tokens;
         log_entry.usage_completion_tokens = usage.completion_tokens;
         log_entry.usage_total_tokens = usage.total_tokens;
+        log_entry.usage_cache_read_tokens = usage.cache_read_tokens;
+        log_entry.usage_cache_write_tokens = usage.cache_write_tokens;
         match &completion {
             Completion::Text { content, reasoning } => {
                 log_entry.response_content = Some(content.clone());
@@ -381,34 +709,16 @@ impl LlmProvider for AnthropicProvider {
 
         Ok((completion, usage))
     }
+}
 
-    async fn complete_streaming(
-        &self,
-        messages: &[Message],
-        tools: &[ToolDefinition],
-        event_tx: mpsc::UnboundedSender<StreamEvent>,
-    ) -> Result<(Completion, UsageStats)> {
-        let (system, anthropic_msgs) = convert_messages(messages);
-        let anthropic_tools = convert_tools(tools);
-
-        let request = AnthropicRequest {
-            model: self.model.clone(),
-            max_tokens: self.max_tokens,
-            messages: anthropic_msgs,
-            system,
-            tools: anthropic_tools,
-            stream: Some(true),
-        };
-
+impl AnthropicProvider {
+    /// POST a non-streaming Messages request with LLM call logging
+    async fn send(&self, messages: &[Message], request: &AnthropicRequest) -> Result<(Completion, UsageStats)> {
         let t_start = std::time::Instant::now();
         let mut log_entry = crate::llm_log::LlmLogEntry::new(&self.model);
         log_entry.messages_count = messages.len();
-        log_entry.streaming = true;
-        log_entry.request_tokens_est = messages
-            .iter()
-            .map(|m| m.content.as_deref().unwrap_or("").len() as u32 / 4)
-            .sum::<u32>()
-            .max(1);
+        log_entry.streaming = false;
+        log_entry.request_tokens_est = crate::tokenizer::for_model(&self.model).count_messages(messages) as u32;
 
         let response = self
             .client
@@ -416,10 +726,10 @@ impl LlmProvider for AnthropicProvider {
             .header("x-api-key", &self.api_key)
             .header("anthropic-version", ANTHROPIC_VERSION)
             .header("Content-Type", "application/json")
-            .json(&request)
+            .json(request)
             .send()
             .await
-            .context("Failed to send streaming request to Anthropic")?;
+            .context("Failed to send request to Anthropic")?;
 
         let status = response.status();
         if !status.is_success() {
@@ -427,122 +737,222 @@ impl LlmProvider for AnthropicProvider {
             log_entry.latency_ms = t_start.elapsed().as_millis() as u64;
             log_entry.error = Some(format!("HTTP {}: {}", status, &body[..body.len().min(500)]));
             crate::llm_log::record(log_entry);
-            anyhow::bail!("Anthropic streaming API returned {}: {}", status, body);
+            anyhow::bail!("Anthropic API returned {}: {}", status, body);
         }
 
-        let mut content = String::new();
-        let mut tool_calls: Vec<PartialAnthropicToolCall> = Vec::new();
-        let mut current_tool_index: Option<usize> = None;
-        let mut usage = UsageStats::default();
+        let api_response: AnthropicResponse = response
+            .json()
+            .await
+            .context("Failed to parse Anthropic response")?;
 
-        let mut stream = response.bytes_stream();
-        let mut buffer = String::new();
+        let (completion, usage, thinking_blocks) = process_response(api_response);
+        if let Completion::ToolCalls { ref calls, .. } = completion {
+            self.remember_thinking(calls, thinking_blocks);
+        }
 
-        while let Some(chunk) = stream.next().await {
-            let chunk = chunk.context("Anthropic stream read error")?;
-            buffer.push_str(&String::from_utf8_lossy(&chunk));
+        log_entry.latency_ms = t_start.elapsed().as_millis() as u64;
+        log_entry.usage_prompt_tokens = usage.prompt_tokens;
+        log_entry.usage_completion_tokens = usage.completion_tokens;
+        log_entry.usage_total_tokens = usage.total_tokens;
+        log_entry.usage_cache_read_tokens = usage.cache_read_tokens;
+        log_entry.usage_cache_write_tokens = usage.cache_write_tokens;
+        match &completion {
+            Completion::Text { content, reasoning } => {
+                log_entry.response_content = Some(content.clone());
+                log_entry.response_reasoning = reasoning.clone();
+            }
+            Completion::ToolCalls { calls, reasoning } => {
+                log_entry.response_tool_calls = calls.len();
+                log_entry.tool_call_names = calls.iter().map(|c| c.function.name.clone()).collect();
+                log_entry.response_reasoning = reasoning.clone();
+            }
+        }
+        crate::llm_log::record(log_entry);
 
-            while let Some(line_end) = buffer.find('\n') {
-                let line = buffer[..line_end].trim().to_string();
-                buffer = buffer[line_end + 1..].to_string();
+        Ok((completion, usage))
+    }
+}
 
-                if line.is_empty() || line.starts_with("event:") {
-                    continue;
-                }
+struct PartialAnthropicToolCall {
+    id: String,
+    name: String,
+    arguments: String,
+}
 
-                if let Some(json_str) = line.strip_prefix("data: ") {
-                    if let Ok(evt) = serde_json::from_str::<StreamEventData>(json_str) {
-                        match evt.event_type.as_str() {
-                            "content_block_start" => {
-                                if let Some(ref block) = evt.content_block {
-                                    let block_type = block.get("type").and_then(|v| v.as_str()).unwrap_or("");
-                                    if block_type == "tool_use" {
-                                        let id = block.get("id").and_then(|v| v.as_str()).unwrap_or("").to_string();
-                                        let name = block.get("name").and_then(|v| v.as_str()).unwrap_or("").to_string();
-                                        let idx = tool_calls.len();
-                                        tool_calls.push(PartialAnthropicToolCall {
-                                            id,
-                                            name: name.clone(),
-                                            arguments: String::new(),
-                                        });
-                                        current_tool_index = Some(idx);
-                                        let _ = event_tx.send(StreamEvent::ToolCallStart { name });
-                                    }
-                                }
-                            }
-                            "content_block_delta" => {
-                                if let Some(ref delta) = evt.delta {
-                                    let delta_type = delta.get("type").and_then(|v| v.as_str()).unwrap_or("");
-                                    match delta_type {
-                                        "text_delta" => {
-                                            if let Some(text) = delta.get("text").and_then(|v| v.as_str()) {
-                                                content.push_str(text);
-                                                let _ = event_tx.send(StreamEvent::ContentDelta(text.to_string()));
-                                            }
-                                        }
-                                        "input_json_delta" => {
-                                            if let Some(partial) = delta.get("partial_json").and_then(|v| v.as_str()) {
-                                                if let Some(idx) = current_tool_index {
-                                                    if let Some(tc) = tool_calls.get_mut(idx) {
-                                                        tc.arguments.push_str(partial);
-                                                    }
-                                                }
-                                            }
-                                        }
-                                        _ => {}
-                                    }
-                                }
-                            }
-                            "content_block_stop" => {
-                                current_tool_index = None;
-                            }
-                            "message_delta" => {
-                                // May contain usage info
-                                if let Some(ref delta) = evt.delta {
-                                    if let Some(u) = delta.get("usage") {
-                                        if let Some(out) = u.get("output_tokens").and_then(|v| v.as_u64()) {
-                                            usage.completion_tokens = out as u32;
-                                        }
-                                    }
-                                }
-                            }
-                            "message_start" => {
-                                if let Some(ref msg) = evt.message {
-                                    if let Some(u) = msg.get("usage") {
-                                        if let Some(inp) = u.get("input_tokens").and_then(|v| v.as_u64()) {
-                                            usage.prompt_tokens = inp as u32;
-                                        }
-                                    }
-                                }
-                            }
-                            "message_stop" => {}
-                            _ => {
-                                debug!("anthropic: unknown stream event: {}", evt.event_type);
+/// A content block being assembled from stream deltas
+enum PartialBlock {
+    Text,
+    Thinking { thinking: String, signature: String },
+    RedactedThinking { data: String },
+    ToolUse { call_index: usize },
+}
+
+/// Accumulates Anthropic SSE events into a completion
+#[derive(Default)]
+struct StreamAccumulator {
+    content: String,
+    reasoning: String,
+    tool_calls: Vec<PartialAnthropicToolCall>,
+    thinking_blocks: Vec<ContentBlock>,
+    current_block: Option<PartialBlock>,
+    usage: UsageStats,
+    /// Set once `message_stop` arrives; a stream that ends without it was cut off
+    stopped: bool,
+    /// Mid-stream `error` event (e.g. overloaded_error)
+    error: Option<String>,
+}
+
+impl StreamAccumulator {
+    fn handle_event(&mut self, evt: StreamEventData, event_tx: &mpsc::UnboundedSender<StreamEvent>) {
+        match evt.event_type.as_str() {
+            "content_block_start" => {
+                let Some(ref block) = evt.content_block else { return };
+                let block_type = block.get("type").and_then(|v| v.as_str()).unwrap_or("");
+                let str_field = |key: &str| block.get(key).and_then(|v| v.as_str()).unwrap_or("").to_string();
+                self.current_block = match block_type {
+                    "tool_use" => {
+                        let name = str_field("name");
+                        self.tool_calls.push(PartialAnthropicToolCall {
+                            id: str_field("id"),
+                            name: name.clone(),
+                            arguments: String::new(),
+                        });
+                        let _ = event_tx.send(StreamEvent::ToolCallStart { name });
+                        Some(PartialBlock::ToolUse { call_index: self.tool_calls.len() - 1 })
+                    }
+                    "thinking" => {
+                        if !self.reasoning.is_empty() {
+                            self.reasoning.push_str("\n\n");
+                        }
+                        Some(PartialBlock::Thinking {
+                            thinking: String::new(),
+                            signature: String::new(),
+                        })
+                    }
+                    "redacted_thinking" => Some(PartialBlock::RedactedThinking { data: str_field("data") }),
+                    _ => Some(PartialBlock::Text),
+                };
+            }
+            "content_block_delta" => {
+                let Some(ref delta) = evt.delta else { return };
+                let delta_type = delta.get("type").and_then(|v| v.as_str()).unwrap_or("");
+                let text_field = |key: &str| delta.get(key).and_then(|v| v.as_str());
+                match (delta_type, self.current_block.as_mut()) {
+                    ("text_delta", _) => {
+                        if let Some(text) = text_field("text") {
+                            self.content.push_str(text);
+                            let _ = event_tx.send(StreamEvent::ContentDelta(text.to_string()));
+                        }
+                    }
+                    ("thinking_delta", Some(PartialBlock::Thinking { thinking, .. })) => {
+                        if let Some(text) = text_field("thinking") {
+                            thinking.push_str(text);
+                            self.reasoning.push_str(text);
+                            let _ = event_tx.send(StreamEvent::ReasoningDelta(text.to_string()));
+                        }
+                    }
+                    ("signature_delta", Some(PartialBlock::Thinking { signature, .. })) => {
+                        if let Some(sig) = text_field("signature") {
+                            signature.push_str(sig);
+                        }
+                    }
+                    ("input_json_delta", Some(PartialBlock::ToolUse { call_index })) => {
+                        if let Some(partial) = text_field("partial_json") {
+                            if let Some(tc) = self.tool_calls.get_mut(*call_index) {
+                                tc.arguments.push_str(partial);
                             }
                         }
                     }
+                    _ => {}
+                }
+            }
+            "content_block_stop" => match self.current_block.take() {
+                Some(PartialBlock::Thinking { thinking, signature }) => {
+                    self.thinking_blocks.push(ContentBlock::Thinking { thinking, signature });
+                }
+                Some(PartialBlock::RedactedThinking { data }) => {
+                    self.thinking_blocks.push(ContentBlock::RedactedThinking { data });
+                }
+                _ => {}
+            },
+            "message_start" => {
+                // Input + cache usage is reported up front
+                if let Some(u) = evt
+                    .message
+                    .as_ref()
+                    .and_then(|m| m.get("usage"))
+                    .and_then(|u| serde_json::from_value::<AnthropicUsage>(u.clone()).ok())
+                {
+                    let stats = u.to_usage_stats();
+                    self.usage.prompt_tokens = stats.prompt_tokens;
+                    self.usage.cache_read_tokens = stats.cache_read_tokens;
+                    self.usage.cache_write_tokens = stats.cache_write_tokens;
+                }
+            }
+            "message_delta" => {
+                // Final output token count (top-level usage, older API versions nest it in delta)
+                let out = evt
+                    .usage
+                    .as_ref()
+                    .map(|u| u.output_tokens as u64)
+                    .or_else(|| {
+                        evt.delta
+                            .as_ref()
+                            .and_then(|d| d.get("usage"))
+                            .and_then(|u| u.get("output_tokens"))
+                            .and_then(|v| v.as_u64())
+                    });
+                if let Some(out) = out {
+                    self.usage.completion_tokens = out as u32;
                 }
             }
+            "message_stop" => self.stopped = true,
+            "error" => {
+                let message = evt
+                    .error
+                    .as_ref()
+                    .and_then(|e| e.get("message").and_then(|m| m.as_str()))
+                    .unwrap_or("unknown error");
+                self.error = Some(message.to_string());
+            }
+            "ping" => {}
+            _ => {
+                debug!("anthropic: unknown stream event: {}", evt.event_type);
+            }
+        }
+    }
+
+    /// Error if the stream failed or ended before `message_stop`
+    fn check_complete(&self) -> Result<()> {
+        if let Some(ref err) = self.error {
+            anyhow::bail!("Anthropic stream error: {}", err);
         }
+        if !self.stopped {
+            anyhow::bail!("Anthropic stream ended before message_stop");
+        }
+        Ok(())
+    }
 
-        usage.total_tokens = usage.prompt_tokens + usage.completion_tokens;
+    fn finish(mut self, messages: &[Message], model: &str) -> (Completion, UsageStats, Vec<ContentBlock>) {
+        self.usage.total_tokens = self.usage.prompt_tokens + self.usage.completion_tokens;
 
         // Fallback token estimation
-        if usage.total_tokens == 0 {
-            let msg_chars: usize = messages
-                .iter()
-                .map(|m| m.content.as_deref().unwrap_or("").len())
-                .sum();
-            usage.prompt_tokens = (msg_chars / 4).max(1) as u32;
-            usage.completion_tokens = (content.len() / 4).max(1) as u32;
-            usage.total_tokens = usage.prompt_tokens + usage.completion_tokens;
+        if self.usage.total_tokens == 0 {
+            let counter = crate::tokenizer::for_model(model);
+            self.usage.prompt_tokens = counter.count_messages(messages).max(1) as u32;
+            self.usage.completion_tokens = (counter.count(&self.content) + counter.count(&self.reasoning)) as u32;
+            self.usage.total_tokens = self.usage.prompt_tokens + self.usage.completion_tokens;
         }
 
-        // NOTE: Do NOT send StreamEvent::Done here. The runtime controls Done
-        // because there may be tool calls to execute after the LLM stream ends.
+        let reasoning = if self.reasoning.is_empty() {
+            None
+        } else {
+            Some(self.reasoning)
+        };
 
-        let completion = if !tool_calls.is_empty() {
-            let calls = tool_calls
+        let completion = if !self.tool_calls.is_empty() {
+            let calls = self
+                .tool_calls
                 .into_iter()
                 .map(|tc| ToolCall {
                     id: tc.id,
@@ -553,44 +963,18 @@ impl LlmProvider for AnthropicProvider {
                     },
                 })
                 .collect();
-            Completion::ToolCalls {
-                calls,
-                reasoning: None,
-            }
+            Completion::ToolCalls { calls, reasoning }
         } else {
             Completion::Text {
-                content,
-                reasoning: None,
+                content: self.content,
+                reasoning,
             }
         };
 
-        log_entry.latency_ms = t_start.elapsed().as_millis() as u64;
-        log_entry.usage_prompt_tokens = usage.prompt_tokens;
-        log_entry.usage_completion_tokens = usage.completion_tokens;
-        log_entry.usage_total_tokens = usage.total_tokens;
-        match &completion {
-            Completion::Text { content, reasoning } => {
-                log_entry.response_content = Some(content.clone());
-                log_entry.response_reasoning = reasoning.clone();
-            }
-            Completion::ToolCalls { calls, reasoning } => {
-                log_entry.response_tool_calls = calls.len();
-                log_entry.tool_call_names = calls.iter().map(|c| c.function.name.clone()).collect();
-                log_entry.response_reasoning = reasoning.clone();
-            }
-        }
-        crate::llm_log::record(log_entry);
-
-        Ok((completion, usage))
+        (completion, self.usage, self.thinking_blocks)
     }
 }
 
-struct PartialAnthropicToolCall {
-    id: String,
-    name: String,
-    arguments: String,
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -601,7 +985,7 @@ mod tests {
             Message::system("You are helpful."),
             Message::user("Hello"),
         ];
-        let (system, anthropic_msgs) = convert_messages(&msgs);
+        let (system, anthropic_msgs) = convert_messages(&msgs, &HashMap::new());
         assert_eq!(system, Some("You are helpful.".to_string()));
         assert_eq!(anthropic_msgs.len(), 1);
     }
@@ -616,9 +1000,10 @@ mod tests {
                 parameters: serde_json::json!({"type": "object"}),
             },
         }];
-        let converted = convert_tools(&tools);
+        let converted = convert_tools(&tools, false);
         assert_eq!(converted.len(), 1);
         assert_eq!(converted[0].name, "exec");
+        assert!(converted[0].cache_control.is_none());
     }
 
     #[test]
@@ -630,10 +1015,12 @@ mod tests {
             usage: Some(AnthropicUsage {
                 input_tokens: 10,
                 output_tokens: 5,
+                cache_creation_input_tokens: 0,
+                cache_read_input_tokens: 0,
             }),
             stop_reason: Some("end_turn".to_string()),
         };
-        let (completion, usage) = process_response(resp);
+        let (completion, usage, _) = process_response(resp);
         match completion {
             Completion::Text { content, .. } => assert_eq!(content, "Hello!"),
             _ => panic!("Expected text completion"),
@@ -652,10 +1039,12 @@ mod tests {
             usage: Some(AnthropicUsage {
                 input_tokens: 20,
                 output_tokens: 10,
+                cache_creation_input_tokens: 0,
+                cache_read_input_tokens: 0,
             }),
             stop_reason: Some("tool_use".to_string()),
         };
-        let (completion, usage) = process_response(resp);
+        let (completion, usage, _) = process_response(resp);
         match completion {
             Completion::ToolCalls { calls, .. } => {
                 assert_eq!(calls.len(), 1);
@@ -665,4 +1054,239 @@ mod tests {
         }
         assert_eq!(usage.total_tokens, 30);
     }
+
+    fn exec_tool() -> ToolDefinition {
+        ToolDefinition {
+            tool_type: "function".to_string(),
+            function: super::super::FunctionDefinition {
+                name: "exec".to_string(),
+                description: "Run a command".to_string(),
+                parameters: serde_json::json!({"type": "object"}),
+            },
+        }
+    }
+
+    fn parse_events(lines: &[&str]) -> StreamAccumulator {
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let mut acc = StreamAccumulator::default();
+        for line in lines {
+            acc.handle_event(serde_json::from_str(line).unwrap(), &tx);
+        }
+        acc
+    }
+
+    #[test]
+    fn test_cache_breakpoint_on_last_tool() {
+        let converted = convert_tools(&[exec_tool(), exec_tool()], true);
+        assert!(converted[0].cache_control.is_none());
+        assert!(converted[1].cache_control.is_some());
+    }
+
+    #[test]
+    fn test_system_blocks_split_runtime_section() {
+        let system = "<!-- SOUL.md -->\nYou are Aitan.\n\n<!-- runtime -->\nCurrent date/time: now";
+        let blocks = build_system_blocks(system, true);
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].text, "<!-- SOUL.md -->\nYou are Aitan.");
+        assert!(blocks[0].cache_control.is_some());
+        assert!(blocks[1].text.starts_with("<!-- runtime -->"));
+        assert!(blocks[1].cache_control.is_none());
+
+        let json = serde_json::to_value(&blocks).unwrap();
+        assert_eq!(json[0]["cache_control"]["type"], "ephemeral");
+        assert!(json[1].get("cache_control").is_none());
+    }
+
+    #[test]
+    fn test_system_blocks_without_caching() {
+        let blocks = build_system_blocks("You are helpful.", false);
+        assert_eq!(blocks.len(), 1);
+        assert!(blocks[0].cache_control.is_none());
+    }
+
+    #[test]
+    fn test_thinking_request_raises_max_tokens() {
+        let provider = AnthropicProvider::new("key", "claude-sonnet-4")
+            .with_max_tokens(4096)
+            .with_thinking(8000);
+        let request = provider.build_request(&[Message::user("hi")], &[], false);
+        assert!(request.max_tokens > 8000);
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["thinking"]["type"], "enabled");
+        assert_eq!(json["thinking"]["budget_tokens"], 8000);
+
+        let plain = AnthropicProvider::new("key", "claude-sonnet-4").build_request(&[Message::user("hi")], &[], false);
+        assert_eq!(plain.max_tokens, 4096);
+        assert!(serde_json::to_value(&plain).unwrap().get("thinking").is_none());
+    }
+
+    #[test]
+    fn test_with_params_request_fields() {
+        let provider = AnthropicProvider::new("key", "claude-sonnet-4").with_params(GenerationParams {
+            temperature: Some(0.3),
+            max_tokens: Some(2000),
+            stop: Some(vec!["END".to_string()]),
+            extra: serde_json::from_value(serde_json::json!({"top_k": 40})).unwrap(),
+            ..Default::default()
+        });
+        let json = serde_json::to_value(provider.build_request(&[Message::user("hi")], &[], false)).unwrap();
+        assert_eq!(json["max_tokens"], 2000);
+        assert_eq!(json["temperature"], serde_json::json!(0.3f32));
+        assert_eq!(json["stop_sequences"][0], "END");
+        assert_eq!(json["top_k"], 40);
+        assert!(json.get("thinking").is_none());
+    }
+
+    #[test]
+    fn test_structured_request_forces_tool() {
+        let provider = AnthropicProvider::new("key", "claude-sonnet-4").with_thinking(2048);
+        let schema = serde_json::json!({"type": "object", "properties": {"ok": {"type": "boolean"}}});
+        let json = serde_json::to_value(provider.build_structured_request(&[Message::user("hi")], &schema)).unwrap();
+        assert_eq!(json["tool_choice"]["type"], "tool");
+        assert_eq!(json["tool_choice"]["name"], structured::STRUCTURED_TOOL_NAME);
+        assert_eq!(json["tools"][0]["name"], structured::STRUCTURED_TOOL_NAME);
+        assert_eq!(json["tools"][0]["input_schema"], schema);
+        assert!(json.get("thinking").is_none());
+    }
+
+    #[test]
+    fn test_reasoning_effort_enables_thinking() {
+        let provider = AnthropicProvider::new("key", "claude-sonnet-4").with_params(GenerationParams {
+            temperature: Some(0.3),
+            reasoning_effort: Some("medium".to_string()),
+            ..Default::default()
+        });
+        let json = serde_json::to_value(provider.build_request(&[Message::user("hi")], &[], false)).unwrap();
+        assert_eq!(json["thinking"]["budget_tokens"], 4096);
+        // temperature is incompatible with thinking and must be dropped
+        assert!(json.get("temperature").is_none());
+
+        // An explicit thinking budget wins over the effort mapping
+        let explicit = AnthropicProvider::new("key", "claude-sonnet-4")
+            .with_thinking(2048)
+            .with_params(GenerationParams {
+                reasoning_effort: Some("high".to_string()),
+                ..Default::default()
+            });
+        assert_eq!(explicit.thinking_budget, Some(2048));
+    }
+
+    #[test]
+    fn test_usage_includes_cache_tokens() {
+        let usage = AnthropicUsage {
+            input_tokens: 50,
+            output_tokens: 20,
+            cache_creation_input_tokens: 1000,
+            cache_read_input_tokens: 3000,
+        }
+        .to_usage_stats();
+        assert_eq!(usage.prompt_tokens, 4050);
+        assert_eq!(usage.total_tokens, 4070);
+        assert_eq!(usage.cache_read_tokens, 3000);
+        assert_eq!(usage.cache_write_tokens, 1000);
+    }
+
+    #[test]
+    fn test_process_response_thinking() {
+        let resp: AnthropicResponse = serde_json::from_value(serde_json::json!({
+            "content": [
+                {"type": "thinking", "thinking": "Let me list files.", "signature": "sig123"},
+                {"type": "tool_use", "id": "toolu_1", "name": "exec", "input": {"command": "ls"}}
+            ],
+            "usage": {"input_tokens": 10, "output_tokens": 5},
+            "stop_reason": "tool_use"
+        }))
+        .unwrap();
+        let (completion, _, blocks) = process_response(resp);
+        match completion {
+            Completion::ToolCalls { reasoning, .. } => {
+                assert_eq!(reasoning.as_deref(), Some("Let me list files."));
+            }
+            _ => panic!("Expected tool calls"),
+        }
+        assert_eq!(blocks.len(), 1);
+    }
+
+    #[test]
+    fn test_stream_thinking_and_cache_usage() {
+        let acc = parse_events(&[
+            r#"{"type":"message_start","message":{"usage":{"input_tokens":12,"cache_read_input_tokens":2048,"cache_creation_input_tokens":0}}}"#,
+            r#"{"type":"content_block_start","index":0,"content_block":{"type":"thinking","thinking":""}}"#,
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"thinking_delta","thinking":"Need to "}}"#,
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"thinking_delta","thinking":"run ls."}}"#,
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"signature_delta","signature":"EqQB"}}"#,
+            r#"{"type":"content_block_stop","index":0}"#,
+            r#"{"type":"content_block_start","index":1,"content_block":{"type":"tool_use","id":"toolu_1","name":"exec","input":{}}}"#,
+            r#"{"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"{\"command\":\"ls\"}"}}"#,
+            r#"{"type":"content_block_stop","index":1}"#,
+            r#"{"type":"message_delta","delta":{"stop_reason":"tool_use"},"usage":{"output_tokens":42}}"#,
+            r#"{"type":"message_stop"}"#,
+        ]);
+        assert!(acc.check_complete().is_ok());
+        let (completion, usage, blocks) = acc.finish(&[], "claude-test");
+        match completion {
+            Completion::ToolCalls { calls, reasoning } => {
+                assert_eq!(calls[0].function.arguments, r#"{"command":"ls"}"#);
+                assert_eq!(reasoning.as_deref(), Some("Need to run ls."));
+            }
+            _ => panic!("Expected tool calls"),
+        }
+        assert_eq!(usage.prompt_tokens, 2060);
+        assert_eq!(usage.completion_tokens, 42);
+        assert_eq!(usage.cache_read_tokens, 2048);
+        match &blocks[0] {
+            ContentBlock::Thinking { thinking, signature } => {
+                assert_eq!(thinking, "Need to run ls.");
+                assert_eq!(signature, "EqQB");
+            }
+            _ => panic!("Expected thinking block"),
+        }
+    }
+
+    #[test]
+    fn test_stream_incomplete_or_errored() {
+        let truncated = parse_events(&[
+            r#"{"type":"message_start","message":{"usage":{"input_tokens":12}}}"#,
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"Hel"}}"#,
+        ]);
+        assert!(truncated.check_complete().unwrap_err().to_string().contains("message_stop"));
+
+        let errored = parse_events(&[
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"Hel"}}"#,
+            r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#,
+        ]);
+        assert!(errored.check_complete().unwrap_err().to_string().contains("Overloaded"));
+    }
+
+    #[test]
+    fn test_thinking_blocks_replayed_before_tool_use() {
+        let provider = AnthropicProvider::new("key", "claude-sonnet-4").with_thinking(2048);
+        let call = ToolCall {
+            id: "toolu_1".to_string(),
+            call_type: "function".to_string(),
+            function: FunctionCall {
+                name: "exec".to_string(),
+                arguments: r#"{"command":"ls"}"#.to_string(),
+            },
+        };
+        provider.remember_thinking(
+            std::slice::from_ref(&call),
+            vec![ContentBlock::Thinking {
+                thinking: "Need to run ls.".to_string(),
+                signature: "EqQB".to_string(),
+            }],
+        );
+
+        let msgs = vec![
+            Message::user("list files"),
+            Message::assistant_tool_calls(vec![call], Some("Need to run ls.".to_string())),
+            Message::tool_result("toolu_1", "a.txt"),
+        ];
+        let request = provider.build_request(&msgs, &[], true);
+        let json = serde_json::to_value(&request).unwrap();
+        let assistant = &json["messages"][1]["content"];
+        assert_eq!(assistant[0]["type"], "thinking");
+        assert_eq!(assistant[0]["signature"], "EqQB");
+        assert_eq!(assistant[1]["type"], "tool_use");
+    }
 }
@@ -1,58 +1,103 @@
 use anyhow::Result;
 use async_trait::async_trait;
+use futures::future::BoxFuture;
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::RwLock;
+use std::sync::Arc;
 use std::time::Instant;
 use tracing::{info, warn};
 
 use super::{Completion, LlmProvider, Message, OpenAiCompatibleProvider, ToolDefinition, UsageStats};
 use super::anthropic::AnthropicProvider;
+use super::gemini::GeminiProvider;
+use super::health::{BreakerConfig, CircuitState, HealthRegistry, HealthSnapshot};
+use super::ollama::OllamaProvider;
+use super::streaming::StreamEvent;
 
 /// A provider entry in the fallback chain
 pub struct FallbackEntry {
     pub provider: Box<dyn LlmProvider>,
     pub label: String,
-    consecutive_failures: AtomicUsize,
+}
+
+/// How the chain orders providers for each request (`models.fallbackMode`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChainOrder {
+    /// Configured order; open circuits are skipped
+    Configured,
+    /// Healthy providers first, lowest rolling latency first
+    FastestHealthy,
+}
+
+impl ChainOrder {
+    pub fn from_config(value: Option<&str>) -> Self {
+        match value {
+            Some("fastest") => Self::FastestHealthy,
+            _ => Self::Configured,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Configured => "ordered",
+            Self::FastestHealthy => "fastest healthy first",
+        }
+    }
 }
 
 /// Tries providers in order. On failure, falls through to the next.
-/// Tracks consecutive failures per provider for health scoring.
+/// Each provider has a circuit breaker (closed / open / half-open) in a shared
+/// `HealthRegistry`, so health survives the chain being rebuilt per request.
 pub struct FallbackProvider {
     entries: Vec<FallbackEntry>,
-    last_successful: RwLock<String>,
+    /// Index into `entries` of the provider that last succeeded
+    last_successful: AtomicUsize,
+    health: Arc<HealthRegistry>,
+    breaker: BreakerConfig,
+    order: ChainOrder,
 }
 
 impl FallbackProvider {
     pub fn new(entries: Vec<(String, Box<dyn LlmProvider>)>) -> Self {
-        let first_label = entries.first().map(|(l, _)| l.clone()).unwrap_or_default();
         Self {
             entries: entries
                 .into_iter()
-                .map(|(label, provider)| FallbackEntry {
-                    provider,
-                    label,
-                    consecutive_failures: AtomicUsize::new(0),
-                })
+                .map(|(label, provider)| FallbackEntry { provider, label })
                 .collect(),
-            last_successful: RwLock::new(first_label),
+            last_successful: AtomicUsize::new(0),
+            health: HealthRegistry::global(),
+            breaker: BreakerConfig::default(),
+            order: ChainOrder::Configured,
         }
     }
 
+    pub fn with_breaker(mut self, breaker: BreakerConfig) -> Self {
+        self.breaker = breaker;
+        self
+    }
+
+    pub fn with_order(mut self, order: ChainOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Use a private health registry instead of the process-wide one
+    pub fn with_health_registry(mut self, health: Arc<HealthRegistry>) -> Self {
+        self.health = health;
+        self
+    }
+
     /// Build a fallback chain from the openclaw-manual.json config
     pub fn from_config() -> Result<Self> {
-        let config_path = openclaw_core::paths::manual_config_path();
-        if !config_path.exists() {
-            anyhow::bail!("Config not found at {}", config_path.display());
-        }
-
-        let content = std::fs::read_to_string(&config_path)?;
-        let config: serde_json::Value = serde_json::from_str(&content)?;
+        let config = load_manual_config()?;
+        // Keep call costing and memory settings in step with the config the chain is built from
+        super::pricing::install(super::pricing::PricingTable::from_config(&config));
+        crate::memory::install(crate::memory::Settings::from_manual(Some(&config)));
+        Self::from_config_value(&config)
+    }
 
-        let providers = config
-            .get("models")
-            .and_then(|m| m.get("providers"))
-            .and_then(|p| p.as_object())
-            .ok_or_else(|| anyhow::anyhow!("No model providers in config"))?;
+    /// Build the default chain (`models.fallbacks`, or every provider) from a parsed config
+    pub fn from_config_value(config: &serde_json::Value) -> Result<Self> {
+        let providers = config_providers(config)?;
 
         // Read fallback order from config, or use default
         let fallback_order = config
@@ -69,17 +114,7 @@ impl FallbackProvider {
 
         if let Some(order) = fallback_order {
             // Use explicit fallback order
-            for model_spec in &order {
-                if let Some((provider_name, model_id)) = parse_model_spec(model_spec) {
-                    if let Some(provider) = providers.get(&provider_name) {
-                        if let Some(entry) =
-                            build_provider_entry(&provider_name, &model_id, provider)
-                        {
-                            entries.push(entry);
-                        }
-                    }
-                }
-            }
+            entries = build_spec_entries(providers, &order);
         }
 
         if entries.is_empty() {
@@ -110,12 +145,155 @@ impl FallbackProvider {
             anyhow::bail!("No usable providers found in config");
         }
 
-        Ok(Self::new(entries))
+        Ok(Self::new(entries).with_chain_settings(config))
+    }
+
+    /// Build a chain from explicit "provider/model" specs, tried in the given order
+    pub fn from_specs(config: &serde_json::Value, specs: &[String]) -> Result<Self> {
+        let entries = build_spec_entries(config_providers(config)?, specs);
+        if entries.is_empty() {
+            anyhow::bail!("None of {:?} is a usable provider/model", specs);
+        }
+        Ok(Self::new(entries).with_chain_settings(config))
+    }
+
+    /// Apply `models.circuitBreaker` and `models.fallbackMode`
+    fn with_chain_settings(self, config: &serde_json::Value) -> Self {
+        let models = config.get("models");
+        self.with_breaker(BreakerConfig::from_json(models.and_then(|m| m.get("circuitBreaker"))))
+            .with_order(ChainOrder::from_config(
+                models.and_then(|m| m.get("fallbackMode")).and_then(|v| v.as_str()),
+            ))
     }
 
     pub fn provider_labels(&self) -> Vec<&str> {
         self.entries.iter().map(|e| e.label.as_str()).collect()
     }
+
+    pub fn order(&self) -> ChainOrder {
+        self.order
+    }
+
+    pub fn breaker_config(&self) -> &BreakerConfig {
+        &self.breaker
+    }
+
+    /// Breaker state and rolling stats for each provider, in configured order
+    pub fn health(&self) -> Vec<HealthSnapshot> {
+        self.entries.iter().map(|e| self.health.snapshot(&e.label)).collect()
+    }
+
+    /// Entries in the order they should be tried for the next request
+    fn ordered_entries(&self) -> Vec<&FallbackEntry> {
+        let mut entries: Vec<&FallbackEntry> = self.entries.iter().collect();
+        if self.order == ChainOrder::FastestHealthy {
+            // Stable sort: unmeasured providers keep their configured position at the front
+            entries.sort_by_cached_key(|e| {
+                let snap = self.health.snapshot(&e.label);
+                (snap.state != CircuitState::Closed, snap.avg_latency_ms.unwrap_or(0))
+            });
+        }
+        entries
+    }
+
+    /// Run `call` against each provider until one succeeds, honouring circuit breakers.
+    /// `on_switch(from, to)` fires before retrying on the next provider after a failure.
+    async fn run_chain<'a, T>(
+        &'a self,
+        kind: &str,
+        call: impl Fn(&'a dyn LlmProvider) -> BoxFuture<'a, Result<T>>,
+        on_switch: impl Fn(&str, &str),
+    ) -> Result<T> {
+        let mut last_error = None;
+        let mut failed_label: Option<&str> = None;
+        let ordered = self.ordered_entries();
+
+        for (i, entry) in ordered.iter().enumerate() {
+            let Some(guard) = self.health.acquire(&entry.label, &self.breaker) else {
+                let snap = self.health.snapshot(&entry.label);
+                info!(
+                    "Skipping {} (circuit {}: {} consecutive failures)",
+                    entry.label,
+                    snap.state.as_str(),
+                    snap.consecutive_failures
+                );
+                continue;
+            };
+
+            if let Some(from) = failed_label {
+                on_switch(from, &entry.label);
+            }
+
+            let t_start = Instant::now();
+            crate::llm_log::set_provider_attempt((i + 1) as u32);
+            info!("Trying {} provider {}/{}: {}", kind, i + 1, ordered.len(), entry.label);
+
+            match call(entry.provider.as_ref()).await {
+                Ok(result) => {
+                    let elapsed = t_start.elapsed().as_millis() as u64;
+                    guard.success(elapsed);
+                    if let Some(idx) = self.entries.iter().position(|e| std::ptr::eq(e, *entry)) {
+                        self.last_successful.store(idx, Ordering::Relaxed);
+                    }
+                    info!("{} {} succeeded in {}ms", entry.label, kind, elapsed);
+                    return Ok(result);
+                }
+                Err(e) => {
+                    let elapsed = t_start.elapsed().as_millis() as u64;
+                    guard.failure(elapsed);
+                    let snap = self.health.snapshot(&entry.label);
+                    warn!(
+                        "{} {} failed in {}ms (attempt {}, consecutive failures: {}, circuit {}): {}",
+                        entry.label, kind, elapsed, i + 1, snap.consecutive_failures, snap.state.as_str(), e
+                    );
+                    failed_label = Some(&entry.label);
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| anyhow::anyhow!("All providers exhausted or circuit-broken")))
+    }
+}
+
+/// Read and parse openclaw-manual.json
+pub(crate) fn load_manual_config() -> Result<serde_json::Value> {
+    let config_path = openclaw_core::paths::manual_config_path();
+    if !config_path.exists() {
+        anyhow::bail!("Config not found at {}", config_path.display());
+    }
+    let content = std::fs::read_to_string(&config_path)?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+fn config_providers(config: &serde_json::Value) -> Result<&serde_json::Map<String, serde_json::Value>> {
+    config
+        .get("models")
+        .and_then(|m| m.get("providers"))
+        .and_then(|p| p.as_object())
+        .ok_or_else(|| anyhow::anyhow!("No model providers in config"))
+}
+
+/// Build entries for "provider/model" specs, skipping any that don't resolve
+fn build_spec_entries(
+    providers: &serde_json::Map<String, serde_json::Value>,
+    specs: &[String],
+) -> Vec<(String, Box<dyn LlmProvider>)> {
+    specs
+        .iter()
+        .filter_map(|spec| {
+            let (provider_name, model_id) = parse_model_spec(spec)?;
+            build_provider_entry(&provider_name, &model_id, providers.get(&provider_name)?)
+        })
+        .collect()
+}
+
+/// Build a single provider for a "provider/model" spec
+pub(crate) fn build_spec_provider(config: &serde_json::Value, spec: &str) -> Result<Box<dyn LlmProvider>> {
+    build_spec_entries(config_providers(config)?, &[spec.to_string()])
+        .pop()
+        .map(|(_, provider)| provider)
+        .ok_or_else(|| anyhow::anyhow!("Model '{}' is not a usable provider/model", spec))
 }
 
 fn parse_model_spec(spec: &str) -> Option<(String, String)> {
@@ -134,12 +312,50 @@ fn build_provider_entry(
 ) -> Option<(String, Box<dyn LlmProvider>)> {
     let label = format!("{}/{}", provider_name, model_id);
 
+    let params = openclaw_core::models::resolve_generation_params(provider, model_id);
+
     if provider_name == "anthropic" {
         let api_key = provider.get("apiKey").and_then(|v| v.as_str())?;
-        return Some((label, Box::new(AnthropicProvider::new(api_key, model_id))));
+        let model = find_model_entry(provider, model_id);
+        let mut anthropic = AnthropicProvider::new(api_key, model_id);
+        if let Some(budget) = model.and_then(|m| m.get("thinkingBudget")).and_then(|v| v.as_u64()) {
+            anthropic = anthropic.with_thinking(budget as u32);
+        }
+        if let Some(caching) = provider.get("promptCaching").and_then(|v| v.as_bool()) {
+            anthropic = anthropic.with_prompt_caching(caching);
+        }
+        return Some((label, Box::new(anthropic.with_params(params))));
+    }
+
+    if is_gemini_provider(provider_name, provider) {
+        let api_key = provider.get("apiKey").and_then(|v| v.as_str())?;
+        let mut gemini = GeminiProvider::new(api_key, model_id);
+        if let Some(base_url) = provider.get("baseUrl").and_then(|v| v.as_str()) {
+            gemini = gemini.with_base_url(base_url);
+        }
+        return Some((label, Box::new(gemini.with_params(params))));
     }
 
     let base_url = provider.get("baseUrl").and_then(|v| v.as_str())?;
+
+    if provider.get("api").and_then(|v| v.as_str()) == Some("ollama") {
+        let model = find_model_entry(provider, model_id);
+        let mut ollama = OllamaProvider::new(base_url, model_id);
+        if let Some(ctx) = model.and_then(|m| m.get("contextWindow")).and_then(|v| v.as_u64()) {
+            ollama = ollama.with_num_ctx(ctx);
+        }
+        if let Some(keep_alive) = model
+            .and_then(|m| m.get("keepAlive"))
+            .or_else(|| provider.get("keepAlive"))
+        {
+            ollama = ollama.with_keep_alive(keep_alive.clone());
+        }
+        if let Some(auto_pull) = provider.get("autoPull").and_then(|v| v.as_bool()) {
+            ollama = ollama.with_auto_pull(auto_pull);
+        }
+        return Some((label, Box::new(ollama.with_params(params))));
+    }
+
     let api_key = provider
         .get("apiKey")
         .and_then(|v| v.as_str())
@@ -147,10 +363,24 @@ fn build_provider_entry(
 
     Some((
         label,
-        Box::new(OpenAiCompatibleProvider::new(base_url, api_key, model_id)),
+        Box::new(OpenAiCompatibleProvider::new(base_url, api_key, model_id).with_params(params)),
     ))
 }
 
+/// Gemini providers are selected by name or by `"api": "google-generative-ai"`
+fn is_gemini_provider(provider_name: &str, provider: &serde_json::Value) -> bool {
+    let api = provider.get("api").and_then(|v| v.as_str()).unwrap_or("");
+    matches!(api, "google-generative-ai" | "gemini") || matches!(provider_name, "google" | "gemini")
+}
+
+/// Find a model's entry in a provider's `models` array by ID
+fn find_model_entry<'a>(provider: &'a serde_json::Value, model_id: &str) -> Option<&'a serde_json::Value> {
+    provider
+        .get("models")
+        .and_then(|m| m.as_array())
+        .and_then(|arr| arr.iter().find(|m| m.get("id").and_then(|v| v.as_str()) == Some(model_id)))
+}
+
 fn build_first_model_entry(
     provider_name: &str,
     provider: &serde_json::Value,
@@ -168,12 +398,11 @@ fn build_first_model_entry(
 #[async_trait]
 impl LlmProvider for FallbackProvider {
     fn name(&self) -> &str {
-        // Return last successful model; falls back to "fallback-chain" if lock poisoned
-        // We leak a &str here since the trait requires &str — acceptable for a long-lived provider
-        let guard = self.last_successful.read().unwrap();
-        let name = guard.clone();
-        drop(guard);
-        Box::leak(name.into_boxed_str())
+        // Label of the last successful provider, so pricing follows the model that answered
+        self.entries
+            .get(self.last_successful.load(Ordering::Relaxed))
+            .map(|e| e.label.as_str())
+            .unwrap_or("fallback-chain")
     }
 
     async fn complete(
@@ -181,95 +410,38 @@ impl LlmProvider for FallbackProvider {
         messages: &[Message],
         tools: &[ToolDefinition],
     ) -> Result<(Completion, UsageStats)> {
-        let mut last_error = None;
-
-        for (i, entry) in self.entries.iter().enumerate() {
-            let failures = entry.consecutive_failures.load(Ordering::Relaxed);
-
-            // Skip providers with >3 consecutive failures (circuit breaker)
-            if failures > 3 {
-                info!(
-                    "Skipping {} (circuit open: {} consecutive failures)",
-                    entry.label, failures
-                );
-                continue;
-            }
-
-            let t_start = Instant::now();
-            crate::llm_log::set_provider_attempt((i + 1) as u32);
-            info!("Trying provider {}/{}: {}", i + 1, self.entries.len(), entry.label);
-
-            match entry.provider.complete(messages, tools).await {
-                Ok(result) => {
-                    let elapsed = t_start.elapsed().as_millis();
-                    entry.consecutive_failures.store(0, Ordering::Relaxed);
-                    if let Ok(mut last) = self.last_successful.write() {
-                        *last = entry.label.clone();
-                    }
-                    info!("{} succeeded in {}ms", entry.label, elapsed);
-                    return Ok(result);
-                }
-                Err(e) => {
-                    let elapsed = t_start.elapsed().as_millis();
-                    let new_failures = entry.consecutive_failures.fetch_add(1, Ordering::Relaxed) + 1;
-                    warn!(
-                        "{} failed in {}ms (attempt {}, consecutive failures: {}): {}",
-                        entry.label, elapsed, i + 1, new_failures, e
-                    );
-                    last_error = Some(e);
-                }
-            }
-        }
+        self.run_chain("completion", |p| p.complete(messages, tools), |_, _| {}).await
+    }
 
-        Err(last_error.unwrap_or_else(|| anyhow::anyhow!("All providers exhausted or circuit-broken")))
+    async fn complete_structured(
+        &self,
+        messages: &[Message],
+        schema: &serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        self.run_chain("structured", |p| p.complete_structured(messages, schema), |_, _| {})
+            .await
     }
 
     async fn complete_streaming(
         &self,
         messages: &[Message],
         tools: &[ToolDefinition],
-        event_tx: tokio::sync::mpsc::UnboundedSender<super::streaming::StreamEvent>,
-    ) -> Result<(super::Completion, super::UsageStats)> {
-        let mut last_error = None;
-
-        for (i, entry) in self.entries.iter().enumerate() {
-            let failures = entry.consecutive_failures.load(Ordering::Relaxed);
-
-            if failures > 3 {
-                info!(
-                    "Skipping {} (circuit open: {} consecutive failures)",
-                    entry.label, failures
-                );
-                continue;
-            }
-
-            let t_start = Instant::now();
-            crate::llm_log::set_provider_attempt((i + 1) as u32);
-            info!("Trying streaming provider {}/{}: {}", i + 1, self.entries.len(), entry.label);
-
-            match entry.provider.complete_streaming(messages, tools, event_tx.clone()).await {
-                Ok(result) => {
-                    let elapsed = t_start.elapsed().as_millis();
-                    entry.consecutive_failures.store(0, Ordering::Relaxed);
-                    if let Ok(mut last) = self.last_successful.write() {
-                        *last = entry.label.clone();
-                    }
-                    info!("{} streaming succeeded in {}ms", entry.label, elapsed);
-                    return Ok(result);
-                }
-                Err(e) => {
-                    let elapsed = t_start.elapsed().as_millis();
-                    let new_failures = entry.consecutive_failures.fetch_add(1, Ordering::Relaxed) + 1;
-                    warn!(
-                        "{} streaming failed in {}ms (consecutive failures: {}): {}",
-                        entry.label, elapsed, new_failures, e
-                    );
-                    last_error = Some(e);
-                }
-            }
-        }
-
-        Err(last_error.unwrap_or_else(|| anyhow::anyhow!("All streaming providers exhausted")))
+        event_tx: tokio::sync::mpsc::UnboundedSender<StreamEvent>,
+    ) -> Result<(Completion, UsageStats)> {
+        // A provider that dies mid-stream may already have emitted deltas; tell the
+        // consumer to discard them before the round restarts on the next provider.
+        // Failed attempts are logged with zero usage, so only the winner is counted.
+        self.run_chain(
+            "streaming",
+            |p| p.complete_streaming(messages, tools, event_tx.clone()),
+            |from, to| {
+                let _ = event_tx.send(StreamEvent::ProviderSwitched {
+                    from: from.to_string(),
+                    to: to.to_string(),
+                });
+            },
+        )
+        .await
     }
 }
 
@@ -289,6 +461,50 @@ mod tests {
         assert!(parse_model_spec("just-a-model").is_none());
     }
 
+    #[test]
+    fn test_find_model_entry() {
+        let provider = serde_json::json!({
+            "apiKey": "sk-ant",
+            "models": [
+                {"id": "claude-haiku-4"},
+                {"id": "claude-sonnet-4", "maxTokens": 16000, "thinkingBudget": 8000}
+            ]
+        });
+        let entry = find_model_entry(&provider, "claude-sonnet-4").unwrap();
+        assert_eq!(entry["thinkingBudget"], 8000);
+        assert!(find_model_entry(&provider, "claude-opus-4").is_none());
+
+        let (label, _) = build_provider_entry("anthropic", "claude-sonnet-4", &provider).unwrap();
+        assert_eq!(label, "anthropic/claude-sonnet-4");
+    }
+
+    #[test]
+    fn test_gemini_provider_selection() {
+        let by_api = serde_json::json!({"api": "google-generative-ai", "apiKey": "g-key"});
+        assert!(is_gemini_provider("my-google", &by_api));
+        assert!(is_gemini_provider("gemini", &serde_json::json!({})));
+        assert!(!is_gemini_provider("moonshot", &serde_json::json!({"api": "openai-completions"})));
+
+        let (label, provider) = build_provider_entry("my-google", "gemini-2.5-flash", &by_api).unwrap();
+        assert_eq!(label, "my-google/gemini-2.5-flash");
+        assert_eq!(provider.name(), "gemini-2.5-flash");
+        // No baseUrl needed, but an API key is
+        assert!(build_provider_entry("gemini", "gemini-2.5-flash", &serde_json::json!({})).is_none());
+    }
+
+    #[test]
+    fn test_ollama_api_selects_native_provider() {
+        let provider = serde_json::json!({
+            "baseUrl": "http://127.0.0.1:11434/v1",
+            "api": "ollama",
+            "keepAlive": "30m",
+            "models": [{"id": "qwen3:8b", "contextWindow": 32768}]
+        });
+        let (label, built) = build_provider_entry("ollama", "qwen3:8b", &provider).unwrap();
+        assert_eq!(label, "ollama/qwen3:8b");
+        assert_eq!(built.name(), "qwen3:8b");
+    }
+
     #[test]
     fn test_fallback_provider_labels() {
         let entries: Vec<(String, Box<dyn LlmProvider>)> = vec![
@@ -319,20 +535,158 @@ mod tests {
                 Box::new(OpenAiCompatibleProvider::new("http://localhost:2", "key", "b")),
             ),
         ];
-        let provider = FallbackProvider::new(entries);
+        let provider = FallbackProvider::new(entries).with_health_registry(Arc::new(HealthRegistry::new()));
+        let cfg = provider.breaker.clone();
 
         // Simulate 4 consecutive failures on primary (exceeds threshold of 3)
         for _ in 0..4 {
-            provider.entries[0].consecutive_failures.fetch_add(1, Ordering::Relaxed);
+            provider.health.record_failure("primary/model-a", &cfg, 100);
         }
-        assert_eq!(provider.entries[0].consecutive_failures.load(Ordering::Relaxed), 4);
+        let health = provider.health();
+        assert_eq!(health[0].consecutive_failures, 4);
+        assert_eq!(health[0].state, CircuitState::Open);
+        assert!(!provider.health.try_acquire("primary/model-a", &cfg));
 
         // Verify backup is still healthy
-        assert_eq!(provider.entries[1].consecutive_failures.load(Ordering::Relaxed), 0);
+        assert_eq!(health[1].consecutive_failures, 0);
+        assert_eq!(health[1].state, CircuitState::Closed);
+
+        // A success (e.g. the half-open probe) closes the circuit again
+        provider.health.record_success("primary/model-a", &cfg, 100);
+        assert_eq!(provider.health()[0].consecutive_failures, 0);
+        assert_eq!(provider.health()[0].state, CircuitState::Closed);
+    }
+
+    /// Succeeds or fails on demand and counts calls
+    struct FlakyProvider {
+        label: &'static str,
+        fail: std::sync::atomic::AtomicBool,
+        calls: std::sync::atomic::AtomicUsize,
+    }
+
+    impl FlakyProvider {
+        fn new(label: &'static str, fail: bool) -> Self {
+            Self {
+                label,
+                fail: std::sync::atomic::AtomicBool::new(fail),
+                calls: std::sync::atomic::AtomicUsize::new(0),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl LlmProvider for Arc<FlakyProvider> {
+        fn name(&self) -> &str {
+            self.label
+        }
+
+        async fn complete(&self, _: &[Message], _: &[ToolDefinition]) -> Result<(Completion, UsageStats)> {
+            use std::sync::atomic::Ordering;
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if self.fail.load(Ordering::SeqCst) {
+                anyhow::bail!("{} is down", self.label);
+            }
+            Ok((
+                Completion::Text { content: self.label.to_string(), reasoning: None },
+                UsageStats::default(),
+            ))
+        }
+
+        /// Emits a partial delta before failing, like a connection dropped mid-stream
+        async fn complete_streaming(
+            &self,
+            messages: &[Message],
+            tools: &[ToolDefinition],
+            event_tx: tokio::sync::mpsc::UnboundedSender<StreamEvent>,
+        ) -> Result<(Completion, UsageStats)> {
+            let _ = event_tx.send(StreamEvent::ContentDelta(format!("{} partial", self.label)));
+            self.complete(messages, tools).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_streaming_failover_emits_provider_switched() {
+        let primary = Arc::new(FlakyProvider::new("primary", true));
+        let backup = Arc::new(FlakyProvider::new("backup", false));
+        let provider = FallbackProvider::new(vec![
+            ("p/primary".to_string(), Box::new(primary) as Box<dyn LlmProvider>),
+            ("b/backup".to_string(), Box::new(backup) as Box<dyn LlmProvider>),
+        ])
+        .with_health_registry(Arc::new(HealthRegistry::new()));
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let (completion, _) = provider.complete_streaming(&[Message::user("hi")], &[], tx).await.unwrap();
+        let Completion::Text { content, .. } = completion else { panic!("expected text") };
+        assert_eq!(content, "backup");
+
+        let mut events = Vec::new();
+        while let Ok(evt) = rx.try_recv() {
+            events.push(format!("{:?}", evt));
+        }
+        assert_eq!(
+            events,
+            vec![
+                r#"ContentDelta("primary partial")"#,
+                r#"ProviderSwitched { from: "p/primary", to: "b/backup" }"#,
+                r#"ContentDelta("backup partial")"#,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_half_open_probe_recovers_primary() {
+        use std::sync::atomic::Ordering;
+        let primary = Arc::new(FlakyProvider::new("primary", true));
+        let backup = Arc::new(FlakyProvider::new("backup", false));
+        let provider = FallbackProvider::new(vec![
+            ("p/primary".to_string(), Box::new(primary.clone()) as Box<dyn LlmProvider>),
+            ("b/backup".to_string(), Box::new(backup.clone()) as Box<dyn LlmProvider>),
+        ])
+        .with_health_registry(Arc::new(HealthRegistry::new()))
+        .with_breaker(BreakerConfig {
+            max_consecutive_failures: 1,
+            cooldown: std::time::Duration::ZERO,
+            ..Default::default()
+        });
+
+        // Two failures open the primary's circuit; backup answers both times
+        for _ in 0..2 {
+            provider.complete(&[Message::user("hi")], &[]).await.unwrap();
+        }
+        assert_eq!(provider.health()[0].state, CircuitState::Open);
+        assert_eq!(provider.name(), "b/backup");
+
+        // Primary recovers; with zero cooldown the next request is a half-open probe
+        primary.fail.store(false, Ordering::SeqCst);
+        let (completion, _) = provider.complete(&[Message::user("hi")], &[]).await.unwrap();
+        let Completion::Text { content, .. } = completion else { panic!("expected text") };
+        assert_eq!(content, "primary");
+        assert_eq!(provider.name(), "p/primary");
+        assert_eq!(provider.health()[0].state, CircuitState::Closed);
+        assert_eq!(primary.calls.load(Ordering::SeqCst), 3);
+    }
 
-        // Reset simulates a successful call
-        provider.entries[0].consecutive_failures.store(0, Ordering::Relaxed);
-        assert_eq!(provider.entries[0].consecutive_failures.load(Ordering::Relaxed), 0);
+    #[tokio::test]
+    async fn test_fastest_healthy_ordering() {
+        let slow = Arc::new(FlakyProvider::new("slow", false));
+        let fast = Arc::new(FlakyProvider::new("fast", false));
+        let registry = Arc::new(HealthRegistry::new());
+        let cfg = BreakerConfig::default();
+        registry.record_success("s/slow", &cfg, 3000);
+        registry.record_success("f/fast", &cfg, 200);
+
+        let provider = FallbackProvider::new(vec![
+            ("s/slow".to_string(), Box::new(slow) as Box<dyn LlmProvider>),
+            ("f/fast".to_string(), Box::new(fast) as Box<dyn LlmProvider>),
+        ])
+        .with_health_registry(registry)
+        .with_order(ChainOrder::FastestHealthy);
+
+        let order: Vec<&str> = provider.ordered_entries().iter().map(|e| e.label.as_str()).collect();
+        assert_eq!(order, vec!["f/fast", "s/slow"]);
+        let (completion, _) = provider.complete(&[Message::user("hi")], &[]).await.unwrap();
+        let Completion::Text { content, .. } = completion else { panic!("expected text") };
+        assert_eq!(content, "fast");
     }
 
     #[test]
@@ -346,7 +700,6 @@ mod tests {
         let provider = FallbackProvider::new(entries);
 
         // Initial last_successful should be the first provider
-        let last = provider.last_successful.read().unwrap().clone();
-        assert_eq!(last, "primary/model-a");
+        assert_eq!(provider.name(), "primary/model-a");
     }
 }
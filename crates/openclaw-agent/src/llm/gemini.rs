@@ -0,0 +1,892 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use openclaw_core::models::GenerationParams;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tokio::sync::mpsc;
+
+use super::anthropic::{parse_data_url, thinking_budget_for_effort};
+use super::streaming::StreamEvent;
+use super::{Completion, FunctionCall, LlmProvider, Message, Role, ToolCall, ToolDefinition, UsageStats};
+
+pub const GEMINI_API_URL: &str = "https://generativelanguage.googleapis.com/v1beta";
+/// Max remembered thought signatures (keyed by tool call ID) before the cache is reset
+const MAX_SIGNATURE_CACHE: usize = 512;
+
+/// Native Google Gemini provider (`generateContent` / `streamGenerateContent`)
+pub struct GeminiProvider {
+    client: reqwest::Client,
+    base_url: String,
+    api_key: String,
+    model: String,
+    max_tokens: u32,
+    params: GenerationParams,
+    /// Thought signatures attached to function calls, keyed by tool call ID.
+    /// Gemini expects them echoed back on the functionCall parts of later turns.
+    thought_signatures: Mutex<HashMap<String, String>>,
+}
+
+impl GeminiProvider {
+    pub fn new(api_key: &str, model: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: GEMINI_API_URL.to_string(),
+            api_key: api_key.to_string(),
+            model: model.strip_prefix("models/").unwrap_or(model).to_string(),
+            max_tokens: 4096,
+            params: GenerationParams::default(),
+            thought_signatures: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = max_tokens;
+        self
+    }
+
+    /// Apply per-model generation params. `reasoningEffort` maps to a thinking budget.
+    pub fn with_params(mut self, params: GenerationParams) -> Self {
+        if let Some(max_tokens) = params.max_tokens {
+            self.max_tokens = max_tokens;
+        }
+        self.params = params;
+        self
+    }
+
+    fn endpoint(&self, stream: bool) -> String {
+        if stream {
+            format!("{}/models/{}:streamGenerateContent?alt=sse", self.base_url, self.model)
+        } else {
+            format!("{}/models/{}:generateContent", self.base_url, self.model)
+        }
+    }
+
+    fn build_request(&self, messages: &[Message], tools: &[ToolDefinition]) -> GeminiRequest {
+        let (system_instruction, contents) = {
+            let signatures = self.thought_signatures.lock().unwrap();
+            convert_messages(messages, &signatures)
+        };
+
+        let thinking_config = self
+            .params
+            .reasoning_effort
+            .as_deref()
+            .and_then(thinking_budget_for_effort)
+            .map(|budget| ThinkingConfig {
+                thinking_budget: budget,
+                include_thoughts: true,
+            });
+
+        GeminiRequest {
+            contents,
+            system_instruction,
+            tools: convert_tools(tools),
+            generation_config: GenerationConfig {
+                max_output_tokens: self.max_tokens,
+                temperature: self.params.temperature,
+                top_p: self.params.top_p,
+                stop_sequences: self.params.stop.clone(),
+                thinking_config,
+            },
+            extra: self.params.extra.clone(),
+        }
+    }
+
+    fn remember_signatures(&self, signatures: Vec<(String, String)>) {
+        if signatures.is_empty() {
+            return;
+        }
+        if let Ok(mut cache) = self.thought_signatures.lock() {
+            if cache.len() + signatures.len() > MAX_SIGNATURE_CACHE {
+                cache.clear();
+            }
+            cache.extend(signatures);
+        }
+    }
+
+    async fn post(&self, request: &GeminiRequest, stream: bool) -> Result<reqwest::Response> {
+        let response = self
+            .client
+            .post(self.endpoint(stream))
+            .header("x-goog-api-key", &self.api_key)
+            .header("Content-Type", "application/json")
+            .json(request)
+            .send()
+            .await
+            .context("Failed to send request to Gemini")?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!("Gemini API returned {}: {}", status, body);
+        }
+        Ok(response)
+    }
+}
+
+// ── Convert internal messages to Gemini format ──
+
+fn convert_messages(
+    messages: &[Message],
+    signatures: &HashMap<String, String>,
+) -> (Option<GeminiContent>, Vec<GeminiContent>) {
+    let mut system_parts = Vec::new();
+    let mut contents: Vec<GeminiContent> = Vec::new();
+    // functionResponse parts need the function name, which tool messages only carry by ID
+    let mut call_names: HashMap<String, String> = HashMap::new();
+
+    for msg in messages {
+        let (role, parts) = match msg.role {
+            Role::System => {
+                if let Some(text) = msg.content.as_deref().filter(|t| !t.is_empty()) {
+                    system_parts.push(GeminiPart::text(text));
+                }
+                continue;
+            }
+            Role::User => {
+                let mut parts = Vec::new();
+                parts.extend(msg.image_urls.iter().map(|url| image_part(url)));
+                if let Some(text) = msg.content.as_deref().filter(|t| !t.is_empty()) {
+                    parts.push(GeminiPart::text(text));
+                }
+                ("user", parts)
+            }
+            Role::Assistant => {
+                let mut parts = Vec::new();
+                if let Some(text) = msg.content.as_deref().filter(|t| !t.is_empty()) {
+                    parts.push(GeminiPart::text(text));
+                }
+                for call in msg.tool_calls.iter().flatten() {
+                    call_names.insert(call.id.clone(), call.function.name.clone());
+                    let args = serde_json::from_str(&call.function.arguments)
+                        .unwrap_or_else(|_| serde_json::json!({}));
+                    parts.push(GeminiPart {
+                        function_call: Some(GeminiFunctionCall {
+                            id: None,
+                            name: call.function.name.clone(),
+                            args,
+                        }),
+                        thought_signature: signatures.get(&call.id).cloned(),
+                        ..Default::default()
+                    });
+                }
+                ("model", parts)
+            }
+            Role::Tool => {
+                let call_id = msg.tool_call_id.clone().unwrap_or_default();
+                let name = call_names.get(&call_id).cloned().unwrap_or_else(|| "tool".to_string());
+                let part = GeminiPart {
+                    function_response: Some(GeminiFunctionResponse {
+                        name,
+                        response: serde_json::json!({ "content": msg.content.clone().unwrap_or_default() }),
+                    }),
+                    ..Default::default()
+                };
+                ("user", vec![part])
+            }
+        };
+
+        if parts.is_empty() {
+            continue;
+        }
+        // Gemini wants alternating turns — merge consecutive same-role contents
+        match contents.last_mut() {
+            Some(last) if last.role.as_deref() == Some(role) => last.parts.extend(parts),
+            _ => contents.push(GeminiContent {
+                role: Some(role.to_string()),
+                parts,
+            }),
+        }
+    }
+
+    let system = if system_parts.is_empty() {
+        None
+    } else {
+        Some(GeminiContent { role: None, parts: system_parts })
+    };
+    (system, contents)
+}
+
+/// Data URLs become inline parts; remote URLs become file references
+fn image_part(url: &str) -> GeminiPart {
+    if let Some((mime_type, data)) = parse_data_url(url) {
+        return GeminiPart {
+            inline_data: Some(InlineData { mime_type, data }),
+            ..Default::default()
+        };
+    }
+    let lower = url.to_lowercase();
+    let mime_type = if lower.ends_with(".png") {
+        "image/png"
+    } else if lower.ends_with(".webp") {
+        "image/webp"
+    } else if lower.ends_with(".gif") {
+        "image/gif"
+    } else {
+        "image/jpeg"
+    };
+    GeminiPart {
+        file_data: Some(FileData {
+            mime_type: mime_type.to_string(),
+            file_uri: url.to_string(),
+        }),
+        ..Default::default()
+    }
+}
+
+fn convert_tools(tools: &[ToolDefinition]) -> Vec<GeminiTools> {
+    if tools.is_empty() {
+        return Vec::new();
+    }
+    let declarations = tools
+        .iter()
+        .map(|t| {
+            let has_properties = t
+                .function
+                .parameters
+                .get("properties")
+                .and_then(|p| p.as_object())
+                .is_some_and(|p| !p.is_empty());
+            FunctionDeclaration {
+                name: t.function.name.clone(),
+                description: t.function.description.clone(),
+                // Gemini rejects OBJECT schemas without properties
+                parameters: has_properties.then(|| sanitize_schema(&t.function.parameters)),
+            }
+        })
+        .collect();
+    vec![GeminiTools { function_declarations: declarations }]
+}
+
+/// Strip JSON Schema keywords outside Gemini's OpenAPI subset
+fn sanitize_schema(schema: &serde_json::Value) -> serde_json::Value {
+    const UNSUPPORTED: &[&str] = &["$schema", "$id", "additionalProperties", "examples", "default"];
+    match schema {
+        serde_json::Value::Object(obj) => serde_json::Value::Object(
+            obj.iter()
+                .filter(|(k, _)| !UNSUPPORTED.contains(&k.as_str()))
+                .map(|(k, v)| {
+                    // Property names are user-defined — only recurse into their schemas
+                    if k == "properties" {
+                        let props = v
+                            .as_object()
+                            .map(|p| p.iter().map(|(name, s)| (name.clone(), sanitize_schema(s))).collect())
+                            .unwrap_or_default();
+                        (k.clone(), serde_json::Value::Object(props))
+                    } else {
+                        (k.clone(), sanitize_schema(v))
+                    }
+                })
+                .collect(),
+        ),
+        serde_json::Value::Array(arr) => serde_json::Value::Array(arr.iter().map(sanitize_schema).collect()),
+        other => other.clone(),
+    }
+}
+
+// ── Gemini API types ──
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiRequest {
+    contents: Vec<GeminiContent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system_instruction: Option<GeminiContent>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<GeminiTools>,
+    generation_config: GenerationConfig,
+    /// Provider-specific fields from `params.extra` (e.g. `safetySettings`)
+    #[serde(flatten)]
+    extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GenerationConfig {
+    max_output_tokens: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop_sequences: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thinking_config: Option<ThinkingConfig>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ThinkingConfig {
+    thinking_budget: u32,
+    include_thoughts: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct GeminiContent {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    role: Option<String>,
+    #[serde(default)]
+    parts: Vec<GeminiPart>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiPart {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+    /// Set on thought-summary parts when `includeThoughts` is on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    thought: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    thought_signature: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    inline_data: Option<InlineData>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    file_data: Option<FileData>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    function_call: Option<GeminiFunctionCall>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    function_response: Option<GeminiFunctionResponse>,
+}
+
+impl GeminiPart {
+    fn text(text: &str) -> Self {
+        Self {
+            text: Some(text.to_string()),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InlineData {
+    mime_type: String,
+    data: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FileData {
+    mime_type: String,
+    file_uri: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct GeminiFunctionCall {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    name: String,
+    #[serde(default)]
+    args: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct GeminiFunctionResponse {
+    name: String,
+    response: serde_json::Value,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiTools {
+    function_declarations: Vec<FunctionDeclaration>,
+}
+
+#[derive(Serialize)]
+struct FunctionDeclaration {
+    name: String,
+    description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parameters: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiResponse {
+    #[serde(default)]
+    candidates: Vec<GeminiCandidate>,
+    usage_metadata: Option<UsageMetadata>,
+    prompt_feedback: Option<PromptFeedback>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GeminiCandidate {
+    content: Option<GeminiContent>,
+    #[serde(rename = "finishReason", default)]
+    finish_reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PromptFeedback {
+    block_reason: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UsageMetadata {
+    #[serde(default)]
+    prompt_token_count: u32,
+    #[serde(default)]
+    candidates_token_count: u32,
+    #[serde(default)]
+    thoughts_token_count: u32,
+    #[serde(default)]
+    total_token_count: u32,
+    #[serde(default)]
+    cached_content_token_count: u32,
+}
+
+impl UsageMetadata {
+    /// Prompt tokens include cached content; thinking tokens count as completion
+    fn to_usage_stats(&self) -> UsageStats {
+        let completion_tokens = self.candidates_token_count + self.thoughts_token_count;
+        UsageStats {
+            prompt_tokens: self.prompt_token_count,
+            completion_tokens,
+            total_tokens: self.total_token_count.max(self.prompt_token_count + completion_tokens),
+            cache_read_tokens: self.cached_content_token_count,
+            cache_write_tokens: 0,
+        }
+    }
+}
+
+// ── Response assembly (shared by streaming and non-streaming) ──
+
+/// Completion, usage, and (tool call ID, thought signature) pairs
+type AssembledResponse = (Completion, UsageStats, Vec<(String, String)>);
+
+#[derive(Default)]
+struct ResponseAccumulator {
+    content: String,
+    reasoning: String,
+    tool_calls: Vec<ToolCall>,
+    /// (tool call ID, thought signature)
+    signatures: Vec<(String, String)>,
+    usage: UsageStats,
+    block_reason: Option<String>,
+    /// A candidate carried a finishReason (the stream wasn't cut off)
+    finished: bool,
+}
+
+impl ResponseAccumulator {
+    fn push(&mut self, resp: GeminiResponse, event_tx: Option<&mpsc::UnboundedSender<StreamEvent>>) {
+        if let Some(usage) = resp.usage_metadata {
+            self.usage = usage.to_usage_stats();
+        }
+        if let Some(reason) = resp.prompt_feedback.and_then(|f| f.block_reason) {
+            self.block_reason = Some(reason);
+        }
+
+        let candidate = resp.candidates.into_iter().next();
+        self.finished |= candidate.as_ref().is_some_and(|c| c.finish_reason.is_some());
+        let parts = candidate
+            .and_then(|c| c.content)
+            .map(|c| c.parts)
+            .unwrap_or_default();
+
+        for part in parts {
+            if let Some(call) = part.function_call {
+                let id = call
+                    .id
+                    .unwrap_or_else(|| format!("call_{}", uuid::Uuid::new_v4().simple()));
+                if let Some(tx) = event_tx {
+                    let _ = tx.send(StreamEvent::ToolCallStart { name: call.name.clone() });
+                }
+                if let Some(signature) = part.thought_signature {
+                    self.signatures.push((id.clone(), signature));
+                }
+                self.tool_calls.push(ToolCall {
+                    id,
+                    call_type: "function".to_string(),
+                    function: FunctionCall {
+                        name: call.name,
+                        arguments: call.args.to_string(),
+                    },
+                });
+            } else if let Some(text) = part.text {
+                if part.thought == Some(true) {
+                    if let Some(tx) = event_tx {
+                        let _ = tx.send(StreamEvent::ReasoningDelta(text.clone()));
+                    }
+                    self.reasoning.push_str(&text);
+                } else {
+                    if let Some(tx) = event_tx {
+                        let _ = tx.send(StreamEvent::ContentDelta(text.clone()));
+                    }
+                    self.content.push_str(&text);
+                }
+            }
+        }
+    }
+
+    fn finish(self) -> Result<AssembledResponse> {
+        if let Some(reason) = self.block_reason {
+            if self.content.is_empty() && self.tool_calls.is_empty() {
+                anyhow::bail!("Gemini blocked the prompt: {}", reason);
+            }
+        }
+
+        let reasoning = if self.reasoning.is_empty() { None } else { Some(self.reasoning) };
+        let completion = if self.tool_calls.is_empty() {
+            Completion::Text {
+                content: self.content,
+                reasoning,
+            }
+        } else {
+            Completion::ToolCalls {
+                calls: self.tool_calls,
+                reasoning,
+            }
+        };
+        Ok((completion, self.usage, self.signatures))
+    }
+}
+
+// ── Provider impl ──
+
+#[async_trait]
+impl LlmProvider for GeminiProvider {
+    fn name(&self) -> &str {
+        &self.model
+    }
+
+    async fn complete(
+        &self,
+        messages: &[Message],
+        tools: &[ToolDefinition],
+    ) -> Result<(Completion, UsageStats)> {
+        let request = self.build_request(messages, tools);
+        let t_start = std::time::Instant::now();
+        let mut log_entry = new_log_entry(&self.model, messages, false);
+
+        let result = async {
+            let response = self.post(&request, false).await?;
+            let api_response: GeminiResponse = response
+                .json()
+                .await
+                .context("Failed to parse Gemini response")?;
+            let mut acc = ResponseAccumulator::default();
+            acc.push(api_response, None);
+            acc.finish()
+        }
+        .await;
+
+        log_entry.latency_ms = t_start.elapsed().as_millis() as u64;
+        self.finish_call(log_entry, result)
+    }
+
+    async fn complete_streaming(
+        &self,
+        messages: &[Message],
+        tools: &[ToolDefinition],
+        event_tx: mpsc::UnboundedSender<StreamEvent>,
+    ) -> Result<(Completion, UsageStats)> {
+        let request = self.build_request(messages, tools);
+        let t_start = std::time::Instant::now();
+        let mut log_entry = new_log_entry(&self.model, messages, true);
+
+        let result = async {
+            let response = self.post(&request, true).await?;
+            let mut acc = ResponseAccumulator::default();
+            let mut stream = response.bytes_stream();
+            let mut buffer = String::new();
+
+            while let Some(chunk) = stream.next().await {
+                let chunk = chunk.context("Gemini stream read error")?;
+                buffer.push_str(&String::from_utf8_lossy(&chunk));
+
+                while let Some(line_end) = buffer.find('\n') {
+                    let line = buffer[..line_end].trim().to_string();
+                    buffer = buffer[line_end + 1..].to_string();
+
+                    if let Some(json_str) = line.strip_prefix("data:") {
+                        if let Ok(resp) = serde_json::from_str::<GeminiResponse>(json_str.trim()) {
+                            acc.push(resp, Some(&event_tx));
+                        }
+                    }
+                }
+            }
+
+            if !acc.finished && acc.block_reason.is_none() {
+                anyhow::bail!("Gemini stream ended before finishReason");
+            }
+
+            // NOTE: Do NOT send StreamEvent::Done here — the runtime controls Done
+            acc.finish()
+        }
+        .await;
+
+        log_entry.latency_ms = t_start.elapsed().as_millis() as u64;
+        self.finish_call(log_entry, result)
+    }
+}
+
+impl GeminiProvider {
+    /// Record the LLM call log entry and stash thought signatures for the next round
+    fn finish_call(
+        &self,
+        mut log_entry: crate::llm_log::LlmLogEntry,
+        result: Result<AssembledResponse>,
+    ) -> Result<(Completion, UsageStats)> {
+        match result {
+            Ok((completion, usage, signatures)) => {
+                self.remember_signatures(signatures);
+                log_entry.usage_prompt_tokens = usage.prompt_tokens;
+                log_entry.usage_completion_tokens = usage.completion_tokens;
+                log_entry.usage_total_tokens = usage.total_tokens;
+                log_entry.usage_cache_read_tokens = usage.cache_read_tokens;
+                match &completion {
+                    Completion::Text { content, reasoning } => {
+                        log_entry.response_content = Some(content.clone());
+                        log_entry.response_reasoning = reasoning.clone();
+                    }
+                    Completion::ToolCalls { calls, reasoning } => {
+                        log_entry.response_tool_calls = calls.len();
+                        log_entry.tool_call_names = calls.iter().map(|c| c.function.name.clone()).collect();
+                        log_entry.response_reasoning = reasoning.clone();
+                    }
+                }
+                crate::llm_log::record(log_entry);
+                Ok((completion, usage))
+            }
+            Err(e) => {
+                log_entry.error = Some(format!("{}", e));
+                crate::llm_log::record(log_entry);
+                Err(e)
+            }
+        }
+    }
+}
+
+fn new_log_entry(model: &str, messages: &[Message], streaming: bool) -> crate::llm_log::LlmLogEntry {
+    let mut log_entry = crate::llm_log::LlmLogEntry::new(model);
+    log_entry.messages_count = messages.len();
+    log_entry.streaming = streaming;
+    log_entry.request_tokens_est = crate::tokenizer::for_model(model).count_messages(messages) as u32;
+    log_entry
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::mock_server::{MockRoute, MockServer};
+    use super::super::FunctionDefinition;
+    use super::*;
+
+    fn exec_tool() -> ToolDefinition {
+        ToolDefinition {
+            tool_type: "function".to_string(),
+            function: FunctionDefinition {
+                name: "exec".to_string(),
+                description: "Run a command".to_string(),
+                parameters: serde_json::json!({
+                    "$schema": "http://json-schema.org/draft-07/schema#",
+                    "type": "object",
+                    "properties": {"command": {"type": "string", "default": "ls"}},
+                    "required": ["command"],
+                    "additionalProperties": false
+                }),
+            },
+        }
+    }
+
+    fn tool_call(id: &str, name: &str, args: &str) -> ToolCall {
+        ToolCall {
+            id: id.to_string(),
+            call_type: "function".to_string(),
+            function: FunctionCall {
+                name: name.to_string(),
+                arguments: args.to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_convert_messages_roles_and_tool_results() {
+        let messages = vec![
+            Message::system("Be brief."),
+            Message::user_with_images("What is this?", vec!["data:image/png;base64,iVBOR".to_string()]),
+            Message::assistant_tool_calls(vec![tool_call("c1", "exec", r#"{"command":"ls"}"#)], None),
+            Message::tool_result("c1", "file.txt"),
+        ];
+        let signatures = HashMap::from([("c1".to_string(), "sig-1".to_string())]);
+        let (system, contents) = convert_messages(&messages, &signatures);
+
+        assert_eq!(system.unwrap().parts[0].text.as_deref(), Some("Be brief."));
+        assert_eq!(contents.len(), 3);
+        assert_eq!(contents[0].role.as_deref(), Some("user"));
+        assert_eq!(contents[0].parts[0].inline_data.as_ref().unwrap().mime_type, "image/png");
+        assert_eq!(contents[0].parts[1].text.as_deref(), Some("What is this?"));
+
+        let call_part = &contents[1].parts[0];
+        assert_eq!(contents[1].role.as_deref(), Some("model"));
+        assert_eq!(call_part.function_call.as_ref().unwrap().args["command"], "ls");
+        assert_eq!(call_part.thought_signature.as_deref(), Some("sig-1"));
+
+        let response = contents[2].parts[0].function_response.as_ref().unwrap();
+        assert_eq!(response.name, "exec");
+        assert_eq!(response.response["content"], "file.txt");
+    }
+
+    #[test]
+    fn test_consecutive_tool_results_merged() {
+        let messages = vec![
+            Message::user("go"),
+            Message::assistant_tool_calls(
+                vec![tool_call("a", "exec", "{}"), tool_call("b", "read", "{}")],
+                None,
+            ),
+            Message::tool_result("a", "one"),
+            Message::tool_result("b", "two"),
+        ];
+        let (_, contents) = convert_messages(&messages, &HashMap::new());
+        assert_eq!(contents.len(), 3);
+        assert_eq!(contents[2].parts.len(), 2);
+        assert_eq!(contents[2].parts[1].function_response.as_ref().unwrap().name, "read");
+    }
+
+    #[test]
+    fn test_convert_tools_sanitizes_schema() {
+        let json = serde_json::to_value(convert_tools(&[exec_tool()])).unwrap();
+        let decl = &json[0]["functionDeclarations"][0];
+        assert_eq!(decl["name"], "exec");
+        assert!(decl["parameters"].get("$schema").is_none());
+        assert!(decl["parameters"].get("additionalProperties").is_none());
+        assert!(decl["parameters"]["properties"]["command"].get("default").is_none());
+        assert_eq!(decl["parameters"]["required"][0], "command");
+    }
+
+    #[test]
+    fn test_request_generation_config() {
+        let provider = GeminiProvider::new("key", "models/gemini-2.5-flash").with_params(GenerationParams {
+            temperature: Some(0.2),
+            max_tokens: Some(1000),
+            reasoning_effort: Some("low".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(provider.name(), "gemini-2.5-flash");
+        let json = serde_json::to_value(provider.build_request(&[Message::user("hi")], &[])).unwrap();
+        assert_eq!(json["generationConfig"]["maxOutputTokens"], 1000);
+        assert_eq!(json["generationConfig"]["temperature"], serde_json::json!(0.2f32));
+        assert_eq!(json["generationConfig"]["thinkingConfig"]["thinkingBudget"], 1024);
+        assert!(json.get("tools").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_complete_against_mock_server() {
+        let server = MockServer::start(vec![MockRoute::json(
+            ":generateContent",
+            &serde_json::json!({
+                "candidates": [{"content": {"role": "model", "parts": [
+                    {"text": "Checking the files.", "thought": true},
+                    {"functionCall": {"name": "exec", "args": {"command": "ls"}}, "thoughtSignature": "sig-x"}
+                ]}}],
+                "usageMetadata": {"promptTokenCount": 50, "candidatesTokenCount": 10,
+                                  "thoughtsTokenCount": 5, "totalTokenCount": 65, "cachedContentTokenCount": 20}
+            }),
+        )])
+        .await;
+
+        let provider = GeminiProvider::new("test-key", "gemini-2.5-flash").with_base_url(&server.base_url);
+        let (completion, usage) = provider.complete(&[Message::user("list files")], &[exec_tool()]).await.unwrap();
+
+        let Completion::ToolCalls { calls, reasoning } = completion else { panic!("expected tool calls") };
+        assert_eq!(calls[0].function.name, "exec");
+        assert_eq!(calls[0].function.arguments, r#"{"command":"ls"}"#);
+        assert_eq!(reasoning.as_deref(), Some("Checking the files."));
+        assert_eq!(usage.completion_tokens, 15);
+        assert_eq!(usage.cache_read_tokens, 20);
+        assert_eq!(
+            provider.thought_signatures.lock().unwrap().get(&calls[0].id).map(String::as_str),
+            Some("sig-x")
+        );
+
+        let requests = server.requests();
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].path, "/models/gemini-2.5-flash:generateContent");
+        assert_eq!(requests[0].header("x-goog-api-key"), Some("test-key"));
+        assert_eq!(requests[0].json()["contents"][0]["parts"][0]["text"], "list files");
+    }
+
+    #[tokio::test]
+    async fn test_complete_streaming_against_mock_server() {
+        let sse = [
+            r#"data: {"candidates":[{"content":{"role":"model","parts":[{"text":"Hel"}]}}]}"#,
+            "",
+            r#"data: {"candidates":[{"content":{"role":"model","parts":[{"text":"lo!"}]},"finishReason":"STOP"}],"usageMetadata":{"promptTokenCount":7,"candidatesTokenCount":2,"totalTokenCount":9}}"#,
+            "",
+        ]
+        .join("\n");
+        let server = MockServer::start(vec![MockRoute::new(
+            ":streamGenerateContent",
+            200,
+            "text/event-stream",
+            &sse,
+        )])
+        .await;
+
+        let provider = GeminiProvider::new("k", "gemini-2.5-pro").with_base_url(&server.base_url);
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let (completion, usage) = provider
+            .complete_streaming(&[Message::user("hi")], &[], tx)
+            .await
+            .unwrap();
+
+        let Completion::Text { content, .. } = completion else { panic!("expected text") };
+        assert_eq!(content, "Hello!");
+        assert_eq!(usage.total_tokens, 9);
+        let mut deltas = Vec::new();
+        while let Ok(StreamEvent::ContentDelta(d)) = rx.try_recv() {
+            deltas.push(d);
+        }
+        assert_eq!(deltas, vec!["Hel", "lo!"]);
+        assert!(server.requests()[0].path.ends_with(":streamGenerateContent?alt=sse"));
+    }
+
+    #[tokio::test]
+    async fn test_truncated_stream_is_an_error() {
+        let sse = "data: {\"candidates\":[{\"content\":{\"role\":\"model\",\"parts\":[{\"text\":\"Hel\"}]}}]}\n\n";
+        let server =
+            MockServer::start(vec![MockRoute::new(":streamGenerateContent", 200, "text/event-stream", sse)]).await;
+
+        let provider = GeminiProvider::new("k", "gemini-2.5-pro").with_base_url(&server.base_url);
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let err = provider
+            .complete_streaming(&[Message::user("hi")], &[], tx)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("finishReason"));
+    }
+
+    #[tokio::test]
+    async fn test_http_error_surfaces() {
+        let server = MockServer::start(vec![MockRoute::new(
+            ":generateContent",
+            400,
+            "application/json",
+            r#"{"error":{"message":"API key not valid"}}"#,
+        )])
+        .await;
+        let provider = GeminiProvider::new("bad", "gemini-2.5-flash").with_base_url(&server.base_url);
+        let err = provider.complete(&[Message::user("hi")], &[]).await.unwrap_err();
+        assert!(err.to_string().contains("API key not valid"));
+    }
+
+    #[test]
+    fn test_blocked_prompt_is_error() {
+        let mut acc = ResponseAccumulator::default();
+        acc.push(
+            serde_json::from_value(serde_json::json!({"promptFeedback": {"blockReason": "SAFETY"}})).unwrap(),
+            None,
+        );
+        assert!(acc.finish().unwrap_err().to_string().contains("SAFETY"));
+    }
+}
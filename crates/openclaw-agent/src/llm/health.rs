@@ -0,0 +1,483 @@
+//! Per-provider circuit breakers and rolling health stats for the fallback chain.
+//!
+//! `FallbackProvider` is rebuilt from config for most requests, so breaker state lives
+//! in a process-wide registry keyed by provider label ("provider/model").
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Circuit breaker tuning (`models.circuitBreaker` in openclaw-manual.json)
+#[derive(Debug, Clone, PartialEq)]
+pub struct BreakerConfig {
+    /// Open the circuit after more than this many consecutive failures
+    pub max_consecutive_failures: usize,
+    /// How long an open circuit waits before letting a single probe through
+    pub cooldown: Duration,
+    /// Number of recent calls kept for latency / error-rate stats
+    pub window_size: usize,
+    /// Open the circuit when the windowed error rate reaches this (0.0–1.0)
+    pub max_error_rate: f64,
+    /// Minimum calls in the window before the error rate can open the circuit
+    pub min_samples: usize,
+}
+
+impl Default for BreakerConfig {
+    fn default() -> Self {
+        Self {
+            max_consecutive_failures: 3,
+            cooldown: Duration::from_secs(30),
+            window_size: 20,
+            max_error_rate: 0.5,
+            min_samples: 10,
+        }
+    }
+}
+
+impl BreakerConfig {
+    /// Parse `{"failureThreshold", "cooldownSecs", "windowSize", "maxErrorRate", "minSamples"}`
+    pub fn from_json(value: Option<&serde_json::Value>) -> Self {
+        let mut cfg = Self::default();
+        let Some(v) = value else { return cfg };
+        if let Some(n) = v.get("failureThreshold").and_then(|x| x.as_u64()) {
+            cfg.max_consecutive_failures = n as usize;
+        }
+        if let Some(n) = v.get("cooldownSecs").and_then(|x| x.as_u64()) {
+            cfg.cooldown = Duration::from_secs(n);
+        }
+        if let Some(n) = v.get("windowSize").and_then(|x| x.as_u64()) {
+            cfg.window_size = (n as usize).max(1);
+        }
+        if let Some(r) = v.get("maxErrorRate").and_then(|x| x.as_f64()) {
+            cfg.max_error_rate = r;
+        }
+        if let Some(n) = v.get("minSamples").and_then(|x| x.as_u64()) {
+            cfg.min_samples = n as usize;
+        }
+        cfg
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Healthy — requests flow
+    Closed,
+    /// Failing — requests skipped until the cooldown elapses
+    Open,
+    /// Cooldown elapsed — one probe request decides whether to close or re-open
+    HalfOpen,
+}
+
+impl CircuitState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Closed => "closed",
+            Self::Open => "open",
+            Self::HalfOpen => "half-open",
+        }
+    }
+
+    /// Numeric value for Prometheus gauges (0 = closed, 1 = half-open, 2 = open)
+    pub fn as_gauge(&self) -> u8 {
+        match self {
+            Self::Closed => 0,
+            Self::HalfOpen => 1,
+            Self::Open => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    latency_ms: u64,
+    ok: bool,
+}
+
+/// Breaker state and rolling stats for one provider
+#[derive(Debug)]
+struct ProviderHealth {
+    state: CircuitState,
+    consecutive_failures: usize,
+    opened_at: Option<Instant>,
+    probe_in_flight: bool,
+    window: VecDeque<Sample>,
+    total_successes: u64,
+    total_failures: u64,
+}
+
+impl ProviderHealth {
+    fn new() -> Self {
+        Self {
+            state: CircuitState::Closed,
+            consecutive_failures: 0,
+            opened_at: None,
+            probe_in_flight: false,
+            window: VecDeque::new(),
+            total_successes: 0,
+            total_failures: 0,
+        }
+    }
+
+    /// Whether a request may be sent now. Moves Open → HalfOpen once the cooldown has passed.
+    fn try_acquire(&mut self, cfg: &BreakerConfig, now: Instant) -> bool {
+        match self.state {
+            CircuitState::Closed => true,
+            CircuitState::Open => {
+                let cooled = self.opened_at.is_none_or(|t| now.duration_since(t) >= cfg.cooldown);
+                if cooled {
+                    self.state = CircuitState::HalfOpen;
+                    self.probe_in_flight = true;
+                }
+                cooled
+            }
+            CircuitState::HalfOpen => {
+                if self.probe_in_flight {
+                    false
+                } else {
+                    self.probe_in_flight = true;
+                    true
+                }
+            }
+        }
+    }
+
+    /// Give up a half-open probe slot without recording an outcome
+    fn release_probe(&mut self) {
+        self.probe_in_flight = false;
+    }
+
+    fn push_sample(&mut self, cfg: &BreakerConfig, sample: Sample) {
+        self.window.push_back(sample);
+        while self.window.len() > cfg.window_size {
+            self.window.pop_front();
+        }
+    }
+
+    fn record_success(&mut self, cfg: &BreakerConfig, latency_ms: u64) {
+        self.push_sample(cfg, Sample { latency_ms, ok: true });
+        self.total_successes += 1;
+        self.consecutive_failures = 0;
+        self.probe_in_flight = false;
+        self.opened_at = None;
+        self.state = CircuitState::Closed;
+    }
+
+    fn record_failure(&mut self, cfg: &BreakerConfig, latency_ms: u64, now: Instant) {
+        self.push_sample(cfg, Sample { latency_ms, ok: false });
+        self.total_failures += 1;
+        self.consecutive_failures += 1;
+        self.probe_in_flight = false;
+
+        let error_rate_tripped =
+            self.window.len() >= cfg.min_samples && self.error_rate() >= cfg.max_error_rate;
+        if self.state == CircuitState::HalfOpen
+            || self.consecutive_failures > cfg.max_consecutive_failures
+            || error_rate_tripped
+        {
+            self.state = CircuitState::Open;
+            self.opened_at = Some(now);
+        }
+    }
+
+    fn error_rate(&self) -> f64 {
+        if self.window.is_empty() {
+            return 0.0;
+        }
+        self.window.iter().filter(|s| !s.ok).count() as f64 / self.window.len() as f64
+    }
+
+    /// Mean latency of successful calls in the window
+    fn avg_latency_ms(&self) -> Option<u64> {
+        let ok: Vec<u64> = self.window.iter().filter(|s| s.ok).map(|s| s.latency_ms).collect();
+        if ok.is_empty() {
+            None
+        } else {
+            Some(ok.iter().sum::<u64>() / ok.len() as u64)
+        }
+    }
+}
+
+/// Point-in-time view of a provider's health, for `/model`, `/status` and `/metrics`
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct HealthSnapshot {
+    pub label: String,
+    #[serde(serialize_with = "serialize_state")]
+    pub state: CircuitState,
+    pub consecutive_failures: usize,
+    /// Mean latency of successful calls in the rolling window
+    pub avg_latency_ms: Option<u64>,
+    /// Failure fraction over the rolling window (0.0–1.0)
+    pub error_rate: f64,
+    pub window_samples: usize,
+    pub total_successes: u64,
+    pub total_failures: u64,
+}
+
+fn serialize_state<S: serde::Serializer>(state: &CircuitState, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(state.as_str())
+}
+
+impl HealthSnapshot {
+    fn unused(label: &str) -> Self {
+        Self {
+            label: label.to_string(),
+            state: CircuitState::Closed,
+            consecutive_failures: 0,
+            avg_latency_ms: None,
+            error_rate: 0.0,
+            window_samples: 0,
+            total_successes: 0,
+            total_failures: 0,
+        }
+    }
+
+    /// Compact one-line summary, e.g. "closed · 850ms · 5% err"
+    pub fn summary(&self) -> String {
+        let latency = self
+            .avg_latency_ms
+            .map(|ms| format!("{}ms", ms))
+            .unwrap_or_else(|| "no data".to_string());
+        format!("{} · {} · {:.0}% err", self.state.as_str(), latency, self.error_rate * 100.0)
+    }
+}
+
+/// An admitted call; record its outcome with `success` or `failure`
+pub struct CallGuard<'a> {
+    registry: &'a HealthRegistry,
+    label: &'a str,
+    cfg: &'a BreakerConfig,
+    recorded: bool,
+}
+
+impl CallGuard<'_> {
+    pub fn success(mut self, latency_ms: u64) {
+        self.recorded = true;
+        self.registry.record_success(self.label, self.cfg, latency_ms);
+    }
+
+    pub fn failure(mut self, latency_ms: u64) {
+        self.recorded = true;
+        self.registry.record_failure(self.label, self.cfg, latency_ms);
+    }
+}
+
+impl Drop for CallGuard<'_> {
+    fn drop(&mut self) {
+        if !self.recorded {
+            if let Some(h) = self.registry.providers.lock().unwrap().get_mut(self.label) {
+                h.release_probe();
+            }
+        }
+    }
+}
+
+/// Breaker state for every provider label seen by this process
+#[derive(Default)]
+pub struct HealthRegistry {
+    providers: Mutex<HashMap<String, ProviderHealth>>,
+}
+
+static GLOBAL_REGISTRY: OnceLock<Arc<HealthRegistry>> = OnceLock::new();
+
+impl HealthRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The process-wide registry shared by all fallback chains
+    pub fn global() -> Arc<HealthRegistry> {
+        GLOBAL_REGISTRY.get_or_init(|| Arc::new(HealthRegistry::new())).clone()
+    }
+
+    pub fn try_acquire(&self, label: &str, cfg: &BreakerConfig) -> bool {
+        let mut map = self.providers.lock().unwrap();
+        map.entry(label.to_string())
+            .or_insert_with(ProviderHealth::new)
+            .try_acquire(cfg, Instant::now())
+    }
+
+    /// Like `try_acquire`, but the returned guard frees a half-open probe slot
+    /// if it is dropped before an outcome is recorded (e.g. the call was cancelled)
+    pub fn acquire<'a>(&'a self, label: &'a str, cfg: &'a BreakerConfig) -> Option<CallGuard<'a>> {
+        self.try_acquire(label, cfg).then_some(CallGuard { registry: self, label, cfg, recorded: false })
+    }
+
+    pub fn record_success(&self, label: &str, cfg: &BreakerConfig, latency_ms: u64) {
+        let mut map = self.providers.lock().unwrap();
+        map.entry(label.to_string())
+            .or_insert_with(ProviderHealth::new)
+            .record_success(cfg, latency_ms);
+    }
+
+    pub fn record_failure(&self, label: &str, cfg: &BreakerConfig, latency_ms: u64) {
+        let mut map = self.providers.lock().unwrap();
+        map.entry(label.to_string())
+            .or_insert_with(ProviderHealth::new)
+            .record_failure(cfg, latency_ms, Instant::now());
+    }
+
+    pub fn snapshot(&self, label: &str) -> HealthSnapshot {
+        let map = self.providers.lock().unwrap();
+        match map.get(label) {
+            Some(h) => HealthSnapshot {
+                label: label.to_string(),
+                state: h.state,
+                consecutive_failures: h.consecutive_failures,
+                avg_latency_ms: h.avg_latency_ms(),
+                error_rate: h.error_rate(),
+                window_samples: h.window.len(),
+                total_successes: h.total_successes,
+                total_failures: h.total_failures,
+            },
+            None => HealthSnapshot::unused(label),
+        }
+    }
+
+    /// Snapshots for every provider that has been called, sorted by label
+    pub fn snapshot_all(&self) -> Vec<HealthSnapshot> {
+        let mut labels: Vec<String> = self.providers.lock().unwrap().keys().cloned().collect();
+        labels.sort();
+        labels.iter().map(|l| self.snapshot(l)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cfg() -> BreakerConfig {
+        BreakerConfig {
+            cooldown: Duration::from_secs(10),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_opens_after_consecutive_failures() {
+        let cfg = cfg();
+        let now = Instant::now();
+        let mut h = ProviderHealth::new();
+        for _ in 0..3 {
+            h.record_failure(&cfg, 100, now);
+        }
+        assert_eq!(h.state, CircuitState::Closed);
+        h.record_failure(&cfg, 100, now);
+        assert_eq!(h.state, CircuitState::Open);
+        assert!(!h.try_acquire(&cfg, now + Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn test_half_open_probe_closes_on_success() {
+        let cfg = cfg();
+        let now = Instant::now();
+        let mut h = ProviderHealth::new();
+        for _ in 0..4 {
+            h.record_failure(&cfg, 100, now);
+        }
+
+        let later = now + Duration::from_secs(11);
+        assert!(h.try_acquire(&cfg, later));
+        assert_eq!(h.state, CircuitState::HalfOpen);
+        // Only one probe at a time
+        assert!(!h.try_acquire(&cfg, later));
+
+        h.record_success(&cfg, 200);
+        assert_eq!(h.state, CircuitState::Closed);
+        assert_eq!(h.consecutive_failures, 0);
+        assert!(h.try_acquire(&cfg, later));
+    }
+
+    #[test]
+    fn test_half_open_probe_failure_reopens() {
+        let cfg = cfg();
+        let now = Instant::now();
+        let mut h = ProviderHealth::new();
+        for _ in 0..4 {
+            h.record_failure(&cfg, 100, now);
+        }
+        let later = now + Duration::from_secs(11);
+        assert!(h.try_acquire(&cfg, later));
+        h.record_failure(&cfg, 100, later);
+        assert_eq!(h.state, CircuitState::Open);
+        assert!(!h.try_acquire(&cfg, later + Duration::from_secs(5)));
+        assert!(h.try_acquire(&cfg, later + Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn test_dropped_probe_is_released() {
+        let registry = HealthRegistry::new();
+        let cfg = BreakerConfig { cooldown: Duration::ZERO, ..Default::default() };
+        for _ in 0..4 {
+            registry.record_failure("p/m", &cfg, 100);
+        }
+        let probe = registry.acquire("p/m", &cfg).expect("cooled down");
+        assert_eq!(registry.snapshot("p/m").state, CircuitState::HalfOpen);
+        assert!(registry.acquire("p/m", &cfg).is_none());
+
+        // A cancelled call drops its guard without an outcome
+        drop(probe);
+        let probe = registry.acquire("p/m", &cfg).expect("probe slot released");
+        probe.success(50);
+        assert_eq!(registry.snapshot("p/m").state, CircuitState::Closed);
+    }
+
+    #[test]
+    fn test_error_rate_window_trips_breaker() {
+        let cfg = BreakerConfig {
+            window_size: 10,
+            min_samples: 10,
+            max_error_rate: 0.5,
+            ..cfg()
+        };
+        let now = Instant::now();
+        let mut h = ProviderHealth::new();
+        // Alternate so consecutive failures never exceed the threshold
+        for i in 0..10 {
+            if i % 2 == 0 {
+                h.record_success(&cfg, 100);
+            } else {
+                h.record_failure(&cfg, 100, now);
+            }
+        }
+        assert_eq!(h.window.len(), 10);
+        assert_eq!(h.state, CircuitState::Open);
+    }
+
+    #[test]
+    fn test_rolling_latency() {
+        let cfg = BreakerConfig { window_size: 3, ..cfg() };
+        let mut h = ProviderHealth::new();
+        for ms in [100, 200, 300, 400] {
+            h.record_success(&cfg, ms);
+        }
+        assert_eq!(h.avg_latency_ms(), Some(300));
+        h.record_failure(&cfg, 5000, Instant::now());
+        // Failures don't count toward latency
+        assert_eq!(h.avg_latency_ms(), Some(350));
+    }
+
+    #[test]
+    fn test_registry_snapshot() {
+        let registry = HealthRegistry::new();
+        let cfg = cfg();
+        registry.record_success("a/x", &cfg, 120);
+        registry.record_failure("b/y", &cfg, 50);
+
+        let a = registry.snapshot("a/x");
+        assert_eq!(a.avg_latency_ms, Some(120));
+        assert_eq!(a.summary(), "closed · 120ms · 0% err");
+        assert_eq!(registry.snapshot("b/y").consecutive_failures, 1);
+        assert_eq!(registry.snapshot("never/used").window_samples, 0);
+        assert_eq!(registry.snapshot_all().len(), 2);
+    }
+
+    #[test]
+    fn test_breaker_config_from_json() {
+        let json = serde_json::json!({"failureThreshold": 5, "cooldownSecs": 60, "windowSize": 0});
+        let cfg = BreakerConfig::from_json(Some(&json));
+        assert_eq!(cfg.max_consecutive_failures, 5);
+        assert_eq!(cfg.cooldown, Duration::from_secs(60));
+        assert_eq!(cfg.window_size, 1);
+        assert_eq!(BreakerConfig::from_json(None), BreakerConfig::default());
+    }
+}
@@ -0,0 +1,142 @@
+//! Minimal HTTP/1.1 mock server for provider tests.
+
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// A canned response served once for the first request whose path contains `path`
+pub struct MockRoute {
+    pub path: String,
+    pub status: u16,
+    pub content_type: String,
+    pub body: String,
+}
+
+impl MockRoute {
+    pub fn new(path: &str, status: u16, content_type: &str, body: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            status,
+            content_type: content_type.to_string(),
+            body: body.to_string(),
+        }
+    }
+
+    pub fn json(path: &str, body: &serde_json::Value) -> Self {
+        Self::new(path, 200, "application/json", &body.to_string())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MockRequest {
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl MockRequest {
+    pub fn json(&self) -> serde_json::Value {
+        serde_json::from_str(&self.body).unwrap_or(serde_json::Value::Null)
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+pub struct MockServer {
+    pub base_url: String,
+    requests: Arc<Mutex<Vec<MockRequest>>>,
+}
+
+impl MockServer {
+    /// Serve `routes` (each used once, in order of match) until the test ends.
+    /// Unmatched requests get a 404.
+    pub async fn start(routes: Vec<MockRoute>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let routes = Arc::new(Mutex::new(routes));
+
+        let recorded = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let Some(request) = read_request(&mut socket).await else { continue };
+                let route = {
+                    let mut routes = routes.lock().unwrap();
+                    routes
+                        .iter()
+                        .position(|r| request.path.contains(&r.path))
+                        .map(|i| routes.remove(i))
+                };
+                recorded.lock().unwrap().push(request);
+
+                let (status, content_type, body) = match route {
+                    Some(r) => (r.status, r.content_type, r.body),
+                    None => (404, "text/plain".to_string(), "not found".to_string()),
+                };
+                let response = format!(
+                    "HTTP/1.1 {} MOCK\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    content_type,
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+                let _ = socket.shutdown().await;
+            }
+        });
+
+        Self { base_url, requests }
+    }
+
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+async fn read_request(socket: &mut tokio::net::TcpStream) -> Option<MockRequest> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+
+    let header_end = loop {
+        let n = socket.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            return None;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+    };
+
+    let head = String::from_utf8_lossy(&buf[..header_end]).to_string();
+    let mut lines = head.lines();
+    let mut request_line = lines.next()?.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let path = request_line.next()?.to_string();
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|l| l.split_once(':'))
+        .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+        .collect();
+    let content_length = headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, v)| v.parse::<usize>().ok())
+        .unwrap_or(0);
+
+    while buf.len() < header_end + content_length {
+        let n = socket.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+    let body = String::from_utf8_lossy(&buf[header_end..]).to_string();
+
+    Some(MockRequest { method, path, headers, body })
+}
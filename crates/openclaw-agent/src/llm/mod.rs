@@ -1,9 +1,18 @@
 pub mod anthropic;
 pub mod fallback;
+pub mod gemini;
+pub mod health;
+#[cfg(test)]
+mod mock_server;
+pub mod ollama;
+pub mod pricing;
+pub mod router;
 pub mod streaming;
+pub mod structured;
 
 use anyhow::{Context, Result};
 use async_trait::async_trait;
+use openclaw_core::models::GenerationParams;
 use serde::{Deserialize, Serialize};
 
 // ── Message types ──
@@ -172,7 +181,8 @@ pub struct FunctionDefinition {
 
 // ── Completion result ──
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum Completion {
     /// LLM responded with text (final answer)
     Text {
@@ -188,11 +198,17 @@ pub enum Completion {
 
 // ── Usage stats ──
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct UsageStats {
+    /// Full prompt size, including any cached tokens
     pub prompt_tokens: u32,
     pub completion_tokens: u32,
     pub total_tokens: u32,
+    /// Prompt tokens served from the provider's prompt cache
+    pub cache_read_tokens: u32,
+    /// Prompt tokens written to the provider's prompt cache
+    pub cache_write_tokens: u32,
 }
 
 // ── Provider trait ──
@@ -223,6 +239,22 @@ pub trait LlmProvider: Send + Sync {
         let _ = event_tx.send(streaming::StreamEvent::Done);
         Ok(result)
     }
+
+    /// Completion constrained to a JSON schema; returns the validated value.
+    /// Default implementation prompts for JSON and retries with the validation errors.
+    async fn complete_structured(
+        &self,
+        messages: &[Message],
+        schema: &serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        structured::complete_with_repair(self, messages, schema).await
+    }
+
+    /// The wrapped provider, for wrappers whose extra behaviour (e.g. recording)
+    /// should not apply to housekeeping calls such as compaction summaries
+    fn unrecorded(&self) -> Option<&dyn LlmProvider> {
+        None
+    }
 }
 
 // ── OpenAI-compatible provider ──
@@ -233,6 +265,7 @@ pub struct OpenAiCompatibleProvider {
     pub api_key: String,
     pub model: String,
     pub max_tokens: u32,
+    pub params: GenerationParams,
 }
 
 impl OpenAiCompatibleProvider {
@@ -243,7 +276,17 @@ impl OpenAiCompatibleProvider {
             api_key: api_key.to_string(),
             model: model.to_string(),
             max_tokens: 4096,
+            params: GenerationParams::default(),
+        }
+    }
+
+    /// Apply per-model generation params (`params.maxTokens` replaces the default max_tokens)
+    pub fn with_params(mut self, params: GenerationParams) -> Self {
+        if let Some(max_tokens) = params.max_tokens {
+            self.max_tokens = max_tokens;
         }
+        self.params = params;
+        self
     }
 
     pub fn client(&self) -> &reqwest::Client {
@@ -266,6 +309,10 @@ impl OpenAiCompatibleProvider {
         self.max_tokens
     }
 
+    pub fn params(&self) -> &GenerationParams {
+        &self.params
+    }
+
     /// Process a ChatResponse into a Completion + UsageStats
     fn process_chat_response(&self, chat_response: ChatResponse) -> Result<(Completion, UsageStats)> {
         let usage = chat_response
@@ -274,6 +321,8 @@ impl OpenAiCompatibleProvider {
                 prompt_tokens: u.prompt_tokens,
                 completion_tokens: u.completion_tokens,
                 total_tokens: u.total_tokens,
+                cache_read_tokens: u.prompt_tokens_details.map(|d| d.cached_tokens).unwrap_or(0),
+                cache_write_tokens: 0,
             })
             .unwrap_or_default();
 
@@ -302,6 +351,27 @@ impl OpenAiCompatibleProvider {
     }
 }
 
+/// Add generation params to an OpenAI-compatible request body.
+/// `extra` fields never replace keys the request already sets (model, messages, ...).
+pub(crate) fn apply_generation_params(body: &mut serde_json::Value, params: &GenerationParams) {
+    let Some(obj) = body.as_object_mut() else { return };
+    if let Some(t) = params.temperature {
+        obj.insert("temperature".to_string(), serde_json::json!(t));
+    }
+    if let Some(p) = params.top_p {
+        obj.insert("top_p".to_string(), serde_json::json!(p));
+    }
+    if let Some(ref stop) = params.stop {
+        obj.insert("stop".to_string(), serde_json::json!(stop));
+    }
+    if let Some(ref effort) = params.reasoning_effort {
+        obj.insert("reasoning_effort".to_string(), serde_json::json!(effort));
+    }
+    for (key, value) in &params.extra {
+        obj.entry(key.clone()).or_insert_with(|| value.clone());
+    }
+}
+
 // ── API request/response types ──
 
 #[derive(Serialize)]
@@ -345,6 +415,15 @@ struct ApiUsage {
     completion_tokens: u32,
     #[serde(default)]
     total_tokens: u32,
+    #[serde(default)]
+    prompt_tokens_details: Option<PromptTokensDetails>,
+}
+
+/// OpenAI-style prompt cache accounting (also used by DeepSeek, Moonshot, etc.)
+#[derive(Deserialize)]
+pub(crate) struct PromptTokensDetails {
+    #[serde(default)]
+    pub(crate) cached_tokens: u32,
 }
 
 #[async_trait]
@@ -364,14 +443,133 @@ impl LlmProvider for OpenAiCompatibleProvider {
             max_tokens: self.max_tokens,
             tools: tools.to_vec(),
         };
+        let mut body = serde_json::to_value(&request).context("Failed to serialize LLM request")?;
+        apply_generation_params(&mut body, &self.params);
+        self.send_chat(messages, &body).await
+    }
+
+    async fn complete_structured(
+        &self,
+        messages: &[Message],
+        schema: &serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        let request = ChatRequest {
+            model: self.model.clone(),
+            messages: messages.to_vec(),
+            max_tokens: self.max_tokens,
+            tools: Vec::new(),
+        };
+        let mut body = serde_json::to_value(&request).context("Failed to serialize LLM request")?;
+        apply_generation_params(&mut body, &self.params);
+        body["response_format"] = serde_json::json!({
+            "type": "json_schema",
+            "json_schema": {
+                "name": structured::schema_name(schema),
+                "schema": schema,
+            }
+        });
+
+        // Not every OpenAI-compatible backend honours json_schema — fall back to
+        // repair when it says so; any other failure is returned as is
+        match self.send_chat(messages, &body).await {
+            Ok((Completion::Text { content, .. }, _)) => {
+                if let Some(value) = structured::extract_json(&content) {
+                    let errors = structured::validate(&value, schema);
+                    if errors.is_empty() {
+                        return Ok(value);
+                    }
+                    tracing::warn!("{}: json_schema response invalid: {}", self.model, errors.join("; "));
+                }
+            }
+            Ok(_) => {}
+            Err(e) if response_format_unsupported(&e) => {
+                tracing::warn!("{}: json_schema not supported, using repair loop: {}", self.model, e)
+            }
+            Err(e) => return Err(e),
+        }
+        structured::complete_with_repair(self, messages, schema).await
+    }
+
+    async fn complete_streaming(
+        &self,
+        messages: &[Message],
+        tools: &[ToolDefinition],
+        event_tx: tokio::sync::mpsc::UnboundedSender<streaming::StreamEvent>,
+    ) -> Result<(Completion, UsageStats)> {
+        let t_start = std::time::Instant::now();
+        let mut log_entry = crate::llm_log::LlmLogEntry::new(&self.model);
+        log_entry.messages_count = messages.len();
+        log_entry.streaming = true;
+        log_entry.request_tokens_est = crate::tokenizer::for_model(&self.model).count_messages(messages) as u32;
+
+        let result = streaming::stream_completion(
+            &self.client,
+            &self.base_url,
+            &self.api_key,
+            &self.model,
+            messages,
+            tools,
+            self.max_tokens,
+            &self.params,
+            Some(event_tx),
+        )
+        .await;
+
+        log_entry.latency_ms = t_start.elapsed().as_millis() as u64;
+        match &result {
+            Ok((completion, usage)) => {
+                log_entry.usage_prompt_tokens = usage.prompt_tokens;
+                log_entry.usage_completion_tokens = usage.completion_tokens;
+                log_entry.usage_total_tokens = usage.total_tokens;
+                log_entry.usage_cache_read_tokens = usage.cache_read_tokens;
+                log_entry.usage_cache_write_tokens = usage.cache_write_tokens;
+                match completion {
+                    Completion::Text { content, reasoning } => {
+                        log_entry.response_content = Some(content.clone());
+                        log_entry.response_reasoning = reasoning.clone();
+                    }
+                    Completion::ToolCalls { calls, reasoning } => {
+                        log_entry.response_tool_calls = calls.len();
+                        log_entry.tool_call_names = calls.iter().map(|c| c.function.name.clone()).collect();
+                        log_entry.response_reasoning = reasoning.clone();
+                    }
+                }
+            }
+            Err(e) => {
+                log_entry.error = Some(format!("{}", e));
+            }
+        }
+        crate::llm_log::record(log_entry);
+
+        result
+    }
+}
 
+/// Whether a failed request was refused because the backend doesn't support
+/// `response_format`: a 4xx (other than auth and rate limits) whose body says so
+fn response_format_unsupported(error: &anyhow::Error) -> bool {
+    let message = error.to_string();
+    let Some(rest) = message.strip_prefix("LLM API returned ") else {
+        return false;
+    };
+    let status: u16 = rest.get(..3).and_then(|s| s.parse().ok()).unwrap_or(0);
+    (400..500).contains(&status)
+        && !matches!(status, 401 | 403 | 429)
+        && (rest.contains("response_format") || rest.contains("json_schema"))
+}
+
+impl OpenAiCompatibleProvider {
+    /// POST a chat/completions body with retries and LLM call logging
+    async fn send_chat(
+        &self,
+        messages: &[Message],
+        body: &serde_json::Value,
+    ) -> Result<(Completion, UsageStats)> {
         let t_start = std::time::Instant::now();
         let mut log_entry = crate::llm_log::LlmLogEntry::new(&self.model);
         log_entry.messages_count = messages.len();
         log_entry.streaming = false;
-        log_entry.request_tokens_est = messages.iter()
-            .map(|m| m.content.as_deref().unwrap_or("").len() as u32 / 4)
-            .sum::<u32>().max(1);
+        log_entry.request_tokens_est = crate::tokenizer::for_model(&self.model).count_messages(messages) as u32;
 
         // Retry with exponential backoff for transient errors (429, 502, 503, 504)
         let max_retries = 3;
@@ -392,7 +590,7 @@ impl LlmProvider for OpenAiCompatibleProvider {
                 .post(format!("{}/chat/completions", self.base_url))
                 .header("Authorization", format!("Bearer {}", self.api_key))
                 .header("Content-Type", "application/json")
-                .json(&request)
+                .json(body)
                 .send()
                 .await
             {
@@ -430,6 +628,8 @@ impl LlmProvider for OpenAiCompatibleProvider {
                 log_entry.usage_prompt_tokens = usage.prompt_tokens;
                 log_entry.usage_completion_tokens = usage.completion_tokens;
                 log_entry.usage_total_tokens = usage.total_tokens;
+                log_entry.usage_cache_read_tokens = usage.cache_read_tokens;
+                log_entry.usage_cache_write_tokens = usage.cache_write_tokens;
                 match completion {
                     Completion::Text { content, reasoning } => {
                         log_entry.response_content = Some(content.clone());
@@ -456,59 +656,6 @@ impl LlmProvider for OpenAiCompatibleProvider {
         crate::llm_log::record(log_entry);
         Err(last_error.unwrap_or_else(|| anyhow::anyhow!("LLM request failed after retries")))
     }
-
-    async fn complete_streaming(
-        &self,
-        messages: &[Message],
-        tools: &[ToolDefinition],
-        event_tx: tokio::sync::mpsc::UnboundedSender<streaming::StreamEvent>,
-    ) -> Result<(Completion, UsageStats)> {
-        let t_start = std::time::Instant::now();
-        let mut log_entry = crate::llm_log::LlmLogEntry::new(&self.model);
-        log_entry.messages_count = messages.len();
-        log_entry.streaming = true;
-        log_entry.request_tokens_est = messages.iter()
-            .map(|m| m.content.as_deref().unwrap_or("").len() as u32 / 4)
-            .sum::<u32>().max(1);
-
-        let result = streaming::stream_completion(
-            &self.client,
-            &self.base_url,
-            &self.api_key,
-            &self.model,
-            messages,
-            tools,
-            self.max_tokens,
-            Some(event_tx),
-        )
-        .await;
-
-        log_entry.latency_ms = t_start.elapsed().as_millis() as u64;
-        match &result {
-            Ok((completion, usage)) => {
-                log_entry.usage_prompt_tokens = usage.prompt_tokens;
-                log_entry.usage_completion_tokens = usage.completion_tokens;
-                log_entry.usage_total_tokens = usage.total_tokens;
-                match completion {
-                    Completion::Text { content, reasoning } => {
-                        log_entry.response_content = Some(content.clone());
-                        log_entry.response_reasoning = reasoning.clone();
-                    }
-                    Completion::ToolCalls { calls, reasoning } => {
-                        log_entry.response_tool_calls = calls.len();
-                        log_entry.tool_call_names = calls.iter().map(|c| c.function.name.clone()).collect();
-                        log_entry.response_reasoning = reasoning.clone();
-                    }
-                }
-            }
-            Err(e) => {
-                log_entry.error = Some(format!("{}", e));
-            }
-        }
-        crate::llm_log::record(log_entry);
-
-        result
-    }
 }
 
 #[cfg(test)]
@@ -525,6 +672,23 @@ mod tests {
         assert!(!json.contains("tool_call_id"));
     }
 
+    #[test]
+    fn test_response_format_unsupported() {
+        let unsupported = anyhow::anyhow!(
+            "LLM API returned 400 Bad Request: {{\"error\": \"response_format json_schema is not supported\"}}"
+        );
+        assert!(response_format_unsupported(&unsupported));
+        for other in [
+            "LLM API returned 401 Unauthorized: bad response_format key",
+            "LLM API returned 429 Too Many Requests: slow down",
+            "LLM API returned 400 Bad Request: max_tokens too large",
+            "LLM API returned 500 Internal Server Error: response_format",
+            "Failed to send request to LLM: connection refused",
+        ] {
+            assert!(!response_format_unsupported(&anyhow::anyhow!("{}", other)), "{}", other);
+        }
+    }
+
     #[test]
     fn test_tool_result_message() {
         let msg = Message::tool_result("call_123", "file contents here");
@@ -553,4 +717,40 @@ mod tests {
         assert!(json.contains("\"type\":\"function\""));
         assert!(json.contains("\"name\":\"exec\""));
     }
+
+    #[test]
+    fn test_apply_generation_params() {
+        let mut extra = serde_json::Map::new();
+        extra.insert("response_format".to_string(), serde_json::json!({"type": "json_object"}));
+        extra.insert("model".to_string(), serde_json::json!("hijacked"));
+        let params = GenerationParams {
+            temperature: Some(0.2),
+            stop: Some(vec!["</done>".to_string()]),
+            reasoning_effort: Some("low".to_string()),
+            extra,
+            ..Default::default()
+        };
+
+        let mut body = serde_json::json!({"model": "kimi-k2.5", "messages": [], "max_tokens": 4096});
+        apply_generation_params(&mut body, &params);
+        assert_eq!(body["temperature"], serde_json::json!(0.2f32));
+        assert_eq!(body["stop"][0], "</done>");
+        assert_eq!(body["reasoning_effort"], "low");
+        assert_eq!(body["response_format"]["type"], "json_object");
+        assert_eq!(body["model"], "kimi-k2.5");
+        assert!(body.get("top_p").is_none());
+    }
+
+    #[test]
+    fn test_with_params_overrides_max_tokens() {
+        let provider = OpenAiCompatibleProvider::new("http://localhost:11434/v1", "key", "llama3.2:1b");
+        assert_eq!(provider.max_tokens(), 4096);
+        let provider = provider.with_params(GenerationParams {
+            max_tokens: Some(1024),
+            temperature: Some(0.9),
+            ..Default::default()
+        });
+        assert_eq!(provider.max_tokens(), 1024);
+        assert_eq!(provider.params().temperature, Some(0.9));
+    }
 }
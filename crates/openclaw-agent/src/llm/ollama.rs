@@ -0,0 +1,717 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use openclaw_core::models::GenerationParams;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tokio::sync::mpsc;
+use tracing::{debug, info};
+
+use super::anthropic::parse_data_url;
+use super::streaming::StreamEvent;
+use super::{Completion, FunctionCall, LlmProvider, Message, Role, ToolCall, ToolDefinition, UsageStats};
+
+/// Failures callers may want to tell apart (e.g. to skip a provider vs. pull a model)
+#[derive(Debug)]
+pub enum OllamaError {
+    /// The Ollama server could not be reached
+    ServerDown { base_url: String, reason: String },
+    /// The server is up but the model has not been pulled
+    ModelNotFound { model: String },
+}
+
+impl std::fmt::Display for OllamaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ServerDown { base_url, reason } => write!(f, "Ollama server at {} is down: {}", base_url, reason),
+            Self::ModelNotFound { model } => write!(f, "Ollama model '{}' is not pulled", model),
+        }
+    }
+}
+
+impl std::error::Error for OllamaError {}
+
+/// Native Ollama provider over `/api/chat` (NDJSON streaming)
+pub struct OllamaProvider {
+    client: reqwest::Client,
+    base_url: String,
+    model: String,
+    max_tokens: u32,
+    params: GenerationParams,
+    /// How long the model stays loaded after a request (e.g. "30m", or seconds; -1 = forever)
+    keep_alive: Option<serde_json::Value>,
+    /// Context length (`options.num_ctx`); Ollama defaults to a small window otherwise
+    num_ctx: Option<u64>,
+    /// Pull the model via `/api/pull` when the server reports it missing
+    auto_pull: bool,
+}
+
+impl OllamaProvider {
+    /// `base_url` may be the server root or its OpenAI-compatible `/v1` endpoint
+    pub fn new(base_url: &str, model: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: server_root(base_url),
+            model: model.to_string(),
+            max_tokens: 4096,
+            params: GenerationParams::default(),
+            keep_alive: None,
+            num_ctx: None,
+            auto_pull: false,
+        }
+    }
+
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = max_tokens;
+        self
+    }
+
+    pub fn with_params(mut self, params: GenerationParams) -> Self {
+        if let Some(max_tokens) = params.max_tokens {
+            self.max_tokens = max_tokens;
+        }
+        self.params = params;
+        self
+    }
+
+    pub fn with_keep_alive(mut self, keep_alive: serde_json::Value) -> Self {
+        self.keep_alive = Some(keep_alive);
+        self
+    }
+
+    pub fn with_num_ctx(mut self, num_ctx: u64) -> Self {
+        self.num_ctx = Some(num_ctx);
+        self
+    }
+
+    pub fn with_auto_pull(mut self, enabled: bool) -> Self {
+        self.auto_pull = enabled;
+        self
+    }
+
+    fn build_request(&self, messages: &[Message], tools: &[ToolDefinition], stream: bool) -> OllamaChatRequest {
+        let mut options = serde_json::Map::new();
+        options.insert("num_predict".to_string(), self.max_tokens.into());
+        if let Some(num_ctx) = self.num_ctx {
+            options.insert("num_ctx".to_string(), num_ctx.into());
+        }
+        if let Some(t) = self.params.temperature {
+            options.insert("temperature".to_string(), serde_json::json!(t));
+        }
+        if let Some(p) = self.params.top_p {
+            options.insert("top_p".to_string(), serde_json::json!(p));
+        }
+        if let Some(ref stop) = self.params.stop {
+            options.insert("stop".to_string(), serde_json::json!(stop));
+        }
+        // `extra` keys are model options here (top_k, repeat_penalty, seed, …)
+        for (k, v) in &self.params.extra {
+            options.entry(k.clone()).or_insert_with(|| v.clone());
+        }
+
+        OllamaChatRequest {
+            model: self.model.clone(),
+            messages: convert_messages(messages),
+            tools: tools.to_vec(),
+            stream,
+            keep_alive: self.keep_alive.clone(),
+            think: self.params.reasoning_effort.as_ref().map(|_| true),
+            options,
+        }
+    }
+
+    /// POST /api/chat, classifying failures and pulling the model once if allowed
+    async fn post_chat(&self, request: &OllamaChatRequest) -> Result<reqwest::Response> {
+        match self.try_post_chat(request).await {
+            Err(e)
+                if self.auto_pull
+                    && matches!(e.downcast_ref::<OllamaError>(), Some(OllamaError::ModelNotFound { .. })) =>
+            {
+                info!("Ollama model {} missing — pulling from {}", self.model, self.base_url);
+                pull_model(&self.client, &self.base_url, &self.model).await?;
+                self.try_post_chat(request).await
+            }
+            other => other,
+        }
+    }
+
+    async fn try_post_chat(&self, request: &OllamaChatRequest) -> Result<reqwest::Response> {
+        let response = self
+            .client
+            .post(format!("{}/api/chat", self.base_url))
+            .json(request)
+            .send()
+            .await
+            .map_err(|e| OllamaError::ServerDown {
+                base_url: self.base_url.clone(),
+                reason: e.to_string(),
+            })?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            if status.as_u16() == 404 || is_model_missing(&body) {
+                return Err(OllamaError::ModelNotFound { model: self.model.clone() }.into());
+            }
+            anyhow::bail!("Ollama API returned {}: {}", status, body);
+        }
+        Ok(response)
+    }
+}
+
+/// Strip a trailing `/v1` (OpenAI-compatible endpoint) to get the native API root
+pub fn server_root(base_url: &str) -> String {
+    let trimmed = base_url.trim_end_matches('/');
+    trimmed.strip_suffix("/v1").unwrap_or(trimmed).to_string()
+}
+
+fn is_model_missing(body: &str) -> bool {
+    let lower = body.to_lowercase();
+    lower.contains("not found") && lower.contains("model")
+}
+
+/// Model names without a tag refer to `:latest`
+fn normalize_model_name(name: &str) -> String {
+    if name.contains(':') {
+        name.to_string()
+    } else {
+        format!("{}:latest", name)
+    }
+}
+
+/// Pull a model (blocking until the download completes)
+pub async fn pull_model(client: &reqwest::Client, base_url: &str, model: &str) -> Result<()> {
+    let response = client
+        .post(format!("{}/api/pull", server_root(base_url)))
+        .json(&serde_json::json!({ "model": model, "stream": false }))
+        .send()
+        .await
+        .context("Failed to send Ollama pull request")?;
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    if !status.is_success() {
+        anyhow::bail!("Ollama pull of {} failed ({}): {}", model, status, body);
+    }
+    if let Some(err) = serde_json::from_str::<serde_json::Value>(&body)
+        .ok()
+        .and_then(|v| v.get("error").and_then(|e| e.as_str()).map(String::from))
+    {
+        anyhow::bail!("Ollama pull of {} failed: {}", model, err);
+    }
+    info!("Pulled Ollama model {}", model);
+    Ok(())
+}
+
+/// Load state of a configured model on an Ollama server
+#[derive(Debug, Clone, PartialEq)]
+pub enum ModelLoadState {
+    /// Resident in memory (`/api/ps`)
+    Loaded { size_vram: u64, expires_at: Option<String> },
+    /// Pulled but not loaded (`/api/tags`)
+    Available,
+    NotPulled,
+}
+
+#[derive(Debug, Deserialize)]
+struct ModelList {
+    #[serde(default)]
+    models: Vec<ListedModel>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListedModel {
+    name: String,
+    #[serde(default)]
+    size_vram: u64,
+    expires_at: Option<String>,
+}
+
+/// Query `/api/tags` and `/api/ps` for the load state of each model
+pub async fn model_load_states(
+    client: &reqwest::Client,
+    base_url: &str,
+    models: &[String],
+) -> Result<Vec<(String, ModelLoadState)>> {
+    let root = server_root(base_url);
+    let tags: ModelList = client.get(format!("{}/api/tags", root)).send().await?.json().await?;
+    let running: ModelList = client.get(format!("{}/api/ps", root)).send().await?.json().await?;
+
+    Ok(models
+        .iter()
+        .map(|model| {
+            let name = normalize_model_name(model);
+            let state = if let Some(m) = running.models.iter().find(|m| normalize_model_name(&m.name) == name) {
+                ModelLoadState::Loaded {
+                    size_vram: m.size_vram,
+                    expires_at: m.expires_at.clone(),
+                }
+            } else if tags.models.iter().any(|m| normalize_model_name(&m.name) == name) {
+                ModelLoadState::Available
+            } else {
+                ModelLoadState::NotPulled
+            };
+            (model.clone(), state)
+        })
+        .collect())
+}
+
+// ── Convert internal messages to Ollama format ──
+
+fn convert_messages(messages: &[Message]) -> Vec<OllamaMessage> {
+    let mut call_names: HashMap<String, String> = HashMap::new();
+
+    messages
+        .iter()
+        .map(|msg| {
+            let role = match msg.role {
+                Role::System => "system",
+                Role::User => "user",
+                Role::Assistant => "assistant",
+                Role::Tool => "tool",
+            };
+
+            // Ollama takes raw base64 images; remote URLs are not fetched
+            let images: Vec<String> = msg
+                .image_urls
+                .iter()
+                .filter_map(|url| {
+                    let parsed = parse_data_url(url).map(|(_, data)| data);
+                    if parsed.is_none() {
+                        debug!("Skipping non-data image URL for Ollama: {}", url);
+                    }
+                    parsed
+                })
+                .collect();
+
+            let tool_calls: Vec<OllamaToolCall> = msg
+                .tool_calls
+                .iter()
+                .flatten()
+                .map(|call| {
+                    call_names.insert(call.id.clone(), call.function.name.clone());
+                    OllamaToolCall {
+                        function: OllamaFunctionCall {
+                            name: call.function.name.clone(),
+                            arguments: serde_json::from_str(&call.function.arguments)
+                                .unwrap_or_else(|_| serde_json::json!({})),
+                        },
+                    }
+                })
+                .collect();
+
+            let tool_name = msg
+                .tool_call_id
+                .as_ref()
+                .and_then(|id| call_names.get(id).cloned());
+
+            OllamaMessage {
+                role: role.to_string(),
+                content: msg.content.clone().unwrap_or_default(),
+                thinking: None,
+                images,
+                tool_calls,
+                tool_name,
+            }
+        })
+        .collect()
+}
+
+// ── Ollama API types ──
+
+#[derive(Serialize)]
+struct OllamaChatRequest {
+    model: String,
+    messages: Vec<OllamaMessage>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<ToolDefinition>,
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    keep_alive: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    think: Option<bool>,
+    options: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct OllamaMessage {
+    #[serde(default)]
+    role: String,
+    #[serde(default)]
+    content: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    thinking: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    images: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tool_calls: Vec<OllamaToolCall>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tool_name: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct OllamaToolCall {
+    function: OllamaFunctionCall,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct OllamaFunctionCall {
+    name: String,
+    #[serde(default)]
+    arguments: serde_json::Value,
+}
+
+/// One NDJSON line of a `/api/chat` response (or the whole non-streaming body)
+#[derive(Debug, Deserialize)]
+struct OllamaChatChunk {
+    message: Option<OllamaMessage>,
+    #[serde(default)]
+    done: bool,
+    #[serde(default)]
+    prompt_eval_count: u32,
+    #[serde(default)]
+    eval_count: u32,
+    error: Option<String>,
+}
+
+#[derive(Default)]
+struct ChatAccumulator {
+    content: String,
+    reasoning: String,
+    tool_calls: Vec<ToolCall>,
+    usage: UsageStats,
+    done: bool,
+}
+
+impl ChatAccumulator {
+    fn push(&mut self, chunk: OllamaChatChunk, event_tx: Option<&mpsc::UnboundedSender<StreamEvent>>) -> Result<()> {
+        if let Some(err) = chunk.error {
+            anyhow::bail!("Ollama error: {}", err);
+        }
+
+        if let Some(msg) = chunk.message {
+            if let Some(thinking) = msg.thinking.filter(|t| !t.is_empty()) {
+                if let Some(tx) = event_tx {
+                    let _ = tx.send(StreamEvent::ReasoningDelta(thinking.clone()));
+                }
+                self.reasoning.push_str(&thinking);
+            }
+            if !msg.content.is_empty() {
+                if let Some(tx) = event_tx {
+                    let _ = tx.send(StreamEvent::ContentDelta(msg.content.clone()));
+                }
+                self.content.push_str(&msg.content);
+            }
+            for call in msg.tool_calls {
+                if let Some(tx) = event_tx {
+                    let _ = tx.send(StreamEvent::ToolCallStart { name: call.function.name.clone() });
+                }
+                self.tool_calls.push(ToolCall {
+                    // Ollama does not assign call IDs
+                    id: format!("call_{}", uuid::Uuid::new_v4().simple()),
+                    call_type: "function".to_string(),
+                    function: FunctionCall {
+                        name: call.function.name,
+                        arguments: call.function.arguments.to_string(),
+                    },
+                });
+            }
+        }
+
+        if chunk.done {
+            self.done = true;
+            self.usage = UsageStats {
+                prompt_tokens: chunk.prompt_eval_count,
+                completion_tokens: chunk.eval_count,
+                total_tokens: chunk.prompt_eval_count + chunk.eval_count,
+                ..Default::default()
+            };
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> (Completion, UsageStats) {
+        let reasoning = if self.reasoning.is_empty() { None } else { Some(self.reasoning) };
+        let completion = if self.tool_calls.is_empty() {
+            Completion::Text {
+                content: self.content,
+                reasoning,
+            }
+        } else {
+            Completion::ToolCalls {
+                calls: self.tool_calls,
+                reasoning,
+            }
+        };
+        (completion, self.usage)
+    }
+}
+
+// ── Provider impl ──
+
+#[async_trait]
+impl LlmProvider for OllamaProvider {
+    fn name(&self) -> &str {
+        &self.model
+    }
+
+    async fn complete(
+        &self,
+        messages: &[Message],
+        tools: &[ToolDefinition],
+    ) -> Result<(Completion, UsageStats)> {
+        let request = self.build_request(messages, tools, false);
+        let t_start = std::time::Instant::now();
+        let mut log_entry = new_log_entry(&self.model, messages, false);
+
+        let result = async {
+            let response = self.post_chat(&request).await?;
+            let chunk: OllamaChatChunk = response.json().await.context("Failed to parse Ollama response")?;
+            let mut acc = ChatAccumulator::default();
+            acc.push(chunk, None)?;
+            Ok(acc.finish())
+        }
+        .await;
+
+        log_entry.latency_ms = t_start.elapsed().as_millis() as u64;
+        record_log(log_entry, &result);
+        result
+    }
+
+    async fn complete_streaming(
+        &self,
+        messages: &[Message],
+        tools: &[ToolDefinition],
+        event_tx: mpsc::UnboundedSender<StreamEvent>,
+    ) -> Result<(Completion, UsageStats)> {
+        let request = self.build_request(messages, tools, true);
+        let t_start = std::time::Instant::now();
+        let mut log_entry = new_log_entry(&self.model, messages, true);
+
+        let result = async {
+            let response = self.post_chat(&request).await?;
+            let mut acc = ChatAccumulator::default();
+            let mut stream = response.bytes_stream();
+            let mut buffer = String::new();
+
+            while let Some(chunk) = stream.next().await {
+                let chunk = chunk.context("Ollama stream read error")?;
+                buffer.push_str(&String::from_utf8_lossy(&chunk));
+
+                while let Some(line_end) = buffer.find('\n') {
+                    let line = buffer[..line_end].trim().to_string();
+                    buffer = buffer[line_end + 1..].to_string();
+                    if line.is_empty() {
+                        continue;
+                    }
+                    if let Ok(chunk) = serde_json::from_str::<OllamaChatChunk>(&line) {
+                        acc.push(chunk, Some(&event_tx))?;
+                    }
+                }
+            }
+            if let Ok(chunk) = serde_json::from_str::<OllamaChatChunk>(buffer.trim()) {
+                acc.push(chunk, Some(&event_tx))?;
+            }
+
+            if !acc.done {
+                anyhow::bail!("Ollama stream ended before done");
+            }
+
+            // NOTE: Do NOT send StreamEvent::Done here — the runtime controls Done
+            Ok(acc.finish())
+        }
+        .await;
+
+        log_entry.latency_ms = t_start.elapsed().as_millis() as u64;
+        record_log(log_entry, &result);
+        result
+    }
+}
+
+fn new_log_entry(model: &str, messages: &[Message], streaming: bool) -> crate::llm_log::LlmLogEntry {
+    let mut log_entry = crate::llm_log::LlmLogEntry::new(model);
+    log_entry.messages_count = messages.len();
+    log_entry.streaming = streaming;
+    log_entry.request_tokens_est = crate::tokenizer::for_model(model).count_messages(messages) as u32;
+    log_entry
+}
+
+fn record_log(mut log_entry: crate::llm_log::LlmLogEntry, result: &Result<(Completion, UsageStats)>) {
+    match result {
+        Ok((completion, usage)) => {
+            log_entry.usage_prompt_tokens = usage.prompt_tokens;
+            log_entry.usage_completion_tokens = usage.completion_tokens;
+            log_entry.usage_total_tokens = usage.total_tokens;
+            match completion {
+                Completion::Text { content, reasoning } => {
+                    log_entry.response_content = Some(content.clone());
+                    log_entry.response_reasoning = reasoning.clone();
+                }
+                Completion::ToolCalls { calls, reasoning } => {
+                    log_entry.response_tool_calls = calls.len();
+                    log_entry.tool_call_names = calls.iter().map(|c| c.function.name.clone()).collect();
+                    log_entry.response_reasoning = reasoning.clone();
+                }
+            }
+        }
+        Err(e) => log_entry.error = Some(format!("{}", e)),
+    }
+    crate::llm_log::record(log_entry);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::mock_server::{MockRoute, MockServer};
+    use super::super::FunctionDefinition;
+    use super::*;
+
+    fn exec_tool() -> ToolDefinition {
+        ToolDefinition {
+            tool_type: "function".to_string(),
+            function: FunctionDefinition {
+                name: "exec".to_string(),
+                description: "Run a command".to_string(),
+                parameters: serde_json::json!({"type": "object", "properties": {"command": {"type": "string"}}}),
+            },
+        }
+    }
+
+    #[test]
+    fn test_server_root_strips_v1() {
+        assert_eq!(server_root("http://127.0.0.1:11434/v1"), "http://127.0.0.1:11434");
+        assert_eq!(server_root("http://127.0.0.1:11434/"), "http://127.0.0.1:11434");
+        assert_eq!(normalize_model_name("llama3.2"), "llama3.2:latest");
+        assert_eq!(normalize_model_name("llama3.2:1b"), "llama3.2:1b");
+    }
+
+    #[test]
+    fn test_build_request_options() {
+        let provider = OllamaProvider::new("http://localhost:11434/v1", "qwen3:8b")
+            .with_num_ctx(32768)
+            .with_keep_alive(serde_json::json!("30m"))
+            .with_params(GenerationParams {
+                temperature: Some(0.4),
+                max_tokens: Some(1024),
+                extra: serde_json::from_value(serde_json::json!({"top_k": 20})).unwrap(),
+                ..Default::default()
+            });
+        let json = serde_json::to_value(provider.build_request(&[Message::user("hi")], &[exec_tool()], true)).unwrap();
+        assert_eq!(json["keep_alive"], "30m");
+        assert_eq!(json["options"]["num_ctx"], 32768);
+        assert_eq!(json["options"]["num_predict"], 1024);
+        assert_eq!(json["options"]["top_k"], 20);
+        assert_eq!(json["tools"][0]["function"]["name"], "exec");
+        assert!(json.get("think").is_none());
+    }
+
+    #[test]
+    fn test_convert_messages_tool_round_trip() {
+        let call = ToolCall {
+            id: "c1".to_string(),
+            call_type: "function".to_string(),
+            function: FunctionCall {
+                name: "exec".to_string(),
+                arguments: r#"{"command":"ls"}"#.to_string(),
+            },
+        };
+        let msgs = convert_messages(&[
+            Message::user_with_images("look", vec!["data:image/png;base64,AAAA".to_string()]),
+            Message::assistant_tool_calls(vec![call], None),
+            Message::tool_result("c1", "a.txt"),
+        ]);
+        assert_eq!(msgs[0].images, vec!["AAAA"]);
+        assert_eq!(msgs[1].tool_calls[0].function.arguments["command"], "ls");
+        assert_eq!(msgs[2].role, "tool");
+        assert_eq!(msgs[2].tool_name.as_deref(), Some("exec"));
+    }
+
+    #[tokio::test]
+    async fn test_streaming_ndjson_with_tool_call() {
+        let ndjson = [
+            r#"{"message":{"role":"assistant","content":"","thinking":"need ls"},"done":false}"#,
+            r#"{"message":{"role":"assistant","content":"","tool_calls":[{"function":{"name":"exec","arguments":{"command":"ls"}}}]},"done":false}"#,
+            r#"{"message":{"role":"assistant","content":""},"done":true,"prompt_eval_count":30,"eval_count":12}"#,
+        ]
+        .join("\n");
+        let server = MockServer::start(vec![MockRoute::new("/api/chat", 200, "application/x-ndjson", &ndjson)]).await;
+
+        let provider = OllamaProvider::new(&format!("{}/v1", server.base_url), "qwen3:8b");
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let (completion, usage) = provider
+            .complete_streaming(&[Message::user("list")], &[exec_tool()], tx)
+            .await
+            .unwrap();
+
+        let Completion::ToolCalls { calls, reasoning } = completion else { panic!("expected tool calls") };
+        assert_eq!(calls[0].function.name, "exec");
+        assert_eq!(reasoning.as_deref(), Some("need ls"));
+        assert_eq!(usage.total_tokens, 42);
+        assert!(matches!(rx.try_recv(), Ok(StreamEvent::ReasoningDelta(_))));
+        assert_eq!(server.requests()[0].path, "/api/chat");
+        assert_eq!(server.requests()[0].json()["stream"], true);
+    }
+
+    #[tokio::test]
+    async fn test_missing_model_is_distinct_error() {
+        let server = MockServer::start(vec![MockRoute::new(
+            "/api/chat",
+            404,
+            "application/json",
+            r#"{"error":"model \"qwen3:8b\" not found, try pulling it first"}"#,
+        )])
+        .await;
+        let provider = OllamaProvider::new(&server.base_url, "qwen3:8b");
+        let err = provider.complete(&[Message::user("hi")], &[]).await.unwrap_err();
+        assert!(matches!(err.downcast_ref::<OllamaError>(), Some(OllamaError::ModelNotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_server_down_is_distinct_error() {
+        // Bind then drop to get a port nothing listens on
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let provider = OllamaProvider::new(&format!("http://127.0.0.1:{}", port), "qwen3:8b");
+        let err = provider.complete(&[Message::user("hi")], &[]).await.unwrap_err();
+        assert!(matches!(err.downcast_ref::<OllamaError>(), Some(OllamaError::ServerDown { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_auto_pull_then_retry() {
+        let server = MockServer::start(vec![
+            MockRoute::new("/api/chat", 404, "application/json", r#"{"error":"model not found"}"#),
+            MockRoute::json("/api/pull", &serde_json::json!({"status": "success"})),
+            MockRoute::json(
+                "/api/chat",
+                &serde_json::json!({"message": {"role": "assistant", "content": "hi!"}, "done": true,
+                                    "prompt_eval_count": 3, "eval_count": 2}),
+            ),
+        ])
+        .await;
+        let provider = OllamaProvider::new(&server.base_url, "qwen3:8b").with_auto_pull(true);
+        let (completion, _) = provider.complete(&[Message::user("hi")], &[]).await.unwrap();
+        let Completion::Text { content, .. } = completion else { panic!("expected text") };
+        assert_eq!(content, "hi!");
+
+        let paths: Vec<String> = server.requests().into_iter().map(|r| r.path).collect();
+        assert_eq!(paths, vec!["/api/chat", "/api/pull", "/api/chat"]);
+    }
+
+    #[tokio::test]
+    async fn test_model_load_states() {
+        let server = MockServer::start(vec![
+            MockRoute::json(
+                "/api/tags",
+                &serde_json::json!({"models": [{"name": "qwen3:8b"}, {"name": "llama3.2:latest"}]}),
+            ),
+            MockRoute::json(
+                "/api/ps",
+                &serde_json::json!({"models": [{"name": "qwen3:8b", "size_vram": 6000000000u64,
+                                                  "expires_at": "2026-10-17T12:00:00Z"}]}),
+            ),
+        ])
+        .await;
+        let models = vec!["qwen3:8b".to_string(), "llama3.2".to_string(), "mistral:7b".to_string()];
+        let states = model_load_states(&reqwest::Client::new(), &server.base_url, &models).await.unwrap();
+        assert!(matches!(states[0].1, ModelLoadState::Loaded { size_vram: 6000000000, .. }));
+        assert_eq!(states[1].1, ModelLoadState::Available);
+        assert_eq!(states[2].1, ModelLoadState::NotPulled);
+    }
+}
@@ -0,0 +1,253 @@
+//! Per-model token pricing and LLM call cost accounting.
+//!
+//! Rates are USD per million tokens, so `tokens × rate` is the cost in
+//! micro-dollars. Costs are kept as integer micro-dollars (`u64`) everywhere
+//! after computation — the orchestrator's cent budgets are `micros / 10_000`.
+//!
+//! Configured in openclaw-manual.json on a provider (default for its models)
+//! or on a model entry:
+//!
+//! ```json
+//! "pricing": { "input": 3.0, "output": 15.0, "cacheRead": 0.3, "cacheWrite": 3.75 }
+//! ```
+//!
+//! Models without configured pricing fall back to a small built-in table;
+//! local providers (Ollama, localhost) are free unless configured otherwise.
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use super::UsageStats;
+
+/// Token rates for one model, in USD per million tokens
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelPricing {
+    #[serde(default)]
+    pub input: f64,
+    #[serde(default)]
+    pub output: f64,
+    /// Rate for prompt tokens read from the cache (defaults to `input`)
+    #[serde(default)]
+    pub cache_read: Option<f64>,
+    /// Rate for prompt tokens written to the cache (defaults to `input`)
+    #[serde(default)]
+    pub cache_write: Option<f64>,
+}
+
+impl ModelPricing {
+    pub const FREE: ModelPricing = ModelPricing {
+        input: 0.0,
+        output: 0.0,
+        cache_read: None,
+        cache_write: None,
+    };
+
+    const fn new(input: f64, output: f64, cache_read: f64, cache_write: f64) -> Self {
+        Self {
+            input,
+            output,
+            cache_read: Some(cache_read),
+            cache_write: Some(cache_write),
+        }
+    }
+
+    /// Cost of a call in micro-dollars. `prompt_tokens` includes cached tokens.
+    pub fn cost_micros(&self, usage: &UsageStats) -> u64 {
+        let cached = usage.cache_read_tokens + usage.cache_write_tokens;
+        let uncached = usage.prompt_tokens.saturating_sub(cached) as f64;
+        let cost = uncached * self.input
+            + usage.cache_read_tokens as f64 * self.cache_read.unwrap_or(self.input)
+            + usage.cache_write_tokens as f64 * self.cache_write.unwrap_or(self.input)
+            + usage.completion_tokens as f64 * self.output;
+        cost.max(0.0).round() as u64
+    }
+}
+
+/// Built-in list prices, matched by longest model ID prefix
+const BUILTIN_PRICING: &[(&str, ModelPricing)] = &[
+    ("claude-opus-4-5", ModelPricing::new(5.0, 25.0, 0.5, 6.25)),
+    ("claude-opus-4", ModelPricing::new(15.0, 75.0, 1.5, 18.75)),
+    ("claude-sonnet-4", ModelPricing::new(3.0, 15.0, 0.3, 3.75)),
+    ("claude-3-7-sonnet", ModelPricing::new(3.0, 15.0, 0.3, 3.75)),
+    ("claude-haiku-4-5", ModelPricing::new(1.0, 5.0, 0.1, 1.25)),
+    ("claude-3-5-haiku", ModelPricing::new(0.8, 4.0, 0.08, 1.0)),
+    ("gpt-4o-mini", ModelPricing::new(0.15, 0.6, 0.075, 0.15)),
+    ("gpt-4o", ModelPricing::new(2.5, 10.0, 1.25, 2.5)),
+    ("gpt-4.1-mini", ModelPricing::new(0.4, 1.6, 0.1, 0.4)),
+    ("gpt-4.1", ModelPricing::new(2.0, 8.0, 0.5, 2.0)),
+    ("gemini-2.5-pro", ModelPricing::new(1.25, 10.0, 0.31, 1.25)),
+    ("gemini-2.5-flash", ModelPricing::new(0.3, 2.5, 0.075, 0.3)),
+];
+
+fn builtin_pricing(model_id: &str) -> Option<ModelPricing> {
+    BUILTIN_PRICING
+        .iter()
+        .filter(|(prefix, _)| model_id.starts_with(prefix))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, p)| *p)
+}
+
+/// Pricing for every configured model, keyed by "provider/model" label and bare model ID
+#[derive(Debug, Default)]
+pub struct PricingTable {
+    by_label: HashMap<String, ModelPricing>,
+    by_model: HashMap<String, ModelPricing>,
+}
+
+impl PricingTable {
+    /// Build from the parsed openclaw-manual.json
+    pub fn from_config(config: &serde_json::Value) -> Self {
+        let mut table = Self::default();
+        let Some(providers) = config
+            .get("models")
+            .and_then(|m| m.get("providers"))
+            .and_then(|p| p.as_object())
+        else {
+            return table;
+        };
+
+        let parse = |v: Option<&serde_json::Value>| -> Option<ModelPricing> {
+            v.and_then(|p| serde_json::from_value(p.clone()).ok())
+        };
+
+        for (name, provider) in providers {
+            let api = provider.get("api").and_then(|v| v.as_str()).unwrap_or("");
+            let base_url = provider.get("baseUrl").and_then(|v| v.as_str()).unwrap_or("");
+            let is_local = api == "ollama" || base_url.contains("localhost") || base_url.contains("127.0.0.1");
+            let provider_default = parse(provider.get("pricing")).or(is_local.then_some(ModelPricing::FREE));
+
+            let models = provider.get("models").and_then(|m| m.as_array());
+            for model in models.into_iter().flatten() {
+                let Some(id) = model.get("id").and_then(|v| v.as_str()) else { continue };
+                let Some(pricing) = parse(model.get("pricing")).or(provider_default) else { continue };
+                table.by_label.insert(format!("{}/{}", name, id), pricing);
+                table.by_model.entry(id.to_string()).or_insert(pricing);
+            }
+        }
+        table
+    }
+
+    /// Load from the manual config on disk (empty table if it can't be read)
+    pub fn load() -> Self {
+        std::fs::read_to_string(openclaw_core::paths::manual_config_path())
+            .ok()
+            .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+            .map(|config| Self::from_config(&config))
+            .unwrap_or_default()
+    }
+
+    /// Resolve pricing for a "provider/model" label or a bare model ID
+    pub fn lookup(&self, model: &str) -> Option<ModelPricing> {
+        let bare = model.split_once('/').map(|(_, rest)| rest);
+        self.by_label
+            .get(model)
+            .or_else(|| self.by_model.get(model))
+            .or_else(|| bare.and_then(|b| self.by_model.get(b)))
+            .copied()
+            .or_else(|| builtin_pricing(model))
+            .or_else(|| bare.and_then(builtin_pricing))
+    }
+
+    /// Cost of a call in micro-dollars (0 for models with unknown pricing)
+    pub fn cost_micros(&self, model: &str, usage: &UsageStats) -> u64 {
+        self.lookup(model).map(|p| p.cost_micros(usage)).unwrap_or(0)
+    }
+}
+
+static GLOBAL_TABLE: RwLock<Option<Arc<PricingTable>>> = RwLock::new(None);
+
+/// The process-wide pricing table, loaded from config on first use
+pub fn global() -> Arc<PricingTable> {
+    if let Some(table) = GLOBAL_TABLE.read().unwrap().as_ref() {
+        return table.clone();
+    }
+    let mut guard = GLOBAL_TABLE.write().unwrap();
+    guard.get_or_insert_with(|| Arc::new(PricingTable::load())).clone()
+}
+
+/// Replace the process-wide pricing table (called whenever config is re-read)
+pub fn install(table: PricingTable) {
+    *GLOBAL_TABLE.write().unwrap() = Some(Arc::new(table));
+}
+
+/// Format micro-dollars for display: "$0.0042", "$1.27"
+pub fn format_cost(micros: u64) -> String {
+    let dollars = micros as f64 / 1_000_000.0;
+    if dollars < 1.0 {
+        format!("${:.4}", dollars)
+    } else {
+        format!("${:.2}", dollars)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(prompt: u32, completion: u32, cache_read: u32, cache_write: u32) -> UsageStats {
+        UsageStats {
+            prompt_tokens: prompt,
+            completion_tokens: completion,
+            total_tokens: prompt + completion,
+            cache_read_tokens: cache_read,
+            cache_write_tokens: cache_write,
+        }
+    }
+
+    #[test]
+    fn test_cost_with_cache_rates() {
+        let p = ModelPricing::new(3.0, 15.0, 0.3, 3.75);
+        // 1000 uncached + 4000 cache read + 1000 cache write in, 500 out
+        let cost = p.cost_micros(&usage(6000, 500, 4000, 1000));
+        assert_eq!(cost, 3000 + 1200 + 3750 + 7500);
+    }
+
+    #[test]
+    fn test_cache_rates_default_to_input() {
+        let p = ModelPricing { input: 2.0, output: 8.0, ..Default::default() };
+        assert_eq!(p.cost_micros(&usage(1000, 100, 400, 0)), 2000 + 800);
+    }
+
+    #[test]
+    fn test_table_from_config() {
+        let config = serde_json::json!({
+            "models": {
+                "providers": {
+                    "moonshot": {
+                        "baseUrl": "https://api.moonshot.ai/v1",
+                        "pricing": {"input": 0.6, "output": 2.5},
+                        "models": [
+                            {"id": "kimi-k2.5"},
+                            {"id": "kimi-k2-turbo", "pricing": {"input": 1.15, "output": 8.0}}
+                        ]
+                    },
+                    "ollama": {
+                        "baseUrl": "http://127.0.0.1:11434",
+                        "api": "ollama",
+                        "models": [{"id": "qwen3:8b"}]
+                    },
+                    "anthropic": {
+                        "models": [{"id": "claude-sonnet-4-5-20250929"}]
+                    }
+                }
+            }
+        });
+        let table = PricingTable::from_config(&config);
+        assert_eq!(table.lookup("moonshot/kimi-k2.5").unwrap().input, 0.6);
+        assert_eq!(table.lookup("kimi-k2-turbo").unwrap().output, 8.0);
+        assert_eq!(table.lookup("qwen3:8b"), Some(ModelPricing::FREE));
+        // Unpriced models fall back to the built-in table by prefix
+        assert_eq!(table.lookup("anthropic/claude-sonnet-4-5-20250929").unwrap().output, 15.0);
+        assert_eq!(table.lookup("claude-opus-4-5").unwrap().input, 5.0);
+        assert!(table.lookup("mystery-model").is_none());
+        assert_eq!(table.cost_micros("mystery-model", &usage(1000, 1000, 0, 0)), 0);
+    }
+
+    #[test]
+    fn test_format_cost() {
+        assert_eq!(format_cost(4200), "$0.0042");
+        assert_eq!(format_cost(1_270_000), "$1.27");
+    }
+}
@@ -0,0 +1,647 @@
+//! Semantic model routing: pick a model tier for each turn by task complexity.
+//!
+//! Configured in openclaw-manual.json under `models.routing`:
+//!
+//! ```json
+//! "routing": {
+//!   "tiers": {
+//!     "cheap": ["ollama/qwen3:8b", "moonshot/kimi-k2-turbo"],
+//!     "strong": ["anthropic/claude-sonnet-4-5"]
+//!   },
+//!   "simple": "cheap",
+//!   "complex": "strong",
+//!   "classifier": "ollama/qwen3:8b",
+//!   "simpleMaxChars": 200,
+//!   "complexMinChars": 1500
+//! }
+//! ```
+//!
+//! Each tier is its own fallback chain. The implicit `default` tier is the
+//! normal `models.fallbacks` chain, used for standard turns and for any
+//! complexity without a tier. Heuristics settle the clear cases; the optional
+//! classifier model is only asked about the ambiguous ones. A chat can pin a
+//! tier with `set_chat_override` (the gateway's `/model <tier>`).
+
+use anyhow::Result;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::{Mutex, RwLock};
+use std::time::Duration;
+use tracing::{info, warn};
+
+use super::fallback::{build_spec_provider, load_manual_config, FallbackProvider};
+use super::streaming::StreamEvent;
+use super::{Completion, LlmProvider, Message, Role, ToolDefinition, UsageStats};
+
+/// Name of the tier backed by the regular fallback chain
+pub const DEFAULT_TIER: &str = "default";
+
+const CLASSIFIER_TIMEOUT: Duration = Duration::from_secs(10);
+const CLASSIFIER_MAX_CHARS: usize = 2000;
+
+const CLASSIFIER_PROMPT: &str = "You route chat requests to AI models. Classify how demanding \
+the user's request is: simple (greetings, small talk, quick facts), standard (everyday questions, \
+light tool use) or complex (coding, debugging, multi-step research, long analysis). \
+Answer with exactly one word: simple, standard or complex.";
+
+/// Phrases that suggest the turn will need tools (files, shell, web, scheduling)
+const TOOL_HINTS: &[&str] = &[
+    "run ", "execute", "install", "file", "folder", "directory", "search", "look up", "fetch",
+    "download", "http://", "https://", "deploy", "build", "compile", "commit", "git ", "schedule",
+    "remind", "browse", "screenshot", "create ", "write ", "edit ", "fix ", "debug",
+];
+
+/// How demanding a turn looks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Complexity {
+    Simple,
+    Standard,
+    Complex,
+}
+
+impl Complexity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Simple => "simple",
+            Self::Standard => "standard",
+            Self::Complex => "complex",
+        }
+    }
+
+    /// Parse a classifier answer, tolerating punctuation and extra words
+    fn from_answer(answer: &str) -> Option<Self> {
+        let answer = answer.to_lowercase();
+        [Self::Complex, Self::Standard, Self::Simple]
+            .into_iter()
+            .find(|c| answer.contains(c.as_str()))
+    }
+}
+
+/// Thresholds for the heuristic classifier (`simpleMaxChars`, `complexMinChars`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RoutingRules {
+    /// Messages up to this length with no other signals are simple
+    pub simple_max_chars: usize,
+    /// Messages at least this long are complex
+    pub complex_min_chars: usize,
+}
+
+impl Default for RoutingRules {
+    fn default() -> Self {
+        Self {
+            simple_max_chars: 200,
+            complex_min_chars: 1500,
+        }
+    }
+}
+
+impl RoutingRules {
+    pub fn from_json(value: &serde_json::Value) -> Self {
+        let defaults = Self::default();
+        let get = |key: &str| value.get(key).and_then(|v| v.as_u64()).map(|v| v as usize);
+        Self {
+            simple_max_chars: get("simpleMaxChars").unwrap_or(defaults.simple_max_chars),
+            complex_min_chars: get("complexMinChars").unwrap_or(defaults.complex_min_chars),
+        }
+    }
+}
+
+/// Heuristic verdict on a turn. Non-decisive verdicts are worth a classifier call.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Classification {
+    pub complexity: Complexity,
+    pub reason: &'static str,
+    pub decisive: bool,
+}
+
+/// Classify a turn from its latest user message (length, images, code, tool hints)
+pub fn classify(messages: &[Message], rules: &RoutingRules) -> Classification {
+    let verdict = |complexity, reason, decisive| Classification { complexity, reason, decisive };
+
+    let Some(pos) = messages.iter().rposition(|m| matches!(m.role, Role::User)) else {
+        return verdict(Complexity::Standard, "no user message", true);
+    };
+    let last = &messages[pos];
+    let text = last.content.as_deref().unwrap_or("").trim();
+    let chars = text.chars().count();
+
+    if text.contains("```") {
+        return verdict(Complexity::Complex, "code block", true);
+    }
+    if chars >= rules.complex_min_chars {
+        return verdict(Complexity::Complex, "long message", true);
+    }
+    if !last.image_urls.is_empty() {
+        return verdict(Complexity::Standard, "image attached", true);
+    }
+    let lower = text.to_lowercase();
+    if TOOL_HINTS.iter().any(|hint| lower.contains(hint)) {
+        return verdict(Complexity::Standard, "likely needs tools", false);
+    }
+    if chars <= rules.simple_max_chars {
+        // A terse reply in a tool-heavy conversation ("yes, do it") continues that work
+        let recent_tools = messages[..pos]
+            .iter()
+            .rev()
+            .take(6)
+            .any(|m| matches!(m.role, Role::Tool) || m.tool_calls.is_some());
+        if recent_tools {
+            return verdict(Complexity::Standard, "follow-up to tool work", false);
+        }
+        return verdict(Complexity::Simple, "short message", true);
+    }
+    verdict(Complexity::Standard, "medium-length message", false)
+}
+
+/// Which tier a turn was sent to, and why
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouteDecision {
+    pub tier: String,
+    /// `None` when the chat pinned the tier
+    pub complexity: Option<Complexity>,
+    pub reason: String,
+}
+
+impl RouteDecision {
+    /// Compact label for logs, e.g. "cheap (simple: short message)"
+    pub fn label(&self) -> String {
+        match self.complexity {
+            Some(c) => format!("{} ({}: {})", self.tier, c.as_str(), self.reason),
+            None => format!("{} ({})", self.tier, self.reason),
+        }
+    }
+}
+
+struct RouteTier {
+    name: String,
+    provider: Box<dyn LlmProvider>,
+}
+
+/// Dispatches each turn to a named model tier based on its complexity.
+/// The decision is made once per user message and reused for the turn's tool rounds.
+pub struct RouterProvider {
+    tiers: Vec<RouteTier>,
+    routes: HashMap<Complexity, String>,
+    classifier: Option<Box<dyn LlmProvider>>,
+    rules: RoutingRules,
+    pinned_tier: Option<String>,
+    decision: Mutex<Option<(u64, RouteDecision)>>,
+    last_tier: RwLock<usize>,
+}
+
+impl RouterProvider {
+    /// Router whose `default` tier is the given provider
+    pub fn new(default: Box<dyn LlmProvider>) -> Self {
+        Self {
+            tiers: vec![RouteTier {
+                name: DEFAULT_TIER.to_string(),
+                provider: default,
+            }],
+            routes: HashMap::new(),
+            classifier: None,
+            rules: RoutingRules::default(),
+            pinned_tier: None,
+            decision: Mutex::new(None),
+            last_tier: RwLock::new(0),
+        }
+    }
+
+    /// Add a tier (replacing any tier of the same name)
+    pub fn with_tier(mut self, name: &str, provider: Box<dyn LlmProvider>) -> Self {
+        let tier = RouteTier {
+            name: name.to_string(),
+            provider,
+        };
+        match self.tiers.iter().position(|t| t.name == name) {
+            Some(i) => self.tiers[i] = tier,
+            None => self.tiers.push(tier),
+        }
+        self
+    }
+
+    /// Send turns of this complexity to the named tier
+    pub fn with_route(mut self, complexity: Complexity, tier: &str) -> Self {
+        self.routes.insert(complexity, tier.to_string());
+        self
+    }
+
+    /// Ask this (cheap) model about turns the heuristics can't settle
+    pub fn with_classifier(mut self, classifier: Box<dyn LlmProvider>) -> Self {
+        self.classifier = Some(classifier);
+        self
+    }
+
+    pub fn with_rules(mut self, rules: RoutingRules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Pin every turn to one tier, bypassing classification (per-chat `/model`)
+    pub fn with_pinned_tier(mut self, tier: Option<String>) -> Self {
+        self.pinned_tier = tier;
+        self
+    }
+
+    /// Build from `models.routing` in openclaw-manual.json; `None` if routing isn't configured
+    pub fn from_config() -> Result<Option<Self>> {
+        let config = load_manual_config()?;
+        super::pricing::install(super::pricing::PricingTable::from_config(&config));
+        crate::memory::install(crate::memory::Settings::from_manual(Some(&config)));
+        Self::from_config_value(&config)
+    }
+
+    pub fn from_config_value(config: &serde_json::Value) -> Result<Option<Self>> {
+        let Some(routing) = config.get("models").and_then(|m| m.get("routing")) else {
+            return Ok(None);
+        };
+        if routing.get("enabled").and_then(|v| v.as_bool()) == Some(false) {
+            return Ok(None);
+        }
+
+        let mut router = Self::new(Box::new(FallbackProvider::from_config_value(config)?))
+            .with_rules(RoutingRules::from_json(routing));
+
+        let tiers = routing.get("tiers").and_then(|t| t.as_object());
+        for (name, specs) in tiers.into_iter().flatten() {
+            let specs: Vec<String> = match specs {
+                serde_json::Value::String(spec) => vec![spec.clone()],
+                other => other
+                    .as_array()
+                    .map(|arr| arr.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+                    .unwrap_or_default(),
+            };
+            match FallbackProvider::from_specs(config, &specs) {
+                Ok(chain) => router = router.with_tier(name, Box::new(chain)),
+                Err(e) => warn!("Skipping routing tier '{}': {}", name, e),
+            }
+        }
+
+        for complexity in [Complexity::Simple, Complexity::Standard, Complexity::Complex] {
+            let Some(tier) = routing.get(complexity.as_str()).and_then(|v| v.as_str()) else {
+                continue;
+            };
+            if router.has_tier(tier) {
+                router = router.with_route(complexity, tier);
+            } else {
+                warn!("Routing for {} turns names unknown tier '{}'", complexity.as_str(), tier);
+            }
+        }
+
+        if let Some(spec) = routing.get("classifier").and_then(|v| v.as_str()) {
+            match build_spec_provider(config, spec) {
+                Ok(classifier) => router = router.with_classifier(classifier),
+                Err(e) => warn!("Routing classifier unavailable: {}", e),
+            }
+        }
+
+        Ok(Some(router))
+    }
+
+    /// Tier names, `default` first
+    pub fn tier_names(&self) -> Vec<&str> {
+        self.tiers.iter().map(|t| t.name.as_str()).collect()
+    }
+
+    pub fn has_tier(&self, name: &str) -> bool {
+        self.tiers.iter().any(|t| t.name == name)
+    }
+
+    /// Tier that turns of this complexity go to
+    pub fn tier_for(&self, complexity: Complexity) -> &str {
+        self.routes.get(&complexity).map(String::as_str).unwrap_or(DEFAULT_TIER)
+    }
+
+    pub fn has_classifier(&self) -> bool {
+        self.classifier.is_some()
+    }
+
+    /// Pick the tier for this request (cached per user message)
+    pub async fn decide(&self, messages: &[Message]) -> RouteDecision {
+        if let Some(ref tier) = self.pinned_tier {
+            if self.has_tier(tier) {
+                return RouteDecision {
+                    tier: tier.clone(),
+                    complexity: None,
+                    reason: "pinned by /model".to_string(),
+                };
+            }
+            warn!("Pinned tier '{}' no longer exists; routing normally", tier);
+        }
+
+        let key = turn_key(messages);
+        if let Some((cached_key, ref decision)) = *self.decision.lock().unwrap() {
+            if cached_key == key {
+                return decision.clone();
+            }
+        }
+
+        let verdict = classify(messages, &self.rules);
+        let (complexity, reason) = match self.classifier {
+            Some(ref classifier) if !verdict.decisive => match ask_classifier(classifier.as_ref(), messages).await {
+                Ok(complexity) => (complexity, "classifier".to_string()),
+                Err(e) => {
+                    warn!("Routing classifier failed, using heuristics: {}", e);
+                    (verdict.complexity, verdict.reason.to_string())
+                }
+            },
+            _ => (verdict.complexity, verdict.reason.to_string()),
+        };
+
+        let decision = RouteDecision {
+            tier: self.tier_for(complexity).to_string(),
+            complexity: Some(complexity),
+            reason,
+        };
+        info!("Routing turn to {}", decision.label());
+        *self.decision.lock().unwrap() = Some((key, decision.clone()));
+        decision
+    }
+
+    /// Choose a tier and tag this task's LLM log entries with the decision
+    async fn route(&self, messages: &[Message]) -> &dyn LlmProvider {
+        let decision = self.decide(messages).await;
+        let index = self.tiers.iter().position(|t| t.name == decision.tier).unwrap_or(0);
+        if let Ok(mut last) = self.last_tier.write() {
+            *last = index;
+        }
+        crate::llm_log::set_route_context(Some(&decision.label()));
+        self.tiers[index].provider.as_ref()
+    }
+}
+
+/// Identifies the turn by its latest user message, so tool rounds reuse the decision
+fn turn_key(messages: &[Message]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    if let Some(last) = messages.iter().rev().find(|m| matches!(m.role, Role::User)) {
+        last.content.hash(&mut hasher);
+        last.image_urls.len().hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+async fn ask_classifier(classifier: &dyn LlmProvider, messages: &[Message]) -> Result<Complexity> {
+    let text = messages
+        .iter()
+        .rev()
+        .find(|m| matches!(m.role, Role::User))
+        .and_then(|m| m.content.as_deref())
+        .unwrap_or("");
+    let text: String = text.chars().take(CLASSIFIER_MAX_CHARS).collect();
+    let prompt = [Message::system(CLASSIFIER_PROMPT), Message::user(&text)];
+
+    let (completion, _) = tokio::time::timeout(CLASSIFIER_TIMEOUT, classifier.complete(&prompt, &[]))
+        .await
+        .map_err(|_| anyhow::anyhow!("timed out after {}s", CLASSIFIER_TIMEOUT.as_secs()))??;
+    match completion {
+        Completion::Text { content, .. } => Complexity::from_answer(&content)
+            .ok_or_else(|| anyhow::anyhow!("unrecognised answer '{}'", content.trim())),
+        Completion::ToolCalls { .. } => anyhow::bail!("classifier answered with tool calls"),
+    }
+}
+
+#[async_trait]
+impl LlmProvider for RouterProvider {
+    fn name(&self) -> &str {
+        let index = self.last_tier.read().map(|i| *i).unwrap_or(0);
+        self.tiers[index].provider.name()
+    }
+
+    async fn complete(
+        &self,
+        messages: &[Message],
+        tools: &[ToolDefinition],
+    ) -> Result<(Completion, UsageStats)> {
+        let provider = self.route(messages).await;
+        let result = provider.complete(messages, tools).await;
+        crate::llm_log::set_route_context(None);
+        result
+    }
+
+    async fn complete_streaming(
+        &self,
+        messages: &[Message],
+        tools: &[ToolDefinition],
+        event_tx: tokio::sync::mpsc::UnboundedSender<StreamEvent>,
+    ) -> Result<(Completion, UsageStats)> {
+        let provider = self.route(messages).await;
+        let result = provider.complete_streaming(messages, tools, event_tx).await;
+        crate::llm_log::set_route_context(None);
+        result
+    }
+
+    async fn complete_structured(
+        &self,
+        messages: &[Message],
+        schema: &serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        let provider = self.route(messages).await;
+        let result = provider.complete_structured(messages, schema).await;
+        crate::llm_log::set_route_context(None);
+        result
+    }
+}
+
+/// Per-chat tier pins, keyed by session key
+static CHAT_PINS: Mutex<Option<HashMap<String, String>>> = Mutex::new(None);
+
+/// Pin a chat to a tier, or clear the pin with `None`
+pub fn set_chat_override(chat_key: &str, tier: Option<&str>) {
+    if let Ok(mut pins) = CHAT_PINS.lock() {
+        let map = pins.get_or_insert_with(HashMap::new);
+        match tier {
+            Some(tier) => map.insert(chat_key.to_string(), tier.to_string()),
+            None => map.remove(chat_key),
+        };
+    }
+}
+
+/// The tier a chat is pinned to, if any
+pub fn chat_override(chat_key: &str) -> Option<String> {
+    CHAT_PINS
+        .lock()
+        .ok()
+        .and_then(|pins| pins.as_ref().and_then(|map| map.get(chat_key).cloned()))
+}
+
+/// Provider for a chat turn: the router when `models.routing` is configured
+/// (honouring the chat's pin), otherwise the plain fallback chain.
+pub fn provider_for_chat(chat_key: &str) -> Result<Box<dyn LlmProvider>> {
+    match RouterProvider::from_config()? {
+        Some(router) => Ok(Box::new(router.with_pinned_tier(chat_override(chat_key)))),
+        None => Ok(Box::new(FallbackProvider::from_config()?)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// Answers every request with its own name
+    struct Named(&'static str, Arc<AtomicUsize>);
+
+    impl Named {
+        fn boxed(name: &'static str) -> Box<dyn LlmProvider> {
+            Box::new(Named(name, Arc::new(AtomicUsize::new(0))))
+        }
+    }
+
+    #[async_trait]
+    impl LlmProvider for Named {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        async fn complete(&self, _: &[Message], _: &[ToolDefinition]) -> Result<(Completion, UsageStats)> {
+            self.1.fetch_add(1, Ordering::SeqCst);
+            Ok((
+                Completion::Text {
+                    content: self.0.to_string(),
+                    reasoning: None,
+                },
+                UsageStats::default(),
+            ))
+        }
+    }
+
+    fn text_of(completion: Completion) -> String {
+        match completion {
+            Completion::Text { content, .. } => content,
+            Completion::ToolCalls { .. } => panic!("expected text"),
+        }
+    }
+
+    fn tiered_router() -> RouterProvider {
+        RouterProvider::new(Named::boxed("default-model"))
+            .with_tier("cheap", Named::boxed("cheap-model"))
+            .with_tier("strong", Named::boxed("strong-model"))
+            .with_route(Complexity::Simple, "cheap")
+            .with_route(Complexity::Complex, "strong")
+    }
+
+    #[test]
+    fn test_classify_heuristics() {
+        let rules = RoutingRules::default();
+        let c = |msgs: &[Message]| classify(msgs, &rules);
+
+        assert_eq!(c(&[Message::user("hi there!")]).complexity, Complexity::Simple);
+        let code = c(&[Message::user("why does this panic?\n```rust\nlet x = v[3];\n```")]);
+        assert_eq!((code.complexity, code.decisive), (Complexity::Complex, true));
+        assert_eq!(c(&[Message::user(&"word ".repeat(400))]).complexity, Complexity::Complex);
+        let image = c(&[Message::user_with_images("what is this", vec!["data:image/png;base64,AA".into()])]);
+        assert_eq!(image.complexity, Complexity::Standard);
+        let tools = c(&[Message::user("search the web for rust 2024 edition notes")]);
+        assert_eq!((tools.complexity, tools.decisive), (Complexity::Standard, false));
+    }
+
+    #[test]
+    fn test_short_follow_up_to_tool_work_is_not_simple() {
+        let call = crate::llm::ToolCall {
+            id: "1".into(),
+            call_type: "function".into(),
+            function: crate::llm::FunctionCall {
+                name: "exec".into(),
+                arguments: "{}".into(),
+            },
+        };
+        let messages = [
+            Message::user("check disk usage"),
+            Message::assistant_tool_calls(vec![call], None),
+            Message::tool_result("1", "92% used"),
+            Message::assistant("The disk is 92% full. Clean up old logs?"),
+            Message::user("yes please"),
+        ];
+        let verdict = classify(&messages, &RoutingRules::default());
+        assert_eq!(verdict.complexity, Complexity::Standard);
+        assert!(!verdict.decisive);
+    }
+
+    #[tokio::test]
+    async fn test_routes_by_complexity() {
+        let router = tiered_router();
+        let (c, _) = router.complete(&[Message::user("hello")], &[]).await.unwrap();
+        assert_eq!(text_of(c), "cheap-model");
+        assert_eq!(router.name(), "cheap-model");
+
+        let router = tiered_router();
+        let long = Message::user(&"please analyse ".repeat(200));
+        let (c, _) = router.complete(&[long], &[]).await.unwrap();
+        assert_eq!(text_of(c), "strong-model");
+
+        // Standard has no tier of its own, so it goes to the default chain
+        let router = tiered_router();
+        let medium = Message::user(&"tell me about the history of tea ".repeat(10));
+        let (c, _) = router.complete(&[medium], &[]).await.unwrap();
+        assert_eq!(text_of(c), "default-model");
+    }
+
+    #[tokio::test]
+    async fn test_classifier_settles_ambiguous_turns_once() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let router = tiered_router().with_classifier(Box::new(Named("complex", calls.clone())));
+        let messages = vec![Message::user(&"compare these two approaches to caching ".repeat(8))];
+
+        let decision = router.decide(&messages).await;
+        assert_eq!(decision.tier, "strong");
+        assert_eq!(decision.label(), "strong (complex: classifier)");
+        // Later rounds of the same turn reuse the decision
+        router.decide(&messages).await;
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // Decisive heuristics skip the classifier
+        router.decide(&[Message::user("thanks!")]).await;
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_pinned_tier() {
+        let router = tiered_router().with_pinned_tier(Some("strong".into()));
+        let (c, _) = router.complete(&[Message::user("hi")], &[]).await.unwrap();
+        assert_eq!(text_of(c), "strong-model");
+
+        // A pin to a tier that no longer exists is ignored
+        let router = tiered_router().with_pinned_tier(Some("gone".into()));
+        assert_eq!(router.decide(&[Message::user("hi")]).await.tier, "cheap");
+    }
+
+    #[test]
+    fn test_from_config_value() {
+        let config = serde_json::json!({
+            "models": {
+                "providers": {
+                    "moonshot": {
+                        "baseUrl": "https://api.moonshot.ai/v1",
+                        "apiKey": "k",
+                        "models": [{"id": "kimi-k2.5"}, {"id": "kimi-k2-turbo"}]
+                    }
+                },
+                "fallbacks": ["moonshot/kimi-k2.5"],
+                "routing": {
+                    "tiers": {"cheap": "moonshot/kimi-k2-turbo", "broken": ["nope/model"]},
+                    "simple": "cheap",
+                    "complex": "broken",
+                    "simpleMaxChars": 80
+                }
+            }
+        });
+        let router = RouterProvider::from_config_value(&config).unwrap().unwrap();
+        assert_eq!(router.tier_names(), vec!["default", "cheap"]);
+        assert_eq!(router.tier_for(Complexity::Simple), "cheap");
+        assert_eq!(router.tier_for(Complexity::Complex), DEFAULT_TIER);
+        assert_eq!(router.rules.simple_max_chars, 80);
+        assert!(!router.has_classifier());
+
+        let unrouted = serde_json::json!({"models": {"providers": {}}});
+        assert!(RouterProvider::from_config_value(&unrouted).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_chat_override() {
+        set_chat_override("tg:test:1:1", Some("strong"));
+        assert_eq!(chat_override("tg:test:1:1").as_deref(), Some("strong"));
+        set_chat_override("tg:test:1:1", None);
+        assert!(chat_override("tg:test:1:1").is_none());
+    }
+}
@@ -4,6 +4,7 @@ use serde::Deserialize;
 use tokio::sync::mpsc;
 
 use super::{Completion, FunctionCall, ToolCall, UsageStats};
+use openclaw_core::models::GenerationParams;
 
 /// Events emitted during streaming
 #[derive(Debug, Clone)]
@@ -16,10 +17,17 @@ pub enum StreamEvent {
     ToolCallStart { name: String },
     /// Tool is being executed (includes arguments for visibility)
     ToolExec { name: String, call_id: String, args_summary: String },
+    /// A chunk of a running tool's stdout/stderr, as it arrives
+    ToolOutputDelta { call_id: String, chunk: String },
     /// Tool execution finished (includes truncated output for visibility)
     ToolResult { name: String, success: bool, output_preview: String },
     /// New round starting (after tool calls)
     RoundStart { round: usize },
+    /// The provider failed mid-stream and the round is restarting on `to`;
+    /// anything streamed since the last RoundStart should be discarded
+    ProviderSwitched { from: String, to: String },
+    /// A tool call is paused until the user answers with `approval::resolve(id, ..)`
+    ApprovalRequired { id: String, tool: String, args_summary: String, reason: String, timeout_secs: u64 },
     /// Stream finished — final completion
     Done,
 }
@@ -34,6 +42,7 @@ pub async fn stream_completion(
     messages: &[super::Message],
     tools: &[super::ToolDefinition],
     max_tokens: u32,
+    params: &GenerationParams,
     event_tx: Option<mpsc::UnboundedSender<StreamEvent>>,
 ) -> Result<(Completion, UsageStats)> {
     let mut body = serde_json::json!({
@@ -47,6 +56,7 @@ pub async fn stream_completion(
     if !tools.is_empty() {
         body["tools"] = serde_json::to_value(tools)?;
     }
+    super::apply_generation_params(&mut body, params);
 
     let response = client
         .post(format!("{}/chat/completions", base_url))
@@ -67,6 +77,8 @@ pub async fn stream_completion(
     let mut reasoning = String::new();
     let mut tool_calls: Vec<PartialToolCall> = Vec::new();
     let mut usage = UsageStats::default();
+    // A stream that closes without [DONE] or a finish_reason was cut off
+    let mut finished = false;
 
     let mut stream = response.bytes_stream();
     let mut buffer = String::new();
@@ -79,13 +91,18 @@ pub async fn stream_completion(
             let line = buffer[..line_end].trim().to_string();
             buffer = buffer[line_end + 1..].to_string();
 
-            if line.is_empty() || line == "data: [DONE]" {
+            if line == "data: [DONE]" {
+                finished = true;
+                continue;
+            }
+            if line.is_empty() {
                 continue;
             }
 
             if let Some(json_str) = line.strip_prefix("data: ") {
                 if let Ok(chunk) = serde_json::from_str::<StreamChunk>(json_str) {
                     if let Some(choice) = chunk.choices.first() {
+                        finished |= choice.finish_reason.is_some();
                         let delta = &choice.delta;
 
                         if let Some(ref c) = delta.content {
@@ -134,6 +151,8 @@ pub async fn stream_completion(
                             prompt_tokens: u.prompt_tokens,
                             completion_tokens: u.completion_tokens,
                             total_tokens: u.total_tokens,
+                            cache_read_tokens: u.prompt_tokens_details.map(|d| d.cached_tokens).unwrap_or(0),
+                            cache_write_tokens: 0,
                         };
                     }
                 }
@@ -141,17 +160,20 @@ pub async fn stream_completion(
         }
     }
 
+    if !finished {
+        anyhow::bail!("Stream ended before completion");
+    }
+
     // Fallback: estimate tokens if the API didn't report them (common in streaming)
     if usage.total_tokens == 0 {
-        let msg_chars: usize = messages.iter()
-            .map(|m| m.content.as_deref().unwrap_or("").len())
-            .sum();
-        let prompt_est = (msg_chars / 4).max(1) as u32;
-        let completion_est = ((content.len() + reasoning.len()) / 4).max(1) as u32;
+        let counter = crate::tokenizer::for_model(model);
+        let prompt_est = counter.count_messages(messages).max(1) as u32;
+        let completion_est = (counter.count(&content) + counter.count(&reasoning)) as u32;
         usage = UsageStats {
             prompt_tokens: prompt_est,
             completion_tokens: completion_est,
             total_tokens: prompt_est + completion_est,
+            ..Default::default()
         };
     }
 
@@ -214,6 +236,8 @@ struct StreamChunk {
 #[derive(Deserialize)]
 struct StreamChoice {
     delta: StreamDelta,
+    #[serde(default)]
+    finish_reason: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -251,6 +275,8 @@ struct StreamUsage {
     completion_tokens: u32,
     #[serde(default)]
     total_tokens: u32,
+    #[serde(default)]
+    prompt_tokens_details: Option<super::PromptTokensDetails>,
 }
 
 #[derive(Default)]
@@ -272,9 +298,17 @@ mod tests {
         assert_eq!(chunk.choices[0].delta.content.as_deref(), Some("Hello"));
         assert!(chunk.choices[0].delta.reasoning_content.is_none());
         assert!(chunk.choices[0].delta.tool_calls.is_none());
+        assert!(chunk.choices[0].finish_reason.is_none());
         assert!(chunk.usage.is_none());
     }
 
+    #[test]
+    fn test_stream_chunk_finish_reason_deserialization() {
+        let json = r#"{"choices":[{"delta":{},"finish_reason":"stop"}]}"#;
+        let chunk: StreamChunk = serde_json::from_str(json).unwrap();
+        assert_eq!(chunk.choices[0].finish_reason.as_deref(), Some("stop"));
+    }
+
     #[test]
     fn test_stream_chunk_reasoning_deserialization() {
         let json = r#"{"choices":[{"delta":{"reasoning_content":"thinking..."}}]}"#;
@@ -304,6 +338,40 @@ mod tests {
         assert_eq!(u.prompt_tokens, 10);
         assert_eq!(u.completion_tokens, 20);
         assert_eq!(u.total_tokens, 30);
+        assert!(u.prompt_tokens_details.is_none());
+    }
+
+    #[test]
+    fn test_stream_chunk_cached_usage_deserialization() {
+        let json = r#"{"choices":[],"usage":{"prompt_tokens":2000,"completion_tokens":20,"total_tokens":2020,"prompt_tokens_details":{"cached_tokens":1536}}}"#;
+        let chunk: StreamChunk = serde_json::from_str(json).unwrap();
+        let u = chunk.usage.unwrap();
+        assert_eq!(u.prompt_tokens_details.unwrap().cached_tokens, 1536);
+    }
+
+    #[tokio::test]
+    async fn test_truncated_stream_is_an_error() {
+        use crate::llm::mock_server::{MockRoute, MockServer};
+
+        let complete = "data: {\"choices\":[{\"delta\":{\"content\":\"Hi\"}}]}\n\ndata: {\"choices\":[{\"delta\":{},\"finish_reason\":\"stop\"}]}\n\ndata: [DONE]\n\n";
+        let truncated = "data: {\"choices\":[{\"delta\":{\"content\":\"Hi\"}}]}\n\n";
+        let server = MockServer::start(vec![
+            MockRoute::new("/chat/completions", 200, "text/event-stream", complete),
+            MockRoute::new("/chat/completions", 200, "text/event-stream", truncated),
+        ])
+        .await;
+        let client = reqwest::Client::new();
+        let params = GenerationParams::default();
+
+        let (completion, _) = stream_completion(&client, &server.base_url, "k", "m", &[], &[], 100, &params, None)
+            .await
+            .unwrap();
+        assert!(matches!(completion, Completion::Text { ref content, .. } if content == "Hi"));
+
+        let err = stream_completion(&client, &server.base_url, "k", "m", &[], &[], 100, &params, None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("ended before completion"));
     }
 
     #[test]
@@ -324,12 +392,13 @@ mod tests {
             StreamEvent::ToolExec { name: "exec".into(), call_id: "c1".into(), args_summary: "`ls -la`".into() },
             StreamEvent::ToolResult { name: "exec".into(), success: true, output_preview: "total 42".into() },
             StreamEvent::RoundStart { round: 1 },
+            StreamEvent::ProviderSwitched { from: "a".into(), to: "b".into() },
             StreamEvent::Done,
         ];
         for evt in &events {
             let _cloned = evt.clone();
             let _debug = format!("{:?}", evt);
         }
-        assert_eq!(events.len(), 7);
+        assert_eq!(events.len(), 8);
     }
 }
@@ -0,0 +1,390 @@
+use anyhow::Result;
+use tracing::warn;
+
+use super::{Completion, LlmProvider, Message};
+
+/// Max follow-up requests asking the model to fix invalid JSON
+pub const MAX_REPAIR_ATTEMPTS: usize = 2;
+
+/// Tool name used to force schema-shaped output via tool calling
+pub const STRUCTURED_TOOL_NAME: &str = "structured_output";
+
+/// Name for the schema in provider requests: its `title` (sanitized) or "response"
+pub fn schema_name(schema: &serde_json::Value) -> String {
+    let name: String = schema
+        .get("title")
+        .and_then(|v| v.as_str())
+        .unwrap_or("response")
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '-' { c } else { '_' })
+        .take(64)
+        .collect();
+    if name.is_empty() {
+        "response".to_string()
+    } else {
+        name
+    }
+}
+
+/// Instruction appended to the conversation for providers without native JSON mode
+fn schema_instruction(schema: &serde_json::Value) -> String {
+    format!(
+        "Respond with ONLY a JSON value that conforms to this JSON schema — no prose, \
+         no markdown fences:\n{}",
+        serde_json::to_string_pretty(schema).unwrap_or_default()
+    )
+}
+
+/// Extract a JSON value from model output: the whole text, a ```json fenced block,
+/// or the outermost {...} / [...] span.
+pub fn extract_json(text: &str) -> Option<serde_json::Value> {
+    let trimmed = text.trim();
+    if let Ok(v) = serde_json::from_str(trimmed) {
+        return Some(v);
+    }
+
+    if let Some(start) = trimmed.find("```") {
+        let after = &trimmed[start + 3..];
+        let body_start = after.find('\n').map(|i| i + 1).unwrap_or(0);
+        if let Some(end) = after[body_start..].find("```") {
+            if let Ok(v) = serde_json::from_str(after[body_start..body_start + end].trim()) {
+                return Some(v);
+            }
+        }
+    }
+
+    for (open, close) in [('{', '}'), ('[', ']')] {
+        if let (Some(start), Some(end)) = (trimmed.find(open), trimmed.rfind(close)) {
+            if start < end {
+                if let Ok(v) = serde_json::from_str(&trimmed[start..=end]) {
+                    return Some(v);
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Validate a value against a JSON schema. Returns a list of violations (empty = valid).
+///
+/// Supports the subset of JSON Schema used for tool/response schemas: `type`, `enum`,
+/// `const`, `properties`, `required`, `additionalProperties`, `items`, `minItems`,
+/// `maxItems`, `minLength`, `maxLength`, `minimum`, `maximum`, `anyOf`, `oneOf`, `allOf`.
+pub fn validate(value: &serde_json::Value, schema: &serde_json::Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    validate_at(value, schema, "$", &mut errors);
+    errors
+}
+
+fn type_matches(value: &serde_json::Value, ty: &str) -> bool {
+    match ty {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|f| f.fract() == 0.0),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+fn validate_at(value: &serde_json::Value, schema: &serde_json::Value, path: &str, errors: &mut Vec<String>) {
+    let Some(schema) = schema.as_object() else { return };
+
+    if let Some(ty) = schema.get("type") {
+        let types: Vec<&str> = match ty {
+            serde_json::Value::String(s) => vec![s.as_str()],
+            serde_json::Value::Array(arr) => arr.iter().filter_map(|v| v.as_str()).collect(),
+            _ => Vec::new(),
+        };
+        if !types.is_empty() && !types.iter().any(|t| type_matches(value, t)) {
+            errors.push(format!("{}: expected {}, got {}", path, types.join(" | "), json_type(value)));
+            return;
+        }
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(|v| v.as_array()) {
+        if !allowed.contains(value) {
+            errors.push(format!("{}: {} is not one of {}", path, value, serde_json::Value::Array(allowed.clone())));
+        }
+    }
+    if let Some(expected) = schema.get("const") {
+        if expected != value {
+            errors.push(format!("{}: expected constant {}", path, expected));
+        }
+    }
+
+    if let Some(options) = schema.get("anyOf").and_then(|v| v.as_array()) {
+        if !options.iter().any(|opt| validate(value, opt).is_empty()) {
+            errors.push(format!("{}: does not match any allowed schema (anyOf)", path));
+        }
+    }
+    if let Some(options) = schema.get("oneOf").and_then(|v| v.as_array()) {
+        match options.iter().filter(|opt| validate(value, opt).is_empty()).count() {
+            1 => {}
+            0 => errors.push(format!("{}: does not match any allowed schema (oneOf)", path)),
+            n => errors.push(format!("{}: matches {} schemas, expected exactly one (oneOf)", path, n)),
+        }
+    }
+    if let Some(all) = schema.get("allOf").and_then(|v| v.as_array()) {
+        for sub in all {
+            validate_at(value, sub, path, errors);
+        }
+    }
+
+    match value {
+        serde_json::Value::Object(obj) => {
+            let props = schema.get("properties").and_then(|v| v.as_object());
+            if let Some(required) = schema.get("required").and_then(|v| v.as_array()) {
+                for key in required.iter().filter_map(|k| k.as_str()) {
+                    if !obj.contains_key(key) {
+                        errors.push(format!("{}: missing required property '{}'", path, key));
+                    }
+                }
+            }
+            for (key, val) in obj {
+                let child = format!("{}.{}", path, key);
+                match props.and_then(|p| p.get(key)) {
+                    Some(prop_schema) => validate_at(val, prop_schema, &child, errors),
+                    None => match schema.get("additionalProperties") {
+                        Some(serde_json::Value::Bool(false)) => {
+                            errors.push(format!("{}: unexpected property '{}'", path, key));
+                        }
+                        Some(extra @ serde_json::Value::Object(_)) => validate_at(val, extra, &child, errors),
+                        _ => {}
+                    },
+                }
+            }
+        }
+        serde_json::Value::Array(items) => {
+            if let Some(min) = schema.get("minItems").and_then(|v| v.as_u64()) {
+                if (items.len() as u64) < min {
+                    errors.push(format!("{}: expected at least {} items, got {}", path, min, items.len()));
+                }
+            }
+            if let Some(max) = schema.get("maxItems").and_then(|v| v.as_u64()) {
+                if (items.len() as u64) > max {
+                    errors.push(format!("{}: expected at most {} items, got {}", path, max, items.len()));
+                }
+            }
+            if let Some(item_schema) = schema.get("items") {
+                for (i, item) in items.iter().enumerate() {
+                    validate_at(item, item_schema, &format!("{}[{}]", path, i), errors);
+                }
+            }
+        }
+        serde_json::Value::String(s) => {
+            let len = s.chars().count() as u64;
+            if let Some(min) = schema.get("minLength").and_then(|v| v.as_u64()) {
+                if len < min {
+                    errors.push(format!("{}: string shorter than {}", path, min));
+                }
+            }
+            if let Some(max) = schema.get("maxLength").and_then(|v| v.as_u64()) {
+                if len > max {
+                    errors.push(format!("{}: string longer than {}", path, max));
+                }
+            }
+        }
+        serde_json::Value::Number(n) => {
+            let n = n.as_f64().unwrap_or(0.0);
+            if let Some(min) = schema.get("minimum").and_then(|v| v.as_f64()) {
+                if n < min {
+                    errors.push(format!("{}: {} is below minimum {}", path, n, min));
+                }
+            }
+            if let Some(max) = schema.get("maximum").and_then(|v| v.as_f64()) {
+                if n > max {
+                    errors.push(format!("{}: {} is above maximum {}", path, n, max));
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn json_type(value: &serde_json::Value) -> &'static str {
+    match value {
+        serde_json::Value::Null => "null",
+        serde_json::Value::Bool(_) => "boolean",
+        serde_json::Value::Number(_) => "number",
+        serde_json::Value::String(_) => "string",
+        serde_json::Value::Array(_) => "array",
+        serde_json::Value::Object(_) => "object",
+    }
+}
+
+/// Validate-and-repair loop for providers without native structured output:
+/// ask for JSON, validate it, and feed the violations back up to MAX_REPAIR_ATTEMPTS times.
+pub async fn complete_with_repair<P: LlmProvider + ?Sized>(
+    provider: &P,
+    messages: &[Message],
+    schema: &serde_json::Value,
+) -> Result<serde_json::Value> {
+    let mut convo = messages.to_vec();
+    convo.push(Message::user(&schema_instruction(schema)));
+
+    let mut last_problem = String::new();
+    for attempt in 0..=MAX_REPAIR_ATTEMPTS {
+        let (completion, _usage) = provider.complete(&convo, &[]).await?;
+        let text = match completion {
+            Completion::Text { content, .. } => content,
+            Completion::ToolCalls { calls, .. } => {
+                // Some models insist on calling a tool — accept its arguments as the answer
+                calls.first().map(|c| c.function.arguments.clone()).unwrap_or_default()
+            }
+        };
+
+        let problem = match extract_json(&text) {
+            Some(value) => {
+                let errors = validate(&value, schema);
+                if errors.is_empty() {
+                    return Ok(value);
+                }
+                format!("Your JSON does not match the schema:\n- {}", errors.join("\n- "))
+            }
+            None => "Your response was not valid JSON.".to_string(),
+        };
+
+        warn!(
+            "{}: structured output invalid (attempt {}/{}): {}",
+            provider.name(),
+            attempt + 1,
+            MAX_REPAIR_ATTEMPTS + 1,
+            problem
+        );
+        convo.push(Message::assistant(&text));
+        convo.push(Message::user(&format!(
+            "{}\nReply again with ONLY the corrected JSON.",
+            problem
+        )));
+        last_problem = problem;
+    }
+
+    anyhow::bail!(
+        "Structured output failed schema validation after {} attempts: {}",
+        MAX_REPAIR_ATTEMPTS + 1,
+        last_problem
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan_schema() -> serde_json::Value {
+        serde_json::json!({
+            "title": "task plan",
+            "type": "object",
+            "properties": {
+                "title": {"type": "string", "minLength": 1},
+                "priority": {"type": "string", "enum": ["low", "high"]},
+                "steps": {"type": "array", "items": {"type": "string"}, "minItems": 1},
+                "estimate": {"type": "integer", "minimum": 0}
+            },
+            "required": ["title", "steps"],
+            "additionalProperties": false
+        })
+    }
+
+    #[test]
+    fn test_schema_name() {
+        assert_eq!(schema_name(&plan_schema()), "task_plan");
+        assert_eq!(schema_name(&serde_json::json!({"type": "object"})), "response");
+    }
+
+    #[test]
+    fn test_extract_json_variants() {
+        assert_eq!(extract_json(r#"{"a":1}"#).unwrap()["a"], 1);
+        assert_eq!(extract_json("Here you go:\n```json\n{\"a\": 2}\n```").unwrap()["a"], 2);
+        assert_eq!(extract_json("Sure! {\"a\": 3} Hope that helps.").unwrap()["a"], 3);
+        assert!(extract_json("[1, 2]").unwrap().is_array());
+        assert!(extract_json("no json here").is_none());
+    }
+
+    #[test]
+    fn test_validate_valid() {
+        let value = serde_json::json!({"title": "Ship it", "steps": ["build", "deploy"], "estimate": 3});
+        assert!(validate(&value, &plan_schema()).is_empty());
+    }
+
+    #[test]
+    fn test_validate_reports_violations() {
+        let value = serde_json::json!({"title": "", "priority": "urgent", "steps": [], "extra": true, "estimate": -1});
+        let errors = validate(&value, &plan_schema());
+        assert!(errors.iter().any(|e| e.contains("shorter than 1")));
+        assert!(errors.iter().any(|e| e.contains("not one of")));
+        assert!(errors.iter().any(|e| e.contains("at least 1 items")));
+        assert!(errors.iter().any(|e| e.contains("unexpected property 'extra'")));
+        assert!(errors.iter().any(|e| e.contains("below minimum")));
+
+        let missing = validate(&serde_json::json!({"title": "x"}), &plan_schema());
+        assert_eq!(missing, vec!["$: missing required property 'steps'"]);
+
+        let wrong_type = validate(&serde_json::json!({"title": "x", "steps": "build"}), &plan_schema());
+        assert_eq!(wrong_type, vec!["$.steps: expected array, got string"]);
+    }
+
+    #[test]
+    fn test_validate_one_of_requires_exactly_one() {
+        let schema = serde_json::json!({"oneOf": [{"type": "integer"}, {"type": "number"}, {"type": "string"}]});
+        assert!(validate(&serde_json::json!("x"), &schema).is_empty());
+        assert_eq!(
+            validate(&serde_json::json!(3), &schema),
+            vec!["$: matches 2 schemas, expected exactly one (oneOf)"]
+        );
+        assert_eq!(validate(&serde_json::json!(true), &schema), vec!["$: does not match any allowed schema (oneOf)"]);
+
+        let any = serde_json::json!({"anyOf": [{"type": "integer"}, {"type": "number"}]});
+        assert!(validate(&serde_json::json!(3), &any).is_empty());
+    }
+
+    /// Replies with a fixed sequence of text completions
+    struct ScriptedProvider {
+        replies: std::sync::Mutex<Vec<String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl LlmProvider for ScriptedProvider {
+        fn name(&self) -> &str {
+            "scripted"
+        }
+
+        async fn complete(
+            &self,
+            _messages: &[Message],
+            _tools: &[super::super::ToolDefinition],
+        ) -> Result<(Completion, super::super::UsageStats)> {
+            let content = self.replies.lock().unwrap().remove(0);
+            Ok((Completion::Text { content, reasoning: None }, Default::default()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_repair_loop_recovers() {
+        let provider = ScriptedProvider {
+            replies: std::sync::Mutex::new(vec![
+                "I think the plan is to build.".to_string(),
+                r#"{"title": "Plan"}"#.to_string(),
+                r#"{"title": "Plan", "steps": ["build"]}"#.to_string(),
+            ]),
+        };
+        let value = provider
+            .complete_structured(&[Message::user("plan the release")], &plan_schema())
+            .await
+            .unwrap();
+        assert_eq!(value["steps"][0], "build");
+    }
+
+    #[tokio::test]
+    async fn test_repair_loop_gives_up() {
+        let provider = ScriptedProvider {
+            replies: std::sync::Mutex::new(vec!["nope".to_string(); MAX_REPAIR_ATTEMPTS + 1]),
+        };
+        let err = complete_with_repair(&provider, &[Message::user("plan")], &plan_schema())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("not valid JSON"));
+    }
+}
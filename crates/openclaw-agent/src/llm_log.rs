@@ -14,7 +14,9 @@ pub fn init_global() {
 }
 
 /// Record an LLM interaction (in-memory + Postgres if available)
-pub fn record(entry: LlmLogEntry) {
+pub fn record(mut entry: LlmLogEntry) {
+    entry.cost_micros = crate::llm::pricing::global().cost_micros(&entry.model, &entry.usage());
+
     // Dual-write to Postgres (fire-and-forget)
     if let Some(pool) = openclaw_db::pool() {
         let id = uuid::Uuid::parse_str(&entry.id).unwrap_or_else(|_| uuid::Uuid::new_v4());
@@ -31,7 +33,11 @@ pub fn record(entry: LlmLogEntry) {
         let usage_prompt = entry.usage_prompt_tokens as i32;
         let usage_completion = entry.usage_completion_tokens as i32;
         let usage_total = entry.usage_total_tokens as i32;
+        let usage_cache_read = entry.usage_cache_read_tokens as i32;
+        let usage_cache_write = entry.usage_cache_write_tokens as i32;
         let latency_ms = entry.latency_ms as i32;
+        let cost_micros = entry.cost_micros as i64;
+        let route = entry.route.clone();
         let error = entry.error.clone();
         let pool = pool.clone();
         tokio::spawn(async move {
@@ -40,7 +46,8 @@ pub fn record(entry: LlmLogEntry) {
                 messages_count, request_tokens_est, streaming,
                 response_content.as_deref(), response_reasoning.as_deref(),
                 response_tool_calls, &tool_call_names, usage_prompt,
-                usage_completion, usage_total, latency_ms, error.as_deref(),
+                usage_completion, usage_total, usage_cache_read, usage_cache_write,
+                latency_ms, cost_micros, route.as_deref(), error.as_deref(),
             ).await;
         });
     }
@@ -105,6 +112,30 @@ pub fn current_provider_attempt() -> u32 {
     CURRENT_PROVIDER_ATTEMPT.load(std::sync::atomic::Ordering::Relaxed)
 }
 
+/// Per-task routing decision, set by `RouterProvider` while it dispatches a call
+static CURRENT_ROUTE: std::sync::Mutex<Option<HashMap<String, String>>> = std::sync::Mutex::new(None);
+
+/// Set (or clear) the routing decision for this tokio task
+pub fn set_route_context(route: Option<&str>) {
+    let task_id = current_task_id();
+    if let Ok(mut ctx) = CURRENT_ROUTE.lock() {
+        let map = ctx.get_or_insert_with(HashMap::new);
+        match route {
+            Some(route) => map.insert(task_id, route.to_string()),
+            None => map.remove(&task_id),
+        };
+    }
+}
+
+/// Get the routing decision for this tokio task (used internally by log entry creation)
+pub fn current_route() -> Option<String> {
+    let task_id = current_task_id();
+    CURRENT_ROUTE
+        .lock()
+        .ok()
+        .and_then(|ctx| ctx.as_ref().and_then(|map| map.get(&task_id).cloned()))
+}
+
 /// Set the current session key for this tokio task (call before running an agent turn)
 pub fn set_session_context(session_key: &str) {
     let task_id = current_task_id();
@@ -149,6 +180,7 @@ pub fn stats() -> LogStats {
             total_recorded: log.total_count,
             buffered: log.entries.len() as u64,
             errors: log.entries.iter().filter(|e| e.error.is_some()).count() as u64,
+            total_cost_micros: log.total_cost_micros,
             avg_latency_ms: if log.entries.is_empty() {
                 0
             } else {
@@ -165,6 +197,9 @@ pub struct LogStats {
     pub buffered: u64,
     pub errors: u64,
     pub avg_latency_ms: u64,
+    /// Cost of every call recorded since startup, in micro-dollars
+    #[serde(default)]
+    pub total_cost_micros: u64,
 }
 
 /// A single LLM API interaction log entry
@@ -194,14 +229,25 @@ pub struct LlmLogEntry {
     pub usage_prompt_tokens: u32,
     pub usage_completion_tokens: u32,
     pub usage_total_tokens: u32,
+    /// Prompt tokens served from / written to the provider's prompt cache
+    #[serde(default)]
+    pub usage_cache_read_tokens: u32,
+    #[serde(default)]
+    pub usage_cache_write_tokens: u32,
     /// Latency in milliseconds
     pub latency_ms: u64,
+    /// Cost in micro-dollars, from the pricing table (see `llm::pricing`)
+    #[serde(default)]
+    pub cost_micros: u64,
     /// Error message if the request failed
     pub error: Option<String>,
     /// Which provider attempt this was (1-based, for fallback chains)
     pub provider_attempt: u32,
     /// Session key (if available from context)
     pub session_key: Option<String>,
+    /// Routing decision that picked this model (see `llm::router`), if routed
+    #[serde(default)]
+    pub route: Option<String>,
 }
 
 impl LlmLogEntry {
@@ -221,10 +267,25 @@ impl LlmLogEntry {
             usage_prompt_tokens: 0,
             usage_completion_tokens: 0,
             usage_total_tokens: 0,
+            usage_cache_read_tokens: 0,
+            usage_cache_write_tokens: 0,
             latency_ms: 0,
+            cost_micros: 0,
             error: None,
             provider_attempt: current_provider_attempt(),
             session_key: current_session_key(),
+            route: current_route(),
+        }
+    }
+
+    /// Token usage as reported by the provider
+    pub fn usage(&self) -> crate::llm::UsageStats {
+        crate::llm::UsageStats {
+            prompt_tokens: self.usage_prompt_tokens,
+            completion_tokens: self.usage_completion_tokens,
+            total_tokens: self.usage_total_tokens,
+            cache_read_tokens: self.usage_cache_read_tokens,
+            cache_write_tokens: self.usage_cache_write_tokens,
         }
     }
 
@@ -253,15 +314,28 @@ impl LlmLogEntry {
         } else {
             String::new()
         };
+        let route = self.route.as_deref()
+            .map(|r| format!(" [route {}]", r))
+            .unwrap_or_default();
         let session = self.session_key.as_deref()
             .map(|s| {
                 let short = if s.len() > 20 { &s[..20] } else { s };
                 format!(" ({})", short)
             })
             .unwrap_or_default();
+        let cached = if self.usage_cache_read_tokens > 0 {
+            format!(" ({} cached)", self.usage_cache_read_tokens)
+        } else {
+            String::new()
+        };
+        let cost = if self.cost_micros > 0 {
+            format!(" {}", crate::llm::pricing::format_cost(self.cost_micros))
+        } else {
+            String::new()
+        };
         format!(
-            "{} {}{} | {}ms | {}tok{} | {}",
-            status, self.model, attempt, self.latency_ms, self.usage_total_tokens, session, content_preview
+            "{} {}{}{} | {}ms | {}tok{}{}{} | {}",
+            status, self.model, attempt, route, self.latency_ms, self.usage_total_tokens, cached, cost, session, content_preview
         )
     }
 }
@@ -271,6 +345,7 @@ struct LlmActivityLog {
     entries: VecDeque<LlmLogEntry>,
     capacity: usize,
     total_count: u64,
+    total_cost_micros: u64,
 }
 
 impl LlmActivityLog {
@@ -279,6 +354,7 @@ impl LlmActivityLog {
             entries: VecDeque::with_capacity(capacity),
             capacity,
             total_count: 0,
+            total_cost_micros: 0,
         }
     }
 
@@ -286,6 +362,7 @@ impl LlmActivityLog {
         if self.entries.len() >= self.capacity {
             self.entries.pop_front();
         }
+        self.total_cost_micros += entry.cost_micros;
         self.entries.push_back(entry);
         self.total_count += 1;
     }
@@ -337,6 +414,35 @@ mod tests {
         assert!(summary.contains("Hello, world!"));
     }
 
+    #[test]
+    fn test_llm_log_entry_summary_cache_hits() {
+        let mut entry = LlmLogEntry::new("anthropic/claude-sonnet-4");
+        entry.response_content = Some("Hi".to_string());
+        entry.usage_total_tokens = 5000;
+        entry.usage_cache_read_tokens = 4096;
+        assert!(entry.summary().contains("5000tok (4096 cached)"));
+    }
+
+    #[test]
+    fn test_llm_log_entry_summary_cost() {
+        let mut entry = LlmLogEntry::new("anthropic/claude-sonnet-4");
+        entry.usage_total_tokens = 1200;
+        entry.cost_micros = 4200;
+        assert!(entry.summary().contains("1200tok $0.0042"));
+    }
+
+    #[test]
+    fn test_llm_log_entry_deserialize_without_cache_fields() {
+        let mut json = serde_json::to_value(LlmLogEntry::new("test/model")).unwrap();
+        let obj = json.as_object_mut().unwrap();
+        obj.remove("usage_cache_read_tokens");
+        obj.remove("usage_cache_write_tokens");
+        obj.remove("cost_micros");
+        let entry: LlmLogEntry = serde_json::from_value(json).unwrap();
+        assert_eq!(entry.usage_cache_read_tokens, 0);
+        assert_eq!(entry.cost_micros, 0);
+    }
+
     #[test]
     fn test_llm_log_entry_summary_error() {
         let mut entry = LlmLogEntry::new("test/model");
@@ -424,6 +530,7 @@ mod tests {
         let mut log = LlmActivityLog::new(2);
         let mut e1 = LlmLogEntry::new("model");
         e1.latency_ms = 1;
+        e1.cost_micros = 100;
         let mut e2 = LlmLogEntry::new("model");
         e2.latency_ms = 2;
         let mut e3 = LlmLogEntry::new("model");
@@ -434,6 +541,8 @@ mod tests {
         log.push(e3);
 
         assert_eq!(log.entries.len(), 2);
+        // Lifetime cost survives eviction
+        assert_eq!(log.total_cost_micros, 100);
         // e1 should be evicted
         let all = log.all();
         assert_eq!(all[0].latency_ms, 3);
@@ -481,6 +590,7 @@ mod tests {
             buffered: 5,
             errors: 2,
             avg_latency_ms: 150,
+            total_cost_micros: 12_500,
         };
         let json = serde_json::to_string(&stats).unwrap();
         assert!(json.contains("\"total_recorded\":10"));
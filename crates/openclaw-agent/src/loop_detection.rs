@@ -9,12 +9,14 @@
 //!
 //! Returns a `LoopVerdict` that the runtime uses to either allow, warn, or block.
 
+use serde::{Deserialize, Serialize};
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 use tracing::warn;
 
 /// Configuration for loop detection thresholds.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct LoopDetectionConfig {
     /// Max tool calls to track in the sliding window.
     pub history_size: usize,
@@ -0,0 +1,713 @@
+//! Semantic memory: notes and past conversation snippets recalled by relevance.
+//!
+//! Records live in Postgres (`memories`) or, without a database, in
+//! `.memory-{agent}.index.json` in the workspace. Recall ranks them with BM25
+//! over the text and, when `agents.defaults.memory.embeddings` points at an
+//! OpenAI-compatible `/embeddings` endpoint, blends in cosine similarity of
+//! the embeddings. Without embeddings recall is pure BM25.
+//!
+//! Memories are stored with the session they came from. A `Memory` opened
+//! `for_session` only recalls and forgets that session's memories (plus shared
+//! ones stored without a session), so users never see each other's notes.
+//! Each session keeps at most `max_notes` notes and `max_conversation_memories`
+//! conversation snippets, and recall ranks only the `recall_window` newest.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use openclaw_core::config::{AgentDefaults, EmbeddingsConfig, MemoryConfig};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use tracing::{debug, warn};
+
+/// BM25 term-frequency saturation
+const BM25_K1: f32 = 1.2;
+/// BM25 length normalization
+const BM25_B: f32 = 0.75;
+/// Cosine similarity at which a memory counts as related without sharing a keyword
+const MIN_SIMILARITY: f32 = 0.3;
+/// Max characters of each side of an exchange kept in a conversation memory
+const MAX_SNIPPET_CHARS: usize = 500;
+/// User messages shorter than this ("ok", "thanks") aren't worth indexing
+const MIN_INDEXED_CHARS: usize = 20;
+
+/// Heading for recalled memories when they are appended to the system prompt
+pub const RECALL_HEADING: &str = "## Recalled memories\n\
+(Notes and past conversations that may be relevant. Use them if they help; they may be outdated.)";
+
+/// Ignored when tokenizing, so filler words don't drive keyword matches
+const STOPWORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "but", "by", "did", "do", "does", "for", "from", "had",
+    "has", "have", "how", "i", "in", "is", "it", "its", "me", "my", "of", "on", "or", "s", "so", "t", "that",
+    "the", "this", "to", "was", "we", "what", "when", "where", "which", "who", "why", "will", "with",
+    "you", "your",
+];
+
+/// A stored note or conversation snippet
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MemoryRecord {
+    pub id: String,
+    pub text: String,
+    /// `note` (stored with `remember`) or `conversation` (indexed from a turn)
+    pub source: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub session_key: Option<String>,
+    pub created_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub embedding: Option<Vec<f32>>,
+}
+
+/// A memory returned by `recall`, with its relevance score
+#[derive(Debug, Clone)]
+pub struct Recalled {
+    pub record: MemoryRecord,
+    pub score: f32,
+}
+
+enum Store {
+    File(PathBuf),
+    Postgres(&'static openclaw_db::PgPool),
+}
+
+/// `agents.defaults.memory` plus the embeddings client built from it
+pub struct Settings {
+    config: MemoryConfig,
+    embedder: Option<Embedder>,
+}
+
+impl Settings {
+    /// Settings from openclaw-manual.json (defaults when it is missing)
+    pub fn from_manual(manual: Option<&serde_json::Value>) -> Settings {
+        let config = manual
+            .and_then(|c| c.pointer("/agents/defaults"))
+            .and_then(|d| serde_json::from_value::<AgentDefaults>(d.clone()).ok())
+            .unwrap_or_default()
+            .memory_config();
+        Settings::new(config, manual)
+    }
+
+    /// `manual` resolves an embeddings `provider` reference
+    pub fn new(config: MemoryConfig, manual: Option<&serde_json::Value>) -> Settings {
+        let embedder = config.embeddings.as_ref().and_then(|e| {
+            Embedder::from_config(e, manual)
+                .map_err(|err| warn!("Memory embeddings unavailable, recall uses BM25 only: {}", err))
+                .ok()
+        });
+        Settings { config, embedder }
+    }
+}
+
+static GLOBAL_SETTINGS: RwLock<Option<Arc<Settings>>> = RwLock::new(None);
+
+/// The process-wide memory settings, loaded from config on first use
+pub fn global() -> Arc<Settings> {
+    if let Some(settings) = GLOBAL_SETTINGS.read().unwrap().as_ref() {
+        return settings.clone();
+    }
+    let mut guard = GLOBAL_SETTINGS.write().unwrap();
+    guard
+        .get_or_insert_with(|| {
+            let manual = crate::llm::fallback::load_manual_config().ok();
+            Arc::new(Settings::from_manual(manual.as_ref()))
+        })
+        .clone()
+}
+
+/// Replace the process-wide memory settings (called whenever config is re-read)
+pub fn install(settings: Settings) {
+    *GLOBAL_SETTINGS.write().unwrap() = Some(Arc::new(settings));
+}
+
+/// An agent's memory store plus the settings used to rank it
+pub struct Memory {
+    agent_name: String,
+    /// Session whose memories are visible; `None` sees all of the agent's
+    session_key: Option<String>,
+    store: Store,
+    settings: Arc<Settings>,
+}
+
+/// Path of the file store for an agent
+pub fn file_path(workspace_dir: &str, agent_name: &str) -> PathBuf {
+    PathBuf::from(workspace_dir).join(format!(".memory-{}.index.json", agent_name))
+}
+
+impl Memory {
+    /// Open an agent's memory with the process-wide settings
+    pub fn open(workspace_dir: &str, agent_name: &str) -> Memory {
+        Memory::with_settings(workspace_dir, agent_name, global())
+    }
+
+    /// Open with explicit settings
+    pub fn with_settings(workspace_dir: &str, agent_name: &str, settings: Arc<Settings>) -> Memory {
+        let store = match (settings.config.store.as_deref(), openclaw_db::pool()) {
+            (Some("file"), _) | (_, None) => Store::File(file_path(workspace_dir, agent_name)),
+            (_, Some(pool)) => Store::Postgres(pool),
+        };
+        if settings.config.store.as_deref() == Some("postgres") && matches!(store, Store::File(_)) {
+            warn!("Memory store is set to postgres but the database is not connected; using the workspace file");
+        }
+        Memory { agent_name: agent_name.to_string(), session_key: None, store, settings }
+    }
+
+    /// Limit recall and forget to `session_key`'s memories and the shared ones
+    pub fn for_session(mut self, session_key: &str) -> Memory {
+        self.session_key = Some(session_key.to_string());
+        self
+    }
+
+    fn visible(&self, record: &MemoryRecord) -> bool {
+        match (self.session_key.as_deref(), record.session_key.as_deref()) {
+            (Some(scope), Some(owner)) => scope == owner,
+            _ => true,
+        }
+    }
+
+    pub fn config(&self) -> &MemoryConfig {
+        &self.settings.config
+    }
+
+    /// The `recall_window` newest visible memories, newest first
+    pub async fn recent(&self) -> Result<Vec<MemoryRecord>> {
+        let window = self.config().recall_window;
+        match self.store {
+            Store::File(ref path) => {
+                let mut records = load_file(path);
+                records.retain(|r| self.visible(r));
+                records.sort_by_key(|r| std::cmp::Reverse(r.created_at));
+                records.truncate(window);
+                Ok(records)
+            }
+            Store::Postgres(pool) => Ok(openclaw_db::memories::list_memories(
+                pool,
+                &self.agent_name,
+                self.session_key.as_deref(),
+                window,
+            )
+            .await?
+                .into_iter()
+                .map(|row| MemoryRecord {
+                    id: row.id,
+                    text: row.text,
+                    source: row.source,
+                    tags: row.tags,
+                    session_key: row.session_key,
+                    created_at: row.created_at,
+                    embedding: row.embedding,
+                })
+                .collect()),
+        }
+    }
+
+    /// Store a memory, embedding it when an embeddings endpoint is configured
+    pub async fn remember(
+        &self,
+        text: &str,
+        source: &str,
+        tags: Vec<String>,
+        session_key: Option<&str>,
+    ) -> Result<MemoryRecord> {
+        let embedding = match self.settings.embedder {
+            Some(ref embedder) => embedder
+                .embed(text)
+                .await
+                .map_err(|e| warn!("Failed to embed memory, storing it for keyword recall only: {}", e))
+                .ok(),
+            None => None,
+        };
+        let record = MemoryRecord {
+            id: new_id(),
+            text: text.trim().to_string(),
+            source: source.to_string(),
+            tags,
+            session_key: session_key.map(str::to_string),
+            created_at: Utc::now(),
+            embedding,
+        };
+
+        match self.store {
+            Store::File(ref path) => {
+                let _guard = file_lock().lock().unwrap_or_else(|e| e.into_inner());
+                let mut records = load_file(path);
+                records.push(record.clone());
+                save_file(path, &records)?;
+            }
+            Store::Postgres(pool) => {
+                let row = openclaw_db::memories::MemoryRow {
+                    id: record.id.clone(),
+                    agent_name: self.agent_name.clone(),
+                    text: record.text.clone(),
+                    source: record.source.clone(),
+                    tags: record.tags.clone(),
+                    session_key: record.session_key.clone(),
+                    embedding: record.embedding.clone(),
+                    created_at: record.created_at,
+                };
+                openclaw_db::memories::insert_memory(pool, &row).await?;
+            }
+        }
+        debug!("Remembered {} ({}) for {}", record.id, record.source, self.agent_name);
+
+        // Keep the session's memories of this kind within their cap
+        if let Some(ref session_key) = record.session_key {
+            let keep = match source {
+                "conversation" => self.config().max_conversation_memories,
+                _ => self.config().max_notes,
+            };
+            match self.prune(session_key, source, keep).await {
+                Ok(0) => {}
+                Ok(n) => debug!("Pruned {} old {} memories of {}", n, source, session_key),
+                Err(e) => warn!("Failed to prune {} memories of {}: {}", source, session_key, e),
+            }
+        }
+        Ok(record)
+    }
+
+    /// The `limit` memories most relevant to `query`, best first
+    pub async fn recall(&self, query: &str, limit: usize) -> Result<Vec<Recalled>> {
+        let records = self.recent().await?;
+        if records.is_empty() || limit == 0 {
+            return Ok(Vec::new());
+        }
+        let query_embedding = match self.settings.embedder {
+            Some(ref embedder) if records.iter().any(|r| r.embedding.is_some()) => embedder
+                .embed(query)
+                .await
+                .map_err(|e| warn!("Failed to embed recall query, using BM25 only: {}", e))
+                .ok(),
+            _ => None,
+        };
+        Ok(rank(query, query_embedding.as_deref(), records, self.config().keyword_weight, limit))
+    }
+
+    /// Delete a memory by ID; returns whether it was deleted. A session-scoped
+    /// memory only deletes its own session's records.
+    pub async fn forget(&self, id: &str) -> Result<bool> {
+        let scope = self.session_key.as_deref();
+        match self.store {
+            Store::File(ref path) => {
+                let _guard = file_lock().lock().unwrap_or_else(|e| e.into_inner());
+                let mut records = load_file(path);
+                let before = records.len();
+                records.retain(|r| r.id != id || scope.is_some_and(|s| r.session_key.as_deref() != Some(s)));
+                if records.len() == before {
+                    return Ok(false);
+                }
+                save_file(path, &records)?;
+                Ok(true)
+            }
+            Store::Postgres(pool) => openclaw_db::memories::delete_memory(pool, &self.agent_name, scope, id).await,
+        }
+    }
+
+    /// Keep only the newest `keep` memories of `source` in `session_key`; returns how many were deleted
+    pub async fn prune(&self, session_key: &str, source: &str, keep: usize) -> Result<u64> {
+        match self.store {
+            Store::File(ref path) => {
+                let _guard = file_lock().lock().unwrap_or_else(|e| e.into_inner());
+                let mut records = load_file(path);
+                let mut matching: Vec<(DateTime<Utc>, String)> = records
+                    .iter()
+                    .filter(|r| r.source == source && r.session_key.as_deref() == Some(session_key))
+                    .map(|r| (r.created_at, r.id.clone()))
+                    .collect();
+                if matching.len() <= keep {
+                    return Ok(0);
+                }
+                matching.sort_unstable_by(|a, b| b.cmp(a));
+                let stale: Vec<String> = matching.split_off(keep).into_iter().map(|(_, id)| id).collect();
+                records.retain(|r| !stale.contains(&r.id));
+                save_file(path, &records)?;
+                Ok(stale.len() as u64)
+            }
+            Store::Postgres(pool) => {
+                openclaw_db::memories::prune_memories(pool, &self.agent_name, session_key, source, keep).await
+            }
+        }
+    }
+}
+
+/// Recall the session's memories for a new user message, formatted for the
+/// system prompt. `None` when recall is disabled or nothing relevant was found.
+pub async fn recall_for_prompt(
+    workspace_dir: &str,
+    agent_name: &str,
+    session_key: &str,
+    user_message: &str,
+) -> Option<String> {
+    let memory = Memory::open(workspace_dir, agent_name).for_session(session_key);
+    let top_k = memory.config().recall_top_k;
+    if top_k == 0 {
+        return None;
+    }
+    let recalled = memory
+        .recall(user_message, top_k)
+        .await
+        .map_err(|e| warn!("Memory recall failed for {}: {}", agent_name, e))
+        .ok()?;
+    if recalled.is_empty() {
+        return None;
+    }
+    debug!("Recalled {} memories for {}", recalled.len(), agent_name);
+    Some(prompt_section(&recalled))
+}
+
+/// Index a finished exchange as a `conversation` memory of the session in the background
+pub fn index_exchange(workspace_dir: &str, agent_name: &str, session_key: &str, user_message: &str, response: &str) {
+    if user_message.trim().chars().count() < MIN_INDEXED_CHARS || response.trim().is_empty() {
+        return;
+    }
+    let text = format!("User: {}\nAssistant: {}", snippet(user_message), snippet(response));
+    let (workspace_dir, agent_name, session_key) =
+        (workspace_dir.to_string(), agent_name.to_string(), session_key.to_string());
+    tokio::spawn(async move {
+        let memory = Memory::open(&workspace_dir, &agent_name);
+        if !memory.config().index_conversations {
+            return;
+        }
+        if let Err(e) = memory.remember(&text, "conversation", Vec::new(), Some(&session_key)).await {
+            warn!("Failed to index conversation for {}: {}", agent_name, e);
+        }
+    });
+}
+
+/// Render recalled memories as a system prompt section
+pub fn prompt_section(recalled: &[Recalled]) -> String {
+    let mut out = String::from(RECALL_HEADING);
+    for r in recalled {
+        out.push_str(&format!(
+            "\n- [{} {}] {}",
+            r.record.created_at.format("%Y-%m-%d"),
+            r.record.source,
+            r.record.text.replace('\n', " / ")
+        ));
+    }
+    out
+}
+
+/// Rank records against a query: BM25 (normalized to the best match) blended
+/// with cosine similarity when both the query and the record have embeddings
+pub fn rank(
+    query: &str,
+    query_embedding: Option<&[f32]>,
+    records: Vec<MemoryRecord>,
+    keyword_weight: f32,
+    limit: usize,
+) -> Vec<Recalled> {
+    let docs: Vec<&str> = records.iter().map(|r| r.text.as_str()).collect();
+    let keyword = bm25_scores(query, &docs);
+    let best = keyword.iter().cloned().fold(0.0f32, f32::max);
+    let weight = keyword_weight.clamp(0.0, 1.0);
+
+    let mut ranked: Vec<Recalled> = records
+        .into_iter()
+        .zip(keyword)
+        .filter_map(|(record, bm25)| {
+            let keyword = if best > 0.0 { bm25 / best } else { 0.0 };
+            let semantic = query_embedding
+                .zip(record.embedding.as_deref())
+                .map(|(q, d)| cosine(q, d).max(0.0));
+            let score = match semantic {
+                Some(sim) if bm25 > 0.0 || sim >= MIN_SIMILARITY => weight * keyword + (1.0 - weight) * sim,
+                Some(_) => return None,
+                None if bm25 > 0.0 => keyword,
+                None => return None,
+            };
+            Some(Recalled { record, score })
+        })
+        .collect();
+    ranked.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(b.record.created_at.cmp(&a.record.created_at))
+    });
+    ranked.truncate(limit);
+    ranked
+}
+
+/// Lowercased alphanumeric terms, without stopwords
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(str::to_lowercase)
+        .filter(|t| !STOPWORDS.contains(&t.as_str()))
+        .collect()
+}
+
+/// Okapi BM25 score of each document for the query
+pub fn bm25_scores(query: &str, docs: &[&str]) -> Vec<f32> {
+    let query_terms = tokenize(query);
+    let doc_terms: Vec<Vec<String>> = docs.iter().map(|d| tokenize(d)).collect();
+    if query_terms.is_empty() || docs.is_empty() {
+        return vec![0.0; docs.len()];
+    }
+
+    let n = docs.len() as f32;
+    let avg_len = doc_terms.iter().map(|t| t.len()).sum::<usize>() as f32 / n;
+    let mut doc_freq: HashMap<&str, usize> = HashMap::new();
+    for terms in &doc_terms {
+        let mut seen: Vec<&str> = terms.iter().map(String::as_str).collect();
+        seen.sort_unstable();
+        seen.dedup();
+        for term in seen {
+            *doc_freq.entry(term).or_insert(0) += 1;
+        }
+    }
+
+    doc_terms
+        .iter()
+        .map(|terms| {
+            let len = terms.len() as f32;
+            query_terms
+                .iter()
+                .map(|q| {
+                    let tf = terms.iter().filter(|t| *t == q).count() as f32;
+                    if tf == 0.0 {
+                        return 0.0;
+                    }
+                    let df = doc_freq.get(q.as_str()).copied().unwrap_or(0) as f32;
+                    let idf = (1.0 + (n - df + 0.5) / (df + 0.5)).ln();
+                    let norm = BM25_K1 * (1.0 - BM25_B + BM25_B * len / avg_len.max(1.0));
+                    idf * tf * (BM25_K1 + 1.0) / (tf + norm)
+                })
+                .sum()
+        })
+        .collect()
+}
+
+fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+fn snippet(text: &str) -> String {
+    let text = text.trim();
+    if text.chars().count() > MAX_SNIPPET_CHARS {
+        let head: String = text.chars().take(MAX_SNIPPET_CHARS).collect();
+        format!("{}…", head)
+    } else {
+        text.to_string()
+    }
+}
+
+fn new_id() -> String {
+    format!("mem-{}", &uuid::Uuid::new_v4().simple().to_string()[..12])
+}
+
+/// Serializes read-modify-write of the file stores
+fn file_lock() -> &'static Mutex<()> {
+    static LOCK: OnceLock<Mutex<()>> = OnceLock::new();
+    LOCK.get_or_init(|| Mutex::new(()))
+}
+
+fn load_file(path: &Path) -> Vec<MemoryRecord> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_file(path: &Path, records: &[MemoryRecord]) -> Result<()> {
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_string(records)?)
+        .with_context(|| format!("Failed to write {}", tmp.display()))?;
+    std::fs::rename(&tmp, path).with_context(|| format!("Failed to replace {}", path.display()))?;
+    Ok(())
+}
+
+/// Client for an OpenAI-compatible `/embeddings` endpoint
+struct Embedder {
+    client: reqwest::Client,
+    base_url: String,
+    api_key: Option<String>,
+    model: String,
+}
+
+impl Embedder {
+    fn from_config(config: &EmbeddingsConfig, manual: Option<&serde_json::Value>) -> Result<Embedder> {
+        let provider = config
+            .provider
+            .as_ref()
+            .map(|name| {
+                manual
+                    .and_then(|m| m.pointer(&format!("/models/providers/{}", name)))
+                    .ok_or_else(|| anyhow::anyhow!("Embeddings provider '{}' is not configured", name))
+            })
+            .transpose()?;
+        let from_provider = |key: &str| provider.and_then(|p| p.get(key)).and_then(|v| v.as_str()).map(str::to_string);
+        let base_url = config
+            .base_url
+            .clone()
+            .or_else(|| from_provider("baseUrl"))
+            .ok_or_else(|| anyhow::anyhow!("Embeddings need a baseUrl or a provider with one"))?;
+        if config.model.is_empty() {
+            anyhow::bail!("Embeddings need a model");
+        }
+        Ok(Embedder {
+            client: reqwest::Client::builder().timeout(std::time::Duration::from_secs(15)).build()?,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key: config.api_key.clone().or_else(|| from_provider("apiKey")),
+            model: config.model.clone(),
+        })
+    }
+
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let mut request = self
+            .client
+            .post(format!("{}/embeddings", self.base_url))
+            .json(&serde_json::json!({ "model": self.model, "input": [text] }));
+        if let Some(ref key) = self.api_key {
+            request = request.bearer_auth(key);
+        }
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!("Embeddings request failed ({}): {}", status, &body[..body.len().min(200)]);
+        }
+        let body: serde_json::Value = response.json().await?;
+        let embedding = body
+            .pointer("/data/0/embedding")
+            .and_then(|v| v.as_array())
+            .ok_or_else(|| anyhow::anyhow!("Embeddings response has no data[0].embedding"))?;
+        Ok(embedding.iter().filter_map(|v| v.as_f64()).map(|v| v as f32).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(id: &str, text: &str, embedding: Option<Vec<f32>>) -> MemoryRecord {
+        MemoryRecord {
+            id: id.to_string(),
+            text: text.to_string(),
+            source: "note".to_string(),
+            tags: Vec::new(),
+            session_key: None,
+            created_at: Utc::now(),
+            embedding,
+        }
+    }
+
+    #[test]
+    fn test_bm25_prefers_rare_matching_terms() {
+        let docs = [
+            "The user's dog is called Biscuit",
+            "The user prefers dark mode in every editor",
+            "Deploys go out on Tuesdays",
+        ];
+        let scores = bm25_scores("what is my dog called?", &docs);
+        assert!(scores[0] > 0.0);
+        assert_eq!(scores[1], 0.0);
+        assert_eq!(scores[2], 0.0);
+        assert_eq!(bm25_scores("the of and", &docs), vec![0.0; 3]);
+    }
+
+    #[test]
+    fn test_hybrid_rank_uses_embeddings_without_shared_keywords() {
+        let records = vec![
+            record("a", "Biscuit is a golden retriever", Some(vec![0.9, 0.1, 0.0])),
+            record("b", "Standup is at 9:30", Some(vec![0.0, 0.2, 0.9])),
+            record("c", "Prefers tea over coffee", None),
+        ];
+        // No keyword overlap with "a", but the query embedding is close to it
+        let ranked = rank("tell me about the puppy", Some(&[1.0, 0.0, 0.0]), records.clone(), 0.4, 5);
+        assert_eq!(ranked.len(), 1);
+        assert_eq!(ranked[0].record.id, "a");
+
+        // Pure BM25 mode: only keyword matches come back
+        let ranked = rank("coffee or tea?", None, records, 0.4, 5);
+        assert_eq!(ranked.len(), 1);
+        assert_eq!(ranked[0].record.id, "c");
+    }
+
+    fn file_memory(workspace: &str, agent_name: &str, config: MemoryConfig) -> Memory {
+        let config = MemoryConfig { store: Some("file".to_string()), ..config };
+        Memory::with_settings(workspace, agent_name, Arc::new(Settings::new(config, None)))
+    }
+
+    #[tokio::test]
+    async fn test_file_store_remember_recall_forget() {
+        let dir = tempfile::tempdir().unwrap();
+        let workspace = dir.path().to_str().unwrap();
+        let memory = file_memory(workspace, "test-mem", MemoryConfig::default());
+
+        let dog = memory.remember("The user's dog is called Biscuit", "note", vec!["pets".to_string()], None).await.unwrap();
+        memory.remember("The user works night shifts", "note", Vec::new(), None).await.unwrap();
+        assert!(file_path(workspace, "test-mem").exists());
+
+        let recalled = memory.recall("what's my dog's name", 3).await.unwrap();
+        assert_eq!(recalled.len(), 1);
+        assert_eq!(recalled[0].record.id, dog.id);
+        assert_eq!(recalled[0].record.tags, vec!["pets"]);
+
+        let section = prompt_section(&recalled);
+        assert!(section.starts_with(RECALL_HEADING));
+        assert!(section.contains("Biscuit"));
+
+        assert!(memory.forget(&dog.id).await.unwrap());
+        assert!(!memory.forget(&dog.id).await.unwrap());
+        assert!(memory.recall("dog", 3).await.unwrap().is_empty());
+        assert_eq!(memory.recent().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_memories_are_scoped_to_their_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let workspace = dir.path().to_str().unwrap();
+        let config = MemoryConfig { max_conversation_memories: 2, max_notes: 1, recall_window: 2, ..MemoryConfig::default() };
+        let alice = file_memory(workspace, "test-scope", config.clone()).for_session("dc:a");
+        let bob = file_memory(workspace, "test-scope", config).for_session("dc:b");
+
+        let secret = alice.remember("Alice's locker code is 4711", "conversation", Vec::new(), Some("dc:a")).await.unwrap();
+        assert_eq!(alice.recall("locker code", 3).await.unwrap().len(), 1);
+        assert!(bob.recall("locker code", 3).await.unwrap().is_empty());
+        assert!(!bob.forget(&secret.id).await.unwrap());
+        assert_eq!(alice.recent().await.unwrap().len(), 1);
+
+        // Memories past the session's caps are pruned as new ones arrive, newest kept
+        for i in 0..4 {
+            let text = format!("Exchange number {} about deploys", i);
+            alice.remember(&text, "conversation", Vec::new(), Some("dc:a")).await.unwrap();
+        }
+        alice.remember("Old note", "note", Vec::new(), Some("dc:a")).await.unwrap();
+        alice.remember("New note", "note", Vec::new(), Some("dc:a")).await.unwrap();
+        assert_eq!(load_file(&file_path(workspace, "test-scope")).len(), 3);
+
+        // Recall ranks only the newest `recall_window` memories
+        let recent: Vec<String> = alice.recent().await.unwrap().into_iter().map(|r| r.text).collect();
+        assert_eq!(recent, vec!["New note", "Exchange number 3 about deploys"]);
+    }
+
+    #[test]
+    fn test_embedder_resolves_provider_reference() {
+        let manual = serde_json::json!({
+            "models": { "providers": { "openai": { "baseUrl": "https://api.openai.com/v1/", "apiKey": "sk-test" } } }
+        });
+        let config = EmbeddingsConfig {
+            provider: Some("openai".to_string()),
+            model: "text-embedding-3-small".to_string(),
+            ..EmbeddingsConfig::default()
+        };
+        let embedder = Embedder::from_config(&config, Some(&manual)).unwrap();
+        assert_eq!(embedder.base_url, "https://api.openai.com/v1");
+        assert_eq!(embedder.api_key.as_deref(), Some("sk-test"));
+
+        let missing = EmbeddingsConfig { provider: Some("nope".to_string()), ..config };
+        assert!(Embedder::from_config(&missing, Some(&manual)).is_err());
+    }
+}
@@ -0,0 +1,507 @@
+//! Record agent turns to JSONL cassettes and replay them deterministically.
+//!
+//! `RecordingProvider` wraps a provider and appends every request/response of
+//! a turn to a cassette, one `CassetteEntry` per line. `ReplayProvider` serves
+//! those responses back in order, so re-running the turn reproduces the
+//! original model output exactly; tools run for real or as `stub_tools`
+//! returning the recorded results. `diff_calls` compares the tool calls of the
+//! replayed turn with the original ones, which turns a production incident
+//! into a regression test.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use crate::llm::streaming::StreamEvent;
+use crate::llm::{Completion, LlmProvider, Message, Role, ToolDefinition, UsageStats};
+use crate::tools::{Tool, ToolContext, ToolRegistry, ToolResult};
+
+/// One LLM call of a recorded turn
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CassetteEntry {
+    /// Provider that answered
+    pub provider: String,
+    /// Request messages as sent
+    pub messages: Vec<Value>,
+    /// Names of the tools offered
+    pub tools: Vec<String>,
+    /// The response; `None` when the call failed
+    pub completion: Option<Completion>,
+    #[serde(default)]
+    pub usage: UsageStats,
+    pub error: Option<String>,
+}
+
+/// Provider wrapper that appends each call to a cassette file
+pub struct RecordingProvider {
+    inner: Box<dyn LlmProvider>,
+    path: PathBuf,
+    file: Mutex<Option<std::fs::File>>,
+}
+
+impl RecordingProvider {
+    pub fn new(inner: Box<dyn LlmProvider>, path: impl Into<PathBuf>) -> Self {
+        Self { inner, path: path.into(), file: Mutex::new(None) }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn record(&self, messages: &[Message], tools: &[ToolDefinition], result: &Result<(Completion, UsageStats)>) {
+        let entry = CassetteEntry {
+            provider: self.inner.name().to_string(),
+            messages: messages.iter().filter_map(|m| serde_json::to_value(m).ok()).collect(),
+            tools: tools.iter().map(|t| t.function.name.clone()).collect(),
+            completion: result.as_ref().ok().map(|(c, _)| c.clone()),
+            usage: result.as_ref().map(|(_, u)| u.clone()).unwrap_or_default(),
+            error: result.as_ref().err().map(|e| e.to_string()),
+        };
+        if let Err(e) = self.append(&entry) {
+            tracing::warn!("Failed to write cassette {}: {}", self.path.display(), e);
+        }
+    }
+
+    fn append(&self, entry: &CassetteEntry) -> Result<()> {
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        if file.is_none() {
+            if let Some(parent) = self.path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            *file = Some(std::fs::OpenOptions::new().create(true).append(true).open(&self.path)?);
+        }
+        let line = serde_json::to_string(entry)?;
+        let file = file.as_mut().expect("cassette file opened above");
+        writeln!(file, "{}", line)?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl LlmProvider for RecordingProvider {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn unrecorded(&self) -> Option<&dyn LlmProvider> {
+        Some(self.inner.as_ref())
+    }
+
+    async fn complete(&self, messages: &[Message], tools: &[ToolDefinition]) -> Result<(Completion, UsageStats)> {
+        let result = self.inner.complete(messages, tools).await;
+        self.record(messages, tools, &result);
+        result
+    }
+
+    async fn complete_streaming(
+        &self,
+        messages: &[Message],
+        tools: &[ToolDefinition],
+        event_tx: tokio::sync::mpsc::UnboundedSender<StreamEvent>,
+    ) -> Result<(Completion, UsageStats)> {
+        let result = self.inner.complete_streaming(messages, tools, event_tx).await;
+        self.record(messages, tools, &result);
+        result
+    }
+
+    /// Structured calls (summaries, classification) aren't part of the turn's script
+    async fn complete_structured(&self, messages: &[Message], schema: &Value) -> Result<Value> {
+        self.inner.complete_structured(messages, schema).await
+    }
+}
+
+/// A recorded turn loaded from a cassette file
+#[derive(Debug, Clone, Default)]
+pub struct Cassette {
+    pub entries: Vec<CassetteEntry>,
+}
+
+/// A tool call of a turn and the result the model saw
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedCall {
+    pub id: String,
+    pub name: String,
+    pub arguments: Value,
+    pub output: Option<String>,
+}
+
+impl Cassette {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read cassette {}", path.display()))?;
+        let entries = content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                serde_json::from_str(line).with_context(|| format!("{}:{}: invalid cassette entry", path.display(), i + 1))
+            })
+            .collect::<Result<Vec<CassetteEntry>>>()?;
+        if entries.is_empty() {
+            anyhow::bail!("Cassette {} is empty", path.display());
+        }
+        Ok(Self { entries })
+    }
+
+    /// The user message that started the turn (the last one in the first request)
+    pub fn user_message(&self) -> Option<String> {
+        self.entries.first()?.messages.iter().rev().find_map(|m| {
+            if m.get("role")?.as_str()? != "user" {
+                return None;
+            }
+            match m.get("content")? {
+                Value::String(text) => Some(text.clone()),
+                // Multimodal content: the text parts
+                Value::Array(parts) => Some(
+                    parts.iter()
+                        .filter_map(|p| p.get("text").and_then(|t| t.as_str()))
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                ),
+                _ => None,
+            }
+        })
+    }
+
+    /// Tool calls of the original turn in order, with the results sent back to the model
+    pub fn tool_calls(&self) -> Vec<RecordedCall> {
+        let mut calls = Vec::new();
+        for (i, entry) in self.entries.iter().enumerate() {
+            let Some(Completion::ToolCalls { calls: ref requested, .. }) = entry.completion else {
+                continue;
+            };
+            for call in requested {
+                let output = self.entries[i + 1..].iter().find_map(|later| {
+                    later.messages.iter().find_map(|m| {
+                        (m.get("tool_call_id")?.as_str()? == call.id)
+                            .then(|| m.get("content").and_then(|c| c.as_str()).map(String::from))
+                            .flatten()
+                    })
+                });
+                calls.push(RecordedCall {
+                    id: call.id.clone(),
+                    name: call.function.name.clone(),
+                    arguments: parse_arguments(&call.function.arguments),
+                    output,
+                });
+            }
+        }
+        calls
+    }
+
+    /// Tools that answer each call with its recorded result instead of running
+    pub fn stub_tools(&self) -> ToolRegistry {
+        let mut stubs: Vec<StubTool> = Vec::new();
+        for call in self.tool_calls() {
+            let index = match stubs.iter().position(|s| s.name == call.name) {
+                Some(index) => index,
+                None => {
+                    stubs.push(StubTool { name: call.name.clone(), calls: Vec::new() });
+                    stubs.len() - 1
+                }
+            };
+            stubs[index].calls.push(call);
+        }
+        let mut registry = ToolRegistry::new();
+        for stub in stubs {
+            registry.register(Box::new(stub));
+        }
+        registry
+    }
+}
+
+/// Serves a cassette's responses back in order, ignoring the requests
+pub struct ReplayProvider {
+    name: String,
+    entries: Vec<CassetteEntry>,
+    next: AtomicUsize,
+}
+
+impl ReplayProvider {
+    pub fn new(cassette: Cassette) -> Self {
+        let name = cassette.entries.first().map(|e| e.provider.clone()).unwrap_or_else(|| "replay".to_string());
+        Self { name, entries: cassette.entries, next: AtomicUsize::new(0) }
+    }
+
+    /// Recorded responses not served yet
+    pub fn remaining(&self) -> usize {
+        self.entries.len().saturating_sub(self.next.load(Ordering::SeqCst))
+    }
+}
+
+#[async_trait]
+impl LlmProvider for ReplayProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn complete(&self, _messages: &[Message], _tools: &[ToolDefinition]) -> Result<(Completion, UsageStats)> {
+        let index = self.next.fetch_add(1, Ordering::SeqCst);
+        let entry = self.entries.get(index).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Cassette exhausted: the replayed turn made more than the {} recorded LLM call(s)",
+                self.entries.len()
+            )
+        })?;
+        match (&entry.completion, &entry.error) {
+            (Some(completion), _) => Ok((completion.clone(), entry.usage.clone())),
+            (None, Some(error)) => Err(anyhow::anyhow!("{}", error)),
+            (None, None) => anyhow::bail!("Cassette entry {} has neither a completion nor an error", index + 1),
+        }
+    }
+}
+
+/// A recorded tool: answers by call ID, or by identical arguments when the
+/// call wasn't in the original turn
+struct StubTool {
+    name: String,
+    calls: Vec<RecordedCall>,
+}
+
+#[async_trait]
+impl Tool for StubTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        "Recorded tool (replay)"
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({ "type": "object" })
+    }
+
+    async fn execute(&self, args: Value, ctx: &ToolContext) -> Result<ToolResult> {
+        let recorded = self.calls.iter()
+            .find(|c| c.id == ctx.call_id)
+            .or_else(|| self.calls.iter().find(|c| c.arguments == args))
+            .and_then(|c| c.output.as_deref());
+        Ok(match recorded {
+            Some(output) => match output.strip_prefix("[ERROR] ") {
+                Some(error) => ToolResult::error(error),
+                None => ToolResult::success(output),
+            },
+            None => ToolResult::error(format!("No recorded result for this {} call", self.name)),
+        })
+    }
+}
+
+/// Tool calls a turn made, from its `turn_messages`
+pub fn calls_from_messages(messages: &[Message]) -> Vec<RecordedCall> {
+    let mut calls: Vec<RecordedCall> = Vec::new();
+    for message in messages {
+        match message.role {
+            Role::Assistant => {
+                for call in message.tool_calls.iter().flatten() {
+                    calls.push(RecordedCall {
+                        id: call.id.clone(),
+                        name: call.function.name.clone(),
+                        arguments: parse_arguments(&call.function.arguments),
+                        output: None,
+                    });
+                }
+            }
+            Role::Tool => {
+                let id = message.tool_call_id.as_deref().unwrap_or_default();
+                if let Some(call) = calls.iter_mut().find(|c| c.id == id) {
+                    call.output = message.content.clone();
+                }
+            }
+            _ => {}
+        }
+    }
+    calls
+}
+
+/// Differences between the original and replayed tool calls, one line each
+/// (empty when the turn behaved the same)
+pub fn diff_calls(original: &[RecordedCall], replayed: &[RecordedCall]) -> Vec<String> {
+    let mut diffs = Vec::new();
+    for i in 0..original.len().max(replayed.len()) {
+        let n = i + 1;
+        match (original.get(i), replayed.get(i)) {
+            (Some(o), Some(r)) if o.name != r.name => {
+                diffs.push(format!("#{}: original called {}, replay called {}", n, o.name, r.name));
+            }
+            (Some(o), Some(r)) if o.arguments != r.arguments => {
+                diffs.push(format!(
+                    "#{} {}: arguments differ\n    original: {}\n    replay:   {}",
+                    n, o.name, o.arguments, r.arguments
+                ));
+            }
+            (Some(o), Some(r)) if o.output != r.output => {
+                diffs.push(format!(
+                    "#{} {}: result differs\n    original: {}\n    replay:   {}",
+                    n,
+                    o.name,
+                    preview(o.output.as_deref()),
+                    preview(r.output.as_deref())
+                ));
+            }
+            (Some(o), None) => diffs.push(format!("#{} {}: missing from the replay", n, o.name)),
+            (None, Some(r)) => diffs.push(format!("#{} {}: not in the original turn", n, r.name)),
+            _ => {}
+        }
+    }
+    diffs
+}
+
+fn parse_arguments(arguments: &str) -> Value {
+    serde_json::from_str(arguments).unwrap_or_else(|_| Value::String(arguments.to_string()))
+}
+
+fn preview(output: Option<&str>) -> String {
+    match output {
+        Some(text) if text.chars().count() > 120 => format!("{}…", text.chars().take(120).collect::<String>()),
+        Some(text) => text.to_string(),
+        None => "(none)".to_string(),
+    }
+    .replace('\n', "⏎")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::{FunctionCall, ToolCall};
+    use crate::runtime::{run_agent_turn, AgentTurnConfig};
+
+    /// Lists a directory once, then answers with the listing
+    struct ListOnceProvider;
+
+    #[async_trait]
+    impl LlmProvider for ListOnceProvider {
+        fn name(&self) -> &str { "list-once" }
+
+        async fn complete(&self, messages: &[Message], _tools: &[ToolDefinition]) -> Result<(Completion, UsageStats)> {
+            let last = messages.last().expect("turn has messages");
+            let completion = match last.role {
+                Role::Tool => Completion::Text { content: format!("Listing: {}", last.content.clone().unwrap_or_default()), reasoning: None },
+                _ => Completion::ToolCalls {
+                    calls: vec![ToolCall {
+                        id: "call-1".into(),
+                        call_type: "function".into(),
+                        function: FunctionCall { name: "list_dir".into(), arguments: r#"{"path":"."}"#.into() },
+                    }],
+                    reasoning: None,
+                },
+            };
+            Ok((completion, UsageStats { total_tokens: 7, ..Default::default() }))
+        }
+    }
+
+    fn turn_config(workspace: &Path) -> AgentTurnConfig {
+        AgentTurnConfig {
+            agent_name: "replay-test".to_string(),
+            session_key: format!("replay-test:{}", uuid::Uuid::new_v4()),
+            workspace_dir: workspace.to_string_lossy().to_string(),
+            minimal_context: true,
+            ..AgentTurnConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_record_and_replay_turn() {
+        let workspace = tempfile::tempdir().unwrap();
+        std::fs::write(workspace.path().join("notes.txt"), "hi").unwrap();
+        let cassette_path = workspace.path().join("cassettes/turn.jsonl");
+        let mut tools = ToolRegistry::new();
+        tools.register(Box::new(crate::tools::list_dir::ListDirTool));
+
+        let recorder = RecordingProvider::new(Box::new(ListOnceProvider), &cassette_path);
+        let original = run_agent_turn(&recorder, "what's here?", &turn_config(workspace.path()), &tools).await.unwrap();
+
+        let cassette = Cassette::load(&cassette_path).unwrap();
+        assert_eq!(cassette.entries.len(), 2);
+        assert_eq!(cassette.user_message().as_deref(), Some("what's here?"));
+        let recorded = cassette.tool_calls();
+        assert_eq!(recorded.len(), 1);
+        assert_eq!(recorded[0].name, "list_dir");
+        assert!(recorded[0].output.as_deref().unwrap().contains("notes.txt"));
+
+        // Stubbed tools: identical turn, nothing left on the cassette
+        let replay = ReplayProvider::new(cassette.clone());
+        let stubs = cassette.stub_tools();
+        let result = run_agent_turn(&replay, "what's here?", &turn_config(workspace.path()), &stubs).await.unwrap();
+        assert_eq!(result.response, original.response);
+        assert_eq!(replay.remaining(), 0);
+        assert!(diff_calls(&recorded, &calls_from_messages(&result.turn_messages)).is_empty());
+
+        // Real tools after the workspace changed: the result diff shows up
+        std::fs::write(workspace.path().join("new.txt"), "x").unwrap();
+        let replay = ReplayProvider::new(cassette);
+        let result = run_agent_turn(&replay, "what's here?", &turn_config(workspace.path()), &tools).await.unwrap();
+        let diffs = diff_calls(&recorded, &calls_from_messages(&result.turn_messages));
+        assert_eq!(diffs.len(), 1);
+        assert!(diffs[0].contains("result differs"), "{}", diffs[0]);
+    }
+
+    /// Answers every call with the same text
+    struct SummaryProvider;
+
+    #[async_trait]
+    impl LlmProvider for SummaryProvider {
+        fn name(&self) -> &str { "summary" }
+
+        async fn complete(&self, _messages: &[Message], _tools: &[ToolDefinition]) -> Result<(Completion, UsageStats)> {
+            Ok((Completion::Text { content: "- user said hi".into(), reasoning: None }, UsageStats::default()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_compaction_is_not_recorded() {
+        let dir = tempfile::tempdir().unwrap();
+        let cassette_path = dir.path().join("turn.jsonl");
+        let recorder = RecordingProvider::new(Box::new(SummaryProvider), &cassette_path);
+        let settings = crate::compaction::Settings {
+            config: Default::default(),
+            budget: crate::compaction::HistoryBudget { max_messages: 10, max_tokens: 100 },
+            summarizer: None,
+        };
+
+        let summarizer = settings.summarizer_for(&recorder);
+        let (summary, _) = crate::compaction::summarize(summarizer, None, &[Message::user("hi")], &settings.config)
+            .await
+            .unwrap();
+        assert_eq!(summary, "- user said hi");
+        assert!(!cassette_path.exists());
+
+        recorder.complete(&[Message::user("hi")], &[]).await.unwrap();
+        assert_eq!(Cassette::load(&cassette_path).unwrap().entries.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_replay_past_the_end_fails() {
+        let replay = ReplayProvider::new(Cassette::default());
+        let err = replay.complete(&[Message::user("hi")], &[]).await.unwrap_err();
+        assert!(err.to_string().contains("exhausted"));
+    }
+
+    #[test]
+    fn test_diff_calls() {
+        let call = |name: &str, args: Value, output: &str| RecordedCall {
+            id: String::new(),
+            name: name.to_string(),
+            arguments: args,
+            output: Some(output.to_string()),
+        };
+        let original = vec![
+            call("read", serde_json::json!({"path": "a"}), "A"),
+            call("exec", serde_json::json!({"command": "ls"}), "x"),
+            call("grep", serde_json::json!({"pattern": "p"}), "g"),
+        ];
+        let replayed = vec![
+            call("read", serde_json::json!({"path": "a"}), "A"),
+            call("exec", serde_json::json!({"command": "ls -la"}), "x"),
+        ];
+        let diffs = diff_calls(&original, &replayed);
+        assert_eq!(diffs.len(), 2);
+        assert!(diffs[0].starts_with("#2 exec: arguments differ"));
+        assert!(diffs[1].contains("missing from the replay"));
+    }
+}
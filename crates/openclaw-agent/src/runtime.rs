@@ -1,20 +1,26 @@
 use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
 use std::path::Path;
 use std::time::Instant;
 use tracing::{debug, info, warn};
 
+use crate::approval::{self, ApprovalDecision, ApprovalPolicy};
 use crate::llm::streaming::StreamEvent;
 use crate::llm::{Completion, LlmProvider, Message, UsageStats};
-use crate::loop_detection::{LoopDetector, LoopVerdict};
+use crate::loop_detection::{LoopDetectionConfig, LoopDetector, LoopVerdict};
+use crate::sandbox::SandboxPolicy;
+use crate::tokenizer::TokenCounter;
 use crate::tools::{ToolContext, ToolRegistry};
 use crate::workspace;
+use openclaw_db::checkpoints::TurnCheckpoint;
 
-const MAX_TOOL_ROUNDS: usize = 20;
-const MAX_HISTORY_MESSAGES: usize = 40;
-/// Approximate max tokens for context history (leave room for system prompt + current turn)
-const MAX_HISTORY_TOKENS: usize = 12000;
-/// Max characters of tool output to send to the LLM (prevents token waste on huge outputs)
-const MAX_TOOL_OUTPUT_CHARS: usize = 32000;
+/// Sent (with the tools still declared) on the last round a turn may use
+const ROUND_CAP_PROMPT: &str = "[SYSTEM] You have used all tool rounds for this turn. Do not call any \
+     more tools. Answer the user now: summarize what you found and did, and say what is still unfinished.";
+/// Reply when the round cap is hit and the model can't produce a final answer
+const ROUND_CAP_MESSAGE: &str = "(Agent reached maximum tool call rounds)";
+/// Reply when a turn is cut short by its spend budget
+const QUOTA_STOP_MESSAGE: &str = "💸 Spend quota reached — stopped before the next round. Use /quota to see your usage.";
 
 /// Detect if the LLM is fabricating tool actions in a text response.
 /// Returns true if the response looks like it's claiming to dispatch/execute something
@@ -90,33 +96,131 @@ fn preview_tool_output(output: &str) -> String {
     }
 }
 
-/// Truncate tool output if it exceeds the limit, preserving head and tail.
-fn truncate_tool_output(output: &str) -> String {
-    if output.len() <= MAX_TOOL_OUTPUT_CHARS {
+/// Truncate tool output if it exceeds `max_tokens`, preserving head and tail.
+fn truncate_tool_output(output: &str, max_tokens: usize, counter: &dyn TokenCounter) -> String {
+    let tokens = counter.count(output);
+    if tokens <= max_tokens {
         return output.to_string();
     }
-    let head_size = MAX_TOOL_OUTPUT_CHARS * 3 / 4; // 75% head
-    let tail_size = MAX_TOOL_OUTPUT_CHARS / 4;       // 25% tail
-    let omitted = output.len() - head_size - tail_size;
-    format!(
-        "{}\n\n... [{} chars truncated] ...\n\n{}",
-        &output[..head_size],
-        omitted,
-        &output[output.len() - tail_size..]
-    )
+    // Keep the same share of characters as of tokens: 75% head, 25% tail
+    let total_chars = output.chars().count();
+    let keep_chars = total_chars * max_tokens / tokens;
+    let head_size = keep_chars * 3 / 4;
+    let tail_size = keep_chars / 4;
+    let omitted = total_chars - head_size - tail_size;
+    let head: String = output.chars().take(head_size).collect();
+    let tail: String = output.chars().skip(total_chars - tail_size).collect();
+    format!("{}\n\n... [{} chars truncated] ...\n\n{}", head, omitted, tail)
 }
 
-/// Rough token estimate: ~4 chars per token for English text.
-/// This is intentionally conservative to avoid exceeding context windows.
-fn estimate_tokens(text: &str) -> usize {
-    // Count chars / 4, with a minimum of 1 token per message
-    (text.len() / 4).max(1)
+/// Add one LLM call's usage to the turn's totals and report it through `config.on_usage`
+fn add_usage(
+    config: &AgentTurnConfig,
+    model: &str,
+    usage: &UsageStats,
+    total_usage: &mut UsageStats,
+    total_cost_micros: &mut u64,
+) {
+    let cost_micros = crate::llm::pricing::global().cost_micros(model, usage);
+    total_usage.prompt_tokens += usage.prompt_tokens;
+    total_usage.completion_tokens += usage.completion_tokens;
+    total_usage.total_tokens += usage.total_tokens;
+    total_usage.cache_read_tokens += usage.cache_read_tokens;
+    total_usage.cache_write_tokens += usage.cache_write_tokens;
+    *total_cost_micros += cost_micros;
+    if let Some(ref on_usage) = config.on_usage {
+        on_usage(usage.total_tokens as u64, cost_micros);
+    }
+}
+
+/// On the last round a turn may use, ask the model for a final answer so the
+/// user gets a summary of the work so far. Tools stay declared (some providers
+/// reject tool history without them), but a tool call falls back to the notice.
+async fn round_cap_answer(
+    provider: &dyn LlmProvider,
+    config: &AgentTurnConfig,
+    messages: &[Message],
+    tool_defs: &[crate::llm::ToolDefinition],
+    event_tx: Option<&tokio::sync::mpsc::UnboundedSender<StreamEvent>>,
+    total_usage: &mut UsageStats,
+    total_cost_micros: &mut u64,
+) -> (String, Option<String>) {
+    let mut request = messages.to_vec();
+    request.push(Message::user(ROUND_CAP_PROMPT));
+    let result = match event_tx {
+        Some(tx) => provider.complete_streaming(&request, tool_defs, tx.clone()).await,
+        None => provider.complete(&request, tool_defs).await,
+    };
+    let (completion, usage) = match result {
+        Ok(r) => r,
+        Err(e) => {
+            warn!("Final answer after round cap failed: {}", e);
+            return (ROUND_CAP_MESSAGE.to_string(), None);
+        }
+    };
+
+    add_usage(config, provider.name(), &usage, total_usage, total_cost_micros);
+
+    match completion {
+        Completion::Text { content, reasoning } if !content.trim().is_empty() => (content, reasoning),
+        _ => {
+            warn!("Model kept calling tools after the round cap");
+            (ROUND_CAP_MESSAGE.to_string(), None)
+        }
+    }
 }
 
-fn estimate_message_tokens(msg: &Message) -> usize {
-    let base = estimate_tokens(msg.content.as_deref().unwrap_or(""));
-    // Role overhead: ~4 tokens for role/formatting
-    base + 4
+/// Pause on calls the approval policy flags until the user decides. Denied or
+/// unanswered calls are not executed; every decision is noted in the call's
+/// result so it lands in the session history.
+async fn request_approvals(
+    config: &AgentTurnConfig,
+    prepared: &[(String, serde_json::Value, String)],
+    execute_list: &mut [(usize, bool, String)],
+    event_tx: Option<&tokio::sync::mpsc::UnboundedSender<StreamEvent>>,
+    cancel_token: Option<&tokio_util::sync::CancellationToken>,
+) {
+    if config.approval.rules.is_empty() {
+        return;
+    }
+    let timeout = config.approval.timeout();
+    for (idx, should_execute, note) in execute_list.iter_mut() {
+        let (name, args, _) = &prepared[*idx];
+        if !*should_execute {
+            continue;
+        }
+        let Some(reason) = config.approval.check(name, args, &config.workspace_dir) else {
+            continue;
+        };
+
+        let (request, rx) =
+            approval::register(name, &summarize_tool_args(name, args), &reason, &config.approval.requester);
+        let delivered = event_tx.is_some_and(|tx| {
+            tx.send(StreamEvent::ApprovalRequired {
+                id: request.id.clone(),
+                tool: request.tool.clone(),
+                args_summary: request.args_summary.clone(),
+                reason: request.reason.clone(),
+                timeout_secs: timeout.as_secs(),
+            })
+            .is_ok()
+        });
+        let decision = if delivered {
+            approval::wait(&request.id, rx, timeout, cancel_token).await
+        } else {
+            approval::cancel(&request.id);
+            ApprovalDecision::Unavailable
+        };
+        info!("Approval for {} ({}): {:?}", name, reason, decision);
+
+        let decision_note = decision.note(timeout);
+        if decision == ApprovalDecision::Approved {
+            *note = if note.is_empty() { decision_note } else { format!("{} {}", note, decision_note) };
+        } else {
+            *should_execute = false;
+            *note = decision_note;
+        }
+    }
 }
 
 /// Configuration for an agent turn
@@ -129,6 +233,74 @@ pub struct AgentTurnConfig {
     pub delegate_tx: Option<crate::tools::DelegateTx>,
     pub task_query_fn: Option<crate::tools::TaskQueryFn>,
     pub task_cancel_fn: Option<crate::tools::TaskCancelFn>,
+    /// Remaining spend allowance; the turn stops between rounds once it is used up
+    pub budget: Option<TurnBudget>,
+    /// Restrictions applied to every tool call in the turn
+    pub sandbox: SandboxPolicy,
+    pub limits: TurnLimits,
+    /// Tool calls that wait for the user's go-ahead before running
+    pub approval: ApprovalPolicy,
+    /// Checkpoint every round under this ID so an interrupted turn can be
+    /// picked up with `resume_agent_turn*` (None = no checkpoints)
+    pub turn_id: Option<String>,
+    /// Appended to the workspace system prompt (e.g. an agent profile's persona)
+    pub system_prompt_overlay: Option<String>,
+    /// Called after every LLM call of the turn, so spend is counted even if the turn fails
+    pub on_usage: Option<UsageHook>,
+    /// Tool call counts to continue from (a subagent shares its parent's); None starts fresh
+    pub turn_calls: Option<crate::tool_policy::TurnCallCounts>,
+}
+
+/// Callback with the tokens and cost (micro-dollars) of one LLM call
+pub type UsageHook = std::sync::Arc<dyn Fn(u64, u64) + Send + Sync>;
+
+/// How long a turn may run and how much tool output it may see
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TurnLimits {
+    /// LLM rounds per turn; the last one asks the model for a final answer
+    pub max_rounds: usize,
+    /// Max tokens of one tool result sent to the LLM (longer output is truncated)
+    pub max_tool_output_tokens: usize,
+    pub loop_detection: LoopDetectionConfig,
+}
+
+impl Default for TurnLimits {
+    fn default() -> Self {
+        Self {
+            max_rounds: 20,
+            max_tool_output_tokens: 8000,
+            loop_detection: LoopDetectionConfig::default(),
+        }
+    }
+}
+
+/// Token / cost allowance for one agent turn (e.g. what is left of a spend quota)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TurnBudget {
+    pub max_tokens: Option<u64>,
+    pub max_cost_micros: Option<u64>,
+}
+
+impl TurnBudget {
+    /// The tighter of two budgets
+    pub fn min(self, other: TurnBudget) -> TurnBudget {
+        fn tighter(a: Option<u64>, b: Option<u64>) -> Option<u64> {
+            match (a, b) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            }
+        }
+        TurnBudget {
+            max_tokens: tighter(self.max_tokens, other.max_tokens),
+            max_cost_micros: tighter(self.max_cost_micros, other.max_cost_micros),
+        }
+    }
+
+    pub fn is_exhausted(&self, tokens: u64, cost_micros: u64) -> bool {
+        self.max_tokens.is_some_and(|max| tokens >= max)
+            || self.max_cost_micros.is_some_and(|max| cost_micros >= max)
+    }
 }
 
 impl Default for AgentTurnConfig {
@@ -142,18 +314,53 @@ impl Default for AgentTurnConfig {
             delegate_tx: None,
             task_query_fn: None,
             task_cancel_fn: None,
+            budget: None,
+            sandbox: SandboxPolicy::default(),
+            limits: TurnLimits::default(),
+            approval: ApprovalPolicy::default(),
+            turn_id: None,
+            system_prompt_overlay: None,
+            on_usage: None,
+            turn_calls: None,
+        }
+    }
+}
+
+/// A fresh ID for `AgentTurnConfig::turn_id`
+pub fn new_turn_id() -> String {
+    uuid::Uuid::new_v4().simple().to_string()
+}
+
+/// History sent with a turn: a rolling summary of compacted messages plus the
+/// recent messages kept verbatim.
+#[derive(Default)]
+struct SessionHistory {
+    summary: Option<String>,
+    messages: Vec<Message>,
+    /// Usage and cost of the summarizer call, if this turn compacted
+    usage: UsageStats,
+    cost_micros: u64,
+}
+
+impl SessionHistory {
+    /// System prompt with the conversation summary appended
+    fn system_prompt(&self, base: &str) -> String {
+        match self.summary {
+            Some(ref summary) => format!("{}\n\n{}\n{}", base, crate::compaction::SUMMARY_HEADING, summary),
+            None => base.to_string(),
         }
     }
 }
 
-/// Load recent conversation history from Postgres.
-/// Returns up to MAX_HISTORY_MESSAGES recent messages (user + assistant only).
-async fn load_session_history(_agent_name: &str, session_key: &str) -> Vec<Message> {
+/// Load conversation history from Postgres. When the unsummarized history
+/// outgrows the budget, the oldest span is compacted into the session summary
+/// (see `compaction`); whatever still doesn't fit is pruned, oldest first.
+async fn load_session_history(_agent_name: &str, session_key: &str, provider: &dyn LlmProvider) -> SessionHistory {
     let pool = match openclaw_db::pool() {
         Some(p) => p,
         None => {
             debug!("Postgres not available for session history");
-            return Vec::new();
+            return SessionHistory::default();
         }
     };
 
@@ -161,7 +368,7 @@ async fn load_session_history(_agent_name: &str, session_key: &str) -> Vec<Messa
         Ok(m) => m,
         Err(e) => {
             debug!("Could not load session history: {}", e);
-            return Vec::new();
+            return SessionHistory::default();
         }
     };
 
@@ -186,16 +393,52 @@ async fn load_session_history(_agent_name: &str, session_key: &str) -> Vec<Messa
         })
     }).collect();
 
+    // ── Compaction: skip what the summary covers, fold overflow into it ──
+    let settings = crate::compaction::load_settings(provider.name());
+    let budget = settings.budget;
+    let counter = crate::tokenizer::for_model(provider.name());
+    let mut history = SessionHistory::default();
+    let mut covered = 0;
+    if settings.config.enabled() {
+        if let Ok(Some(summary)) = openclaw_db::sessions::load_summary(pool, session_key).await {
+            covered = (summary.covered_messages.max(0) as usize).min(msgs.len());
+            history.summary = Some(summary.text);
+        }
+
+        let live = &msgs[covered..];
+        if crate::compaction::over_budget(live, &budget, counter.as_ref()) {
+            let cut = crate::compaction::split_point(live, &budget, counter.as_ref());
+            let summarizer = settings.summarizer_for(provider);
+            match crate::compaction::summarize(summarizer, history.summary.as_deref(), &live[..cut], &settings.config).await {
+                Ok((summary, usage)) => {
+                    info!(
+                        "Compacted {} messages of session {} into a {}-char summary",
+                        cut, session_key, summary.len()
+                    );
+                    covered += cut;
+                    if let Err(e) = openclaw_db::sessions::save_summary(pool, session_key, &summary, covered as i64).await {
+                        warn!("Failed to save conversation summary: {}", e);
+                    }
+                    history.cost_micros = crate::llm::pricing::global().cost_micros(summarizer.name(), &usage);
+                    history.usage = usage;
+                    history.summary = Some(summary);
+                }
+                Err(e) => warn!("Context compaction failed, pruning instead: {}", e),
+            }
+        }
+    }
+
     // Hard cap first
-    let start = msgs.len().saturating_sub(MAX_HISTORY_MESSAGES);
-    let candidates: Vec<Message> = msgs[start..].to_vec();
+    let live = &msgs[covered..];
+    let start = live.len().saturating_sub(budget.max_messages);
+    let candidates: Vec<Message> = live[start..].to_vec();
 
     // Token-aware pruning: walk backwards, keep messages until budget exhausted
-    let mut token_budget = MAX_HISTORY_TOKENS;
+    let mut token_budget = budget.max_tokens;
     let mut kept: Vec<Message> = Vec::new();
 
     for msg in candidates.iter().rev() {
-        let msg_tokens = estimate_message_tokens(msg);
+        let msg_tokens = counter.count_message(msg);
         if msg_tokens > token_budget {
             break;
         }
@@ -237,13 +480,14 @@ async fn load_session_history(_agent_name: &str, session_key: &str) -> Vec<Messa
     }
 
     if !kept.is_empty() {
-        let total_tokens: usize = kept.iter().map(|m| estimate_message_tokens(m)).sum();
+        let total_tokens = counter.count_messages(&kept);
         debug!(
-            "Loaded {} history messages (~{} tokens) for session {} (pruned from {})",
-            kept.len(), total_tokens, session_key, msgs.len()
+            "Loaded {} history messages ({} {} tokens, budget {}) for session {} (pruned from {}, {} summarized)",
+            kept.len(), total_tokens, counter.name(), budget.max_tokens, session_key, msgs.len(), covered
         );
     }
-    kept
+    history.messages = kept;
+    history
 }
 
 /// Result of a complete agent turn
@@ -255,25 +499,62 @@ pub struct AgentTurnResult {
     pub tool_calls_made: usize,
     pub total_rounds: usize,
     pub total_usage: UsageStats,
+    /// Cost of the turn's LLM calls in micro-dollars (see `llm::pricing`)
+    pub total_cost_micros: u64,
     pub elapsed_ms: u128,
     /// All messages generated during this turn (tool calls + tool results + final assistant).
     /// Excludes the system prompt and loaded history — only new messages from this turn.
     pub turn_messages: Vec<Message>,
+    /// The turn stopped before its final answer (cancelled or out of budget)
+    /// and its checkpoint was kept, so it can be resumed
+    pub resumable: bool,
 }
 
-/// Run a single agent turn: assemble context, call LLM, execute tools, loop until text response
-pub async fn run_agent_turn(
-    provider: &dyn LlmProvider,
-    user_message: &str,
-    config: &AgentTurnConfig,
-    tools: &ToolRegistry,
-) -> Result<AgentTurnResult> {
-    let t_start = Instant::now();
+/// Where a turn begins: a new user message, or the checkpoint of an interrupted turn
+enum TurnStart<'a> {
+    New { user_message: &'a str, image_urls: Vec<String> },
+    Resume(&'a TurnCheckpoint),
+}
 
-    // Set session context for LLM log tagging
-    crate::llm_log::set_session_context(&config.session_key);
+/// Saves a turn's progress to `turn_checkpoints` after each round
+struct Checkpointer {
+    pool: &'static openclaw_db::PgPool,
+    checkpoint: TurnCheckpoint,
+}
+
+impl Checkpointer {
+    async fn save(&mut self, turn_messages: &[Message], rounds: usize) {
+        self.checkpoint.messages = serde_json::to_value(turn_messages).unwrap_or_default();
+        self.checkpoint.rounds = rounds as i32;
+        if let Err(e) = openclaw_db::checkpoints::save_checkpoint(self.pool, &self.checkpoint).await {
+            warn!("Failed to checkpoint turn {}: {}", self.checkpoint.turn_id, e);
+        }
+    }
+
+    /// The turn has its final answer; nothing left to resume
+    async fn finish(&self) {
+        if let Err(e) = openclaw_db::checkpoints::delete_checkpoint(self.pool, &self.checkpoint.turn_id).await {
+            warn!("Failed to delete checkpoint of turn {}: {}", self.checkpoint.turn_id, e);
+        }
+    }
+}
 
-    // Load workspace context
+/// Context a turn loop starts from
+struct TurnSetup {
+    messages: Vec<Message>,
+    /// Index of the first message generated by the turn (after the user message)
+    turn_start_idx: usize,
+    rounds: usize,
+    tool_calls_made: usize,
+    /// Usage and cost of compacting the history for this turn
+    usage: UsageStats,
+    cost_micros: u64,
+    checkpointer: Option<Checkpointer>,
+}
+
+/// Load the workspace and session history, then add the user message and, when
+/// resuming, the messages the interrupted turn had already generated
+async fn setup_turn(provider: &dyn LlmProvider, config: &AgentTurnConfig, start: TurnStart<'_>) -> Result<TurnSetup> {
     let workspace_dir = Path::new(&config.workspace_dir);
     let ws = workspace::load_workspace(workspace_dir, config.minimal_context)
         .await
@@ -285,46 +566,274 @@ pub async fn run_agent_turn(
         ws.dir.display()
     );
 
+    // A new turn supersedes whatever was left unfinished in the session; its
+    // progress goes into the history first so it is not lost
+    if let (TurnStart::New { .. }, Some(_), Some(pool)) = (&start, &config.turn_id, openclaw_db::pool()) {
+        supersede_checkpoints(pool, config, provider.name()).await;
+    }
+
     // Build initial messages with session history
-    let history = load_session_history(&config.agent_name, &config.session_key).await;
-    let mut messages = vec![Message::system(&ws.system_prompt)];
-    messages.extend(history);
-    messages.push(Message::user(user_message));
+    let history = load_session_history(&config.agent_name, &config.session_key, provider).await;
+    if let Some(ref on_usage) = config.on_usage {
+        if history.usage.total_tokens > 0 || history.cost_micros > 0 {
+            on_usage(history.usage.total_tokens as u64, history.cost_micros);
+        }
+    }
+    let base_prompt = match config.system_prompt_overlay {
+        Some(ref overlay) => format!("{}\n\n{}", ws.system_prompt, overlay),
+        None => ws.system_prompt,
+    };
+    // Memories recalled for the new message go into the per-request part of the prompt
+    let base_prompt = match &start {
+        TurnStart::New { user_message, .. } if !config.minimal_context => {
+            let recalled = crate::memory::recall_for_prompt(
+                &config.workspace_dir,
+                &config.agent_name,
+                &config.session_key,
+                user_message,
+            )
+            .await;
+            match recalled {
+                Some(recalled) => format!("{}\n\n{}", base_prompt, recalled),
+                None => base_prompt,
+            }
+        }
+        _ => base_prompt,
+    };
+    let mut messages = vec![Message::system(&history.system_prompt(&base_prompt))];
+    messages.extend(history.messages);
+
+    let (checkpoint, prior) = match start {
+        TurnStart::New { user_message, image_urls } => {
+            let checkpoint = config.turn_id.as_ref().map(|turn_id| TurnCheckpoint {
+                turn_id: turn_id.clone(),
+                session_key: config.session_key.clone(),
+                agent_name: config.agent_name.clone(),
+                user_message: user_message.to_string(),
+                image_urls: image_urls.clone(),
+                messages: serde_json::json!([]),
+                rounds: 0,
+            });
+            messages.push(turn_user_message(user_message, image_urls));
+            (checkpoint, Vec::new())
+        }
+        TurnStart::Resume(checkpoint) => {
+            messages.push(turn_user_message(&checkpoint.user_message, checkpoint.image_urls.clone()));
+            let prior: Vec<Message> = serde_json::from_value(checkpoint.messages.clone())
+                .with_context(|| format!("Checkpoint of turn {} is unreadable", checkpoint.turn_id))?;
+            info!(
+                "Resuming turn {} after round {} ({} saved messages)",
+                checkpoint.turn_id, checkpoint.rounds, prior.len()
+            );
+            (Some(checkpoint.clone()), prior)
+        }
+    };
+
+    let turn_start_idx = messages.len();
+    let rounds = checkpoint.as_ref().map(|c| c.rounds.max(0) as usize).unwrap_or(0);
+    let tool_calls_made = prior.iter().filter(|m| matches!(m.role, crate::llm::Role::Tool)).count();
+    messages.extend(prior);
+
+    let mut checkpointer = match (checkpoint, openclaw_db::pool()) {
+        (Some(checkpoint), Some(pool)) => Some(Checkpointer { pool, checkpoint }),
+        _ => None,
+    };
+    if let Some(ref mut cp) = checkpointer {
+        if cp.checkpoint.rounds == 0 {
+            cp.save(&[], 0).await;
+        }
+    }
+
+    Ok(TurnSetup {
+        messages,
+        turn_start_idx,
+        rounds,
+        tool_calls_made,
+        usage: history.usage,
+        cost_micros: history.cost_micros,
+        checkpointer,
+    })
+}
+
+/// The user message that opens a turn; images go inline with a hint so the
+/// model knows it can already see them
+fn turn_user_message(text: &str, image_urls: Vec<String>) -> Message {
+    if image_urls.is_empty() {
+        return Message::user(text);
+    }
+    let image_hint = format!(
+        "[SYSTEM: {} image(s) attached inline below. You can see them directly — \
+         analyze them from your visual input. Do NOT use the `image` tool or try to \
+         open a file path. The image data is already in this message.]\n\n{}",
+        image_urls.len(),
+        text,
+    );
+    Message::user_with_images(&image_hint, image_urls)
+}
+
+/// Move the session's interrupted turns into its history, then drop their checkpoints
+async fn supersede_checkpoints(pool: &'static openclaw_db::PgPool, config: &AgentTurnConfig, model: &str) {
+    let checkpoints = match openclaw_db::checkpoints::session_checkpoints(pool, &config.session_key).await {
+        Ok(checkpoints) => checkpoints,
+        Err(e) => {
+            warn!("Failed to load old checkpoints for {}: {}", config.session_key, e);
+            return;
+        }
+    };
+    if checkpoints.is_empty() {
+        return;
+    }
+    for checkpoint in &checkpoints {
+        if let Err(e) = persist_interrupted_turn(pool, model, checkpoint).await {
+            warn!("Failed to save interrupted turn {} to history: {}", checkpoint.turn_id, e);
+        }
+    }
+    if let Err(e) = openclaw_db::checkpoints::delete_session_checkpoints(pool, &config.session_key).await {
+        warn!("Failed to clear old checkpoints for {}: {}", config.session_key, e);
+    }
+}
+
+/// Record an interrupted turn's user message and completed rounds as session messages
+async fn persist_interrupted_turn(pool: &openclaw_db::PgPool, model: &str, checkpoint: &TurnCheckpoint) -> Result<()> {
+    let prior: Vec<Message> = serde_json::from_value(checkpoint.messages.clone())?;
+    let sid = openclaw_db::sessions::upsert_session(
+        pool, &checkpoint.session_key, &checkpoint.agent_name, model, None, None,
+    ).await?;
+    openclaw_db::messages::record_message(pool, sid, "user", Some(&checkpoint.user_message), None, None, None).await?;
+    for msg in &prior {
+        let role = match msg.role {
+            crate::llm::Role::System => "system",
+            crate::llm::Role::User => "user",
+            crate::llm::Role::Assistant => "assistant",
+            crate::llm::Role::Tool => "tool",
+        };
+        let tool_calls = msg.tool_calls.as_ref().map(|tc| serde_json::to_value(tc).unwrap_or_default());
+        openclaw_db::messages::record_message(
+            pool, sid, role,
+            msg.content.as_deref(),
+            msg.reasoning_content.as_deref(),
+            tool_calls.as_ref(),
+            msg.tool_call_id.as_deref(),
+        ).await?;
+    }
+    info!("Saved interrupted turn {} ({} messages) to session history", checkpoint.turn_id, prior.len() + 1);
+    Ok(())
+}
+
+/// Index a turn that ended with an answer so later turns can recall it
+fn index_turn(config: &AgentTurnConfig, user_message: &Message, response: &str) {
+    if config.minimal_context {
+        return;
+    }
+    if let Some(ref text) = user_message.content {
+        crate::memory::index_exchange(&config.workspace_dir, &config.agent_name, &config.session_key, text, response);
+    }
+}
+
+/// Run a single agent turn: assemble context, call LLM, execute tools, loop until text response
+pub async fn run_agent_turn(
+    provider: &dyn LlmProvider,
+    user_message: &str,
+    config: &AgentTurnConfig,
+    tools: &ToolRegistry,
+) -> Result<AgentTurnResult> {
+    let start = TurnStart::New { user_message, image_urls: Vec::new() };
+    run_turn(provider, start, config, tools).await
+}
+
+/// Continue an interrupted turn from its checkpoint
+pub async fn resume_agent_turn(
+    provider: &dyn LlmProvider,
+    checkpoint: &TurnCheckpoint,
+    config: &AgentTurnConfig,
+    tools: &ToolRegistry,
+) -> Result<AgentTurnResult> {
+    run_turn(provider, TurnStart::Resume(checkpoint), config, tools).await
+}
+
+async fn run_turn(
+    provider: &dyn LlmProvider,
+    start: TurnStart<'_>,
+    config: &AgentTurnConfig,
+    tools: &ToolRegistry,
+) -> Result<AgentTurnResult> {
+    let t_start = Instant::now();
+
+    // Set session context for LLM log tagging
+    crate::llm_log::set_session_context(&config.session_key);
+
+    let TurnSetup {
+        mut messages, turn_start_idx, mut rounds, mut tool_calls_made, usage, cost_micros, mut checkpointer,
+    } = setup_turn(provider, config, start).await?;
 
     // Get tool definitions
     let tool_defs = tools.definitions();
+    let counter = crate::tokenizer::for_model(provider.name());
     let tool_ctx = ToolContext {
         workspace_dir: config.workspace_dir.clone(),
         agent_name: config.agent_name.clone(),
         session_key: config.session_key.clone(),
-        sandbox: crate::sandbox::SandboxPolicy::default(),
+        sandbox: config.sandbox.clone(),
+        approval: config.approval.clone(),
+        tool_policy: tools.policy().clone(),
+        turn_calls: config.turn_calls.clone().unwrap_or_default(),
         chat_id: config.chat_id,
         delegate_tx: config.delegate_tx.clone(),
         task_query_fn: config.task_query_fn.clone(),
         task_cancel_fn: config.task_cancel_fn.clone(),
         stream_tx: None,
+        call_id: String::new(),
     };
 
-    let mut total_usage = UsageStats::default();
-    let mut tool_calls_made = 0;
-    let mut rounds = 0;
-    let mut loop_detector = LoopDetector::new();
-    let turn_start_idx = messages.len(); // track where new messages begin
+    // A compaction summary written for this turn counts towards its usage
+    let mut total_usage = usage;
+    let mut total_cost_micros = cost_micros;
+    let mut loop_detector = LoopDetector::with_config(config.limits.loop_detection.clone());
 
     loop {
         rounds += 1;
-        if rounds > MAX_TOOL_ROUNDS {
-            warn!("Agent hit max tool rounds ({}), forcing stop", MAX_TOOL_ROUNDS);
+        if config.budget.is_some_and(|b| b.is_exhausted(total_usage.total_tokens as u64, total_cost_micros)) {
+            warn!("Spend quota reached after {} round(s), stopping turn", rounds - 1);
             crate::llm_log::clear_session_context();
             return Ok(AgentTurnResult {
-                response: "(Agent reached maximum tool call rounds)".to_string(),
+                response: QUOTA_STOP_MESSAGE.to_string(),
                 reasoning: None,
                 model_name: provider.name().to_string(),
                 tool_calls_made,
-                total_rounds: rounds,
+                total_rounds: rounds - 1,
+                total_usage,
+                total_cost_micros,
+                elapsed_ms: t_start.elapsed().as_millis(),
+                turn_messages: messages[turn_start_idx..].to_vec(),
+                resumable: checkpointer.is_some(),
+            });
+        }
+
+        // The final answer takes the last allowed round; a cap of one round leaves no room for it
+        if rounds > 1 && rounds >= config.limits.max_rounds {
+            warn!("Agent hit max tool rounds ({}), asking for a final answer", config.limits.max_rounds);
+            let (response, reasoning) = if rounds > config.limits.max_rounds {
+                (ROUND_CAP_MESSAGE.to_string(), None)
+            } else {
+                round_cap_answer(
+                    provider, config, &messages, &tool_defs, None, &mut total_usage, &mut total_cost_micros,
+                ).await
+            };
+            if let Some(ref cp) = checkpointer {
+                cp.finish().await;
+            }
+            crate::llm_log::clear_session_context();
+            return Ok(AgentTurnResult {
+                response,
+                reasoning,
+                model_name: provider.name().to_string(),
+                tool_calls_made,
+                total_rounds: rounds.min(config.limits.max_rounds),
                 total_usage,
+                total_cost_micros,
                 elapsed_ms: t_start.elapsed().as_millis(),
                 turn_messages: messages[turn_start_idx..].to_vec(),
+                resumable: false,
             });
         }
 
@@ -335,9 +844,8 @@ pub async fn run_agent_turn(
             .await
             .with_context(|| format!("LLM call failed on round {}", rounds))?;
 
-        total_usage.prompt_tokens += usage.prompt_tokens;
-        total_usage.completion_tokens += usage.completion_tokens;
-        total_usage.total_tokens += usage.total_tokens;
+        // The provider's name is the model that actually served this round
+        add_usage(config, provider.name(), &usage, &mut total_usage, &mut total_cost_micros);
 
         match completion {
             Completion::Text { content, reasoning } => {
@@ -362,6 +870,10 @@ pub async fn run_agent_turn(
                     t_start.elapsed().as_millis()
                 );
 
+                if let Some(ref cp) = checkpointer {
+                    cp.finish().await;
+                }
+                index_turn(config, &messages[turn_start_idx - 1], &content);
                 crate::llm_log::clear_session_context();
                 return Ok(AgentTurnResult {
                     response: content,
@@ -370,8 +882,10 @@ pub async fn run_agent_turn(
                     tool_calls_made,
                     total_rounds: rounds,
                     total_usage,
+                    total_cost_micros,
                     elapsed_ms: t_start.elapsed().as_millis(),
                     turn_messages: messages[turn_start_idx..].to_vec(),
+                    resumable: false,
                 });
             }
 
@@ -420,6 +934,8 @@ pub async fn run_agent_turn(
                     }
                 }
 
+                request_approvals(config, &prepared, &mut execute_list, None, None).await;
+
                 let to_execute: Vec<(String, serde_json::Value, String)> = execute_list.iter()
                     .filter(|(_, should_exec, _)| *should_exec)
                     .map(|(idx, _, _)| prepared[*idx].clone())
@@ -478,9 +994,13 @@ pub async fn run_agent_turn(
                         result.is_error
                     );
 
-                    let output = truncate_tool_output(&output);
+                    let output = truncate_tool_output(&output, config.limits.max_tool_output_tokens, counter.as_ref());
                     messages.push(Message::tool_result(call_id, &output));
                 }
+
+                if let Some(ref mut cp) = checkpointer {
+                    cp.save(&messages[turn_start_idx..], rounds).await;
+                }
             }
         }
     }
@@ -497,55 +1017,62 @@ pub async fn run_agent_turn_streaming(
     event_tx: tokio::sync::mpsc::UnboundedSender<StreamEvent>,
     image_urls: Vec<String>,
     cancel_token: Option<tokio_util::sync::CancellationToken>,
+) -> Result<AgentTurnResult> {
+    let start = TurnStart::New { user_message, image_urls };
+    run_turn_streaming(provider, start, config, tools, event_tx, cancel_token).await
+}
+
+/// Continue an interrupted turn from its checkpoint, streaming like `run_agent_turn_streaming`
+pub async fn resume_agent_turn_streaming(
+    provider: &dyn LlmProvider,
+    checkpoint: &TurnCheckpoint,
+    config: &AgentTurnConfig,
+    tools: &ToolRegistry,
+    event_tx: tokio::sync::mpsc::UnboundedSender<StreamEvent>,
+    cancel_token: Option<tokio_util::sync::CancellationToken>,
+) -> Result<AgentTurnResult> {
+    run_turn_streaming(provider, TurnStart::Resume(checkpoint), config, tools, event_tx, cancel_token).await
+}
+
+async fn run_turn_streaming(
+    provider: &dyn LlmProvider,
+    start: TurnStart<'_>,
+    config: &AgentTurnConfig,
+    tools: &ToolRegistry,
+    event_tx: tokio::sync::mpsc::UnboundedSender<StreamEvent>,
+    cancel_token: Option<tokio_util::sync::CancellationToken>,
 ) -> Result<AgentTurnResult> {
     let t_start = Instant::now();
 
     // Set session context for LLM log tagging
     crate::llm_log::set_session_context(&config.session_key);
 
-    let workspace_dir = Path::new(&config.workspace_dir);
-    let ws = workspace::load_workspace(workspace_dir, config.minimal_context)
-        .await
-        .context("Failed to load workspace")?;
-
-    // Build initial messages with session history
-    let history = load_session_history(&config.agent_name, &config.session_key).await;
-    let mut messages = vec![Message::system(&ws.system_prompt)];
-    messages.extend(history);
-
-    // Use multimodal message if images are present
-    if image_urls.is_empty() {
-        messages.push(Message::user(user_message));
-    } else {
-        // Inject a hint so the LLM knows the image is already in its context
-        let image_hint = format!(
-            "[SYSTEM: {} image(s) attached inline below. You can see them directly — \
-             analyze them from your visual input. Do NOT use the `image` tool or try to \
-             open a file path. The image data is already in this message.]\n\n{}",
-            image_urls.len(),
-            user_message,
-        );
-        messages.push(Message::user_with_images(&image_hint, image_urls));
-    }
+    let TurnSetup {
+        mut messages, turn_start_idx, mut rounds, mut tool_calls_made, usage, cost_micros, mut checkpointer,
+    } = setup_turn(provider, config, start).await?;
 
     let tool_defs = tools.definitions();
+    let counter = crate::tokenizer::for_model(provider.name());
     let tool_ctx = ToolContext {
         workspace_dir: config.workspace_dir.clone(),
         agent_name: config.agent_name.clone(),
         session_key: config.session_key.clone(),
-        sandbox: crate::sandbox::SandboxPolicy::default(),
+        sandbox: config.sandbox.clone(),
+        approval: config.approval.clone(),
+        tool_policy: tools.policy().clone(),
+        turn_calls: config.turn_calls.clone().unwrap_or_default(),
         chat_id: config.chat_id,
         delegate_tx: config.delegate_tx.clone(),
         task_query_fn: config.task_query_fn.clone(),
         task_cancel_fn: config.task_cancel_fn.clone(),
         stream_tx: Some(event_tx.clone()),
+        call_id: String::new(),
     };
 
-    let mut total_usage = UsageStats::default();
-    let mut tool_calls_made = 0;
-    let mut rounds = 0;
-    let mut loop_detector = LoopDetector::new();
-    let turn_start_idx = messages.len(); // track where new messages begin
+    // A compaction summary written for this turn counts towards its usage
+    let mut total_usage = usage;
+    let mut total_cost_micros = cost_micros;
+    let mut loop_detector = LoopDetector::with_config(config.limits.loop_detection.clone());
 
     loop {
         // ── Check cancellation before each round ──
@@ -561,26 +1088,60 @@ pub async fn run_agent_turn_streaming(
                     tool_calls_made,
                     total_rounds: rounds,
                     total_usage,
+                    total_cost_micros,
                     elapsed_ms: t_start.elapsed().as_millis(),
                     turn_messages: messages[turn_start_idx..].to_vec(),
+                    resumable: checkpointer.is_some(),
                 });
             }
         }
 
         rounds += 1;
-        if rounds > MAX_TOOL_ROUNDS {
-            warn!("Agent hit max tool rounds ({}), forcing stop", MAX_TOOL_ROUNDS);
+        if config.budget.is_some_and(|b| b.is_exhausted(total_usage.total_tokens as u64, total_cost_micros)) {
+            warn!("Spend quota reached after {} round(s), stopping turn", rounds - 1);
             let _ = event_tx.send(StreamEvent::Done);
             crate::llm_log::clear_session_context();
             return Ok(AgentTurnResult {
-                response: "(Agent reached maximum tool call rounds)".to_string(),
+                response: QUOTA_STOP_MESSAGE.to_string(),
                 reasoning: None,
                 model_name: provider.name().to_string(),
                 tool_calls_made,
-                total_rounds: rounds,
+                total_rounds: rounds - 1,
+                total_usage,
+                total_cost_micros,
+                elapsed_ms: t_start.elapsed().as_millis(),
+                turn_messages: messages[turn_start_idx..].to_vec(),
+                resumable: checkpointer.is_some(),
+            });
+        }
+
+        // The final answer takes the last allowed round; a cap of one round leaves no room for it
+        if rounds > 1 && rounds >= config.limits.max_rounds {
+            warn!("Agent hit max tool rounds ({}), asking for a final answer", config.limits.max_rounds);
+            let _ = event_tx.send(StreamEvent::RoundStart { round: rounds });
+            let (response, reasoning) = if rounds > config.limits.max_rounds {
+                (ROUND_CAP_MESSAGE.to_string(), None)
+            } else {
+                round_cap_answer(
+                    provider, config, &messages, &tool_defs, Some(&event_tx), &mut total_usage, &mut total_cost_micros,
+                ).await
+            };
+            if let Some(ref cp) = checkpointer {
+                cp.finish().await;
+            }
+            let _ = event_tx.send(StreamEvent::Done);
+            crate::llm_log::clear_session_context();
+            return Ok(AgentTurnResult {
+                response,
+                reasoning,
+                model_name: provider.name().to_string(),
+                tool_calls_made,
+                total_rounds: rounds.min(config.limits.max_rounds),
                 total_usage,
+                total_cost_micros,
                 elapsed_ms: t_start.elapsed().as_millis(),
                 turn_messages: messages[turn_start_idx..].to_vec(),
+                resumable: false,
             });
         }
 
@@ -605,8 +1166,10 @@ pub async fn run_agent_turn_streaming(
                         tool_calls_made,
                         total_rounds: rounds,
                         total_usage,
+                        total_cost_micros,
                         elapsed_ms: t_start.elapsed().as_millis(),
                         turn_messages: messages[turn_start_idx..].to_vec(),
+                        resumable: checkpointer.is_some(),
                     });
                 }
             }
@@ -617,9 +1180,8 @@ pub async fn run_agent_turn_streaming(
         let (completion, usage) = llm_result
             .with_context(|| format!("LLM streaming call failed on round {}", rounds))?;
 
-        total_usage.prompt_tokens += usage.prompt_tokens;
-        total_usage.completion_tokens += usage.completion_tokens;
-        total_usage.total_tokens += usage.total_tokens;
+        // The provider's name is the model that actually served this round
+        add_usage(config, provider.name(), &usage, &mut total_usage, &mut total_cost_micros);
 
         match completion {
             Completion::Text { content, reasoning } => {
@@ -644,6 +1206,10 @@ pub async fn run_agent_turn_streaming(
 
                 let _ = event_tx.send(StreamEvent::Done);
 
+                if let Some(ref cp) = checkpointer {
+                    cp.finish().await;
+                }
+                index_turn(config, &messages[turn_start_idx - 1], &content);
                 crate::llm_log::clear_session_context();
                 return Ok(AgentTurnResult {
                     response: content,
@@ -652,8 +1218,10 @@ pub async fn run_agent_turn_streaming(
                     tool_calls_made,
                     total_rounds: rounds,
                     total_usage,
+                    total_cost_micros,
                     elapsed_ms: t_start.elapsed().as_millis(),
                     turn_messages: messages[turn_start_idx..].to_vec(),
+                    resumable: false,
                 });
             }
 
@@ -713,6 +1281,8 @@ pub async fn run_agent_turn_streaming(
                 }
 
                 // Only execute non-blocked calls
+                request_approvals(config, &prepared, &mut execute_list, Some(&event_tx), cancel_token.as_ref()).await;
+
                 let to_execute: Vec<(String, serde_json::Value, String)> = execute_list.iter()
                     .filter(|(_, should_exec, _)| *should_exec)
                     .map(|(idx, _, _)| prepared[*idx].clone())
@@ -773,9 +1343,13 @@ pub async fn run_agent_turn_streaming(
                         output
                     };
 
-                    let output = truncate_tool_output(&output);
+                    let output = truncate_tool_output(&output, config.limits.max_tool_output_tokens, counter.as_ref());
                     messages.push(Message::tool_result(call_id, &output));
                 }
+
+                if let Some(ref mut cp) = checkpointer {
+                    cp.save(&messages[turn_start_idx..], rounds).await;
+                }
             }
         }
     }
@@ -784,6 +1358,7 @@ pub async fn run_agent_turn_streaming(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tokenizer::HeuristicCounter;
 
     #[test]
     fn test_agent_turn_config() {
@@ -792,30 +1367,44 @@ mod tests {
             session_key: "test-session".to_string(),
             workspace_dir: "/tmp".to_string(),
             minimal_context: false,
-        ..AgentTurnConfig::default()
+            ..AgentTurnConfig::default()
         };
         assert_eq!(config.agent_name, "main");
     }
 
+    #[test]
+    fn test_turn_budget() {
+        let user = TurnBudget { max_tokens: Some(5000), max_cost_micros: None };
+        let session = TurnBudget { max_tokens: Some(8000), max_cost_micros: Some(20_000) };
+        let budget = user.min(session);
+        assert_eq!(budget, TurnBudget { max_tokens: Some(5000), max_cost_micros: Some(20_000) });
+        assert!(!budget.is_exhausted(4999, 19_999));
+        assert!(budget.is_exhausted(5000, 0));
+        assert!(budget.is_exhausted(0, 20_000));
+        assert!(!TurnBudget::default().is_exhausted(u64::MAX, u64::MAX));
+    }
+
     #[test]
     fn test_truncate_tool_output_short() {
         let short = "hello world";
-        assert_eq!(truncate_tool_output(short), short);
+        assert_eq!(truncate_tool_output(short, 8000, &HeuristicCounter), short);
     }
 
     #[test]
     fn test_truncate_tool_output_long() {
         let long = "x".repeat(50000);
-        let result = truncate_tool_output(&long);
+        let result = truncate_tool_output(&long, 8000, &HeuristicCounter);
         assert!(result.len() < long.len());
         assert!(result.contains("chars truncated"));
     }
 
     #[test]
-    fn test_estimate_tokens() {
-        assert_eq!(estimate_tokens(""), 1); // minimum 1
-        assert_eq!(estimate_tokens("hello world!"), 3); // 12 chars / 4
-        assert_eq!(estimate_tokens("a".repeat(100).as_str()), 25);
+    fn test_truncate_tool_output_counts_tokens_not_chars() {
+        // 12K CJK chars fit a 32K char limit but are ~12K tokens
+        let cjk = "字".repeat(12000);
+        let result = truncate_tool_output(&cjk, 8000, &HeuristicCounter);
+        assert!(result.contains("4000 chars truncated"));
+        assert!(result.starts_with(&"字".repeat(6000)));
     }
 
     #[test]
@@ -873,7 +1462,7 @@ mod tests {
             session_key: "cancel-test-session".to_string(),
             workspace_dir: "/tmp".to_string(),
             minimal_context: true,
-        ..AgentTurnConfig::default()
+            ..AgentTurnConfig::default()
         };
         let tools = crate::tools::ToolRegistry::new();
         let (event_tx, _event_rx) = tokio::sync::mpsc::unbounded_channel::<StreamEvent>();
@@ -900,4 +1489,313 @@ mod tests {
         assert!(result.response.contains("Cancelled"));
         assert!(result.elapsed_ms < 5000); // Should abort quickly, not wait 10s
     }
+
+    /// Calls `exec` with a fixed command, then answers with the tool's output
+    struct ExecOnceProvider {
+        command: &'static str,
+    }
+
+    #[async_trait::async_trait]
+    impl LlmProvider for ExecOnceProvider {
+        fn name(&self) -> &str { "exec-once" }
+
+        async fn complete(
+            &self,
+            messages: &[Message],
+            _tools: &[crate::llm::ToolDefinition],
+        ) -> Result<(Completion, UsageStats)> {
+            let last = messages.last().expect("turn has messages");
+            let completion = match last.role {
+                crate::llm::Role::Tool => Completion::Text {
+                    content: last.content.clone().unwrap_or_default(),
+                    reasoning: None,
+                },
+                _ => Completion::ToolCalls {
+                    calls: vec![crate::llm::ToolCall {
+                        id: "call-1".into(),
+                        call_type: "function".into(),
+                        function: crate::llm::FunctionCall {
+                            name: "exec".into(),
+                            arguments: serde_json::json!({ "command": self.command }).to_string(),
+                        },
+                    }],
+                    reasoning: None,
+                },
+            };
+            Ok((completion, UsageStats::default()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_turn_sandbox_blocks_configured_command() {
+        let mut sandbox = SandboxPolicy::default();
+        sandbox.command_blocklist.push("curl".to_string());
+        let config = AgentTurnConfig {
+            agent_name: "test-sandbox".to_string(),
+            session_key: "sandbox-test-session".to_string(),
+            workspace_dir: "/tmp".to_string(),
+            minimal_context: true,
+            sandbox,
+            ..AgentTurnConfig::default()
+        };
+        let mut tools = crate::tools::ToolRegistry::new();
+        tools.register(Box::new(crate::tools::exec::ExecTool));
+
+        let provider = ExecOnceProvider { command: "curl -s https://example.com" };
+        let result = run_agent_turn(&provider, "fetch example.com", &config, &tools).await.unwrap();
+        assert_eq!(result.tool_calls_made, 1);
+        assert!(result.response.contains("blocked by sandbox policy"), "{}", result.response);
+
+        // Without the configured entry the same command is not refused by the sandbox
+        let open = AgentTurnConfig { sandbox: SandboxPolicy::default(), ..config };
+        let provider = ExecOnceProvider { command: "echo curl-free" };
+        let result = run_agent_turn(&provider, "say hi", &open, &tools).await.unwrap();
+        assert!(!result.response.contains("blocked by sandbox policy"));
+    }
+
+    #[tokio::test]
+    async fn test_flagged_call_waits_for_approval() {
+        let approval: crate::approval::ApprovalPolicy = serde_json::from_value(serde_json::json!({
+            "rules": [{ "tool": "exec", "matches": "echo approve-me" }]
+        }))
+        .unwrap();
+        let approval = approval.with_requester(42, 42);
+        let config = AgentTurnConfig {
+            agent_name: "test-approval".to_string(),
+            session_key: "approval-test-session".to_string(),
+            workspace_dir: "/tmp".to_string(),
+            minimal_context: true,
+            approval,
+            ..AgentTurnConfig::default()
+        };
+        let mut tools = crate::tools::ToolRegistry::new();
+        tools.register(Box::new(crate::tools::exec::ExecTool));
+        let provider = ExecOnceProvider { command: "echo approve-me" };
+
+        // Nobody to ask outside a streaming turn: the call is refused
+        let result = run_agent_turn(&provider, "run it", &config, &tools).await.unwrap();
+        assert!(result.response.contains("not executed"), "{}", result.response);
+
+        // Streaming: the frontend approves the request and the command runs
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let approver = tokio::spawn(async move {
+            while let Some(event) = rx.recv().await {
+                if let StreamEvent::ApprovalRequired { id, tool, .. } = event {
+                    assert_eq!(tool, "exec");
+                    let requester = crate::approval::Requester::new(42, 42);
+                    assert_eq!(crate::approval::resolve(&id, true, &requester), crate::approval::Resolution::Answered);
+                }
+            }
+        });
+        let result = run_agent_turn_streaming(&provider, "run it", &config, &tools, tx, vec![], None)
+            .await
+            .unwrap();
+        approver.await.unwrap();
+        assert!(result.response.contains("approve-me"), "{}", result.response);
+        assert!(result.response.contains("Approved by the user"), "{}", result.response);
+    }
+
+    #[tokio::test]
+    async fn test_resume_continues_from_checkpoint() {
+        let call = crate::llm::ToolCall {
+            id: "call-1".into(),
+            call_type: "function".into(),
+            function: crate::llm::FunctionCall {
+                name: "exec".into(),
+                arguments: serde_json::json!({ "command": "echo saved" }).to_string(),
+            },
+        };
+        let saved = vec![
+            Message::assistant_tool_calls(vec![call], None),
+            Message::tool_result("call-1", "saved-output"),
+        ];
+        let checkpoint = TurnCheckpoint {
+            turn_id: "turn-1".to_string(),
+            session_key: "resume-test-session".to_string(),
+            agent_name: "test-resume".to_string(),
+            user_message: "run it".to_string(),
+            image_urls: Vec::new(),
+            messages: serde_json::to_value(&saved).unwrap(),
+            rounds: 1,
+        };
+        let config = AgentTurnConfig {
+            agent_name: "test-resume".to_string(),
+            session_key: "resume-test-session".to_string(),
+            workspace_dir: "/tmp".to_string(),
+            minimal_context: true,
+            ..AgentTurnConfig::default()
+        };
+        let tools = crate::tools::ToolRegistry::new();
+
+        // The saved tool result is the last message, so the model answers without calling exec again
+        let provider = ExecOnceProvider { command: "echo again" };
+        let result = resume_agent_turn(&provider, &checkpoint, &config, &tools).await.unwrap();
+        assert_eq!(result.response, "saved-output");
+        assert_eq!(result.total_rounds, 2);
+        assert_eq!(result.tool_calls_made, 1);
+        assert_eq!(result.turn_messages.len(), 2);
+        assert!(!result.resumable);
+    }
+
+    /// Answers with the number of images the request carries
+    struct ImageCountProvider;
+
+    #[async_trait::async_trait]
+    impl LlmProvider for ImageCountProvider {
+        fn name(&self) -> &str { "image-count" }
+
+        async fn complete(
+            &self,
+            messages: &[Message],
+            _tools: &[crate::llm::ToolDefinition],
+        ) -> Result<(Completion, UsageStats)> {
+            let images: usize = messages.iter().map(|m| m.image_urls.len()).sum();
+            let content = format!("{} image(s)", images);
+            Ok((Completion::Text { content, reasoning: None }, UsageStats::default()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resume_keeps_original_images() {
+        let checkpoint = TurnCheckpoint {
+            turn_id: "turn-images".to_string(),
+            session_key: "resume-images-test-session".to_string(),
+            agent_name: "test-resume".to_string(),
+            user_message: "what is this?".to_string(),
+            image_urls: vec!["data:image/png;base64,iVBORw0KGgo=".to_string()],
+            messages: serde_json::json!([]),
+            rounds: 0,
+        };
+        let config = AgentTurnConfig {
+            agent_name: "test-resume".to_string(),
+            session_key: "resume-images-test-session".to_string(),
+            workspace_dir: "/tmp".to_string(),
+            minimal_context: true,
+            ..AgentTurnConfig::default()
+        };
+        let tools = crate::tools::ToolRegistry::new();
+
+        let result = resume_agent_turn(&ImageCountProvider, &checkpoint, &config, &tools).await.unwrap();
+        assert_eq!(result.response, "1 image(s)");
+    }
+
+    /// Keeps calling a tool until asked for a final answer
+    struct ToolHungryProvider;
+
+    #[async_trait::async_trait]
+    impl LlmProvider for ToolHungryProvider {
+        fn name(&self) -> &str { "tool-hungry" }
+
+        async fn complete(
+            &self,
+            messages: &[Message],
+            _tools: &[crate::llm::ToolDefinition],
+        ) -> Result<(Completion, UsageStats)> {
+            let usage = UsageStats { total_tokens: 10, ..Default::default() };
+            if messages.last().and_then(|m| m.content.as_deref()) == Some(ROUND_CAP_PROMPT) {
+                let content = "Listed /tmp twice; nothing else done.".to_string();
+                return Ok((Completion::Text { content, reasoning: None }, usage));
+            }
+            let round = messages.iter().filter(|m| m.tool_calls.is_some()).count();
+            let call = crate::llm::ToolCall {
+                id: format!("call-{}", round),
+                call_type: "function".into(),
+                function: crate::llm::FunctionCall {
+                    name: "exec".into(),
+                    arguments: serde_json::json!({ "command": format!("echo {}", round) }).to_string(),
+                },
+            };
+            Ok((Completion::ToolCalls { calls: vec![call], reasoning: None }, usage))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_round_cap_asks_for_final_answer() {
+        let config = AgentTurnConfig {
+            agent_name: "test-limits".to_string(),
+            session_key: "limits-test-session".to_string(),
+            workspace_dir: "/tmp".to_string(),
+            minimal_context: true,
+            limits: TurnLimits { max_rounds: 3, ..TurnLimits::default() },
+            ..AgentTurnConfig::default()
+        };
+        let mut tools = crate::tools::ToolRegistry::new();
+        tools.register(Box::new(crate::tools::exec::ExecTool));
+
+        let result = run_agent_turn(&ToolHungryProvider, "look around", &config, &tools).await.unwrap();
+        assert_eq!(result.tool_calls_made, 2);
+        assert_eq!(result.response, "Listed /tmp twice; nothing else done.");
+        // Two tool rounds plus the final answer, all within the cap
+        assert_eq!(result.total_rounds, 3);
+        assert_eq!(result.total_usage.total_tokens, 30);
+        assert!(result.turn_messages.iter().all(|m| m.content.as_deref() != Some(ROUND_CAP_PROMPT)));
+    }
+
+    #[tokio::test]
+    async fn test_round_cap_answer_respects_budget() {
+        let config = AgentTurnConfig {
+            agent_name: "test-limits".to_string(),
+            session_key: "limits-budget-test-session".to_string(),
+            workspace_dir: "/tmp".to_string(),
+            minimal_context: true,
+            limits: TurnLimits { max_rounds: 3, ..TurnLimits::default() },
+            budget: Some(TurnBudget { max_tokens: Some(20), max_cost_micros: None }),
+            ..AgentTurnConfig::default()
+        };
+        let mut tools = crate::tools::ToolRegistry::new();
+        tools.register(Box::new(crate::tools::exec::ExecTool));
+
+        // The budget is spent after two rounds, so no final answer is requested
+        let result = run_agent_turn(&ToolHungryProvider, "look around", &config, &tools).await.unwrap();
+        assert_eq!(result.response, QUOTA_STOP_MESSAGE);
+        assert_eq!(result.total_usage.total_tokens, 20);
+    }
+
+    /// Calls a tool, then fails on the next round
+    struct FailsAfterToolProvider;
+
+    #[async_trait::async_trait]
+    impl LlmProvider for FailsAfterToolProvider {
+        fn name(&self) -> &str { "fails-after-tool" }
+
+        async fn complete(
+            &self,
+            messages: &[Message],
+            _tools: &[crate::llm::ToolDefinition],
+        ) -> Result<(Completion, UsageStats)> {
+            if messages.iter().any(|m| matches!(m.role, crate::llm::Role::Tool)) {
+                anyhow::bail!("provider went away");
+            }
+            let call = crate::llm::ToolCall {
+                id: "call-1".into(),
+                call_type: "function".into(),
+                function: crate::llm::FunctionCall {
+                    name: "exec".into(),
+                    arguments: serde_json::json!({ "command": "echo hi" }).to_string(),
+                },
+            };
+            let usage = UsageStats { total_tokens: 7, ..Default::default() };
+            Ok((Completion::ToolCalls { calls: vec![call], reasoning: None }, usage))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_usage_reported_per_call_when_turn_fails() {
+        let reported = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = reported.clone();
+        let config = AgentTurnConfig {
+            agent_name: "test-usage".to_string(),
+            session_key: "usage-test-session".to_string(),
+            workspace_dir: "/tmp".to_string(),
+            minimal_context: true,
+            on_usage: Some(std::sync::Arc::new(move |tokens, _| sink.lock().unwrap().push(tokens))),
+            ..AgentTurnConfig::default()
+        };
+        let mut tools = crate::tools::ToolRegistry::new();
+        tools.register(Box::new(crate::tools::exec::ExecTool));
+
+        let result = run_agent_turn(&FailsAfterToolProvider, "say hi", &config, &tools).await;
+        assert!(result.is_err());
+        assert_eq!(*reported.lock().unwrap(), vec![7]);
+    }
 }
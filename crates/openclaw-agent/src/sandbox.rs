@@ -1,3 +1,4 @@
+use serde::{Deserialize, Serialize};
 use std::path::Path;
 
 /// Security sandbox policy for tool execution
@@ -17,6 +18,36 @@ pub struct SandboxPolicy {
     pub network_allowed: bool,
     /// Per-turn timeout in seconds (0 = no limit)
     pub turn_timeout_secs: u64,
+    /// Where `exec` and `process` commands run
+    pub exec_backend: ExecBackend,
+    /// Resource limits for `exec` and `process` commands
+    pub exec_limits: ExecLimits,
+}
+
+/// How shell commands are isolated from the host (see `crate::isolation`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExecBackend {
+    /// `sh -c` directly on the host
+    #[default]
+    Host,
+    /// Linux user/mount/pid (and, without network access, network) namespaces:
+    /// the workspace and `write_allow` dirs are writable, everything else read-only
+    Namespace,
+}
+
+/// rlimits applied to shell commands (unset = inherited)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExecLimits {
+    /// CPU seconds (`RLIMIT_CPU`)
+    pub cpu_secs: Option<u64>,
+    /// Address space in MiB (`RLIMIT_AS`)
+    pub memory_mb: Option<u64>,
+    /// Processes of the gateway's user (`RLIMIT_NPROC`). The kernel counts every
+    /// process and thread of that real uid on the host, the gateway's included,
+    /// so this must sit well above the gateway's own thread count.
+    pub max_pids: Option<u64>,
 }
 
 impl Default for SandboxPolicy {
@@ -29,6 +60,8 @@ impl Default for SandboxPolicy {
             max_output_bytes: 64 * 1024,
             network_allowed: true,
             turn_timeout_secs: 120,
+            exec_backend: ExecBackend::Host,
+            exec_limits: ExecLimits::default(),
         }
     }
 }
@@ -10,18 +10,33 @@ use tracing::{info, warn};
 use crate::llm::fallback::FallbackProvider;
 use crate::llm::streaming::StreamEvent;
 use crate::llm::{LlmProvider, OpenAiCompatibleProvider};
+use crate::approval::ApprovalPolicy;
 use crate::runtime::{run_agent_turn_streaming, AgentTurnConfig};
+use crate::sandbox::SandboxPolicy;
+use crate::tool_policy::{ToolPolicy, TurnCallCounts};
 use crate::tools::ToolRegistry;
 
+/// Restrictions a subagent inherits from the turn that delegated to it
+#[derive(Debug, Clone, Default)]
+pub struct ParentPolicy {
+    pub sandbox: SandboxPolicy,
+    pub approval: ApprovalPolicy,
+    pub tools: ToolPolicy,
+    /// Per-turn call counts of the parent, shared so its caps cover the subagent too
+    pub turn_calls: TurnCallCounts,
+}
+
 /// Run a subagent turn with the given prompt.
 /// Uses the same provider config as the parent agent but with a fresh message history.
 /// Returns the subagent's text response.
+/// The subagent runs under the parent turn's sandbox, approval and tool policy.
 /// If `event_forward_tx` is provided, subagent stream events are forwarded to it
 /// (for real-time activity display in the chat).
 pub async fn run_subagent_turn(
     prompt: &str,
     agent_name: &str,
     workspace_dir: &str,
+    parent: ParentPolicy,
     cancel_token: Option<tokio_util::sync::CancellationToken>,
     event_forward_tx: Option<tokio::sync::mpsc::UnboundedSender<StreamEvent>>,
 ) -> Result<String> {
@@ -35,6 +50,9 @@ pub async fn run_subagent_turn(
         session_key: format!("subagent:{}:{}", agent_name, uuid::Uuid::new_v4()),
         workspace_dir: workspace_dir.to_string(),
         minimal_context: true,
+        sandbox: parent.sandbox,
+        approval: parent.approval,
+        turn_calls: Some(parent.turn_calls),
         ..AgentTurnConfig::default()
     };
 
@@ -50,7 +68,7 @@ pub async fn run_subagent_turn(
 
     // Remove the delegate tool from subagent to prevent infinite recursion
     // (subagents cannot spawn further subagents)
-    let tools = ToolRegistry::without_tool(tools, "delegate");
+    let tools = ToolRegistry::without_tool(tools, "delegate").with_policy(parent.tools);
 
     let (event_tx, mut event_rx) = tokio::sync::mpsc::unbounded_channel::<StreamEvent>();
 
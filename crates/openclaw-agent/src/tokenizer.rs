@@ -0,0 +1,374 @@
+//! Token counting for history budgets, tool output limits and usage estimates.
+//!
+//! `for_model` picks a `TokenCounter` by model family:
+//!
+//! - OpenAI `gpt-4o` / `gpt-4.1` / `o*` / `gpt-5` models use the `o200k_base` BPE
+//! - everything else uses `cl100k_base`, the vocabulary of older OpenAI models
+//!   and the closest published one for Anthropic, Moonshot, Qwen and Llama
+//!
+//! Vocabularies are the published tiktoken rank files (`cl100k_base.tiktoken`,
+//! `o200k_base.tiktoken` from openaipublic.blob.core.windows.net/encodings),
+//! installed by hand into `$OPENCLAW_TOKENIZERS_DIR` or `~/.openclaw/tokenizers/`
+//! and never fetched. Without a vocabulary the script-aware `HeuristicCounter`
+//! estimates counts instead.
+
+use base64::Engine;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+use tracing::{debug, info};
+
+use crate::llm::Message;
+
+/// Formatting overhead per message (role, separators)
+const MESSAGE_OVERHEAD_TOKENS: usize = 4;
+/// Cost of one inline image; a ~1 megapixel image on the OpenAI and Anthropic vision APIs
+const IMAGE_TOKENS: usize = 1600;
+
+/// Counts tokens the way a model family's tokenizer does
+pub trait TokenCounter: Send + Sync {
+    /// Short name for logs, e.g. "cl100k_base" or "heuristic"
+    fn name(&self) -> &str;
+
+    fn count(&self, text: &str) -> usize;
+
+    /// Tokens a message occupies in a request: text, reasoning, tool calls, images
+    fn count_message(&self, msg: &Message) -> usize {
+        let mut tokens = MESSAGE_OVERHEAD_TOKENS;
+        for text in [msg.content.as_deref(), msg.reasoning_content.as_deref()].into_iter().flatten() {
+            tokens += self.count(text);
+        }
+        for call in msg.tool_calls.iter().flatten() {
+            tokens += self.count(&call.function.name) + self.count(&call.function.arguments);
+        }
+        tokens + msg.image_urls.len() * IMAGE_TOKENS
+    }
+
+    fn count_messages(&self, messages: &[Message]) -> usize {
+        messages.iter().map(|m| self.count_message(m)).sum()
+    }
+}
+
+/// Script-aware estimate: ~4 ASCII chars per token, ~2 other chars per token,
+/// one token per CJK character
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HeuristicCounter;
+
+impl TokenCounter for HeuristicCounter {
+    fn name(&self) -> &str {
+        "heuristic"
+    }
+
+    fn count(&self, text: &str) -> usize {
+        let (mut ascii, mut cjk, mut other) = (0usize, 0usize, 0usize);
+        for c in text.chars() {
+            if c.is_ascii() {
+                ascii += 1;
+            } else if is_cjk(c) {
+                cjk += 1;
+            } else {
+                other += 1;
+            }
+        }
+        (ascii / 4 + other / 2 + cjk).max(1)
+    }
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x3040..=0x30FF      // Hiragana, Katakana
+        | 0x3400..=0x4DBF    // CJK Extension A
+        | 0x4E00..=0x9FFF    // CJK Unified Ideographs
+        | 0xAC00..=0xD7AF    // Hangul syllables
+        | 0xF900..=0xFAFF    // CJK Compatibility Ideographs
+        | 0x20000..=0x2FFFF) // CJK Extensions B+
+}
+
+/// Byte-level BPE over a tiktoken rank file
+pub struct BpeCounter {
+    name: String,
+    ranks: HashMap<Vec<u8>, u32>,
+}
+
+impl BpeCounter {
+    /// Parse a tiktoken rank file: one `<base64 token> <rank>` per line
+    pub fn from_tiktoken(name: &str, data: &str) -> anyhow::Result<Self> {
+        let engine = base64::engine::general_purpose::STANDARD;
+        let mut ranks = HashMap::new();
+        for (n, line) in data.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+            let (token, rank) = line
+                .split_once(' ')
+                .ok_or_else(|| anyhow::anyhow!("{}: malformed line {}", name, n + 1))?;
+            let token = engine
+                .decode(token)
+                .map_err(|e| anyhow::anyhow!("{}: bad token on line {}: {}", name, n + 1, e))?;
+            ranks.insert(token, rank.trim().parse()?);
+        }
+        if ranks.is_empty() {
+            anyhow::bail!("{}: empty vocabulary", name);
+        }
+        Ok(Self {
+            name: name.to_string(),
+            ranks,
+        })
+    }
+
+    /// Number of tokens in one pre-tokenized piece
+    fn piece_tokens(&self, piece: &[u8]) -> usize {
+        if piece.len() < 2 || self.ranks.contains_key(piece) {
+            return piece.len().min(1);
+        }
+        byte_pair_merge(&self.ranks, piece).len() - 1
+    }
+}
+
+impl TokenCounter for BpeCounter {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn count(&self, text: &str) -> usize {
+        pre_tokenize(text).map(|piece| self.piece_tokens(piece.as_bytes())).sum()
+    }
+}
+
+/// Merge the lowest-ranked adjacent pair until no pair is in the vocabulary
+/// (tiktoken's algorithm). Returns the part boundaries, including the end.
+fn byte_pair_merge(ranks: &HashMap<Vec<u8>, u32>, piece: &[u8]) -> Vec<(usize, u32)> {
+    let rank_of = |bytes: &[u8]| ranks.get(bytes).copied().unwrap_or(u32::MAX);
+    let mut parts: Vec<(usize, u32)> = (0..piece.len() - 1).map(|i| (i, rank_of(&piece[i..i + 2]))).collect();
+    parts.push((piece.len() - 1, u32::MAX));
+    parts.push((piece.len(), u32::MAX));
+
+    let get_rank = |parts: &[(usize, u32)], i: usize| {
+        if i + 3 < parts.len() {
+            rank_of(&piece[parts[i].0..parts[i + 3].0])
+        } else {
+            u32::MAX
+        }
+    };
+
+    while let Some((i, _)) = parts[..parts.len() - 1]
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, rank))| *rank != u32::MAX)
+        .min_by_key(|(_, (_, rank))| *rank)
+    {
+        if i > 0 {
+            parts[i - 1].1 = get_rank(&parts, i - 1);
+        }
+        parts[i].1 = get_rank(&parts, i);
+        parts.remove(i + 1);
+    }
+    parts
+}
+
+/// Split text into the pieces BPE runs on, following the cl100k split pattern:
+/// contractions, letter runs with one leading symbol or space, 1–3 digit
+/// groups, punctuation runs, and whitespace (a single space joins the next word).
+fn pre_tokenize(text: &str) -> impl Iterator<Item = &str> {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut pieces = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let end = piece_end(&chars, i);
+        let from = chars[i].0;
+        let to = chars.get(end).map(|(b, _)| *b).unwrap_or(text.len());
+        pieces.push(&text[from..to]);
+        i = end;
+    }
+    pieces.into_iter()
+}
+
+/// Index just past the piece that starts at `i`
+fn piece_end(chars: &[(usize, char)], i: usize) -> usize {
+    let at = |j: usize| chars.get(j).map(|(_, c)| *c);
+    let is_letter = |j: usize| at(j).is_some_and(char::is_alphabetic);
+    let is_number = |j: usize| at(j).is_some_and(char::is_numeric);
+    let is_space = |j: usize| at(j).is_some_and(char::is_whitespace);
+    let is_symbol = |j: usize| at(j).is_some_and(|c| !c.is_whitespace() && !c.is_alphanumeric());
+    let run = |mut j: usize, pred: &dyn Fn(usize) -> bool| {
+        while j < chars.len() && pred(j) {
+            j += 1;
+        }
+        j
+    };
+    let c = chars[i].1;
+
+    // 's 't 're 've 'm 'll 'd
+    if c == '\'' {
+        let next: String = chars[i + 1..].iter().take(2).map(|(_, c)| c.to_ascii_lowercase()).collect();
+        for suffix in ["re", "ve", "ll", "s", "t", "m", "d"] {
+            if next.starts_with(suffix) {
+                return i + 1 + suffix.len();
+            }
+        }
+    }
+    // Letters, optionally led by one non-newline symbol or space
+    if is_letter(i) {
+        return run(i, &is_letter);
+    }
+    if c != '\r' && c != '\n' && !is_number(i) && is_letter(i + 1) {
+        return run(i + 1, &is_letter);
+    }
+    if is_number(i) {
+        let end = run(i, &is_number);
+        return end.min(i + 3);
+    }
+    // Punctuation run, optionally led by a space, swallowing trailing newlines
+    let start = if c == ' ' { i + 1 } else { i };
+    if is_symbol(start) {
+        return run(run(start, &is_symbol), &|j| matches!(at(j), Some('\r' | '\n')));
+    }
+    // Whitespace: through the last newline, else all but the space before a word
+    let end = run(i, &is_space);
+    if let Some(last_newline) = (i..end).rev().find(|&j| matches!(at(j), Some('\r' | '\n'))) {
+        return last_newline + 1;
+    }
+    if end < chars.len() && end - i > 1 {
+        return end - 1;
+    }
+    end
+}
+
+/// BPE vocabularies known to this module
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Cl100k,
+    O200k,
+}
+
+impl Encoding {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Cl100k => "cl100k_base",
+            Self::O200k => "o200k_base",
+        }
+    }
+
+    /// Vocabulary used for a "provider/model" label or bare model ID
+    pub fn for_model(model: &str) -> Self {
+        let id = model.rsplit('/').next().unwrap_or(model).to_lowercase();
+        let o200k = ["gpt-4o", "gpt-4.1", "gpt-4.5", "gpt-5", "o1", "o3", "o4", "chatgpt-4o"];
+        if o200k.iter().any(|p| id.starts_with(p)) {
+            Self::O200k
+        } else {
+            Self::Cl100k
+        }
+    }
+
+    fn loaded(&self) -> Option<Arc<BpeCounter>> {
+        static CL100K: OnceLock<Option<Arc<BpeCounter>>> = OnceLock::new();
+        static O200K: OnceLock<Option<Arc<BpeCounter>>> = OnceLock::new();
+        let cell = match self {
+            Self::Cl100k => &CL100K,
+            Self::O200k => &O200K,
+        };
+        cell.get_or_init(|| load_vocabulary(self.name())).clone()
+    }
+}
+
+/// Directories searched for `<encoding>.tiktoken`, in order
+fn vocabulary_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Ok(dir) = std::env::var("OPENCLAW_TOKENIZERS_DIR") {
+        dirs.push(PathBuf::from(dir));
+    }
+    dirs.push(openclaw_core::paths::openclaw_home().join("tokenizers"));
+    dirs
+}
+
+fn load_vocabulary(name: &str) -> Option<Arc<BpeCounter>> {
+    for dir in vocabulary_dirs() {
+        let path = dir.join(format!("{}.tiktoken", name));
+        let Ok(data) = std::fs::read_to_string(&path) else { continue };
+        match BpeCounter::from_tiktoken(name, &data) {
+            Ok(counter) => {
+                info!("Loaded {} tokenizer ({} tokens) from {}", name, counter.ranks.len(), path.display());
+                return Some(Arc::new(counter));
+            }
+            Err(e) => debug!("Ignoring {}: {}", path.display(), e),
+        }
+    }
+    debug!("No {} vocabulary found; using heuristic token counts", name);
+    None
+}
+
+/// Token counter for a "provider/model" label or bare model ID
+pub fn for_model(model: &str) -> Arc<dyn TokenCounter> {
+    match Encoding::for_model(model).loaded() {
+        Some(bpe) => bpe,
+        None => Arc::new(HeuristicCounter),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tiny vocabulary: all single bytes, plus merges for "he", "ll", "hell", "hello"
+    fn toy_counter() -> BpeCounter {
+        let engine = base64::engine::general_purpose::STANDARD;
+        let mut lines: Vec<String> = (0u8..=255).map(|b| format!("{} {}", engine.encode([b]), b as u32)).collect();
+        for (i, token) in ["he", "ll", "hell", "hello", " w", "or"].iter().enumerate() {
+            lines.push(format!("{} {}", engine.encode(token), 256 + i));
+        }
+        BpeCounter::from_tiktoken("toy", &lines.join("\n")).unwrap()
+    }
+
+    #[test]
+    fn test_heuristic_counter() {
+        let h = HeuristicCounter;
+        assert_eq!(h.count(""), 1); // minimum 1
+        assert_eq!(h.count("hello world!"), 3); // 12 ASCII chars / 4
+        assert_eq!(h.count(&"a".repeat(100)), 25);
+        // CJK is roughly a token per character, not a quarter of one
+        assert_eq!(h.count("你好世界"), 4);
+        assert_eq!(h.count("привет"), 3);
+    }
+
+    #[test]
+    fn test_pre_tokenize_cl100k_splits() {
+        let pieces: Vec<&str> = pre_tokenize("Hello, world! I'm 12345 ok\n\n  x").collect();
+        assert_eq!(
+            pieces,
+            vec!["Hello", ",", " world", "!", " I", "'m", " ", "123", "45", " ok", "\n\n", " ", " x"]
+        );
+    }
+
+    #[test]
+    fn test_bpe_merges() {
+        let bpe = toy_counter();
+        assert_eq!(bpe.count("hello"), 1);
+        // " world" → " w" + "or" + "l" + "d"
+        assert_eq!(bpe.count("hello world"), 5);
+        assert_eq!(bpe.count("help"), 3); // "he" + "l" + "p"
+    }
+
+    #[test]
+    fn test_count_message_includes_tool_calls_and_images() {
+        let h = HeuristicCounter;
+        let call = crate::llm::ToolCall {
+            id: "1".into(),
+            call_type: "function".into(),
+            function: crate::llm::FunctionCall {
+                name: "exec".into(),
+                arguments: r#"{"command":"ls -la /tmp"}"#.into(),
+            },
+        };
+        let msg = Message::assistant_tool_calls(vec![call], None);
+        assert_eq!(h.count_message(&msg), MESSAGE_OVERHEAD_TOKENS + 1 + 6);
+
+        let photo = Message::user_with_images("what's this?", vec!["data:image/jpeg;base64,/9j/".repeat(1000)]);
+        assert_eq!(h.count_message(&photo), MESSAGE_OVERHEAD_TOKENS + 3 + IMAGE_TOKENS);
+    }
+
+    #[test]
+    fn test_encoding_for_model() {
+        assert_eq!(Encoding::for_model("openai/gpt-4o-mini"), Encoding::O200k);
+        assert_eq!(Encoding::for_model("o3-mini"), Encoding::O200k);
+        assert_eq!(Encoding::for_model("gpt-4-turbo"), Encoding::Cl100k);
+        assert_eq!(Encoding::for_model("anthropic/claude-sonnet-4-5"), Encoding::Cl100k);
+        assert_eq!(Encoding::for_model("moonshot/kimi-k2.5"), Encoding::Cl100k);
+    }
+}
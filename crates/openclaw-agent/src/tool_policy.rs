@@ -0,0 +1,247 @@
+//! Declarative limits on which tools a turn may use and how often.
+//!
+//! A `ToolPolicy` lives on the `ToolRegistry`: tools it doesn't allow are left
+//! out of `definitions()`, and `execute()` refuses denied or over-cap calls
+//! with an explanatory `ToolResult::error` so the model can adapt. Patterns are
+//! globs over tool names (`*` and `?`), so `mcp_*` covers every MCP tool and
+//! plugin names work like built-ins.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Which tools are offered and how often they may be called
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ToolPolicy {
+    /// Tools to offer (empty = all), e.g. `["read", "grep", "mcp_docs_*"]`
+    pub allow: Vec<String>,
+    /// Tools never offered, even if allowed
+    pub deny: Vec<String>,
+    /// Max calls per turn, by tool pattern (all tools matching one pattern share its cap)
+    pub per_turn: HashMap<String, u32>,
+    /// Max calls per minute within a session, by tool pattern
+    pub per_minute: HashMap<String, u32>,
+}
+
+/// Calls made so far in one turn, per cap pattern (shared by the turn's
+/// `ToolContext`s and by subagents it delegates to)
+#[derive(Debug, Clone, Default)]
+pub struct TurnCallCounts(Arc<Mutex<HashMap<String, u32>>>);
+
+impl ToolPolicy {
+    pub fn is_unrestricted(&self) -> bool {
+        self.allow.is_empty() && self.deny.is_empty() && self.per_turn.is_empty() && self.per_minute.is_empty()
+    }
+
+    /// Whether the tool is offered at all
+    pub fn permits(&self, tool: &str) -> bool {
+        (self.allow.is_empty() || self.allow.iter().any(|p| glob_match(p, tool)))
+            && !self.deny.iter().any(|p| glob_match(p, tool))
+    }
+
+    /// `self` with `over` layered on top: a non-empty `allow` replaces ours,
+    /// `deny` is added to, and caps for the same pattern are replaced
+    pub fn with_override(&self, over: &ToolPolicy) -> ToolPolicy {
+        let mut policy = self.clone();
+        if !over.allow.is_empty() {
+            policy.allow = over.allow.clone();
+        }
+        policy.deny.extend(over.deny.iter().cloned());
+        policy.per_turn.extend(over.per_turn.iter().map(|(p, n)| (p.clone(), *n)));
+        policy.per_minute.extend(over.per_minute.iter().map(|(p, n)| (p.clone(), *n)));
+        policy
+    }
+
+    /// Check a call against the policy and count it if it may run.
+    /// The error explains the refusal to the model.
+    pub fn admit(&self, tool: &str, session_key: &str, turn_calls: &TurnCallCounts) -> Result<(), String> {
+        if !self.permits(tool) {
+            return Err(format!(
+                "Tool '{}' is not available here (blocked by the tool policy). Use a different tool or ask the user.",
+                tool
+            ));
+        }
+
+        let mut counts = turn_calls.0.lock().unwrap_or_else(|e| e.into_inner());
+        let turn_caps: Vec<(&String, u32)> = matching_caps(&self.per_turn, tool);
+        for (pattern, max) in &turn_caps {
+            if counts.get(*pattern).copied().unwrap_or(0) >= *max {
+                return Err(format!(
+                    "Tool '{}' is limited to {} call(s) per turn and the limit is used up. \
+                     Continue without it or finish with what you have.",
+                    tool, max
+                ));
+            }
+        }
+
+        let minute_caps = matching_caps(&self.per_minute, tool);
+        if !minute_caps.is_empty() {
+            let mut windows = minute_windows().lock().unwrap_or_else(|e| e.into_inner());
+            let now = Instant::now();
+            // Expire old calls in every window, so finished sessions don't pile up
+            windows.retain(|_, window| {
+                while window.front().is_some_and(|t| now.duration_since(*t) >= MINUTE) {
+                    window.pop_front();
+                }
+                !window.is_empty()
+            });
+            for (pattern, max) in &minute_caps {
+                let Some(window) = windows.get(&window_key(session_key, pattern)) else { continue };
+                if window.len() >= *max as usize {
+                    let wait = MINUTE.saturating_sub(now.duration_since(window[0]));
+                    return Err(format!(
+                        "Tool '{}' is limited to {} call(s) per minute; try again in {}s.",
+                        tool,
+                        max,
+                        wait.as_secs().max(1)
+                    ));
+                }
+            }
+            for (pattern, _) in &minute_caps {
+                windows.entry(window_key(session_key, pattern)).or_default().push_back(now);
+            }
+        }
+
+        for (pattern, _) in turn_caps {
+            *counts.entry(pattern.clone()).or_insert(0) += 1;
+        }
+        Ok(())
+    }
+}
+
+const MINUTE: Duration = Duration::from_secs(60);
+
+fn matching_caps<'a>(caps: &'a HashMap<String, u32>, tool: &str) -> Vec<(&'a String, u32)> {
+    caps.iter()
+        .filter(|(pattern, _)| glob_match(pattern, tool))
+        .map(|(pattern, max)| (pattern, *max))
+        .collect()
+}
+
+/// Recent call times per session and cap pattern, for `per_minute`
+fn minute_windows() -> &'static Mutex<HashMap<String, VecDeque<Instant>>> {
+    static WINDOWS: OnceLock<Mutex<HashMap<String, VecDeque<Instant>>>> = OnceLock::new();
+    WINDOWS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn window_key(session_key: &str, pattern: &str) -> String {
+    format!("{}\u{0}{}", session_key, pattern)
+}
+
+/// Match `name` against a glob where `*` is any run of characters and `?` one character
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Position of the last `*` and the name index it is currently matched up to
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, n));
+            p += 1;
+        } else if let Some((sp, sn)) = star {
+            p = sp + 1;
+            n = sn + 1;
+            star = Some((sp, sn + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(json: serde_json::Value) -> ToolPolicy {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("mcp_*", "mcp_github_search"));
+        assert!(glob_match("*", "exec"));
+        assert!(glob_match("web_*", "web_fetch"));
+        assert!(glob_match("re?d", "read"));
+        assert!(glob_match("mcp_*_query", "mcp_my_server_query"));
+        assert!(!glob_match("mcp_*", "exec"));
+        assert!(!glob_match("read", "read_file"));
+        assert!(!glob_match("web_*_x", "web_fetch"));
+    }
+
+    #[test]
+    fn test_allow_and_deny() {
+        let read_only = policy(serde_json::json!({
+            "allow": ["read", "grep", "find", "list_dir", "mcp_*"],
+            "deny": ["mcp_*_write*"]
+        }));
+        assert!(read_only.permits("read"));
+        assert!(read_only.permits("mcp_fs_read_file"));
+        assert!(!read_only.permits("mcp_fs_write_file"));
+        assert!(!read_only.permits("exec"));
+        assert!(ToolPolicy::default().permits("exec"));
+        assert!(ToolPolicy::default().is_unrestricted());
+
+        let counts = TurnCallCounts::default();
+        let err = read_only.admit("exec", "s", &counts).unwrap_err();
+        assert!(err.contains("not available"));
+    }
+
+    #[test]
+    fn test_per_turn_cap() {
+        let capped = policy(serde_json::json!({ "per_turn": { "web_*": 2 } }));
+        let counts = TurnCallCounts::default();
+        assert!(capped.admit("web_fetch", "s", &counts).is_ok());
+        assert!(capped.admit("web_search", "s", &counts).is_ok());
+        let err = capped.admit("web_fetch", "s", &counts).unwrap_err();
+        assert!(err.contains("2 call(s) per turn"));
+        assert!(capped.admit("read", "s", &counts).is_ok());
+        // A subagent gets a clone of its parent's counts and shares the cap
+        let parent = TurnCallCounts::default();
+        assert!(capped.admit("web_fetch", "s", &parent).is_ok());
+        let subagent = parent.clone();
+        assert!(capped.admit("web_fetch", "sub", &subagent).is_ok());
+        assert!(capped.admit("web_fetch", "s", &parent).is_err());
+        // A new turn starts from zero
+        assert!(capped.admit("web_fetch", "s", &TurnCallCounts::default()).is_ok());
+    }
+
+    #[test]
+    fn test_per_minute_cap_is_per_session() {
+        let capped = policy(serde_json::json!({ "per_minute": { "exec": 1 } }));
+        let session = format!("test:{}", uuid::Uuid::new_v4());
+        assert!(capped.admit("exec", &session, &TurnCallCounts::default()).is_ok());
+        let err = capped.admit("exec", &session, &TurnCallCounts::default()).unwrap_err();
+        assert!(err.contains("per minute"));
+        let other = format!("test:{}", uuid::Uuid::new_v4());
+        assert!(capped.admit("exec", &other, &TurnCallCounts::default()).is_ok());
+    }
+
+    #[test]
+    fn test_expired_minute_windows_are_pruned() {
+        let stale = window_key(&format!("test:{}", uuid::Uuid::new_v4()), "exec");
+        let long_ago = Instant::now().checked_sub(Duration::from_secs(61)).unwrap();
+        minute_windows().lock().unwrap().insert(stale.clone(), VecDeque::from([long_ago]));
+
+        let capped = policy(serde_json::json!({ "per_minute": { "exec": 5 } }));
+        let session = format!("test:{}", uuid::Uuid::new_v4());
+        assert!(capped.admit("exec", &session, &TurnCallCounts::default()).is_ok());
+        assert!(!minute_windows().lock().unwrap().contains_key(&stale));
+    }
+
+    #[test]
+    fn test_override() {
+        let base = policy(serde_json::json!({ "deny": ["exec"], "per_turn": { "web_fetch": 10 } }));
+        let over = policy(serde_json::json!({ "allow": ["read", "web_fetch"], "deny": ["write"], "per_turn": { "web_fetch": 3 } }));
+        let merged = base.with_override(&over);
+        assert_eq!(merged.allow, vec!["read", "web_fetch"]);
+        assert_eq!(merged.deny, vec!["exec", "write"]);
+        assert_eq!(merged.per_turn["web_fetch"], 3);
+    }
+}
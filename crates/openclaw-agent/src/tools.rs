@@ -2,6 +2,7 @@ pub mod browser;
 pub mod claude_code;
 pub mod cron;
 pub mod delegate;
+pub mod diff;
 pub mod exec;
 pub mod find;
 pub mod grep;
@@ -10,12 +11,14 @@ pub mod list_dir;
 pub mod mcp_bridge;
 pub mod memory;
 pub mod patch;
+pub mod plugins;
 pub mod process;
 pub mod read;
 pub mod script_plugin;
 pub mod sessions;
 pub mod tasks;
 pub mod tts;
+pub mod wasm_plugin;
 pub mod web_fetch;
 pub mod web_search;
 pub mod write;
@@ -27,7 +30,9 @@ use std::sync::Arc;
 
 use crate::llm::{FunctionDefinition, ToolDefinition};
 use crate::llm::streaming::StreamEvent;
+use crate::approval::ApprovalPolicy;
 use crate::sandbox::SandboxPolicy;
+use crate::tool_policy::{ToolPolicy, TurnCallCounts};
 
 pub use tasks::{TaskInfo, TaskQueryFn};
 /// Callback type for cancelling a task by ID. Returns true if cancelled.
@@ -41,6 +46,12 @@ pub struct DelegateRequest {
     pub agent_name: String,
     pub workspace_dir: String,
     pub chat_id: i64,
+    /// The parent turn's sandbox and approval policy, inherited by the subagent
+    pub sandbox: SandboxPolicy,
+    pub approval: ApprovalPolicy,
+    pub tool_policy: ToolPolicy,
+    /// The parent turn's call counts, so the subagent's calls use up the same per-turn caps
+    pub turn_calls: TurnCallCounts,
 }
 
 /// Sender half for dispatching delegate requests to the gateway.
@@ -53,6 +64,12 @@ pub struct ToolContext {
     pub agent_name: String,
     pub session_key: String,
     pub sandbox: SandboxPolicy,
+    /// The turn's approval policy, passed on to delegated subagents
+    pub approval: ApprovalPolicy,
+    /// The registry's tool policy, passed on to delegated subagents (set per call)
+    pub tool_policy: ToolPolicy,
+    /// Calls made so far in this turn, for the tool policy's per-turn caps
+    pub turn_calls: TurnCallCounts,
     /// Chat ID for sending background task updates (0 = unknown).
     pub chat_id: i64,
     /// If set, delegate tool dispatches async instead of blocking.
@@ -63,6 +80,114 @@ pub struct ToolContext {
     pub task_cancel_fn: Option<TaskCancelFn>,
     /// If set, tools can emit streaming events mid-execution (e.g. claude_code progress).
     pub stream_tx: Option<tokio::sync::mpsc::UnboundedSender<StreamEvent>>,
+    /// ID of the tool call being executed (set per call by `execute_parallel`)
+    pub call_id: String,
+}
+
+impl ToolContext {
+    /// Forward a chunk of live tool output to the turn's stream
+    pub fn emit_output(&self, chunk: &str) {
+        if chunk.is_empty() {
+            return;
+        }
+        if let Some(ref tx) = self.stream_tx {
+            let _ = tx.send(StreamEvent::ToolOutputDelta {
+                call_id: self.call_id.clone(),
+                chunk: chunk.to_string(),
+            });
+        }
+    }
+}
+
+/// Read a pipe to EOF, passing each chunk to `on_chunk` as it arrives
+pub(crate) async fn pump_output<R: tokio::io::AsyncRead + Unpin>(mut reader: R, mut on_chunk: impl FnMut(&[u8])) {
+    use tokio::io::AsyncReadExt;
+    let mut buf = [0u8; 4096];
+    while let Ok(n) = reader.read(&mut buf).await {
+        if n == 0 {
+            break;
+        }
+        on_chunk(&buf[..n]);
+    }
+}
+
+/// Decodes a byte stream chunk by chunk without splitting a UTF-8 sequence
+/// across chunk boundaries
+#[derive(Debug, Default)]
+pub(crate) struct Utf8Chunker {
+    pending: Vec<u8>,
+}
+
+impl Utf8Chunker {
+    /// Text complete so far; a trailing partial character waits for the next chunk
+    pub(crate) fn push(&mut self, chunk: &[u8]) -> String {
+        self.pending.extend_from_slice(chunk);
+        let keep = incomplete_utf8_tail(&self.pending);
+        let rest = self.pending.split_off(self.pending.len() - keep);
+        let text = String::from_utf8_lossy(&self.pending).into_owned();
+        self.pending = rest;
+        text
+    }
+
+    /// Whatever is left at end of stream
+    pub(crate) fn finish(self) -> String {
+        String::from_utf8_lossy(&self.pending).into_owned()
+    }
+}
+
+/// Length of a multi-byte sequence cut off at the end of `bytes` (0 if none)
+fn incomplete_utf8_tail(bytes: &[u8]) -> usize {
+    for back in 1..=bytes.len().min(3) {
+        let b = bytes[bytes.len() - back];
+        if b & 0xC0 != 0x80 {
+            let needed = match b {
+                0xC0..=0xDF => 2,
+                0xE0..=0xEF => 3,
+                0xF0..=0xF7 => 4,
+                _ => 1,
+            };
+            return if needed > back { back } else { 0 };
+        }
+    }
+    0
+}
+
+/// Like `Child::wait_with_output`, but forwards stdout/stderr to the turn's
+/// stream as `ToolOutputDelta` chunks while the command runs
+pub(crate) async fn wait_with_streamed_output(
+    mut child: tokio::process::Child,
+    ctx: &ToolContext,
+) -> std::io::Result<std::process::Output> {
+    let mut stdout = Vec::new();
+    let mut stderr = Vec::new();
+    let stdout_pipe = child.stdout.take();
+    let stderr_pipe = child.stderr.take();
+
+    let read_stdout = async {
+        if let Some(pipe) = stdout_pipe {
+            let mut text = Utf8Chunker::default();
+            pump_output(pipe, |chunk| {
+                ctx.emit_output(&text.push(chunk));
+                stdout.extend_from_slice(chunk);
+            })
+            .await;
+            ctx.emit_output(&text.finish());
+        }
+    };
+    let read_stderr = async {
+        if let Some(pipe) = stderr_pipe {
+            let mut text = Utf8Chunker::default();
+            pump_output(pipe, |chunk| {
+                ctx.emit_output(&text.push(chunk));
+                stderr.extend_from_slice(chunk);
+            })
+            .await;
+            ctx.emit_output(&text.finish());
+        }
+    };
+    let (status, _, _) = tokio::join!(child.wait(), read_stdout, read_stderr);
+
+    Ok(std::process::Output { status: status?, stdout, stderr })
 }
 
 impl std::fmt::Debug for ToolContext {
@@ -85,11 +210,15 @@ impl Default for ToolContext {
             agent_name: String::new(),
             session_key: String::new(),
             sandbox: SandboxPolicy::default(),
+            approval: ApprovalPolicy::default(),
+            tool_policy: ToolPolicy::default(),
+            turn_calls: TurnCallCounts::default(),
             chat_id: 0,
             delegate_tx: None,
             task_query_fn: None,
             task_cancel_fn: None,
             stream_tx: None,
+            call_id: String::new(),
         }
     }
 }
@@ -129,11 +258,12 @@ pub trait Tool: Send + Sync {
 /// Registry of available tools
 pub struct ToolRegistry {
     tools: Vec<Box<dyn Tool>>,
+    policy: ToolPolicy,
 }
 
 impl ToolRegistry {
     pub fn new() -> Self {
-        Self { tools: Vec::new() }
+        Self { tools: Vec::new(), policy: ToolPolicy::default() }
     }
 
     /// Create a registry with the default built-in tools
@@ -169,17 +299,28 @@ impl ToolRegistry {
             .into_iter()
             .filter(|t| t.name() != exclude_name)
             .collect();
-        Self { tools }
+        Self { tools, policy: source.policy }
+    }
+
+    /// Limit the tools offered and how often they may be called
+    pub fn with_policy(mut self, policy: ToolPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn policy(&self) -> &ToolPolicy {
+        &self.policy
     }
 
     pub fn register(&mut self, tool: Box<dyn Tool>) {
         self.tools.push(tool);
     }
 
-    /// Get tool definitions for sending to the LLM
+    /// Get tool definitions for sending to the LLM (only tools the policy allows)
     pub fn definitions(&self) -> Vec<ToolDefinition> {
         self.tools
             .iter()
+            .filter(|t| self.policy.permits(t.name()))
             .map(|t| ToolDefinition {
                 tool_type: "function".to_string(),
                 function: FunctionDefinition {
@@ -191,7 +332,8 @@ impl ToolRegistry {
             .collect()
     }
 
-    /// Execute a tool by name
+    /// Execute a tool by name. Calls the policy refuses come back as an error
+    /// result explaining why.
     pub async fn execute(
         &self,
         name: &str,
@@ -204,7 +346,11 @@ impl ToolRegistry {
             .find(|t| t.name() == name)
             .ok_or_else(|| anyhow::anyhow!("Unknown tool: {}", name))?;
 
-        tool.execute(args, ctx).await
+        if let Err(reason) = self.policy.admit(name, &ctx.session_key, &ctx.turn_calls) {
+            return Ok(ToolResult::error(reason));
+        }
+        let ctx = ToolContext { tool_policy: self.policy.clone(), ..ctx.clone() };
+        tool.execute(args, &ctx).await
     }
 
     /// Execute multiple tool calls concurrently using futures::join_all.
@@ -216,16 +362,26 @@ impl ToolRegistry {
     ) -> Vec<(String, Result<ToolResult>)> {
         let futures: Vec<_> = calls
             .iter()
-            .map(|(tool_name, args, _call_id)| {
+            .map(|(tool_name, args, call_id)| {
                 let tool = self.tools.iter().find(|t| t.name() == tool_name);
-                let ctx = ctx.clone();
+                // Admit calls in order, before any of them runs
+                let admitted = match tool {
+                    Some(_) => self.policy.admit(tool_name, &ctx.session_key, &ctx.turn_calls),
+                    None => Ok(()),
+                };
+                let ctx = ToolContext {
+                    call_id: call_id.clone(),
+                    tool_policy: self.policy.clone(),
+                    ..ctx.clone()
+                };
                 let args = args.clone();
                 let name = tool_name.clone();
 
                 async move {
-                    match tool {
-                        Some(t) => (name, t.execute(args, &ctx).await),
-                        None => (name.clone(), Err(anyhow::anyhow!("Unknown tool: {}", name))),
+                    match (tool, admitted) {
+                        (Some(_), Err(reason)) => (name, Ok(ToolResult::error(reason))),
+                        (Some(t), Ok(())) => (name, t.execute(args, &ctx).await),
+                        (None, _) => (name.clone(), Err(anyhow::anyhow!("Unknown tool: {}", name))),
                     }
                 }
             })
@@ -234,20 +390,23 @@ impl ToolRegistry {
         futures::future::join_all(futures).await
     }
 
-    /// Load and register script plugins from the workspace plugins directory.
-    /// Returns the number of plugins loaded.
+    /// Register the validated script and WASM plugins of the workspace plugins
+    /// directory (see `plugins` for caching and reload). Returns the number loaded.
     pub fn load_plugins(&mut self, workspace_dir: &std::path::Path) -> usize {
-        let plugins_dir = script_plugin::plugins_dir(workspace_dir);
-        let plugins = script_plugin::load_plugins(&plugins_dir);
-        let count = plugins.len();
-        for plugin in plugins {
-            self.register(Box::new(plugin));
+        let set = plugins::plugin_set(&script_plugin::plugins_dir(workspace_dir));
+        for plugin in set.tools() {
+            self.register(plugin);
         }
-        count
+        set.report.loaded.len()
     }
 
+    /// Names of the tools offered (those the policy allows)
     pub fn tool_names(&self) -> Vec<&str> {
-        self.tools.iter().map(|t| t.name()).collect()
+        self.tools
+            .iter()
+            .map(|t| t.name())
+            .filter(|name| self.policy.permits(name))
+            .collect()
     }
 
     /// Connect to external MCP servers and register their tools.
@@ -321,4 +480,48 @@ mod tests {
         assert!(!filtered.tool_names().contains(&"delegate"));
         assert_eq!(filtered.tool_names().len(), 17);
     }
+
+    #[tokio::test]
+    async fn test_policy_filters_and_refuses_calls() {
+        let policy: ToolPolicy = serde_json::from_value(serde_json::json!({
+            "allow": ["read", "list_dir", "web_*"],
+            "deny": ["web_search"],
+            "per_turn": { "list_dir": 1 }
+        }))
+        .unwrap();
+        let registry = ToolRegistry::with_defaults().with_policy(policy);
+        assert_eq!(registry.tool_names(), vec!["read", "list_dir", "web_fetch"]);
+        let defs = registry.definitions();
+        assert_eq!(defs.len(), 3);
+
+        let ctx = ToolContext { workspace_dir: "/tmp".to_string(), ..ToolContext::default() };
+        let denied = registry.execute("exec", serde_json::json!({"command": "true"}), &ctx).await.unwrap();
+        assert!(denied.is_error);
+        assert!(denied.output.contains("tool policy"));
+
+        let calls = vec![
+            ("list_dir".to_string(), serde_json::json!({"path": "/tmp"}), "c1".to_string()),
+            ("list_dir".to_string(), serde_json::json!({"path": "/tmp"}), "c2".to_string()),
+        ];
+        let results = registry.execute_parallel(&calls, &ctx).await;
+        let first = results[0].1.as_ref().unwrap();
+        let second = results[1].1.as_ref().unwrap();
+        assert!(!first.is_error);
+        assert!(second.is_error);
+        assert!(second.output.contains("per turn"));
+    }
+
+    #[test]
+    fn test_utf8_chunker_keeps_split_characters_whole() {
+        let bytes = "héllo → ✓".as_bytes();
+        let mut chunker = Utf8Chunker::default();
+        let mut text = String::new();
+        for chunk in bytes.chunks(1) {
+            let piece = chunker.push(chunk);
+            assert!(!piece.contains('\u{FFFD}'), "split character in {:?}", piece);
+            text.push_str(&piece);
+        }
+        text.push_str(&chunker.finish());
+        assert_eq!(text, "héllo → ✓");
+    }
 }
@@ -55,6 +55,10 @@ impl Tool for BrowserTool {
     }
 
     async fn execute(&self, args: Value, ctx: &ToolContext) -> Result<ToolResult> {
+        if !ctx.sandbox.network_allowed {
+            return Ok(ToolResult::error("Network access is disabled by sandbox policy"));
+        }
+
         let action = args
             .get("action")
             .and_then(|v| v.as_str())
@@ -68,6 +68,10 @@ impl Tool for DelegateTool {
                 agent_name: ctx.agent_name.clone(),
                 workspace_dir: ctx.workspace_dir.clone(),
                 chat_id: ctx.chat_id,
+                sandbox: ctx.sandbox.clone(),
+                approval: ctx.approval.clone(),
+                tool_policy: ctx.tool_policy.clone(),
+                turn_calls: ctx.turn_calls.clone(),
             };
             if tx.send(req).is_ok() {
                 return Ok(ToolResult::success(format!(
@@ -89,6 +93,12 @@ impl Tool for DelegateTool {
             &prompt,
             &ctx.agent_name,
             &ctx.workspace_dir,
+            crate::subagent::ParentPolicy {
+                sandbox: ctx.sandbox.clone(),
+                approval: ctx.approval.clone(),
+                tools: ctx.tool_policy.clone(),
+                turn_calls: ctx.turn_calls.clone(),
+            },
             None,
             None, // no event forwarding for blocking delegate calls
         )
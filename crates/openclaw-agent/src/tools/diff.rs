@@ -0,0 +1,657 @@
+//! Parsing and in-memory application of multi-file patches for `patch` in diff mode.
+//!
+//! Accepts standard unified diffs (`---`/`+++`/`@@`, including git's rename,
+//! new-file and deleted-file headers) and the `*** Begin Patch` apply-patch
+//! format. Hunks are located by their context, falling back from an exact
+//! match to ignoring trailing whitespace, then all surrounding whitespace,
+//! then to dropping up to two outer context lines, so small drift between
+//! the model's view of a file and the file on disk doesn't reject the edit.
+
+use anyhow::Result;
+
+/// Outer context lines a hunk may lose when matching with reduced context
+const MAX_CONTEXT_FUZZ: usize = 2;
+
+/// One file's change
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilePatch {
+    pub path: String,
+    pub change: Change,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    /// Create a file with this content
+    Add(String),
+    Delete,
+    /// Edit a file in place, optionally moving it
+    Update { move_to: Option<String>, hunks: Vec<Hunk> },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hunk {
+    /// The `@@` line, for reports
+    pub header: String,
+    /// 0-based line the hunk starts at in the original file, when the diff says
+    pub hint: Option<usize>,
+    /// Line to search for before the hunk (`@@ fn main()` in apply-patch format)
+    pub anchor: Option<String>,
+    /// The hunk ends at the end of the file
+    pub at_eof: bool,
+    pub lines: Vec<HunkLine>,
+    /// The new side has no newline at the end of the file
+    pub no_newline: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum HunkLine {
+    Context(String),
+    Remove(String),
+    Add(String),
+}
+
+/// How loosely a hunk had to be matched
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Fuzz {
+    Exact,
+    TrailingWhitespace,
+    Whitespace,
+    ReducedContext,
+}
+
+impl Fuzz {
+    fn describe(self) -> &'static str {
+        match self {
+            Fuzz::Exact => "exact",
+            Fuzz::TrailingWhitespace => "ignoring trailing whitespace",
+            Fuzz::Whitespace => "ignoring whitespace",
+            Fuzz::ReducedContext => "with reduced context",
+        }
+    }
+}
+
+/// Result of applying a file's hunks
+#[derive(Debug, Default)]
+pub struct Applied {
+    pub content: String,
+    /// Hunks that only matched loosely or away from their line number
+    pub notes: Vec<String>,
+    pub rejected: Vec<String>,
+}
+
+/// Parse a unified diff or an apply-patch block
+pub fn parse(text: &str) -> Result<Vec<FilePatch>> {
+    let text = strip_fences(text);
+    let patches = if text.lines().any(|l| l.trim_end() == "*** Begin Patch") {
+        parse_apply_patch(&text)?
+    } else {
+        parse_unified(&text)?
+    };
+    if patches.is_empty() {
+        anyhow::bail!("No file changes found — expected a unified diff (---/+++/@@) or a '*** Begin Patch' block");
+    }
+    Ok(patches)
+}
+
+/// Drop a surrounding ``` fence, which models often add
+fn strip_fences(text: &str) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let start = usize::from(lines.first().is_some_and(|l| l.trim_start().starts_with("```")));
+    let mut end = lines.len();
+    while end > start && lines[end - 1].trim().is_empty() {
+        end -= 1;
+    }
+    if end > start && start == 1 && lines[end - 1].trim() == "```" {
+        end -= 1;
+    }
+    lines[start..end].join("\n")
+}
+
+/// Git and unified path headers: drop `a/`/`b/` prefixes and trailing timestamps
+fn clean_path(raw: &str, strip_prefix: bool) -> Option<String> {
+    let path = raw.split('\t').next().unwrap_or(raw).trim();
+    if path == "/dev/null" {
+        return None;
+    }
+    let path = if strip_prefix {
+        path.strip_prefix("a/").or_else(|| path.strip_prefix("b/")).unwrap_or(path)
+    } else {
+        path
+    };
+    Some(path.to_string())
+}
+
+/// A file section of a unified diff while it is being read
+#[derive(Default)]
+struct Section {
+    old: Option<Option<String>>,
+    new: Option<Option<String>>,
+    created: bool,
+    deleted: bool,
+    hunks: Vec<Hunk>,
+    /// `---`/`+++` lines were read
+    file_header: bool,
+    /// Paths from `diff --git a/x b/y` (used when there are no ---/+++ lines)
+    git_paths: Option<(String, String)>,
+}
+
+impl Section {
+    fn is_empty(&self) -> bool {
+        self.old.is_none() && self.new.is_none() && self.git_paths.is_none() && self.hunks.is_empty()
+    }
+
+    fn finish(self, patches: &mut Vec<FilePatch>) -> Result<()> {
+        if self.is_empty() {
+            return Ok(());
+        }
+        let (git_old, git_new) = self.git_paths.map(|(o, n)| (Some(o), Some(n))).unwrap_or((None, None));
+        let old = self.old.unwrap_or(git_old);
+        let new = self.new.unwrap_or(git_new);
+
+        let patch = match (old, new) {
+            (None, None) => anyhow::bail!("Diff section has hunks but no file names"),
+            (None, Some(path)) => FilePatch { path, change: Change::Add(added_content(&self.hunks)) },
+            (Some(path), _) if self.deleted => FilePatch { path, change: Change::Delete },
+            (Some(path), None) => FilePatch { path, change: Change::Delete },
+            (Some(path), Some(_)) if self.created => FilePatch { path, change: Change::Add(added_content(&self.hunks)) },
+            (Some(old), Some(new)) => {
+                let move_to = (new != old).then_some(new);
+                if move_to.is_none() && self.hunks.is_empty() {
+                    return Ok(());
+                }
+                FilePatch { path: old, change: Change::Update { move_to, hunks: self.hunks } }
+            }
+        };
+        patches.push(patch);
+        Ok(())
+    }
+}
+
+/// Content of a new file given as hunks of added lines
+fn added_content(hunks: &[Hunk]) -> String {
+    let lines: Vec<&str> = hunks
+        .iter()
+        .flat_map(|h| h.lines.iter())
+        .filter_map(|l| match l {
+            HunkLine::Add(text) => Some(text.as_str()),
+            _ => None,
+        })
+        .collect();
+    let mut content = lines.join("\n");
+    if !lines.is_empty() && !hunks.last().is_some_and(|h| h.no_newline) {
+        content.push('\n');
+    }
+    content
+}
+
+fn is_file_header(lines: &[&str], i: usize) -> bool {
+    lines[i].starts_with("--- ") && lines.get(i + 1).is_some_and(|next| next.starts_with("+++ "))
+}
+
+fn parse_unified(text: &str) -> Result<Vec<FilePatch>> {
+    let lines: Vec<&str> = text.lines().collect();
+    let git = lines.iter().any(|l| l.starts_with("diff --git "))
+        || (lines.iter().any(|l| l.starts_with("--- a/")) && lines.iter().any(|l| l.starts_with("+++ b/")));
+    let mut patches = Vec::new();
+    let mut section = Section::default();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i];
+        if let Some(rest) = line.strip_prefix("diff --git ") {
+            std::mem::take(&mut section).finish(&mut patches)?;
+            if let Some((a, b)) = rest.split_once(" b/") {
+                section.git_paths = Some((a.trim_start_matches("a/").to_string(), b.to_string()));
+            }
+            i += 1;
+        } else if is_file_header(&lines, i) {
+            if section.file_header || !section.hunks.is_empty() {
+                std::mem::take(&mut section).finish(&mut patches)?;
+            }
+            section.file_header = true;
+            section.old = Some(clean_path(&line[4..], git));
+            section.new = Some(clean_path(&lines[i + 1][4..], git));
+            i += 2;
+        } else if let Some(from) = line.strip_prefix("rename from ") {
+            section.old = Some(Some(from.trim().to_string()));
+            i += 1;
+        } else if let Some(to) = line.strip_prefix("rename to ") {
+            section.new = Some(Some(to.trim().to_string()));
+            i += 1;
+        } else if line.starts_with("new file mode") {
+            section.created = true;
+            i += 1;
+        } else if line.starts_with("deleted file mode") {
+            section.deleted = true;
+            i += 1;
+        } else if line.starts_with("@@") {
+            let mut hunk = Hunk {
+                header: line.to_string(),
+                hint: parse_hunk_hint(line),
+                anchor: None,
+                at_eof: false,
+                lines: Vec::new(),
+                no_newline: false,
+            };
+            i += 1;
+            while i < lines.len() {
+                let body = lines[i];
+                if body.starts_with("@@") || body.starts_with("diff --git ") || is_file_header(&lines, i) {
+                    break;
+                }
+                match body.chars().next() {
+                    Some(' ') => hunk.lines.push(HunkLine::Context(body[1..].to_string())),
+                    Some('-') => hunk.lines.push(HunkLine::Remove(body[1..].to_string())),
+                    Some('+') => hunk.lines.push(HunkLine::Add(body[1..].to_string())),
+                    Some('\\') => {
+                        if matches!(hunk.lines.last(), Some(HunkLine::Add(_)) | Some(HunkLine::Context(_))) {
+                            hunk.no_newline = true;
+                        }
+                    }
+                    // Editors strip the lone space of blank context lines
+                    None => hunk.lines.push(HunkLine::Context(String::new())),
+                    Some(_) => break,
+                }
+                i += 1;
+            }
+            section.hunks.push(hunk);
+        } else {
+            // index lines, similarity, mode changes, commentary
+            i += 1;
+        }
+    }
+    section.finish(&mut patches)?;
+    Ok(patches)
+}
+
+/// Start line from `@@ -12,7 +12,8 @@`: 0-based, or the insertion point for a pure addition
+fn parse_hunk_hint(header: &str) -> Option<usize> {
+    let old = header.split_whitespace().find(|t| t.starts_with('-'))?;
+    let mut parts = old[1..].splitn(2, ',');
+    let start: usize = parts.next()?.parse().ok()?;
+    let count: usize = parts.next().map(|c| c.parse().ok()).unwrap_or(Some(1))?;
+    Some(if count == 0 { start } else { start.saturating_sub(1) })
+}
+
+fn parse_apply_patch(text: &str) -> Result<Vec<FilePatch>> {
+    let lines: Vec<&str> = text
+        .lines()
+        .skip_while(|l| l.trim_end() != "*** Begin Patch")
+        .skip(1)
+        .take_while(|l| l.trim_end() != "*** End Patch")
+        .collect();
+    let mut patches = Vec::new();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i].trim_end();
+        i += 1;
+        if let Some(path) = line.strip_prefix("*** Add File: ") {
+            let mut added = Vec::new();
+            while i < lines.len() && !lines[i].starts_with("*** ") {
+                added.push(lines[i].strip_prefix('+').unwrap_or(lines[i]));
+                i += 1;
+            }
+            let mut content = added.join("\n");
+            if !added.is_empty() {
+                content.push('\n');
+            }
+            patches.push(FilePatch { path: path.trim().to_string(), change: Change::Add(content) });
+        } else if let Some(path) = line.strip_prefix("*** Delete File: ") {
+            patches.push(FilePatch { path: path.trim().to_string(), change: Change::Delete });
+        } else if let Some(path) = line.strip_prefix("*** Update File: ") {
+            let mut move_to = None;
+            if let Some(to) = lines.get(i).and_then(|l| l.strip_prefix("*** Move to: ")) {
+                move_to = Some(to.trim().to_string());
+                i += 1;
+            }
+            let mut hunks: Vec<Hunk> = Vec::new();
+            while i < lines.len() {
+                let body = lines[i];
+                if body.trim_end() == "*** End of File" {
+                    if let Some(hunk) = hunks.last_mut() {
+                        hunk.at_eof = true;
+                    }
+                    i += 1;
+                    continue;
+                }
+                if body.starts_with("*** ") {
+                    break;
+                }
+                if let Some(anchor) = body.strip_prefix("@@") {
+                    let anchor = anchor.trim();
+                    hunks.push(Hunk {
+                        header: body.to_string(),
+                        hint: None,
+                        anchor: (!anchor.is_empty()).then(|| anchor.to_string()),
+                        at_eof: false,
+                        lines: Vec::new(),
+                        no_newline: false,
+                    });
+                    i += 1;
+                    continue;
+                }
+                if hunks.is_empty() {
+                    hunks.push(Hunk {
+                        header: "@@".to_string(),
+                        hint: None,
+                        anchor: None,
+                        at_eof: false,
+                        lines: Vec::new(),
+                        no_newline: false,
+                    });
+                }
+                let hunk = hunks.last_mut().expect("hunk pushed above");
+                let parsed = match body.chars().next() {
+                    Some('-') => HunkLine::Remove(body[1..].to_string()),
+                    Some('+') => HunkLine::Add(body[1..].to_string()),
+                    Some(' ') => HunkLine::Context(body[1..].to_string()),
+                    None => HunkLine::Context(String::new()),
+                    Some(_) => anyhow::bail!(
+                        "Unexpected line in '*** Update File: {}' (hunk lines start with ' ', '-' or '+'): {}",
+                        path.trim(),
+                        body
+                    ),
+                };
+                hunk.lines.push(parsed);
+                i += 1;
+            }
+            hunks.retain(|h| !h.lines.is_empty());
+            patches.push(FilePatch { path: path.trim().to_string(), change: Change::Update { move_to, hunks } });
+        } else if !line.trim().is_empty() {
+            anyhow::bail!("Unexpected line in patch (expected '*** Add/Delete/Update File: <path>'): {}", line);
+        }
+    }
+    Ok(patches)
+}
+
+/// Apply hunks to a file's content. All hunks are tried, so every rejection is reported.
+pub fn apply_hunks(content: &str, hunks: &[Hunk]) -> Applied {
+    let eol = if content.contains("\r\n") { "\r\n" } else { "\n" };
+    let mut trailing_newline = content.is_empty() || content.ends_with('\n');
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    let mut applied = Applied::default();
+    // Lines before this index were produced by earlier hunks and aren't searched again
+    let mut cursor = 0;
+    // How far earlier hunks moved the original line numbers
+    let mut shift: isize = 0;
+
+    for (n, hunk) in hunks.iter().enumerate() {
+        let label = format!("hunk {} ({})", n + 1, hunk.header);
+        let mut hint = hunk.hint.map(|h| (h as isize + shift).max(0) as usize);
+        let mut from = cursor;
+        if let Some(ref anchor) = hunk.anchor {
+            if let Some(p) = lines[cursor..].iter().position(|l| l.trim() == anchor.trim()) {
+                from = cursor + p;
+                // A hunk that only adds lines goes right after its anchor
+                hint = hint.or(Some(from + 1));
+            }
+        }
+
+        let Some(found) = locate(&lines, hunk, from, hint) else {
+            let expected: Vec<&str> = hunk
+                .lines
+                .iter()
+                .filter_map(|l| match l {
+                    HunkLine::Context(t) | HunkLine::Remove(t) => Some(t.as_str()),
+                    HunkLine::Add(_) => None,
+                })
+                .take(3)
+                .collect();
+            applied.rejected.push(format!(
+                "{}: lines to replace not found{} — expected:\n{}",
+                label,
+                hint.map(|h| format!(" near line {}", h + 1)).unwrap_or_default(),
+                expected.iter().map(|l| format!("    |{}", l)).collect::<Vec<_>>().join("\n")
+            ));
+            continue;
+        };
+
+        // Context lines keep the file's own text; only removed/added lines change
+        let body = &hunk.lines[found.skip_front..hunk.lines.len() - found.skip_back];
+        let mut replacement = Vec::new();
+        let mut old_idx = found.start;
+        for line in body {
+            match line {
+                HunkLine::Context(_) => {
+                    replacement.push(lines[old_idx].clone());
+                    old_idx += 1;
+                }
+                HunkLine::Remove(_) => old_idx += 1,
+                HunkLine::Add(text) => replacement.push(text.clone()),
+            }
+        }
+        let old_len = old_idx - found.start;
+        let new_len = replacement.len();
+        let end_of_file = found.start + old_len == lines.len();
+        lines.splice(found.start..found.start + old_len, replacement);
+        cursor = found.start + new_len;
+        shift += new_len as isize - old_len as isize;
+        if end_of_file && hunk.no_newline {
+            trailing_newline = false;
+        }
+
+        let mut how = Vec::new();
+        if found.fuzz != Fuzz::Exact {
+            how.push(found.fuzz.describe().to_string());
+        }
+        if let Some(h) = hint {
+            if h != found.start && hunk.lines.first().is_some_and(|l| !matches!(l, HunkLine::Add(_))) {
+                let offset = found.start as isize - h as isize;
+                how.push(format!("offset {:+} line(s)", offset));
+            }
+        }
+        if !how.is_empty() {
+            applied.notes.push(format!("{} applied at line {} {}", label, found.start + 1, how.join(", ")));
+        }
+    }
+
+    let mut out = lines.join(eol);
+    if trailing_newline && !lines.is_empty() {
+        out.push_str(eol);
+    }
+    applied.content = out;
+    applied
+}
+
+struct Found {
+    start: usize,
+    fuzz: Fuzz,
+    /// Leading and trailing hunk lines (context) left out of the match
+    skip_front: usize,
+    skip_back: usize,
+}
+
+type LineEq = fn(&str, &str) -> bool;
+
+/// Line comparisons from strictest to loosest
+const MATCH_LEVELS: [(Fuzz, LineEq); 3] = [
+    (Fuzz::Exact, |a, b| a == b),
+    (Fuzz::TrailingWhitespace, |a, b| a.trim_end() == b.trim_end()),
+    (Fuzz::Whitespace, |a, b| a.split_whitespace().eq(b.split_whitespace())),
+];
+
+/// Where a hunk's old lines are, trying progressively looser matches
+fn locate(lines: &[String], hunk: &Hunk, from: usize, hint: Option<usize>) -> Option<Found> {
+    let leading_context = hunk.lines.iter().take_while(|l| matches!(l, HunkLine::Context(_))).count();
+    let trailing_context = hunk.lines.iter().rev().take_while(|l| matches!(l, HunkLine::Context(_))).count();
+
+    for drop in 0..=MAX_CONTEXT_FUZZ {
+        let (skip_front, skip_back) = (drop.min(leading_context), drop.min(trailing_context));
+        if drop > 0 && skip_front + skip_back == 0 {
+            break;
+        }
+        let body = &hunk.lines[skip_front..hunk.lines.len() - skip_back];
+        let old: Vec<&str> = body
+            .iter()
+            .filter_map(|l| match l {
+                HunkLine::Context(t) | HunkLine::Remove(t) => Some(t.as_str()),
+                HunkLine::Add(_) => None,
+            })
+            .collect();
+        if old.is_empty() {
+            if drop > 0 {
+                break;
+            }
+            // Pure insertion: at the line the diff names, or the end of the file
+            let start = hint.unwrap_or(lines.len()).clamp(from, lines.len());
+            return Some(Found { start, fuzz: Fuzz::Exact, skip_front, skip_back });
+        }
+        for (fuzz, eq) in MATCH_LEVELS {
+            if let Some(start) = best_match(lines, &old, from, hint, hunk.at_eof, eq) {
+                let fuzz = if drop > 0 { Fuzz::ReducedContext } else { fuzz };
+                return Some(Found { start, fuzz, skip_front, skip_back });
+            }
+        }
+    }
+    None
+}
+
+/// The match closest to `hint` (or the first, or the one at the end of the file for `at_eof`)
+fn best_match(
+    lines: &[String],
+    old: &[&str],
+    from: usize,
+    hint: Option<usize>,
+    at_eof: bool,
+    eq: LineEq,
+) -> Option<usize> {
+    if old.len() > lines.len() {
+        return None;
+    }
+    let matches_at = |start: usize| old.iter().enumerate().all(|(k, o)| eq(&lines[start + k], o));
+    let last = lines.len() - old.len();
+    if at_eof {
+        return (last >= from && matches_at(last)).then_some(last);
+    }
+    let mut candidates = (from..=last).filter(|&start| matches_at(start));
+    match hint {
+        Some(h) => candidates.min_by_key(|&start| start.abs_diff(h)),
+        None => candidates.next(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_git_diff_with_rename_create_delete() {
+        let diff = "\
+diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,3 +1,3 @@
+ fn a() {}
+-fn b() {}
++fn b() { todo!() }
+ fn c() {}
+diff --git a/old.txt b/new.txt
+similarity index 100%
+rename from old.txt
+rename to new.txt
+diff --git a/NOTES.md b/NOTES.md
+new file mode 100644
+--- /dev/null
++++ b/NOTES.md
+@@ -0,0 +1,2 @@
++# Notes
++first
+diff --git a/gone.txt b/gone.txt
+deleted file mode 100644
+--- a/gone.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-bye
+";
+        let patches = parse(diff).unwrap();
+        assert_eq!(patches.len(), 4);
+        assert_eq!(patches[0].path, "src/lib.rs");
+        assert!(matches!(patches[0].change, Change::Update { move_to: None, ref hunks } if hunks.len() == 1));
+        assert_eq!(
+            patches[1].change,
+            Change::Update { move_to: Some("new.txt".to_string()), hunks: Vec::new() }
+        );
+        assert_eq!(patches[2], FilePatch { path: "NOTES.md".to_string(), change: Change::Add("# Notes\nfirst\n".to_string()) });
+        assert_eq!(patches[3], FilePatch { path: "gone.txt".to_string(), change: Change::Delete });
+    }
+
+    #[test]
+    fn test_parse_apply_patch_format() {
+        let patch = "\
+*** Begin Patch
+*** Add File: hello.txt
++Hello
+*** Update File: src/main.rs
+*** Move to: src/bin/main.rs
+@@ fn main() {
+-    println!(\"hi\");
++    println!(\"hello\");
+*** End of File
+*** Delete File: junk.txt
+*** End Patch";
+        let patches = parse(patch).unwrap();
+        assert_eq!(patches.len(), 3);
+        assert_eq!(patches[0].change, Change::Add("Hello\n".to_string()));
+        let Change::Update { ref move_to, ref hunks } = patches[1].change else { panic!("expected update") };
+        assert_eq!(move_to.as_deref(), Some("src/bin/main.rs"));
+        assert_eq!(hunks[0].anchor.as_deref(), Some("fn main() {"));
+        assert!(hunks[0].at_eof);
+        assert_eq!(patches[2].change, Change::Delete);
+    }
+
+    #[test]
+    fn test_apply_multiple_hunks_with_offset_and_whitespace_drift() {
+        let file = "one\ntwo\nthree\nfour\nfive\nsix\nseven\neight\nnine\nten\n";
+        // Line numbers are off by two and the second hunk's context has stray indentation
+        let diff = "\
+--- a/f.txt
++++ b/f.txt
+@@ -4,3 +4,3 @@
+ two
+-three
++THREE
+ four
+@@ -10,2 +10,3 @@
+   eight
+ nine
++nine and a half
+";
+        let patches = parse(diff).unwrap();
+        let Change::Update { ref hunks, .. } = patches[0].change else { panic!("expected update") };
+        let applied = apply_hunks(file, hunks);
+        assert!(applied.rejected.is_empty(), "{:?}", applied.rejected);
+        assert_eq!(applied.content, "one\ntwo\nTHREE\nfour\nfive\nsix\nseven\neight\nnine\nnine and a half\nten\n");
+        assert_eq!(applied.notes.len(), 2);
+        assert!(applied.notes[0].contains("offset -2"));
+        assert!(applied.notes[1].contains("ignoring whitespace"));
+    }
+
+    #[test]
+    fn test_apply_reports_each_rejected_hunk() {
+        let hunks = vec![
+            Hunk {
+                header: "@@ -1 +1 @@".to_string(),
+                hint: Some(0),
+                anchor: None,
+                at_eof: false,
+                lines: vec![HunkLine::Remove("missing".to_string()), HunkLine::Add("x".to_string())],
+                no_newline: false,
+            },
+            Hunk {
+                header: "@@ -2 +2 @@".to_string(),
+                hint: Some(1),
+                anchor: None,
+                at_eof: false,
+                lines: vec![HunkLine::Remove("b".to_string()), HunkLine::Add("B".to_string())],
+                no_newline: false,
+            },
+        ];
+        let applied = apply_hunks("a\r\nb\r\n", &hunks);
+        assert_eq!(applied.rejected.len(), 1);
+        assert!(applied.rejected[0].starts_with("hunk 1 (@@ -1 +1 @@)"));
+        assert!(applied.rejected[0].contains("|missing"));
+        assert_eq!(applied.content, "a\r\nB\r\n");
+    }
+}
@@ -2,7 +2,6 @@ use anyhow::Result;
 use async_trait::async_trait;
 use serde_json::Value;
 use std::process::Stdio;
-use tokio::process::Command;
 
 use super::{Tool, ToolContext, ToolResult};
 
@@ -60,15 +59,22 @@ impl Tool for ExecTool {
         // Sandbox: clamp timeout
         let timeout_secs = ctx.sandbox.clamp_timeout(timeout_secs);
 
+        let mut cmd = match crate::isolation::shell_command(&ctx.sandbox, command, &ctx.workspace_dir) {
+            Ok(cmd) => cmd,
+            Err(e) => return Ok(ToolResult::error(format!("exec unavailable: {}", e))),
+        };
+
         let result = tokio::time::timeout(
             std::time::Duration::from_secs(timeout_secs),
-            Command::new("sh")
-                .arg("-c")
-                .arg(command)
-                .current_dir(&ctx.workspace_dir)
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped())
-                .output(),
+            async {
+                let child = cmd
+                    .stdin(Stdio::null())
+                    .stdout(Stdio::piped())
+                    .stderr(Stdio::piped())
+                    .spawn()?;
+                // Output is streamed to the chat as it arrives
+                super::wait_with_streamed_output(child, ctx).await
+            },
         )
         .await;
 
@@ -183,6 +189,30 @@ mod tests {
         assert!(result.output.contains("blocked"));
     }
 
+    #[tokio::test]
+    async fn test_exec_streams_output() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let ctx = ToolContext {
+            workspace_dir: "/tmp".to_string(),
+            stream_tx: Some(tx),
+            call_id: "call-7".to_string(),
+        ..ToolContext::default()
+        };
+        let args = serde_json::json!({"command": "echo first; echo second >&2"});
+        let result = ExecTool.execute(args, &ctx).await.unwrap();
+        assert!(result.output.contains("first"));
+        drop(ctx);
+
+        let mut streamed = String::new();
+        while let Some(event) = rx.recv().await {
+            if let crate::llm::streaming::StreamEvent::ToolOutputDelta { call_id, chunk } = event {
+                assert_eq!(call_id, "call-7");
+                streamed.push_str(&chunk);
+            }
+        }
+        assert!(streamed.contains("first") && streamed.contains("second"), "{}", streamed);
+    }
+
     #[tokio::test]
     async fn test_exec_timeout() {
         let tool = ExecTool;
@@ -2,18 +2,19 @@
 //! exposes their tools as agent Tool trait objects.
 //!
 //! Uses a global persistent connection pool so MCP server processes are
-//! spawned once at startup and reused across all agent turns.
+//! spawned once at startup and reused across all agent turns. The pool can be
+//! rebuilt from new configs with `reload_mcp_pool`.
 
 use anyhow::{Context, Result};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::process::Stdio;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::process::{Child, Command};
-use tokio::sync::{Mutex, OnceCell};
+use tokio::sync::Mutex;
 use tracing::{debug, error, info, warn};
 
 use super::{Tool, ToolContext, ToolResult};
@@ -21,7 +22,7 @@ use super::{Tool, ToolContext, ToolResult};
 // ── Config ──
 
 /// Configuration for an external MCP server to connect to
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct McpServerConfig {
     pub name: String,
     pub command: String,
@@ -33,53 +34,178 @@ pub struct McpServerConfig {
 
 // ── Global persistent pool ──
 
-/// Global pool of MCP clients + their discovered tools, initialized once.
-static MCP_POOL: OnceCell<McpPool> = OnceCell::const_new();
+/// Global pool of MCP clients + their discovered tools, built on first use and
+/// replaced as a whole on reload. Registries already built keep the clients
+/// they hold, so in-flight turns are not cut off.
+static MCP_POOL: RwLock<Option<Arc<McpPool>>> = RwLock::new(None);
+
+/// Serializes pool initialization and reloads
+static POOL_INIT: Mutex<()> = Mutex::const_new(());
 
 struct McpPool {
+    servers: Vec<PooledServer>,
+}
+
+#[derive(Clone)]
+struct PooledServer {
+    config: McpServerConfig,
+    client: Arc<McpClient>,
     tools: Vec<PooledTool>,
 }
 
+#[derive(Clone)]
 struct PooledTool {
     prefixed_name: String,
     tool_name: String,
     description: String,
     input_schema: Value,
     server_name: String,
-    client: Arc<McpClient>,
+}
+
+async fn connect_server(config: &McpServerConfig) -> Result<PooledServer> {
+    let client = Arc::new(McpClient::connect(config).await?);
+    let discovered = client.list_tools().await?;
+    let tools = discovered
+        .into_iter()
+        .map(|t| {
+            let prefixed = format!("mcp_{}_{}", t.server_name.replace('-', "_"), t.name);
+            debug!("  → {}: {}", prefixed, t.description);
+            PooledTool {
+                prefixed_name: prefixed,
+                tool_name: t.name,
+                description: t.description,
+                input_schema: t.input_schema,
+                server_name: t.server_name,
+            }
+        })
+        .collect();
+    Ok(PooledServer { config: config.clone(), client, tools })
 }
 
 async fn init_pool(configs: &[McpServerConfig]) -> McpPool {
-    let mut tools = Vec::new();
+    let mut servers = Vec::new();
 
     for config in configs {
-        match McpClient::connect(config).await {
-            Ok(client) => {
-                let client = Arc::new(client);
-                match client.list_tools().await {
-                    Ok(discovered) => {
-                        for t in discovered {
-                            let prefixed = format!("mcp_{}_{}", t.server_name.replace('-', "_"), t.name);
-                            debug!("  → {}: {}", prefixed, t.description);
-                            tools.push(PooledTool {
-                                prefixed_name: prefixed,
-                                tool_name: t.name,
-                                description: t.description,
-                                input_schema: t.input_schema,
-                                server_name: t.server_name,
-                                client: Arc::clone(&client),
-                            });
-                        }
-                    }
-                    Err(e) => error!("Failed to list tools from MCP '{}': {}", config.name, e),
-                }
+        match connect_server(config).await {
+            Ok(server) => servers.push(server),
+            Err(e) => error!("Failed to start MCP '{}': {:#}", config.name, e),
+        }
+    }
+
+    let tool_count: usize = servers.iter().map(|s| s.tools.len()).sum();
+    info!("MCP pool: {} tools from {} servers (persistent)", tool_count, configs.len());
+    McpPool { servers }
+}
+
+fn current_pool() -> Option<Arc<McpPool>> {
+    MCP_POOL.read().unwrap().clone()
+}
+
+fn install_pool(pool: Arc<McpPool>, configs: &[McpServerConfig]) {
+    *MCP_CONFIGS.write().unwrap() = configs.to_vec();
+    *MCP_POOL.write().unwrap() = Some(pool);
+}
+
+/// The pool, initializing it from `configs` if nothing has yet
+async fn pool(configs: &[McpServerConfig]) -> Arc<McpPool> {
+    if let Some(pool) = current_pool() {
+        return pool;
+    }
+    let _init = POOL_INIT.lock().await;
+    if let Some(pool) = current_pool() {
+        return pool;
+    }
+    let pool = Arc::new(init_pool(configs).await);
+    install_pool(Arc::clone(&pool), configs);
+    pool
+}
+
+/// What `reload_mcp_pool` did with each server
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct McpReloadReport {
+    /// Same config and still running: kept as is
+    pub unchanged: Vec<String>,
+    /// New, changed or no longer running: (re)connected
+    pub connected: Vec<String>,
+    /// No longer configured
+    pub removed: Vec<String>,
+    /// Invalid configs and failed connections, by server name
+    pub failed: BTreeMap<String, String>,
+}
+
+impl McpReloadReport {
+    pub fn summary(&self) -> String {
+        format!(
+            "{} unchanged, {} connected, {} removed, {} failed",
+            self.unchanged.len(),
+            self.connected.len(),
+            self.removed.len(),
+            self.failed.len()
+        )
+    }
+}
+
+/// Rebuild the pool from `configs`: servers whose config changed (or whose
+/// process died) are reconnected, removed ones are dropped once no registry
+/// holds them, and the new pool replaces the old one in a single swap.
+pub async fn reload_mcp_pool(configs: &[McpServerConfig]) -> McpReloadReport {
+    let _init = POOL_INIT.lock().await;
+    let old = current_pool();
+    let mut report = McpReloadReport::default();
+    let mut servers = Vec::new();
+
+    for (i, config) in configs.iter().enumerate() {
+        if configs[..i].iter().any(|c| c.name == config.name) {
+            report.failed.insert(config.name.clone(), "duplicate server name".to_string());
+            continue;
+        }
+        if let Some(problem) = config_problem(config) {
+            report.failed.insert(config.name.clone(), problem);
+            continue;
+        }
+        let existing = old.as_ref().and_then(|pool| pool.servers.iter().find(|s| s.config.name == config.name));
+        if let Some(server) = existing {
+            if server.config == *config && server.client.is_alive().await {
+                report.unchanged.push(config.name.clone());
+                servers.push(server.clone());
+                continue;
             }
-            Err(e) => error!("Failed to connect to MCP '{}': {}", config.name, e),
         }
+        match connect_server(config).await {
+            Ok(server) => {
+                report.connected.push(config.name.clone());
+                servers.push(server);
+            }
+            Err(e) => {
+                report.failed.insert(config.name.clone(), format!("{:#}", e));
+            }
+        }
+    }
+    if let Some(old) = &old {
+        report.removed = old
+            .servers
+            .iter()
+            .filter(|s| !configs.iter().any(|c| c.name == s.config.name))
+            .map(|s| s.config.name.clone())
+            .collect();
     }
 
-    info!("MCP pool: {} tools from {} servers (persistent)", tools.len(), configs.len());
-    McpPool { tools }
+    info!("MCP pool reloaded: {}", report.summary());
+    install_pool(Arc::new(McpPool { servers }), configs);
+    report
+}
+
+/// Why a server config can't be started, if it can't
+fn config_problem(config: &McpServerConfig) -> Option<String> {
+    if config.name.trim().is_empty() {
+        return Some("missing name".to_string());
+    }
+    let found = if config.command.contains('/') {
+        std::path::Path::new(&config.command).exists()
+    } else {
+        crate::isolation::find_program(&config.command).is_some()
+    };
+    (!found).then(|| format!("command '{}' not found", config.command))
 }
 
 // ── MCP Client (stdio transport) ──
@@ -195,7 +321,7 @@ impl McpClient {
     }
 
     /// Check if the child process is still alive
-    async fn is_alive(&self) -> bool {
+    pub async fn is_alive(&self) -> bool {
         let mut inner = self.inner.lock().await;
         matches!(inner.child.try_wait(), Ok(None))
     }
@@ -301,38 +427,40 @@ impl Tool for McpTool {
 
 // ── Public API ──
 
-/// Configs stored for subagent access
-static MCP_CONFIGS: std::sync::OnceLock<Vec<McpServerConfig>> = std::sync::OnceLock::new();
+/// Configs of the current pool, for subagent access
+static MCP_CONFIGS: RwLock<Vec<McpServerConfig>> = RwLock::new(Vec::new());
 
 /// Initialize the global MCP pool (call once at startup).
-/// Subsequent calls are no-ops — the pool is initialized exactly once.
+/// Does nothing if the pool already exists; use `reload_mcp_pool` to change it.
 pub async fn init_mcp_pool(configs: &[McpServerConfig]) {
-    let _ = MCP_CONFIGS.set(configs.to_vec());
-    MCP_POOL.get_or_init(|| init_pool(configs)).await;
+    pool(configs).await;
 }
 
 /// Get the MCP pool configs (for subagents to load tools from the same pool).
-/// Returns empty slice if pool not initialized.
+/// Returns empty vec if pool not initialized.
 pub fn get_mcp_pool_configs() -> Vec<McpServerConfig> {
-    MCP_CONFIGS.get().cloned().unwrap_or_default()
+    MCP_CONFIGS.read().unwrap().clone()
 }
 
 /// Get MCP tools from the persistent pool as boxed Tool objects.
-/// Must call `init_mcp_pool` first. Returns empty vec if pool not initialized.
+/// Initializes the pool from `configs` on first use.
 pub async fn load_mcp_tools(configs: &[McpServerConfig]) -> Vec<Box<dyn Tool>> {
-    // Ensure pool is initialized (idempotent)
-    let pool = MCP_POOL.get_or_init(|| init_pool(configs)).await;
-
-    pool.tools.iter().map(|t| -> Box<dyn Tool> {
-        Box::new(McpTool {
-            prefixed_name: t.prefixed_name.clone(),
-            tool_name: t.tool_name.clone(),
-            desc: t.description.clone(),
-            schema: t.input_schema.clone(),
-            server_name: t.server_name.clone(),
-            client: Arc::clone(&t.client),
+    let pool = pool(configs).await;
+
+    pool.servers
+        .iter()
+        .flat_map(|server| server.tools.iter().map(move |t| (server, t)))
+        .map(|(server, t)| -> Box<dyn Tool> {
+            Box::new(McpTool {
+                prefixed_name: t.prefixed_name.clone(),
+                tool_name: t.tool_name.clone(),
+                desc: t.description.clone(),
+                schema: t.input_schema.clone(),
+                server_name: t.server_name.clone(),
+                client: Arc::clone(&server.client),
+            })
         })
-    }).collect()
+        .collect()
 }
 
 #[cfg(test)]
@@ -356,6 +484,21 @@ mod tests {
         assert!(c.env.is_empty());
     }
 
+    #[tokio::test]
+    async fn test_reload_reports_invalid_configs() {
+        let configs: Vec<McpServerConfig> = serde_json::from_value(json!([
+            {"name": "gone", "command": "definitely-not-an-mcp-server-xyz"},
+            {"name": "", "command": "cat"},
+        ]))
+        .unwrap();
+        let report = reload_mcp_pool(&configs).await;
+        assert_eq!(report.failed.len(), 2);
+        assert!(report.failed["gone"].contains("not found"));
+        assert!(report.connected.is_empty() && report.unchanged.is_empty());
+        assert_eq!(get_mcp_pool_configs().len(), 2);
+        assert!(load_mcp_tools(&[]).await.is_empty());
+    }
+
     #[test]
     fn test_prefixed_name() {
         assert_eq!(
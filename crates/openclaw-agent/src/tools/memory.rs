@@ -6,6 +6,7 @@ use std::path::PathBuf;
 use tracing::info;
 
 use super::{Tool, ToolContext, ToolResult};
+use crate::memory::Memory;
 
 /// Unified persistent memory tool — searches BOTH the built-in key-value store
 /// AND the MCP knowledge graph when recalling information.
@@ -15,6 +16,7 @@ use super::{Tool, ToolContext, ToolResult};
 ///
 /// On `get` or `list`, this tool automatically searches both sources and merges results.
 /// On `set`, this tool writes to the built-in store (use mcp_memory_* for knowledge graph writes).
+/// `remember`/`recall`/`forget` work on the relevance-ranked notes in `crate::memory`.
 pub struct MemoryTool;
 
 fn memory_path(workspace_dir: &str, agent_name: &str) -> PathBuf {
@@ -136,6 +138,9 @@ impl Tool for MemoryTool {
 
     fn description(&self) -> &str {
         "Unified persistent memory: searches BOTH the key-value store AND the knowledge graph. \
+         Use 'remember' to save a free-form note (a fact, preference or decision), 'recall' to find \
+         the notes and past conversations most relevant to a query (each result has an id), \
+         'forget' to delete one by id. \
          Use 'set' to store a key-value pair, 'get' to search ALL memory sources for a key/query, \
          'list' to see everything stored, 'delete' to remove a key-value entry. \
          When recalling information, this tool automatically searches the knowledge graph too — \
@@ -148,16 +153,33 @@ impl Tool for MemoryTool {
             "properties": {
                 "action": {
                     "type": "string",
-                    "enum": ["set", "get", "list", "delete"],
+                    "enum": ["remember", "recall", "forget", "set", "get", "list", "delete"],
                     "description": "The memory operation to perform"
                 },
                 "key": {
                     "type": "string",
-                    "description": "The memory key or search query (required for set/get/delete)"
+                    "description": "The memory key or search query (required for set/get/delete/recall)"
                 },
                 "value": {
                     "type": "string",
                     "description": "The value to store (required for set)"
+                },
+                "text": {
+                    "type": "string",
+                    "description": "The note to save (required for remember)"
+                },
+                "tags": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Optional tags for remember"
+                },
+                "id": {
+                    "type": "string",
+                    "description": "Memory id from recall (required for forget)"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Max results for recall (default 5)"
                 }
             },
             "required": ["action"]
@@ -173,6 +195,50 @@ impl Tool for MemoryTool {
         let path = memory_path(&ctx.workspace_dir, &ctx.agent_name);
 
         match action {
+            "remember" => {
+                let text = args.get("text").or_else(|| args.get("value")).and_then(|v| v.as_str())
+                    .filter(|t| !t.trim().is_empty())
+                    .ok_or_else(|| anyhow::anyhow!("Missing 'text' for remember"))?;
+                let tags: Vec<String> = args.get("tags").and_then(|v| v.as_array())
+                    .map(|a| a.iter().filter_map(|t| t.as_str().map(str::to_string)).collect())
+                    .unwrap_or_default();
+
+                let memory = Memory::open(&ctx.workspace_dir, &ctx.agent_name).for_session(&ctx.session_key);
+                let record = memory.remember(text, "note", tags, Some(&ctx.session_key)).await?;
+                info!("Memory remembered: id={}, agent={}", record.id, ctx.agent_name);
+                Ok(ToolResult::success(format!("Remembered as {}", record.id)))
+            }
+            "recall" => {
+                let query = args.get("key").or_else(|| args.get("text")).and_then(|v| v.as_str())
+                    .ok_or_else(|| anyhow::anyhow!("Missing 'key' (the query) for recall"))?;
+                let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(5).clamp(1, 50) as usize;
+
+                let memory = Memory::open(&ctx.workspace_dir, &ctx.agent_name).for_session(&ctx.session_key);
+                let recalled = memory.recall(query, limit).await?;
+                if recalled.is_empty() {
+                    return Ok(ToolResult::success(format!("No memories relevant to '{}'.", query)));
+                }
+                let lines: Vec<String> = recalled.iter().map(|r| {
+                    let tags = if r.record.tags.is_empty() { String::new() } else { format!(" #{}", r.record.tags.join(" #")) };
+                    format!(
+                        "[{}] ({}, {}, score {:.2}){} {}",
+                        r.record.id, r.record.source, r.record.created_at.format("%Y-%m-%d"), r.score, tags, r.record.text
+                    )
+                }).collect();
+                Ok(ToolResult::success(lines.join("\n")))
+            }
+            "forget" => {
+                let id = args.get("id").and_then(|v| v.as_str())
+                    .ok_or_else(|| anyhow::anyhow!("Missing 'id' for forget (use recall to find it)"))?;
+
+                let memory = Memory::open(&ctx.workspace_dir, &ctx.agent_name).for_session(&ctx.session_key);
+                if memory.forget(id).await? {
+                    info!("Memory forgotten: id={}, agent={}", id, ctx.agent_name);
+                    Ok(ToolResult::success(format!("Forgot memory {}", id)))
+                } else {
+                    Ok(ToolResult::success(format!("No memory with id '{}'", id)))
+                }
+            }
             "set" => {
                 let key = args.get("key").and_then(|v| v.as_str())
                     .ok_or_else(|| anyhow::anyhow!("Missing 'key' for set"))?;
@@ -256,7 +322,7 @@ impl Tool for MemoryTool {
                     Ok(ToolResult::success(format!("No memory found for key '{}'", key)))
                 }
             }
-            _ => Ok(ToolResult::error(format!("Unknown action '{}'. Use set/get/list/delete.", action))),
+            _ => Ok(ToolResult::error(format!("Unknown action '{}'. Use remember/recall/forget/set/get/list/delete.", action))),
         }
     }
 }
@@ -312,6 +378,31 @@ mod tests {
         assert!(result.output.contains("No memory found"));
     }
 
+    #[tokio::test]
+    async fn test_memory_remember_recall_forget() {
+        let dir = tempfile::tempdir().unwrap();
+        let ctx = ToolContext {
+            workspace_dir: dir.path().to_str().unwrap().to_string(),
+            agent_name: "test-recall".to_string(),
+            session_key: "s1".to_string(),
+            ..ToolContext::default()
+        };
+        let tool = MemoryTool;
+
+        let result = tool.execute(serde_json::json!({"action": "remember", "text": "Deploy window is Tuesday 14:00 UTC", "tags": ["ops"]}), &ctx).await.unwrap();
+        assert!(result.output.starts_with("Remembered as mem-"));
+        let id = result.output.trim_start_matches("Remembered as ").to_string();
+
+        let result = tool.execute(serde_json::json!({"action": "recall", "key": "when is the deploy window?"}), &ctx).await.unwrap();
+        assert!(result.output.contains(&id));
+        assert!(result.output.contains("#ops"));
+
+        let result = tool.execute(serde_json::json!({"action": "forget", "id": id}), &ctx).await.unwrap();
+        assert!(result.output.contains("Forgot"));
+        let result = tool.execute(serde_json::json!({"action": "recall", "key": "deploy window"}), &ctx).await.unwrap();
+        assert!(result.output.contains("No memories"));
+    }
+
     #[test]
     fn test_load_memory_missing_file() {
         let path = PathBuf::from("/tmp/nonexistent-memory-test-12345.json");
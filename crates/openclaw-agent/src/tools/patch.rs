@@ -1,8 +1,10 @@
 use anyhow::Result;
 use async_trait::async_trait;
 use serde_json::Value;
+use std::collections::HashMap;
 use std::path::PathBuf;
 
+use super::diff::{self, Change};
 use super::{Tool, ToolContext, ToolResult};
 
 pub struct PatchTool;
@@ -40,7 +42,9 @@ impl Tool for PatchTool {
     }
 
     fn description(&self) -> &str {
-        "Apply a surgical edit to a file by replacing an exact string match with new content. More precise than rewriting the entire file. The old_string must match exactly (including whitespace)."
+        "Apply a surgical edit to a file by replacing an exact string match with new content. More precise than rewriting the entire file. The old_string must match exactly (including whitespace). \
+         For edits in several places or files, pass 'diff' instead: a unified diff or a '*** Begin Patch' block. \
+         It can create, delete and rename files, tolerates small whitespace and line-number drift, and is applied all-or-nothing."
     }
 
     fn parameters(&self) -> Value {
@@ -58,13 +62,20 @@ impl Tool for PatchTool {
                 "new_string": {
                     "type": "string",
                     "description": "The replacement string"
+                },
+                "diff": {
+                    "type": "string",
+                    "description": "Unified diff or '*** Begin Patch' block touching any number of files and hunks (replaces path/old_string/new_string)"
                 }
-            },
-            "required": ["path", "old_string", "new_string"]
+            }
         })
     }
 
     async fn execute(&self, args: Value, ctx: &ToolContext) -> Result<ToolResult> {
+        if let Some(diff_text) = args.get("diff").and_then(|v| v.as_str()) {
+            return apply_diff(diff_text, ctx).await;
+        }
+
         let file_path = args
             .get("path")
             .and_then(|v| v.as_str())
@@ -88,6 +99,9 @@ impl Tool for PatchTool {
             Ok(p) => p,
             Err(e) => return Ok(ToolResult::error(format!("{}", e))),
         };
+        if !ctx.sandbox.can_write(&safe_path.to_string_lossy()) {
+            return Ok(ToolResult::error(format!("Write access to {} denied by sandbox policy", file_path)));
+        }
 
         let content = match tokio::fs::read_to_string(&safe_path).await {
             Ok(c) => c,
@@ -126,6 +140,203 @@ impl Tool for PatchTool {
     }
 }
 
+/// A file's state while a diff is applied: original and pending content (`None` = absent)
+struct Staged {
+    display: String,
+    original: Option<String>,
+    content: Option<String>,
+}
+
+/// Stages every file change of a diff in memory before anything is written
+struct Stage<'a> {
+    ctx: &'a ToolContext,
+    files: HashMap<PathBuf, Staged>,
+    order: Vec<PathBuf>,
+}
+
+impl Stage<'_> {
+    /// Resolve a path the diff touches, check it may be written, and load it
+    async fn resolve(&mut self, path: &str, must_exist: bool) -> Result<PathBuf, String> {
+        let resolved = if must_exist {
+            resolve_safe_write_path(&self.ctx.workspace_dir, path)
+        } else {
+            super::write::resolve_safe_write_path(&self.ctx.workspace_dir, path)
+        }
+        .map_err(|e| e.to_string())?;
+        if !self.ctx.sandbox.can_write(&resolved.to_string_lossy()) {
+            return Err(format!("Write access to {} denied by sandbox policy", path));
+        }
+        if !self.files.contains_key(&resolved) {
+            let original = match tokio::fs::read_to_string(&resolved).await {
+                Ok(c) => Some(c),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+                Err(e) => return Err(format!("Failed to read {}: {}", path, e)),
+            };
+            self.order.push(resolved.clone());
+            self.files.insert(
+                resolved.clone(),
+                Staged { display: path.to_string(), content: original.clone(), original },
+            );
+        }
+        Ok(resolved)
+    }
+
+    fn staged(&mut self, path: &PathBuf) -> &mut Staged {
+        self.files.get_mut(path).expect("resolved paths are staged")
+    }
+
+    /// Write every changed file; on failure, restore the ones already written
+    async fn commit(self) -> Result<(), String> {
+        let mut done: Vec<&PathBuf> = Vec::new();
+        for path in &self.order {
+            let file = &self.files[path];
+            if file.content == file.original {
+                continue;
+            }
+            if let Err(e) = write_staged(path, file.content.as_deref()).await {
+                for undo in done {
+                    let _ = write_staged(undo, self.files[undo].original.as_deref()).await;
+                }
+                return Err(format!("Failed to write {}: {} (changes rolled back)", file.display, e));
+            }
+            done.push(path);
+        }
+        Ok(())
+    }
+}
+
+async fn write_staged(path: &PathBuf, content: Option<&str>) -> std::io::Result<()> {
+    match content {
+        Some(content) => {
+            if let Some(parent) = path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            tokio::fs::write(path, content).await
+        }
+        None => match tokio::fs::remove_file(path).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        },
+    }
+}
+
+/// Apply a multi-file diff: every hunk must apply or nothing is written
+async fn apply_diff(diff_text: &str, ctx: &ToolContext) -> Result<ToolResult> {
+    let patches = match diff::parse(diff_text) {
+        Ok(p) => p,
+        Err(e) => return Ok(ToolResult::error(format!("Could not parse diff: {}", e))),
+    };
+
+    let mut stage = Stage { ctx, files: HashMap::new(), order: Vec::new() };
+    let mut summary = Vec::new();
+    let mut notes = Vec::new();
+    let mut rejected = Vec::new();
+    let mut hunk_count = 0;
+
+    for patch in &patches {
+        let path = patch.path.as_str();
+        match patch.change {
+            Change::Add(ref content) => {
+                let target = match stage.resolve(path, false).await {
+                    Ok(t) => t,
+                    Err(e) => {
+                        rejected.push(format!("{}: {}", path, e));
+                        continue;
+                    }
+                };
+                let file = stage.staged(&target);
+                if file.content.is_some() {
+                    rejected.push(format!("{}: cannot create, the file already exists", path));
+                    continue;
+                }
+                file.content = Some(content.clone());
+                summary.push(format!("  A {} ({} line(s))", path, content.lines().count()));
+            }
+            Change::Delete => {
+                let target = match stage.resolve(path, true).await {
+                    Ok(t) => t,
+                    Err(e) => {
+                        rejected.push(format!("{}: {}", path, e));
+                        continue;
+                    }
+                };
+                let file = stage.staged(&target);
+                if file.content.take().is_none() {
+                    rejected.push(format!("{}: cannot delete, the file does not exist", path));
+                    continue;
+                }
+                summary.push(format!("  D {}", path));
+            }
+            Change::Update { ref move_to, ref hunks } => {
+                let source = match stage.resolve(path, true).await {
+                    Ok(s) => s,
+                    Err(e) => {
+                        rejected.push(format!("{}: {}", path, e));
+                        continue;
+                    }
+                };
+                let Some(current) = stage.staged(&source).content.clone() else {
+                    rejected.push(format!("{}: cannot update, the file was deleted earlier in this diff", path));
+                    continue;
+                };
+                hunk_count += hunks.len();
+                let applied = diff::apply_hunks(&current, hunks);
+                notes.extend(applied.notes.iter().map(|n| format!("{} {}", path, n)));
+                if !applied.rejected.is_empty() {
+                    rejected.extend(applied.rejected.iter().map(|r| format!("{} {}", path, r)));
+                    continue;
+                }
+
+                match move_to {
+                    Some(dest) => {
+                        let target = match stage.resolve(dest, false).await {
+                            Ok(t) => t,
+                            Err(e) => {
+                                rejected.push(format!("{}: {}", dest, e));
+                                continue;
+                            }
+                        };
+                        if target != source && stage.staged(&target).content.is_some() {
+                            rejected.push(format!("{} -> {}: the destination already exists", path, dest));
+                            continue;
+                        }
+                        stage.staged(&source).content = None;
+                        stage.staged(&target).content = Some(applied.content);
+                        summary.push(format!("  R {} -> {} ({} hunk(s))", path, dest, hunks.len()));
+                    }
+                    None => {
+                        stage.staged(&source).content = Some(applied.content);
+                        summary.push(format!("  M {} ({} hunk(s))", path, hunks.len()));
+                    }
+                }
+            }
+        }
+    }
+
+    if !rejected.is_empty() {
+        return Ok(ToolResult::error(format!(
+            "Diff not applied — nothing was changed. {} problem(s):\n{}\n\
+             Re-read the affected files and send a corrected diff for all changes.",
+            rejected.len(),
+            rejected.join("\n")
+        )));
+    }
+    if let Err(e) = stage.commit().await {
+        return Ok(ToolResult::error(e));
+    }
+
+    let mut output = format!(
+        "Applied diff: {} file change(s), {} hunk(s)\n{}",
+        summary.len(),
+        hunk_count,
+        summary.join("\n")
+    );
+    if !notes.is_empty() {
+        output.push_str(&format!("\nFuzzy matches (check the result):\n  {}", notes.join("\n  ")));
+    }
+    Ok(ToolResult::success(output))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -190,6 +401,93 @@ mod tests {
         tokio::fs::remove_file("/tmp/openclaw-test-patch2.txt").await.ok();
     }
 
+    #[tokio::test]
+    async fn test_patch_diff_multi_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let ws = dir.path();
+        std::fs::write(ws.join("a.txt"), "alpha\nbeta\ngamma\ndelta\nepsilon\n").unwrap();
+        std::fs::write(ws.join("old.txt"), "keep\nchange me\n").unwrap();
+        std::fs::write(ws.join("junk.txt"), "bye\n").unwrap();
+        let ctx = ToolContext {
+            workspace_dir: ws.to_str().unwrap().to_string(),
+            ..ToolContext::default()
+        };
+
+        let diff = "\
+--- a/a.txt
++++ b/a.txt
+@@ -1,3 +1,3 @@
+ alpha
+-beta
++BETA
+ gamma
+@@ -4,2 +4,2 @@
+ delta
+-epsilon
++EPSILON
+--- a/old.txt
++++ b/new/renamed.txt
+@@ -1,2 +1,2 @@
+ keep
+-change me
++changed
+--- /dev/null
++++ b/created.txt
+@@ -0,0 +1 @@
++fresh
+--- a/junk.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-bye
+";
+        let result = PatchTool.execute(serde_json::json!({ "diff": diff }), &ctx).await.unwrap();
+        assert!(!result.is_error, "{}", result.output);
+        assert!(result.output.contains("4 file change(s), 3 hunk(s)"));
+        assert_eq!(std::fs::read_to_string(ws.join("a.txt")).unwrap(), "alpha\nBETA\ngamma\ndelta\nEPSILON\n");
+        assert!(!ws.join("old.txt").exists());
+        assert_eq!(std::fs::read_to_string(ws.join("new/renamed.txt")).unwrap(), "keep\nchanged\n");
+        assert_eq!(std::fs::read_to_string(ws.join("created.txt")).unwrap(), "fresh\n");
+        assert!(!ws.join("junk.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn test_patch_diff_is_all_or_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let ws = dir.path();
+        std::fs::write(ws.join("one.txt"), "one\n").unwrap();
+        std::fs::write(ws.join("two.txt"), "two\n").unwrap();
+        let ctx = ToolContext {
+            workspace_dir: ws.to_str().unwrap().to_string(),
+            ..ToolContext::default()
+        };
+
+        let patch = "\
+*** Begin Patch
+*** Update File: one.txt
+@@
+-one
++ONE
+*** Update File: two.txt
+@@
+-three
++THREE
+*** End Patch";
+        let result = PatchTool.execute(serde_json::json!({ "diff": patch }), &ctx).await.unwrap();
+        assert!(result.is_error);
+        assert!(result.output.contains("nothing was changed"));
+        assert!(result.output.contains("two.txt hunk 1"));
+        assert_eq!(std::fs::read_to_string(ws.join("one.txt")).unwrap(), "one\n");
+
+        let sandboxed = ToolContext {
+            sandbox: crate::sandbox::SandboxPolicy { write_allow: vec!["/nonexistent".to_string()], ..Default::default() },
+            ..ctx
+        };
+        let patch = patch.replace("-three\n+THREE", "-two\n+TWO");
+        let result = PatchTool.execute(serde_json::json!({ "diff": patch }), &sandboxed).await.unwrap();
+        assert!(result.is_error);
+        assert!(result.output.contains("denied by sandbox policy"));
+    }
+
     #[tokio::test]
     async fn test_patch_ambiguous() {
         let tool = PatchTool;
@@ -0,0 +1,361 @@
+//! Validated, hot-reloadable plugin sets.
+//!
+//! Every registry built for a plugins directory shares that directory's
+//! `PluginSet`. A reload validates all manifests first and then swaps the new
+//! set in as a whole, so turns that are already running keep the tools they
+//! started with. `spawn_watcher` polls the directories in use and reloads the
+//! ones whose files changed.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::{Duration, SystemTime};
+use tracing::{info, warn};
+
+use super::script_plugin::{self, PluginManifest, PluginRuntime};
+use super::{Tool, ToolContext, ToolRegistry, ToolResult};
+
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+const MAX_NAME_LEN: usize = 64;
+/// Tool names with this prefix belong to MCP servers
+const MCP_PREFIX: &str = "mcp_";
+/// JSON Schema type names accepted in `parameters`
+const SCHEMA_TYPES: &[&str] = &["object", "string", "number", "integer", "boolean", "array", "null"];
+/// Shell builtins a plugin command may start with; they never resolve on PATH
+const SHELL_BUILTINS: &[&str] = &[".", ":", "[", "cd", "echo", "eval", "exec", "exit", "export", "printf", "read", "set", "test", "true", "false"];
+
+/// A manifest that failed validation and was left out of the set
+#[derive(Debug, Clone, Serialize)]
+pub struct RejectedPlugin {
+    pub file: String,
+    pub problems: Vec<String>,
+}
+
+/// Outcome of loading one plugins directory
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PluginReport {
+    pub dir: String,
+    /// Tool names of the plugins that passed validation
+    pub loaded: Vec<String>,
+    pub rejected: Vec<RejectedPlugin>,
+}
+
+impl PluginReport {
+    pub fn summary(&self) -> String {
+        format!("{} plugin(s) loaded, {} rejected", self.loaded.len(), self.rejected.len())
+    }
+}
+
+/// The validated plugins of one directory
+pub struct PluginSet {
+    tools: Vec<Arc<dyn Tool>>,
+    pub report: PluginReport,
+    fingerprint: Fingerprint,
+}
+
+impl PluginSet {
+    /// The plugin tools, ready to register in a per-turn registry
+    pub fn tools(&self) -> impl Iterator<Item = Box<dyn Tool>> + '_ {
+        self.tools.iter().map(|tool| Box::new(SharedTool(Arc::clone(tool))) as Box<dyn Tool>)
+    }
+}
+
+/// Name, modification time and size of every file in a plugins directory
+type Fingerprint = Vec<(String, Option<SystemTime>, u64)>;
+
+fn sets() -> &'static RwLock<HashMap<PathBuf, Arc<PluginSet>>> {
+    static SETS: OnceLock<RwLock<HashMap<PathBuf, Arc<PluginSet>>>> = OnceLock::new();
+    SETS.get_or_init(Default::default)
+}
+
+/// The current plugin set of a directory, loading it on first use
+pub fn plugin_set(plugins_dir: &Path) -> Arc<PluginSet> {
+    if let Some(set) = sets().read().unwrap().get(plugins_dir) {
+        return Arc::clone(set);
+    }
+    reload(plugins_dir)
+}
+
+/// Re-read and validate a plugins directory, then swap the new set in
+pub fn reload(plugins_dir: &Path) -> Arc<PluginSet> {
+    let set = Arc::new(load(plugins_dir));
+    let report = &set.report;
+    if !report.loaded.is_empty() || !report.rejected.is_empty() {
+        info!("Plugins in {}: {}", report.dir, report.summary());
+    }
+    for rejected in &report.rejected {
+        warn!("Rejected plugin {}: {}", rejected.file, rejected.problems.join("; "));
+    }
+    sets().write().unwrap().insert(plugins_dir.to_path_buf(), Arc::clone(&set));
+    set
+}
+
+/// Poll the plugin directories in use and reload those whose files changed.
+/// Meant to be started once by a long-running process such as the gateway.
+pub fn spawn_watcher() -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async {
+        let mut interval = tokio::time::interval(WATCH_INTERVAL);
+        loop {
+            interval.tick().await;
+            let _ = tokio::task::spawn_blocking(reload_changed).await;
+        }
+    })
+}
+
+fn reload_changed() {
+    let stale: Vec<PathBuf> = sets()
+        .read()
+        .unwrap()
+        .iter()
+        .filter(|(dir, set)| fingerprint(dir) != set.fingerprint)
+        .map(|(dir, _)| dir.clone())
+        .collect();
+    for dir in stale {
+        info!("Plugins changed in {}, reloading", dir.display());
+        reload(&dir);
+    }
+}
+
+fn fingerprint(plugins_dir: &Path) -> Fingerprint {
+    let mut files: Fingerprint = std::fs::read_dir(plugins_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let meta = entry.metadata().ok()?;
+            Some((entry.file_name().to_string_lossy().to_string(), meta.modified().ok(), meta.len()))
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+fn load(plugins_dir: &Path) -> PluginSet {
+    let fingerprint = fingerprint(plugins_dir);
+    let reserved = builtin_tool_names();
+    let mut report = PluginReport { dir: plugins_dir.display().to_string(), ..Default::default() };
+    let mut tools: Vec<Arc<dyn Tool>> = Vec::new();
+
+    for (path, manifest) in script_plugin::read_manifests(plugins_dir) {
+        let file = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let mut problems = Vec::new();
+        match manifest {
+            Err(e) => problems.push(e),
+            Ok(manifest) => {
+                problems = validate_manifest(&manifest, plugins_dir, reserved);
+                if report.loaded.contains(&manifest.name) {
+                    problems.push(format!("name '{}' is already used by another plugin", manifest.name));
+                }
+                if problems.is_empty() {
+                    match script_plugin::plugin_tool(manifest, plugins_dir) {
+                        Ok(tool) => {
+                            report.loaded.push(tool.name().to_string());
+                            tools.push(Arc::from(tool));
+                        }
+                        Err(e) => problems.push(e.to_string()),
+                    }
+                }
+            }
+        }
+        if !problems.is_empty() {
+            report.rejected.push(RejectedPlugin { file, problems });
+        }
+    }
+
+    PluginSet { tools, report, fingerprint }
+}
+
+fn builtin_tool_names() -> &'static [String] {
+    static NAMES: OnceLock<Vec<String>> = OnceLock::new();
+    NAMES.get_or_init(|| ToolRegistry::with_defaults().tools.iter().map(|t| t.name().to_string()).collect())
+}
+
+/// Everything wrong with a manifest; empty when it can be loaded
+pub fn validate_manifest(manifest: &PluginManifest, plugins_dir: &Path, reserved: &[String]) -> Vec<String> {
+    let mut problems = Vec::new();
+    let name = &manifest.name;
+    if name.is_empty()
+        || name.len() > MAX_NAME_LEN
+        || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        problems.push(format!(
+            "name '{}' must be 1-{} letters, digits, '_' or '-'",
+            name, MAX_NAME_LEN
+        ));
+    }
+    if reserved.contains(name) {
+        problems.push(format!("name '{}' collides with a built-in tool", name));
+    }
+    if name.starts_with(MCP_PREFIX) {
+        problems.push(format!("names starting with '{}' are reserved for MCP tools", MCP_PREFIX));
+    }
+
+    if !manifest.parameters.is_null() {
+        if manifest.parameters.get("type").and_then(Value::as_str) != Some("object") {
+            problems.push("parameters must be an object schema (\"type\": \"object\")".to_string());
+        }
+        schema_problems(&manifest.parameters, "parameters", &mut problems);
+    }
+
+    match manifest.runtime {
+        PluginRuntime::Shell => {
+            // Commands run in the workspace, two levels above .openclaw/plugins
+            let workspace = plugins_dir.parent().and_then(Path::parent).unwrap_or(plugins_dir);
+            if let Some(problem) = command_problem(&manifest.command, workspace) {
+                problems.push(problem);
+            }
+        }
+        PluginRuntime::Wasm => match manifest.module.as_deref().filter(|m| !m.is_empty()) {
+            None => problems.push("missing module".to_string()),
+            Some(module) => {
+                if let Err(e) = super::wasm_plugin::compiled_module(&plugins_dir.join(module)) {
+                    problems.push(format!("module '{}': {:#}", module, e));
+                }
+            }
+        },
+    }
+    problems
+}
+
+/// Why a shell command can't run, judged by its program (the first word
+/// after any `VAR=value` assignments)
+pub fn command_problem(command: &str, workspace_dir: &Path) -> Option<String> {
+    let program = command.split_whitespace().find(|word| !word.contains('='));
+    let Some(program) = program else {
+        return Some("missing command".to_string());
+    };
+    let found = if program.contains('/') {
+        workspace_dir.join(program).exists()
+    } else {
+        SHELL_BUILTINS.contains(&program) || crate::isolation::find_program(program).is_some()
+    };
+    (!found).then(|| format!("command '{}' not found", program))
+}
+
+/// Basic structural checks of a JSON Schema: types, properties, items, required
+fn schema_problems(schema: &Value, path: &str, out: &mut Vec<String>) {
+    let Some(obj) = schema.as_object() else {
+        out.push(format!("{} must be a JSON object", path));
+        return;
+    };
+    let known_type = |t: &Value| t.as_str().is_some_and(|t| SCHEMA_TYPES.contains(&t));
+    match obj.get("type") {
+        None => {}
+        Some(Value::Array(types)) if types.iter().all(known_type) => {}
+        Some(t) if known_type(t) => {}
+        Some(t) => out.push(format!("{}.type: unknown type {}", path, t)),
+    }
+    match obj.get("properties") {
+        None => {}
+        Some(Value::Object(props)) => {
+            for (name, prop) in props {
+                schema_problems(prop, &format!("{}.properties.{}", path, name), out);
+            }
+        }
+        Some(_) => out.push(format!("{}.properties must be an object", path)),
+    }
+    if let Some(items) = obj.get("items") {
+        schema_problems(items, &format!("{}.items", path), out);
+    }
+    match obj.get("required") {
+        None => {}
+        Some(Value::Array(names)) => {
+            for name in names {
+                match name.as_str() {
+                    Some(n) if obj.get("properties").and_then(|p| p.get(n)).is_some() => {}
+                    Some(n) => out.push(format!("{}.required: '{}' is not a property", path, n)),
+                    None => out.push(format!("{}.required must list property names", path)),
+                }
+            }
+        }
+        Some(_) => out.push(format!("{}.required must be an array", path)),
+    }
+    if obj.get("enum").is_some_and(|e| !e.is_array()) {
+        out.push(format!("{}.enum must be an array", path));
+    }
+}
+
+/// A plugin tool shared between the set and the registries built from it
+struct SharedTool(Arc<dyn Tool>);
+
+#[async_trait]
+impl Tool for SharedTool {
+    fn name(&self) -> &str {
+        self.0.name()
+    }
+
+    fn description(&self) -> &str {
+        self.0.description()
+    }
+
+    fn parameters(&self) -> Value {
+        self.0.parameters()
+    }
+
+    async fn execute(&self, args: Value, ctx: &ToolContext) -> Result<ToolResult> {
+        self.0.execute(args, ctx).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn manifest(value: Value) -> PluginManifest {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_validate_manifest() {
+        let dir = Path::new("/tmp");
+        let reserved = vec!["exec".to_string()];
+        let ok = manifest(json!({
+            "name": "greet", "description": "Say hi", "command": "echo hi",
+            "parameters": {"type": "object", "properties": {"who": {"type": "string"}}, "required": ["who"]}
+        }));
+        assert!(validate_manifest(&ok, dir, &reserved).is_empty());
+
+        let bad = manifest(json!({
+            "name": "exec", "description": "Shadow", "command": "definitely-not-a-command-xyz --flag",
+            "parameters": {"type": "object", "properties": {"n": {"type": "int"}}, "required": ["missing"]}
+        }));
+        let problems = validate_manifest(&bad, dir, &reserved).join("\n");
+        assert!(problems.contains("collides with a built-in tool"));
+        assert!(problems.contains("command 'definitely-not-a-command-xyz' not found"));
+        assert!(problems.contains("parameters.properties.n.type: unknown type \"int\""));
+        assert!(problems.contains("'missing' is not a property"));
+
+        let wasm = manifest(json!({"name": "mcp_x", "description": "", "runtime": "wasm", "module": "nope.wasm"}));
+        let problems = validate_manifest(&wasm, dir, &reserved).join("\n");
+        assert!(problems.contains("reserved for MCP tools"));
+        assert!(problems.contains("module 'nope.wasm'"));
+    }
+
+    #[test]
+    fn test_reload_swaps_set() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |file: &str, body: Value| std::fs::write(dir.path().join(file), body.to_string()).unwrap();
+        write("greet.json", json!({"name": "greet", "description": "Say hi", "command": "echo hi"}));
+        write("read.json", json!({"name": "read", "description": "Shadows a built-in", "command": "cat"}));
+        std::fs::write(dir.path().join("broken.json"), "{").unwrap();
+
+        let first = plugin_set(dir.path());
+        assert_eq!(first.report.loaded, vec!["greet"]);
+        let rejected: Vec<&str> = first.report.rejected.iter().map(|r| r.file.as_str()).collect();
+        assert_eq!(rejected, vec!["broken.json", "read.json"]);
+        assert!(Arc::ptr_eq(&first, &plugin_set(dir.path())));
+
+        write("second.json", json!({"name": "second", "description": "Another", "command": "true"}));
+        assert_ne!(fingerprint(dir.path()), first.fingerprint);
+        reload_changed();
+        let second = plugin_set(dir.path());
+        assert_eq!(second.report.loaded, vec!["greet", "second"]);
+        // The old set stays usable for whoever still holds it
+        assert_eq!(first.tools().count(), 1);
+    }
+}
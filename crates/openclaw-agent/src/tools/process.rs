@@ -4,19 +4,54 @@ use serde_json::Value;
 use std::collections::HashMap;
 use std::process::Stdio;
 use std::sync::{Mutex, OnceLock};
-use tokio::process::Command;
 
-use super::{Tool, ToolContext, ToolResult};
+use super::{Tool, ToolContext, ToolResult, Utf8Chunker};
+use crate::llm::streaming::StreamEvent;
 
 const MAX_OUTPUT_BYTES: usize = 64 * 1024;
 
+/// The last `MAX_OUTPUT_BYTES` a pipe produced
+#[derive(Default)]
+struct PipeTail {
+    bytes: Vec<u8>,
+    /// Earlier bytes dropped to stay within the cap
+    dropped: usize,
+}
+
+impl PipeTail {
+    fn push(&mut self, chunk: &[u8]) {
+        self.bytes.extend_from_slice(chunk);
+        if self.bytes.len() > MAX_OUTPUT_BYTES {
+            let excess = self.bytes.len() - MAX_OUTPUT_BYTES;
+            self.bytes.drain(..excess);
+            self.dropped += excess;
+        }
+    }
+
+    fn text(&self) -> String {
+        if self.dropped == 0 {
+            return String::from_utf8_lossy(&self.bytes).into_owned();
+        }
+        // Skip a character cut in half by the drop
+        let start = self.bytes.iter().take(3).take_while(|b| *b & 0xC0 == 0x80).count();
+        format!(
+            "... ({} earlier bytes dropped)\n{}",
+            self.dropped + start,
+            String::from_utf8_lossy(&self.bytes[start..])
+        )
+    }
+}
+
 /// A running or finished background process
 struct ProcessEntry {
     label: String,
     command: String,
     child: Option<tokio::process::Child>,
-    stdout_buf: Vec<u8>,
-    stderr_buf: Vec<u8>,
+    /// Filled live by the reader tasks
+    stdout_buf: PipeTail,
+    stderr_buf: PipeTail,
+    /// Tasks draining stdout/stderr into the buffers
+    readers: Vec<tokio::task::JoinHandle<()>>,
     exit_code: Option<i32>,
     started_at: std::time::Instant,
 }
@@ -26,6 +61,37 @@ fn processes() -> &'static Mutex<HashMap<String, ProcessEntry>> {
     PROCESSES.get_or_init(|| Mutex::new(HashMap::new()))
 }
 
+/// Drain one of a background process's pipes into its buffer, forwarding
+/// chunks to the turn that started it for as long as that turn is streaming
+fn spawn_reader<R>(id: String, pipe: R, is_stderr: bool, ctx: &ToolContext) -> tokio::task::JoinHandle<()>
+where
+    R: tokio::io::AsyncRead + Unpin + Send + 'static,
+{
+    // Weak, so a long-lived process doesn't keep the turn's stream open
+    let stream_tx = ctx.stream_tx.as_ref().map(|tx| tx.downgrade());
+    let call_id = ctx.call_id.clone();
+    tokio::spawn(async move {
+        let mut text = Utf8Chunker::default();
+        let forward = |chunk: String| {
+            if chunk.is_empty() {
+                return;
+            }
+            if let Some(tx) = stream_tx.as_ref().and_then(|tx| tx.upgrade()) {
+                let _ = tx.send(StreamEvent::ToolOutputDelta { call_id: call_id.clone(), chunk });
+            }
+        };
+        super::pump_output(pipe, |chunk| {
+            if let Some(entry) = processes().lock().unwrap().get_mut(&id) {
+                let buf = if is_stderr { &mut entry.stderr_buf } else { &mut entry.stdout_buf };
+                buf.push(chunk);
+            }
+            forward(text.push(chunk));
+        })
+        .await;
+        forward(text.finish());
+    })
+}
+
 fn next_id() -> String {
     use std::sync::atomic::{AtomicU64, Ordering};
     static COUNTER: AtomicU64 = AtomicU64::new(1);
@@ -110,23 +176,28 @@ impl ProcessTool {
             .unwrap_or(command)
             .to_string();
 
-        let child = Command::new("sh")
-            .arg("-c")
-            .arg(command)
-            .current_dir(&ctx.workspace_dir)
+        let mut cmd = match crate::isolation::shell_command(&ctx.sandbox, command, &ctx.workspace_dir) {
+            Ok(cmd) => cmd,
+            Err(e) => return Ok(ToolResult::error(format!("process start unavailable: {}", e))),
+        };
+        let child = cmd
+            .stdin(Stdio::null())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .spawn();
 
         match child {
-            Ok(child) => {
+            Ok(mut child) => {
                 let id = next_id();
+                let stdout = child.stdout.take();
+                let stderr = child.stderr.take();
                 let entry = ProcessEntry {
                     label: label.clone(),
                     command: command.to_string(),
                     child: Some(child),
-                    stdout_buf: Vec::new(),
-                    stderr_buf: Vec::new(),
+                    stdout_buf: PipeTail::default(),
+                    stderr_buf: PipeTail::default(),
+                    readers: Vec::new(),
                     exit_code: None,
                     started_at: std::time::Instant::now(),
                 };
@@ -136,6 +207,17 @@ impl ProcessTool {
                     procs.insert(id.clone(), entry);
                 }
 
+                let mut readers = Vec::new();
+                if let Some(pipe) = stdout {
+                    readers.push(spawn_reader(id.clone(), pipe, false, ctx));
+                }
+                if let Some(pipe) = stderr {
+                    readers.push(spawn_reader(id.clone(), pipe, true, ctx));
+                }
+                if let Some(entry) = processes().lock().unwrap().get_mut(&id) {
+                    entry.readers = readers;
+                }
+
                 Ok(ToolResult::success(format!(
                     "Started background process: id={}, label={}",
                     id, label
@@ -168,8 +250,8 @@ impl ProcessTool {
 
             if entry.exit_code.is_some() && entry.child.is_none() {
                 PollState::AlreadyDone {
-                    stdout: String::from_utf8_lossy(&entry.stdout_buf).to_string(),
-                    stderr: String::from_utf8_lossy(&entry.stderr_buf).to_string(),
+                    stdout: entry.stdout_buf.text(),
+                    stderr: entry.stderr_buf.text(),
                     label: entry.label.clone(),
                     exit_code: entry.exit_code,
                     elapsed: entry.started_at.elapsed(),
@@ -211,32 +293,26 @@ impl ProcessTool {
             PollState::JustFinished => {}
         }
 
-        // Process just finished — take child out of lock, read output, store back
-        let mut child_opt = {
+        // Process just finished — drop the child and let the readers drain what's left
+        // (bounded, in case a grandchild still holds the pipes open)
+        let readers = {
             let mut procs = processes().lock().unwrap();
-            procs.get_mut(&id).and_then(|e| e.child.take())
-        };
-
-        let mut stdout_buf = Vec::new();
-        let mut stderr_buf = Vec::new();
-
-        if let Some(ref mut child) = child_opt {
-            if let Some(mut reader) = child.stdout.take() {
-                use tokio::io::AsyncReadExt;
-                let _ = reader.read_to_end(&mut stdout_buf).await;
-            }
-            if let Some(mut reader) = child.stderr.take() {
-                use tokio::io::AsyncReadExt;
-                let _ = reader.read_to_end(&mut stderr_buf).await;
+            match procs.get_mut(&id) {
+                Some(entry) => {
+                    entry.child = None;
+                    std::mem::take(&mut entry.readers)
+                }
+                None => Vec::new(),
             }
+        };
+        for reader in readers {
+            let _ = tokio::time::timeout(std::time::Duration::from_secs(2), reader).await;
         }
 
-        let mut procs = processes().lock().unwrap();
-        if let Some(entry) = procs.get_mut(&id) {
-            entry.stdout_buf = stdout_buf;
-            entry.stderr_buf = stderr_buf;
-            let stdout = String::from_utf8_lossy(&entry.stdout_buf).to_string();
-            let stderr = String::from_utf8_lossy(&entry.stderr_buf).to_string();
+        let procs = processes().lock().unwrap();
+        if let Some(entry) = procs.get(&id) {
+            let stdout = entry.stdout_buf.text();
+            let stderr = entry.stderr_buf.text();
             Ok(ToolResult::success(format_poll_output(
                 &id,
                 &entry.label,
@@ -338,26 +414,15 @@ fn format_poll_output(
         elapsed.as_secs()
     ));
 
-    if !stdout.is_empty() {
-        let s = if stdout.len() > MAX_OUTPUT_BYTES {
-            format!("{}... (truncated)", &stdout[..MAX_OUTPUT_BYTES])
-        } else {
-            stdout.to_string()
-        };
-        out.push_str(&s);
-    }
+    // Both are already capped to their last MAX_OUTPUT_BYTES
+    out.push_str(stdout);
 
     if !stderr.is_empty() {
         if !out.ends_with('\n') {
             out.push('\n');
         }
         out.push_str("[stderr] ");
-        let s = if stderr.len() > MAX_OUTPUT_BYTES {
-            format!("{}... (truncated)", &stderr[..MAX_OUTPUT_BYTES])
-        } else {
-            stderr.to_string()
-        };
-        out.push_str(&s);
+        out.push_str(stderr);
     }
 
     out
@@ -377,6 +442,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_pipe_tail_keeps_last_bytes() {
+        let mut tail = PipeTail::default();
+        tail.push(&vec![b'a'; MAX_OUTPUT_BYTES]);
+        tail.push("é".repeat(10).as_bytes());
+        assert_eq!(tail.bytes.len(), MAX_OUTPUT_BYTES);
+        assert_eq!(tail.dropped, 20);
+        let text = tail.text();
+        assert!(text.starts_with("... (20 earlier bytes dropped)\n"));
+        assert!(text.ends_with(&"é".repeat(10)));
+
+        // A drop that cuts a character in half skips its remains
+        let mut tail = PipeTail::default();
+        tail.push("é".repeat(MAX_OUTPUT_BYTES / 2).as_bytes());
+        tail.push(b"x");
+        assert!(tail.text().starts_with("... (2 earlier bytes dropped)\né"));
+    }
+
     #[tokio::test]
     async fn test_process_start_and_poll() {
         let tool = ProcessTool;
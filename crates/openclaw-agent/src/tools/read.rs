@@ -80,6 +80,9 @@ impl Tool for ReadTool {
             Ok(p) => p,
             Err(e) => return Ok(ToolResult::error(format!("{}", e))),
         };
+        if !ctx.sandbox.can_read(&safe_path.to_string_lossy()) {
+            return Ok(ToolResult::error(format!("Read access to {} denied by sandbox policy", file_path)));
+        }
 
         let content = match tokio::fs::read_to_string(&safe_path).await {
             Ok(c) => c,
@@ -7,27 +7,53 @@ use std::process::Stdio;
 use tokio::process::Command;
 use tracing::{debug, warn};
 
+use super::wasm_plugin::{WasmCapabilities, WasmPluginTool};
 use super::{Tool, ToolContext, ToolResult};
 
 const PLUGIN_TIMEOUT_SECS: u64 = 30;
 const MAX_OUTPUT_BYTES: usize = 64 * 1024;
 
 /// A tool defined by a JSON manifest in the plugins directory.
-/// The manifest specifies a shell command that receives arguments as JSON on stdin.
-#[derive(Debug, Clone, Deserialize)]
+/// The manifest specifies a shell command or a WASM module that receives
+/// arguments as JSON on stdin.
+#[derive(Debug, Clone, Default, Deserialize)]
 pub struct PluginManifest {
     pub name: String,
     pub description: String,
     #[serde(default)]
     pub parameters: Value,
+    /// `shell` (default) or `wasm`
+    #[serde(default)]
+    pub runtime: PluginRuntime,
     /// Shell command to execute. Receives tool arguments as JSON on stdin.
     /// Working directory is set to the workspace.
+    #[serde(default)]
     pub command: String,
+    /// WASM module, relative to the plugins directory
+    #[serde(default)]
+    pub module: Option<String>,
+    /// Host capabilities granted to a WASM plugin
+    #[serde(default)]
+    pub capabilities: WasmCapabilities,
+    /// Fuel budget per WASM call
+    #[serde(default)]
+    pub fuel: Option<u64>,
+    /// Linear memory cap for a WASM call
+    #[serde(default)]
+    pub memory_mb: Option<u64>,
     /// Optional timeout override in seconds (default: 30)
     #[serde(default)]
     pub timeout_secs: Option<u64>,
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PluginRuntime {
+    #[default]
+    Shell,
+    Wasm,
+}
+
 pub struct ScriptPluginTool {
     manifest: PluginManifest,
 }
@@ -92,7 +118,7 @@ impl Tool for ScriptPluginTool {
                     drop(stdin);
                 }
 
-                child.wait_with_output().await
+                super::wait_with_streamed_output(child, ctx).await
             },
         )
         .await;
@@ -138,15 +164,13 @@ impl Tool for ScriptPluginTool {
     }
 }
 
-/// Load all plugin manifests from a directory.
-/// Each .json file in the directory is treated as a plugin manifest.
-pub fn load_plugins(plugins_dir: &Path) -> Vec<ScriptPluginTool> {
+/// Read every .json manifest in a directory, sorted by file name.
+/// Unreadable or unparseable files come back as errors.
+pub fn read_manifests(plugins_dir: &Path) -> Vec<(PathBuf, Result<PluginManifest, String>)> {
     if !plugins_dir.exists() || !plugins_dir.is_dir() {
         return Vec::new();
     }
 
-    let mut plugins = Vec::new();
-
     let entries = match std::fs::read_dir(plugins_dir) {
         Ok(e) => e,
         Err(e) => {
@@ -155,29 +179,38 @@ pub fn load_plugins(plugins_dir: &Path) -> Vec<ScriptPluginTool> {
         }
     };
 
-    for entry in entries.flatten() {
-        let path = entry.path();
-        if path.extension().and_then(|e| e.to_str()) != Some("json") {
-            continue;
-        }
-
-        match std::fs::read_to_string(&path) {
-            Ok(content) => match serde_json::from_str::<PluginManifest>(&content) {
-                Ok(manifest) => {
-                    debug!("Loaded plugin: {} from {}", manifest.name, path.display());
-                    plugins.push(ScriptPluginTool::from_manifest(manifest));
-                }
-                Err(e) => {
-                    warn!("Failed to parse plugin {}: {}", path.display(), e);
-                }
-            },
-            Err(e) => {
-                warn!("Failed to read plugin {}: {}", path.display(), e);
+    let mut paths: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|e| e.to_str()) == Some("json"))
+        .collect();
+    paths.sort();
+
+    paths
+        .into_iter()
+        .map(|path| {
+            let manifest = std::fs::read_to_string(&path)
+                .map_err(|e| format!("failed to read: {}", e))
+                .and_then(|content| {
+                    serde_json::from_str::<PluginManifest>(&content).map_err(|e| format!("invalid manifest: {}", e))
+                });
+            if let Ok(ref manifest) = manifest {
+                debug!("Read plugin manifest: {} from {}", manifest.name, path.display());
             }
+            (path, manifest)
+        })
+        .collect()
+}
+
+/// Build the tool for a manifest according to its runtime
+pub fn plugin_tool(manifest: PluginManifest, plugins_dir: &Path) -> Result<Box<dyn Tool>> {
+    match manifest.runtime {
+        PluginRuntime::Shell if manifest.command.trim().is_empty() => {
+            anyhow::bail!("plugin '{}' has no command", manifest.name)
         }
+        PluginRuntime::Shell => Ok(Box::new(ScriptPluginTool::from_manifest(manifest))),
+        PluginRuntime::Wasm => Ok(Box::new(WasmPluginTool::from_manifest(manifest, plugins_dir)?)),
     }
-
-    plugins
 }
 
 /// Resolve the plugins directory for a workspace
@@ -207,6 +240,7 @@ mod tests {
         assert_eq!(manifest.name, "greet");
         assert_eq!(manifest.command, "echo hello");
         assert!(manifest.timeout_secs.is_none());
+        assert_eq!(manifest.runtime, PluginRuntime::Shell);
     }
 
     #[test]
@@ -230,6 +264,7 @@ mod tests {
             parameters: serde_json::json!({"type": "object", "properties": {}}),
             command: "cat".to_string(), // cat will echo stdin back
             timeout_secs: Some(5),
+            ..Default::default()
         };
 
         let tool = ScriptPluginTool::from_manifest(manifest);
@@ -249,7 +284,7 @@ mod tests {
 
     #[test]
     fn test_load_plugins_empty() {
-        let plugins = load_plugins(Path::new("/nonexistent_plugins_dir"));
+        let plugins = read_manifests(Path::new("/nonexistent_plugins_dir"));
         assert!(plugins.is_empty());
     }
 }
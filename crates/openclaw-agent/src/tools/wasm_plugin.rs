@@ -0,0 +1,617 @@
+//! WebAssembly plugins: `.openclaw/plugins/*.json` manifests with `"runtime": "wasm"`.
+//!
+//! The module is a WASI preview 1 command. Like a script plugin it receives the
+//! tool arguments as JSON on stdin and its stdout is the tool result. Host access
+//! is limited to what the manifest's `capabilities` grant, and every call runs
+//! with a fuel budget, a memory cap and a wall-clock deadline.
+//!
+//! Host API, imported from the `openclaw` module:
+//! - `log(ptr, len)`: write a UTF-8 message to the gateway log
+//! - `http_fetch(ptr, len) -> len`: perform the JSON request
+//!   `{"url", "method"?, "headers"?, "body"?}` against an allowlisted host and
+//!   return the byte length of the JSON response (`{"status", "headers", "body"}`
+//!   or `{"error"}`)
+//! - `take_result(ptr)`: copy that response into guest memory at `ptr`
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime};
+use tracing::{debug, info};
+use wasmtime::{Caller, Config, Engine, Extern, Linker, Memory, Module, Store, StoreLimits, StoreLimitsBuilder, Trap};
+use wasmtime_wasi::pipe::{MemoryInputPipe, MemoryOutputPipe};
+use wasmtime_wasi::preview1::WasiP1Ctx;
+use wasmtime_wasi::{DirPerms, FilePerms, I32Exit, WasiCtxBuilder};
+
+use super::script_plugin::PluginManifest;
+use super::{Tool, ToolContext, ToolResult};
+use crate::tool_policy::glob_match;
+
+const PLUGIN_TIMEOUT_SECS: u64 = 30;
+const DEFAULT_FUEL: u64 = 1_000_000_000;
+const DEFAULT_MEMORY_MB: u64 = 64;
+const MAX_OUTPUT_BYTES: usize = 64 * 1024;
+const MAX_LOG_BYTES: u32 = 4096;
+const MAX_FETCH_BYTES: usize = 1024 * 1024;
+const FETCH_TIMEOUT_SECS: u64 = 30;
+/// How often the shared engine's epoch advances; deadlines are counted in ticks
+const EPOCH_TICK: Duration = Duration::from_millis(100);
+/// Where the workspace is mounted inside the guest
+pub const GUEST_WORKSPACE: &str = "/workspace";
+
+/// Host capabilities a WASM plugin manifest can request
+#[derive(Debug, Clone, Deserialize)]
+pub struct WasmCapabilities {
+    /// Mount the workspace read-only at `/workspace`
+    #[serde(default)]
+    pub workspace_read: bool,
+    /// Hosts `http_fetch` may reach; `*` globs allowed (e.g. `*.github.com`)
+    #[serde(default)]
+    pub http_allow: Vec<String>,
+    /// Forward `log` calls to the gateway log
+    #[serde(default = "default_true")]
+    pub log: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for WasmCapabilities {
+    fn default() -> Self {
+        Self { workspace_read: false, http_allow: Vec::new(), log: true }
+    }
+}
+
+pub struct WasmPluginTool {
+    manifest: PluginManifest,
+    module_path: PathBuf,
+}
+
+impl WasmPluginTool {
+    /// The manifest's `module` is resolved relative to the plugins directory
+    pub fn from_manifest(manifest: PluginManifest, plugins_dir: &Path) -> Result<Self> {
+        let module = manifest
+            .module
+            .as_deref()
+            .filter(|m| !m.is_empty())
+            .with_context(|| format!("WASM plugin '{}' has no module", manifest.name))?;
+        let module_path = plugins_dir.join(module);
+        Ok(Self { manifest, module_path })
+    }
+}
+
+#[async_trait]
+impl Tool for WasmPluginTool {
+    fn name(&self) -> &str {
+        &self.manifest.name
+    }
+
+    fn description(&self) -> &str {
+        &self.manifest.description
+    }
+
+    fn parameters(&self) -> Value {
+        if self.manifest.parameters.is_null() {
+            json!({"type": "object", "properties": {}})
+        } else {
+            self.manifest.parameters.clone()
+        }
+    }
+
+    async fn execute(&self, args: Value, ctx: &ToolContext) -> Result<ToolResult> {
+        let timeout = ctx.sandbox.clamp_timeout(self.manifest.timeout_secs.unwrap_or(PLUGIN_TIMEOUT_SECS));
+        let module = match compiled_module(&self.module_path) {
+            Ok(module) => module,
+            Err(e) => return Ok(ToolResult::error(format!("Failed to load WASM plugin: {:#}", e))),
+        };
+
+        let caps = &self.manifest.capabilities;
+        let call = Call {
+            plugin: self.manifest.name.clone(),
+            module,
+            input: serde_json::to_vec(&args)?,
+            workspace: (caps.workspace_read && ctx.sandbox.can_read(&ctx.workspace_dir))
+                .then(|| PathBuf::from(&ctx.workspace_dir)),
+            http_allow: if ctx.sandbox.network_allowed { caps.http_allow.clone() } else { Vec::new() },
+            log: caps.log,
+            fuel: self.manifest.fuel.unwrap_or(DEFAULT_FUEL),
+            memory_bytes: self.manifest.memory_mb.unwrap_or(DEFAULT_MEMORY_MB).saturating_mul(1024 * 1024) as usize,
+            timeout,
+            runtime: tokio::runtime::Handle::current(),
+        };
+
+        // The epoch deadline normally stops the guest first; the extra second
+        // only matters if a host call outlives it
+        let task = tokio::task::spawn_blocking(move || call.run());
+        let output = match tokio::time::timeout(Duration::from_secs(timeout + 1), task).await {
+            Ok(Ok(Ok(output))) => output,
+            Ok(Ok(Err(e))) => return Ok(ToolResult::error(format!("Plugin failed: {}", e))),
+            Ok(Err(e)) => return Ok(ToolResult::error(format!("Plugin task failed: {}", e))),
+            Err(_) => return Ok(ToolResult::error(format!("Plugin failed: timed out after {}s", timeout))),
+        };
+
+        let mut result_text = String::from_utf8_lossy(&output.stdout).to_string();
+        if output.stdout.len() >= MAX_OUTPUT_BYTES {
+            result_text.push_str("\n... (output truncated)");
+        }
+        if !output.stderr.is_empty() {
+            if !result_text.is_empty() {
+                result_text.push('\n');
+            }
+            result_text.push_str("[stderr] ");
+            result_text.push_str(&String::from_utf8_lossy(&output.stderr));
+        }
+        if result_text.is_empty() {
+            result_text = format!("(exit code {})", output.exit_code);
+        }
+
+        if output.exit_code == 0 {
+            Ok(ToolResult::success(result_text))
+        } else {
+            Ok(ToolResult::error(result_text))
+        }
+    }
+}
+
+/// One plugin invocation, run on a blocking thread
+struct Call {
+    plugin: String,
+    module: Module,
+    input: Vec<u8>,
+    workspace: Option<PathBuf>,
+    http_allow: Vec<String>,
+    log: bool,
+    fuel: u64,
+    memory_bytes: usize,
+    timeout: u64,
+    runtime: tokio::runtime::Handle,
+}
+
+struct CallOutput {
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+    exit_code: i32,
+}
+
+impl Call {
+    fn run(self) -> Result<CallOutput> {
+        let stdout = MemoryOutputPipe::new(MAX_OUTPUT_BYTES);
+        let stderr = MemoryOutputPipe::new(MAX_OUTPUT_BYTES);
+        let mut wasi = WasiCtxBuilder::new();
+        wasi.stdin(MemoryInputPipe::new(self.input))
+            .stdout(stdout.clone())
+            .stderr(stderr.clone())
+            .arg(&self.plugin);
+        if let Some(dir) = &self.workspace {
+            wasi.preopened_dir(dir, GUEST_WORKSPACE, DirPerms::READ, FilePerms::READ)?;
+        }
+
+        let state = HostState {
+            wasi: wasi.build_p1(),
+            limits: StoreLimitsBuilder::new().memory_size(self.memory_bytes).instances(1).build(),
+            plugin: self.plugin,
+            http_allow: self.http_allow,
+            log: self.log,
+            runtime: self.runtime,
+            deadline: Instant::now() + Duration::from_secs(self.timeout),
+            pending: Vec::new(),
+        };
+        let mut store = Store::new(engine(), state);
+        store.limiter(|state| &mut state.limits);
+        store.set_fuel(self.fuel)?;
+        let ticks = Duration::from_secs(self.timeout).as_millis() / EPOCH_TICK.as_millis();
+        store.set_epoch_deadline(ticks.max(1) as u64);
+
+        let instance = linker().instantiate(&mut store, &self.module)?;
+        let start = instance.get_typed_func::<(), ()>(&mut store, "_start")?;
+        let exit_code = match start.call(&mut store, ()) {
+            Ok(()) => 0,
+            Err(e) => match (e.downcast_ref::<I32Exit>(), e.downcast_ref::<Trap>()) {
+                (Some(exit), _) => exit.0,
+                (_, Some(Trap::OutOfFuel)) => anyhow::bail!("ran out of fuel ({} units)", self.fuel),
+                (_, Some(Trap::Interrupt)) => anyhow::bail!("timed out after {}s", self.timeout),
+                _ => return Err(anyhow::anyhow!("{}", e.root_cause())),
+            },
+        };
+
+        Ok(CallOutput { stdout: stdout.contents().to_vec(), stderr: stderr.contents().to_vec(), exit_code })
+    }
+}
+
+/// Per-call store data: the WASI context plus what the host API needs
+struct HostState {
+    wasi: WasiP1Ctx,
+    limits: StoreLimits,
+    plugin: String,
+    http_allow: Vec<String>,
+    log: bool,
+    runtime: tokio::runtime::Handle,
+    /// Wall-clock end of the call; host calls block the guest, so the epoch
+    /// deadline can't interrupt them and they check this instead
+    deadline: Instant,
+    /// Response of the last `http_fetch`, waiting for `take_result`
+    pending: Vec<u8>,
+}
+
+#[derive(Deserialize)]
+struct FetchRequest {
+    url: String,
+    #[serde(default)]
+    method: Option<String>,
+    #[serde(default)]
+    headers: HashMap<String, String>,
+    #[serde(default)]
+    body: Option<String>,
+}
+
+impl HostState {
+    fn fetch(&self, request: FetchRequest) -> Value {
+        let remaining = self.deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return json!({"error": "the plugin's time limit has passed"});
+        }
+        let url = match reqwest::Url::parse(&request.url) {
+            Ok(url) => url,
+            Err(e) => return json!({"error": format!("invalid url: {}", e)}),
+        };
+        let host = url.host_str().unwrap_or_default();
+        if !matches!(url.scheme(), "http" | "https") || !self.http_allow.iter().any(|p| glob_match(p, host)) {
+            return json!({"error": format!("host '{}' is not in the plugin's http_allow list", host)});
+        }
+        let method = match reqwest::Method::from_bytes(request.method.as_deref().unwrap_or("GET").as_bytes()) {
+            Ok(method) => method,
+            Err(e) => return json!({"error": format!("invalid method: {}", e)}),
+        };
+
+        let result: Result<Value> = self.runtime.block_on(async {
+            // No redirects: they could leave the allowlist
+            let client = reqwest::Client::builder()
+                .timeout(remaining.min(Duration::from_secs(FETCH_TIMEOUT_SECS)))
+                .redirect(reqwest::redirect::Policy::none())
+                .build()?;
+            let mut builder = client.request(method, url);
+            for (name, value) in &request.headers {
+                builder = builder.header(name, value);
+            }
+            if let Some(body) = request.body {
+                builder = builder.body(body);
+            }
+            let mut response = builder.send().await?;
+            let status = response.status().as_u16();
+            let headers: HashMap<&str, &str> = response
+                .headers()
+                .iter()
+                .filter_map(|(k, v)| Some((k.as_str(), v.to_str().ok()?)))
+                .collect();
+            let headers = json!(headers);
+            let mut bytes = Vec::new();
+            while let Some(chunk) = response.chunk().await? {
+                let room = MAX_FETCH_BYTES - bytes.len();
+                bytes.extend_from_slice(&chunk[..chunk.len().min(room)]);
+                if bytes.len() == MAX_FETCH_BYTES {
+                    break;
+                }
+            }
+            let body = String::from_utf8_lossy(&bytes);
+            Ok(json!({"status": status, "headers": headers, "body": body}))
+        });
+        result.unwrap_or_else(|e| json!({"error": e.to_string()}))
+    }
+}
+
+/// The shared engine, with fuel metering and a background thread ticking its epoch
+fn engine() -> &'static Engine {
+    static ENGINE: OnceLock<Engine> = OnceLock::new();
+    ENGINE.get_or_init(|| {
+        let mut config = Config::new();
+        config.consume_fuel(true).epoch_interruption(true);
+        let engine = Engine::new(&config).expect("valid wasmtime config");
+        let ticker = engine.clone();
+        std::thread::Builder::new()
+            .name("wasm-epoch".to_string())
+            .spawn(move || loop {
+                std::thread::sleep(EPOCH_TICK);
+                ticker.increment_epoch();
+            })
+            .expect("spawn wasm epoch thread");
+        engine
+    })
+}
+
+fn linker() -> &'static Linker<HostState> {
+    static LINKER: OnceLock<Linker<HostState>> = OnceLock::new();
+    LINKER.get_or_init(|| {
+        let mut linker = Linker::new(engine());
+        wasmtime_wasi::preview1::add_to_linker_sync(&mut linker, |state: &mut HostState| &mut state.wasi)
+            .expect("register WASI imports");
+        add_host_api(&mut linker).expect("register openclaw imports");
+        linker
+    })
+}
+
+fn add_host_api(linker: &mut Linker<HostState>) -> Result<()> {
+    linker.func_wrap("openclaw", "log", |mut caller: Caller<'_, HostState>, ptr: u32, len: u32| -> Result<()> {
+        let message = read_guest(&mut caller, ptr, len.min(MAX_LOG_BYTES))?;
+        let state = caller.data();
+        if state.log {
+            info!("[plugin {}] {}", state.plugin, String::from_utf8_lossy(&message));
+        }
+        Ok(())
+    })?;
+    linker.func_wrap(
+        "openclaw",
+        "http_fetch",
+        |mut caller: Caller<'_, HostState>, ptr: u32, len: u32| -> Result<u32> {
+            let request = read_guest(&mut caller, ptr, len)?;
+            let state = caller.data_mut();
+            let response = match serde_json::from_slice::<FetchRequest>(&request) {
+                Ok(request) => state.fetch(request),
+                Err(e) => json!({"error": format!("invalid request: {}", e)}),
+            };
+            state.pending = serde_json::to_vec(&response)?;
+            Ok(state.pending.len() as u32)
+        },
+    )?;
+    linker.func_wrap("openclaw", "take_result", |mut caller: Caller<'_, HostState>, ptr: u32| -> Result<()> {
+        let pending = std::mem::take(&mut caller.data_mut().pending);
+        guest_memory(&mut caller)?.write(&mut caller, ptr as usize, &pending)?;
+        Ok(())
+    })?;
+    Ok(())
+}
+
+fn guest_memory(caller: &mut Caller<'_, HostState>) -> Result<Memory> {
+    match caller.get_export("memory") {
+        Some(Extern::Memory(memory)) => Ok(memory),
+        _ => anyhow::bail!("plugin does not export its memory"),
+    }
+}
+
+fn read_guest(caller: &mut Caller<'_, HostState>, ptr: u32, len: u32) -> Result<Vec<u8>> {
+    let memory = guest_memory(caller)?;
+    // Check the range before allocating, so a bogus length can't make the host allocate 4 GiB
+    if ptr as usize + len as usize > memory.data_size(&*caller) {
+        anyhow::bail!("guest range {}+{} is out of bounds", ptr, len);
+    }
+    let mut buf = vec![0; len as usize];
+    memory.read(&*caller, ptr as usize, &mut buf)?;
+    Ok(buf)
+}
+
+struct CachedModule {
+    modified: Option<SystemTime>,
+    len: u64,
+    module: Module,
+}
+
+/// Compile a module once and reuse it until the file changes
+pub(super) fn compiled_module(path: &Path) -> Result<Module> {
+    static CACHE: OnceLock<Mutex<HashMap<PathBuf, CachedModule>>> = OnceLock::new();
+    let cache = CACHE.get_or_init(Default::default);
+    let meta = std::fs::metadata(path).with_context(|| format!("{}", path.display()))?;
+    let (modified, len) = (meta.modified().ok(), meta.len());
+    if let Some(cached) = cache.lock().unwrap().get(path) {
+        if cached.modified == modified && cached.len == len {
+            return Ok(cached.module.clone());
+        }
+    }
+
+    debug!("Compiling WASM plugin module {}", path.display());
+    let module = Module::from_file(engine(), path).with_context(|| format!("compiling {}", path.display()))?;
+    cache
+        .lock()
+        .unwrap()
+        .insert(path.to_path_buf(), CachedModule { modified, len, module: module.clone() });
+    Ok(module)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_encoder::{
+        CodeSection, ConstExpr, DataSection, EntityType, ExportKind, ExportSection, Function, FunctionSection,
+        ImportSection, Instruction, MemArg, MemorySection, MemoryType, TypeSection, ValType,
+    };
+
+    const FD_READ: u32 = 0;
+    const FD_WRITE: u32 = 1;
+    const LOG: u32 = 2;
+    const HTTP_FETCH: u32 = 3;
+    const TAKE_RESULT: u32 = 4;
+    /// Where test modules keep their data segment
+    const DATA: i32 = 1024;
+
+    /// A module importing the WASI and host functions above, with `data` at
+    /// offset `DATA` and `_start` running `body`
+    fn build_module(data: &[u8], body: &[Instruction]) -> Vec<u8> {
+        let mut types = TypeSection::new();
+        types.ty().function([ValType::I32; 4], [ValType::I32]);
+        types.ty().function([ValType::I32; 2], []);
+        types.ty().function([ValType::I32; 2], [ValType::I32]);
+        types.ty().function([ValType::I32], []);
+        types.ty().function([], []);
+
+        let mut imports = ImportSection::new();
+        imports.import("wasi_snapshot_preview1", "fd_read", EntityType::Function(0));
+        imports.import("wasi_snapshot_preview1", "fd_write", EntityType::Function(0));
+        imports.import("openclaw", "log", EntityType::Function(1));
+        imports.import("openclaw", "http_fetch", EntityType::Function(2));
+        imports.import("openclaw", "take_result", EntityType::Function(3));
+
+        let mut functions = FunctionSection::new();
+        functions.function(4);
+        let mut memories = MemorySection::new();
+        memories.memory(MemoryType { minimum: 1, maximum: None, memory64: false, shared: false, page_size_log2: None });
+        let mut exports = ExportSection::new();
+        exports.export("memory", ExportKind::Memory, 0);
+        exports.export("_start", ExportKind::Func, 5);
+
+        let mut start = Function::new([]);
+        for instruction in body {
+            start.instruction(instruction);
+        }
+        start.instruction(&Instruction::End);
+        let mut code = CodeSection::new();
+        code.function(&start);
+        let mut data_section = DataSection::new();
+        data_section.active(0, &ConstExpr::i32_const(DATA), data.iter().copied());
+
+        let mut module = wasm_encoder::Module::new();
+        module
+            .section(&types)
+            .section(&imports)
+            .section(&functions)
+            .section(&memories)
+            .section(&exports)
+            .section(&code)
+            .section(&data_section);
+        module.finish()
+    }
+
+    fn store(addr: i32, value: i32) -> [Instruction<'static>; 3] {
+        let mem = MemArg { offset: 0, align: 2, memory_index: 0 };
+        [Instruction::I32Const(addr), Instruction::I32Const(value), Instruction::I32Store(mem)]
+    }
+
+    /// `fd_write(1, iovs, 1, nwritten)` with the iovec at `iovs`
+    fn write_stdout(iovs: i32) -> [Instruction<'static>; 6] {
+        [
+            Instruction::I32Const(1),
+            Instruction::I32Const(iovs),
+            Instruction::I32Const(1),
+            Instruction::I32Const(8),
+            Instruction::Call(FD_WRITE),
+            Instruction::Drop,
+        ]
+    }
+
+    fn plugin(dir: &Path, wasm: &[u8], extra: Value) -> WasmPluginTool {
+        std::fs::write(dir.join("plugin.wasm"), wasm).unwrap();
+        let mut manifest = json!({"name": "wasm_test", "description": "Test", "runtime": "wasm", "module": "plugin.wasm"});
+        manifest.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+        WasmPluginTool::from_manifest(serde_json::from_value(manifest).unwrap(), dir).unwrap()
+    }
+
+    fn ctx(workspace: &Path) -> ToolContext {
+        ToolContext { workspace_dir: workspace.to_string_lossy().to_string(), ..ToolContext::default() }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_wasm_plugin_echoes_args_and_denies_unlisted_hosts() {
+        let request = br#"{"url":"https://evil.example/steal"}"#;
+        let mut data = request.to_vec();
+        data.extend_from_slice(b"hello log");
+        let mut body = Vec::new();
+        // Read stdin into 4096.. and write it back out
+        body.extend(store(0, 4096));
+        body.extend(store(4, 1024 * 8));
+        body.extend([
+            Instruction::I32Const(0),
+            Instruction::I32Const(0),
+            Instruction::I32Const(1),
+            Instruction::I32Const(8),
+            Instruction::Call(FD_READ),
+            Instruction::Drop,
+            Instruction::I32Const(4),
+            Instruction::I32Const(8),
+            Instruction::I32Load(MemArg { offset: 0, align: 2, memory_index: 0 }),
+            Instruction::I32Store(MemArg { offset: 0, align: 2, memory_index: 0 }),
+        ]);
+        body.extend(write_stdout(0));
+        body.extend([
+            Instruction::I32Const(DATA + request.len() as i32),
+            Instruction::I32Const(9),
+            Instruction::Call(LOG),
+        ]);
+        // Fetch a host outside the allowlist and print the response
+        body.extend(store(16, 32768));
+        body.extend([Instruction::I32Const(20), Instruction::I32Const(DATA), Instruction::I32Const(request.len() as i32)]);
+        body.extend([
+            Instruction::Call(HTTP_FETCH),
+            Instruction::I32Store(MemArg { offset: 0, align: 2, memory_index: 0 }),
+            Instruction::I32Const(32768),
+            Instruction::Call(TAKE_RESULT),
+        ]);
+        body.extend(write_stdout(16));
+
+        let dir = tempfile::tempdir().unwrap();
+        let tool = plugin(dir.path(), &build_module(&data, &body), json!({"capabilities": {"http_allow": ["api.github.com"]}}));
+        let result = tool.execute(json!({"message": "hi wasm"}), &ctx(dir.path())).await.unwrap();
+        assert!(!result.is_error, "{}", result.output);
+        assert!(result.output.starts_with(r#"{"message":"hi wasm"}"#), "{}", result.output);
+        assert!(result.output.contains("host 'evil.example' is not in the plugin's http_allow list"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_wasm_plugin_fuel_limit() {
+        let body = [Instruction::Loop(wasm_encoder::BlockType::Empty), Instruction::Br(0), Instruction::End];
+        let dir = tempfile::tempdir().unwrap();
+        let tool = plugin(dir.path(), &build_module(&[], &body), json!({"fuel": 100_000}));
+        let result = tool.execute(json!({}), &ctx(dir.path())).await.unwrap();
+        assert!(result.is_error);
+        assert!(result.output.contains("ran out of fuel"), "{}", result.output);
+
+        // Same file, unchanged: the compiled module comes from the cache
+        let path = dir.path().join("plugin.wasm");
+        let first = compiled_module(&path).unwrap();
+        assert_eq!(first.image_range(), compiled_module(&path).unwrap().image_range());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_wasm_plugin_out_of_bounds_request() {
+        let body = [
+            Instruction::I32Const(DATA),
+            Instruction::I32Const(i32::MAX),
+            Instruction::Call(HTTP_FETCH),
+            Instruction::Drop,
+        ];
+        let dir = tempfile::tempdir().unwrap();
+        let tool = plugin(dir.path(), &build_module(&[], &body), json!({"capabilities": {"http_allow": ["*"]}}));
+        let result = tool.execute(json!({}), &ctx(dir.path())).await.unwrap();
+        assert!(result.is_error);
+        assert!(result.output.contains("out of bounds"), "{}", result.output);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_fetch_after_deadline_fails() {
+        let state = HostState {
+            wasi: WasiCtxBuilder::new().build_p1(),
+            limits: StoreLimitsBuilder::new().build(),
+            plugin: "late".to_string(),
+            http_allow: vec!["*".to_string()],
+            log: false,
+            runtime: tokio::runtime::Handle::current(),
+            deadline: Instant::now(),
+            pending: Vec::new(),
+        };
+        let request = FetchRequest {
+            url: "http://127.0.0.1:9/".to_string(),
+            method: None,
+            headers: HashMap::new(),
+            body: None,
+        };
+        let response = tokio::task::spawn_blocking(move || state.fetch(request)).await.unwrap();
+        assert_eq!(response["error"], "the plugin's time limit has passed");
+    }
+
+    #[test]
+    fn test_wasm_manifest() {
+        let manifest: PluginManifest = serde_json::from_str(
+            r#"{"name": "wc", "description": "Count words", "runtime": "wasm", "module": "wc.wasm",
+                "capabilities": {"workspace_read": true, "http_allow": ["*.github.com"]}, "memory_mb": 16}"#,
+        )
+        .unwrap();
+        assert!(manifest.capabilities.workspace_read && manifest.capabilities.log);
+        let tool = WasmPluginTool::from_manifest(manifest, Path::new("/plugins")).unwrap();
+        assert_eq!(tool.module_path, Path::new("/plugins/wc.wasm"));
+
+        let missing: PluginManifest =
+            serde_json::from_str(r#"{"name": "x", "description": "", "runtime": "wasm"}"#).unwrap();
+        assert!(WasmPluginTool::from_manifest(missing, Path::new("/plugins")).is_err());
+    }
+}
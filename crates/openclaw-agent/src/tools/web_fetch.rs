@@ -36,7 +36,11 @@ impl Tool for WebFetchTool {
         })
     }
 
-    async fn execute(&self, args: Value, _ctx: &ToolContext) -> Result<ToolResult> {
+    async fn execute(&self, args: Value, ctx: &ToolContext) -> Result<ToolResult> {
+        if !ctx.sandbox.network_allowed {
+            return Ok(ToolResult::error("Network access is disabled by sandbox policy"));
+        }
+
         let url = args
             .get("url")
             .and_then(|v| v.as_str())
@@ -36,7 +36,11 @@ impl Tool for WebSearchTool {
         })
     }
 
-    async fn execute(&self, args: Value, _ctx: &ToolContext) -> Result<ToolResult> {
+    async fn execute(&self, args: Value, ctx: &ToolContext) -> Result<ToolResult> {
+        if !ctx.sandbox.network_allowed {
+            return Ok(ToolResult::error("Network access is disabled by sandbox policy"));
+        }
+
         let query = args
             .get("query")
             .and_then(|v| v.as_str())
@@ -7,7 +7,7 @@ use super::{Tool, ToolContext, ToolResult};
 
 pub struct WriteTool;
 
-fn resolve_safe_write_path(workspace: &str, file_path: &str) -> Result<PathBuf> {
+pub(super) fn resolve_safe_write_path(workspace: &str, file_path: &str) -> Result<PathBuf> {
     let workspace = PathBuf::from(workspace).canonicalize()?;
     let target = if file_path.starts_with('/') {
         PathBuf::from(file_path)
@@ -102,6 +102,9 @@ impl Tool for WriteTool {
             Ok(p) => p,
             Err(e) => return Ok(ToolResult::error(format!("{}", e))),
         };
+        if !ctx.sandbox.can_write(&safe_path.to_string_lossy()) {
+            return Ok(ToolResult::error(format!("Write access to {} denied by sandbox policy", file_path)));
+        }
 
         // Create parent directories
         if let Some(parent) = safe_path.parent() {
@@ -210,4 +213,26 @@ mod tests {
             .await
             .ok();
     }
+
+    #[tokio::test]
+    async fn test_write_outside_allowlist_denied() {
+        let tool = WriteTool;
+        let ctx = ToolContext {
+            workspace_dir: "/tmp".to_string(),
+            sandbox: crate::sandbox::SandboxPolicy {
+                write_allow: vec!["/tmp/openclaw-allowed".to_string()],
+                ..Default::default()
+            },
+            ..ToolContext::default()
+        };
+
+        let args = serde_json::json!({
+            "path": "/tmp/openclaw-test-denied.txt",
+            "content": "nope"
+        });
+        let result = tool.execute(args, &ctx).await.unwrap();
+        assert!(result.is_error);
+        assert!(result.output.contains("denied by sandbox policy"));
+        assert!(!std::path::Path::new("/tmp/openclaw-test-denied.txt").exists());
+    }
 }
@@ -38,7 +38,8 @@ impl std::fmt::Display for TimeoutReason {
 }
 
 /// Activity-based watchdog that cancels via a `CancellationToken` when
-/// the monitored task stops making progress.
+/// the monitored task stops making progress. Clones share the same activity clock.
+#[derive(Clone)]
 pub struct ActivityWatchdog {
     last_activity_ms: Arc<AtomicU64>,
     cancel_token: CancellationToken,
@@ -17,6 +17,10 @@ const BOOTSTRAP_FILES: &[&str] = &[
 /// Minimal set for cron/subagent sessions
 const MINIMAL_BOOTSTRAP_FILES: &[&str] = &["AGENTS.md", "TOOLS.md"];
 
+/// Marks the start of the per-request runtime section at the end of the system prompt.
+/// Everything before it is stable across requests (and thus prompt-cacheable).
+pub const RUNTIME_MARKER: &str = "<!-- runtime -->";
+
 #[derive(Debug, Clone)]
 pub struct BootstrapFile {
     pub name: String,
@@ -70,7 +74,8 @@ pub async fn load_workspace(dir: &Path, minimal: bool) -> Result<WorkspaceContex
                 // Cache hit — just update the runtime timestamp
                 let now = chrono::Local::now();
                 let runtime = format!(
-                    "<!-- runtime -->\nCurrent date/time: {}",
+                    "{}\nCurrent date/time: {}",
+                    RUNTIME_MARKER,
                     now.format("%A, %B %e, %Y — %l:%M %p %Z")
                 );
                 let system_prompt = format!("{}\n\n{}", cached.system_prompt_base, runtime);
@@ -189,8 +194,9 @@ fn assemble_system_prompt(files: &[BootstrapFile]) -> String {
 
     let now = chrono::Local::now();
     format!(
-        "{}\n\n<!-- runtime -->\nCurrent date/time: {}",
+        "{}\n\n{}\nCurrent date/time: {}",
         base,
+        RUNTIME_MARKER,
         now.format("%A, %B %e, %Y — %l:%M %p %Z")
     )
 }
@@ -1,14 +1,44 @@
 use anyhow::Result;
+use clap::Subcommand;
 use colored::Colorize;
 use openclaw_agent::llm::fallback::FallbackProvider;
 use openclaw_agent::llm::{LlmProvider, OpenAiCompatibleProvider};
-use openclaw_agent::runtime::{self, AgentTurnConfig};
+use openclaw_agent::replay::{self, Cassette, RecordingProvider, ReplayProvider};
+use openclaw_agent::runtime::{self, AgentTurnConfig, TurnLimits};
 use openclaw_agent::tools::ToolRegistry;
 use openclaw_agent::workspace;
+use std::path::PathBuf;
 use std::time::Instant;
 
+#[derive(Subcommand)]
+pub enum AgentAction {
+    /// Re-run a recorded turn (from `agent --record`) and diff its tool calls against the original
+    Replay {
+        /// Cassette file (JSONL)
+        cassette: PathBuf,
+        /// Agent whose workspace the turn runs in
+        #[arg(long, default_value = "main")]
+        agent: String,
+        /// Ask the configured model chain instead of serving the recorded responses
+        #[arg(long, default_value_t = false)]
+        live: bool,
+        /// Answer tool calls with the recorded results instead of running the tools
+        #[arg(long, default_value_t = false)]
+        stub_tools: bool,
+    },
+}
+
+pub async fn run_action(action: AgentAction) -> Result<()> {
+    match action {
+        AgentAction::Replay { cassette, agent, live, stub_tools } => {
+            replay_turn(&cassette, &agent, live, stub_tools).await
+        }
+    }
+}
+
 pub struct AgentOptions {
-    pub message: String,
+    /// Required unless `resume_turn` is set
+    pub message: Option<String>,
     pub agent: String,
     pub model: Option<String>,
     pub api_key: Option<String>,
@@ -17,6 +47,12 @@ pub struct AgentOptions {
     pub session: Option<String>,
     pub continue_session: bool,
     pub fallback: bool,
+    pub max_rounds: Option<usize>,
+    pub max_tool_output_tokens: Option<usize>,
+    /// Turn ID of an interrupted turn to continue from its checkpoint
+    pub resume_turn: Option<String>,
+    /// Cassette file to record the turn's LLM calls to
+    pub record: Option<PathBuf>,
 }
 
 pub async fn run(opts: AgentOptions) -> Result<()> {
@@ -68,6 +104,13 @@ pub async fn run(opts: AgentOptions) -> Result<()> {
         );
         Box::new(OpenAiCompatibleProvider::new(&base_url, &api_key, &model))
     };
+    let provider: Box<dyn LlmProvider> = match opts.record {
+        Some(ref path) => {
+            eprintln!("  {} {}", "Recording to".dimmed(), path.display().to_string().dimmed());
+            Box::new(RecordingProvider::new(provider, path))
+        }
+        None => provider,
+    };
 
     // ── Session handling (Postgres) ──
     // Initialize Postgres if available
@@ -75,7 +118,36 @@ pub async fn run(opts: AgentOptions) -> Result<()> {
     let pool = openclaw_db::pool()
         .ok_or_else(|| anyhow::anyhow!("Postgres not available — set DATABASE_URL"))?;
 
-    let session_key = if opts.continue_session {
+    // ── Interrupted turn to resume ──
+    let resume = match opts.resume_turn {
+        Some(ref turn_id) => {
+            let checkpoint = openclaw_db::checkpoints::load_checkpoint(pool, turn_id)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("No checkpoint for turn {} (it finished or was superseded)", turn_id))?;
+            if checkpoint.agent_name != opts.agent {
+                anyhow::bail!(
+                    "Turn {} belongs to agent '{}' — pass --agent {}",
+                    turn_id, checkpoint.agent_name, checkpoint.agent_name
+                );
+            }
+            Some(checkpoint)
+        }
+        None => None,
+    };
+    let message = match resume {
+        Some(ref checkpoint) => checkpoint.user_message.clone(),
+        None => opts.message.clone().unwrap_or_default(),
+    };
+
+    let session_key = if let Some(ref checkpoint) = resume {
+        eprintln!(
+            "  {} {} (after round {})",
+            "Resuming turn in".dimmed(),
+            checkpoint.session_key.dimmed(),
+            checkpoint.rounds
+        );
+        checkpoint.session_key.clone()
+    } else if opts.continue_session {
         match openclaw_db::sessions::find_latest_session(pool, &format!("agent:{}:", opts.agent)).await? {
             Some(key) => {
                 eprintln!("  {} {}", "Continuing session:".dimmed(), key.dimmed());
@@ -98,7 +170,7 @@ pub async fn run(opts: AgentOptions) -> Result<()> {
     ).await?;
 
     // ── Load prior messages if continuing ──
-    let _prior_messages = if opts.continue_session || opts.session.is_some() {
+    let _prior_messages = if opts.continue_session || opts.session.is_some() || resume.is_some() {
         let msgs = openclaw_db::sessions::load_messages(pool, &session_key).await?;
         if !msgs.is_empty() {
             eprintln!(
@@ -114,22 +186,29 @@ pub async fn run(opts: AgentOptions) -> Result<()> {
 
     // ── Set up tools and config ──
     let tools = ToolRegistry::with_defaults();
+    // Printed up front so a turn killed mid-way can still be resumed with --resume-turn
+    let turn_id = resume.as_ref().map(|c| c.turn_id.clone()).unwrap_or_else(runtime::new_turn_id);
+    eprintln!("  {} {}", "Turn:".dimmed(), turn_id.dimmed());
+    let defaults = TurnLimits::default();
     let config = AgentTurnConfig {
         agent_name: opts.agent.clone(),
         session_key: session_key.clone(),
         workspace_dir: workspace_dir.to_string_lossy().to_string(),
         minimal_context: false,
+        limits: TurnLimits {
+            max_rounds: opts.max_rounds.unwrap_or(defaults.max_rounds),
+            max_tool_output_tokens: opts.max_tool_output_tokens.unwrap_or(defaults.max_tool_output_tokens),
+            ..defaults
+        },
+        turn_id: Some(turn_id.clone()),
     ..AgentTurnConfig::default()
     };
 
     // ── Run agent turn ──
-    let result = runtime::run_agent_turn(
-        provider.as_ref(),
-        &opts.message,
-        &config,
-        &tools,
-    )
-    .await?;
+    let result = match resume {
+        Some(ref checkpoint) => runtime::resume_agent_turn(provider.as_ref(), checkpoint, &config, &tools).await?,
+        None => runtime::run_agent_turn(provider.as_ref(), &message, &config, &tools).await?,
+    };
 
     // ── Print response ──
     if !result.response.is_empty() {
@@ -138,18 +217,26 @@ pub async fn run(opts: AgentOptions) -> Result<()> {
         println!("{}", reasoning);
     }
 
-    // ── Persist messages to Postgres ──
+    if result.resumable {
+        eprintln!("  {} openclaw agent --resume-turn {}", "Stopped early — resume with:".yellow(), turn_id);
+    }
+
+    // ── Persist messages to Postgres (an interrupted turn stays in its checkpoint; the next turn moves it into the history) ──
     if let Ok(Some(sid)) = openclaw_db::sessions::get_session_id(pool, &session_key).await {
-        let _ = openclaw_db::messages::record_message(
-            pool, sid, "user", Some(&opts.message), None, None, None,
-        ).await;
-        if !result.response.is_empty() {
+        if !result.resumable {
+            let _ = openclaw_db::messages::record_message(
+                pool, sid, "user", Some(&message), None, None, None,
+            ).await;
+        }
+        if !result.resumable && !result.response.is_empty() {
             let _ = openclaw_db::messages::record_message(
                 pool, sid, "assistant", Some(&result.response), None, None, None,
             ).await;
         }
         let _ = openclaw_db::sessions::add_tokens(pool, &session_key,
             result.total_usage.total_tokens as i64).await;
+        let _ = openclaw_db::sessions::add_cost(pool, &session_key,
+            result.total_cost_micros as i64).await;
     }
 
     // ── Print stats ──
@@ -165,11 +252,12 @@ pub async fn run(opts: AgentOptions) -> Result<()> {
         result.tool_calls_made,
     );
     eprintln!(
-        "  {} {} prompt + {} completion = {} total",
+        "  {} {} prompt + {} completion = {} total ({})",
         "Tokens:".dimmed(),
         result.total_usage.prompt_tokens,
         result.total_usage.completion_tokens,
         result.total_usage.total_tokens,
+        openclaw_agent::llm::pricing::format_cost(result.total_cost_micros),
     );
     eprintln!(
         "  {} {} / {} / {}",
@@ -182,6 +270,60 @@ pub async fn run(opts: AgentOptions) -> Result<()> {
     Ok(())
 }
 
+/// Re-run a recorded turn with the recorded (or live) model responses and
+/// real (or stubbed) tools; fails when the tool calls differ from the original
+async fn replay_turn(cassette_path: &std::path::Path, agent: &str, live: bool, stub_tools: bool) -> Result<()> {
+    let cassette = Cassette::load(cassette_path)?;
+    let message = cassette
+        .user_message()
+        .ok_or_else(|| anyhow::anyhow!("Cassette has no user message to start the turn from"))?;
+    let original = cassette.tool_calls();
+
+    let workspace_dir = workspace::resolve_workspace_dir(agent);
+    if !workspace_dir.exists() {
+        anyhow::bail!("Workspace not found: {}", workspace_dir.display());
+    }
+
+    let provider: Box<dyn LlmProvider> = if live {
+        Box::new(FallbackProvider::from_config()?)
+    } else {
+        Box::new(ReplayProvider::new(cassette.clone()))
+    };
+    let tools = if stub_tools { cassette.stub_tools() } else { ToolRegistry::with_defaults() };
+    eprintln!(
+        "{} {} {} ({} LLM call(s), {} tool call(s)) → {} model, {} tools",
+        "●".green(),
+        "Replaying".bold(),
+        cassette_path.display(),
+        cassette.entries.len(),
+        original.len(),
+        if live { "live".yellow() } else { "recorded".cyan() },
+        if stub_tools { "stubbed".cyan() } else { "real".yellow() },
+    );
+
+    let config = AgentTurnConfig {
+        agent_name: agent.to_string(),
+        session_key: format!("replay:{}:{}", agent, uuid::Uuid::new_v4()),
+        workspace_dir: workspace_dir.to_string_lossy().to_string(),
+        minimal_context: false,
+        ..AgentTurnConfig::default()
+    };
+    let result = runtime::run_agent_turn(provider.as_ref(), &message, &config, &tools).await?;
+    println!("{}", result.response);
+
+    let diffs = replay::diff_calls(&original, &replay::calls_from_messages(&result.turn_messages));
+    eprintln!();
+    eprintln!("{}", "─".repeat(50).dimmed());
+    if diffs.is_empty() {
+        eprintln!("  {} {} tool call(s) match the original turn", "✓".green(), original.len());
+        return Ok(());
+    }
+    for diff in &diffs {
+        eprintln!("  {} {}", "✗".red(), diff);
+    }
+    anyhow::bail!("Replay differs from the original turn in {} place(s)", diffs.len())
+}
+
 fn resolve_provider_from_config() -> Result<(String, String, String)> {
     let config_path = openclaw_core::paths::manual_config_path();
     if !config_path.exists() {
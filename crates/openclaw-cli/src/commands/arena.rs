@@ -71,6 +71,12 @@ pub async fn run(ollama_url: &str) -> Result<()> {
                     // Write to Postgres if available
                     if let Some(pool) = openclaw_db::pool() {
                         let id = uuid::Uuid::new_v4();
+                        let usage = openclaw_agent::llm::UsageStats {
+                            completion_tokens: tokens as u32,
+                            total_tokens: tokens as u32,
+                            ..Default::default()
+                        };
+                        let cost = openclaw_agent::llm::pricing::global().cost_micros(model, &usage);
                         let _ = openclaw_db::llm_log::record_llm_call(
                             pool, &id,
                             Some(&format!("arena:{}", difficulty)),
@@ -78,7 +84,7 @@ pub async fn run(ollama_url: &str) -> Result<()> {
                             1, 0, false,
                             Some(&response), None,
                             0, &[], 0, tokens as i32, tokens as i32,
-                            ms as i32, None,
+                            0, 0, ms as i32, cost as i64, None, None,
                         ).await;
                     }
 
@@ -347,6 +347,11 @@ async fn log_detail(client: &reqwest::Client, base: &str, id: &str) -> Result<()
     println!("  {} {}", "Prompt Tokens:".bold(), entry["usage_prompt_tokens"].as_u64().unwrap_or(0));
     println!("  {} {}", "Completion Tokens:".bold(), entry["usage_completion_tokens"].as_u64().unwrap_or(0));
     println!("  {} {}", "Total Tokens:".bold(), entry["usage_total_tokens"].as_u64().unwrap_or(0));
+    let cache_read = entry["usage_cache_read_tokens"].as_u64().unwrap_or(0);
+    let cache_write = entry["usage_cache_write_tokens"].as_u64().unwrap_or(0);
+    if cache_read > 0 || cache_write > 0 {
+        println!("  {} {} read / {} written", "Prompt Cache:".bold(), cache_read, cache_write);
+    }
     println!("  {} {}ms", "Latency:".bold(), entry["latency_ms"].as_u64().unwrap_or(0));
 
     if let Some(err) = entry["error"].as_str() {
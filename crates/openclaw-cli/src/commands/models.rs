@@ -1,6 +1,7 @@
 use anyhow::Result;
 use clap::Subcommand;
 use colored::Colorize;
+use openclaw_agent::llm::ollama::{self, ModelLoadState};
 use openclaw_core::models;
 use openclaw_core::paths;
 
@@ -14,7 +15,7 @@ pub enum ModelAction {
     },
     /// Show the fallback chain order
     Fallback,
-    /// Scan providers for reachability (ping base URLs)
+    /// Scan providers for reachability (ping base URLs) and Ollama model load state
     Scan,
 }
 
@@ -84,6 +85,9 @@ fn list_models(config_path: &std::path::Path, show_all: bool) -> Result<()> {
             if model.local {
                 tags.push("free".green().to_string());
             }
+            if let Some(temp) = model.params.temperature {
+                tags.push(format!("temp {}", temp).dimmed().to_string());
+            }
 
             let tag_str = if tags.is_empty() {
                 String::new()
@@ -138,7 +142,7 @@ fn show_fallback(config_path: &std::path::Path) -> Result<()> {
     }
     println!(
         "\n  {}",
-        "Circuit breaker: >3 consecutive failures = provider skipped".dimmed()
+        "Circuit breaker: >3 consecutive failures opens the circuit; one probe after the cooldown".dimmed()
     );
 
     Ok(())
@@ -160,7 +164,7 @@ async fn scan_providers(config_path: &std::path::Path) -> Result<()> {
 
     for provider in &providers {
         let url = if provider.api == "ollama" {
-            format!("{}/api/tags", provider.base_url)
+            format!("{}/api/tags", ollama::server_root(&provider.base_url))
         } else {
             format!("{}/models", provider.base_url)
         };
@@ -183,6 +187,9 @@ async fn scan_providers(config_path: &std::path::Path) -> Result<()> {
                     status_text,
                     ms,
                 );
+                if provider.api == "ollama" {
+                    print_ollama_load_states(&client, provider).await;
+                }
             }
             Ok(resp) => {
                 println!(
@@ -213,3 +220,27 @@ async fn scan_providers(config_path: &std::path::Path) -> Result<()> {
 
     Ok(())
 }
+
+/// Per-model load state for an Ollama provider (loaded / pulled / missing)
+async fn print_ollama_load_states(client: &reqwest::Client, provider: &models::ProviderInfo) {
+    let ids: Vec<String> = provider.models.iter().map(|m| m.id.clone()).collect();
+    match ollama::model_load_states(client, &provider.base_url, &ids).await {
+        Ok(states) => {
+            for (id, state) in states {
+                let state_text = match state {
+                    ModelLoadState::Loaded { size_vram, expires_at } => {
+                        let mut text = format!("loaded, {:.1} GB VRAM", size_vram as f64 / 1e9);
+                        if let Some(expires) = expires_at {
+                            text.push_str(&format!(", unloads {}", expires));
+                        }
+                        text.green()
+                    }
+                    ModelLoadState::Available => "pulled, not loaded".normal(),
+                    ModelLoadState::NotPulled => "not pulled".red(),
+                };
+                println!("      {} {} — {}", "→".dimmed(), id.cyan(), state_text);
+            }
+        }
+        Err(e) => println!("      {} {}", "→".dimmed(), format!("load state unavailable: {}", e).dimmed()),
+    }
+}
@@ -33,10 +33,13 @@ enum Commands {
         action: commands::cron::CronAction,
     },
     /// Run one agent turn with tools (exec, read, write)
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
     Agent {
+        #[command(subcommand)]
+        action: Option<commands::agent::AgentAction>,
         /// The message to send
-        #[arg(short, long)]
-        message: String,
+        #[arg(short, long, required_unless_present = "resume_turn")]
+        message: Option<String>,
         /// Agent name
         #[arg(long, default_value = "main")]
         agent: String,
@@ -61,6 +64,18 @@ enum Commands {
         /// Use model fallback chain from config
         #[arg(long, default_value_t = false)]
         fallback: bool,
+        /// LLM rounds before the agent must give a final answer (default 20)
+        #[arg(long)]
+        max_rounds: Option<usize>,
+        /// Max tokens of one tool result sent to the model (default 8000)
+        #[arg(long)]
+        max_tool_output_tokens: Option<usize>,
+        /// Continue an interrupted turn from its checkpoint (turn ID)
+        #[arg(long, conflicts_with_all = ["message", "session", "continue_session"])]
+        resume_turn: Option<String>,
+        /// Record the turn's LLM calls to this cassette file (see `agent replay`)
+        #[arg(long)]
+        record: Option<std::path::PathBuf>,
     },
     /// Send a raw chat message to an LLM (no tools, no workspace context)
     Chat {
@@ -128,7 +143,11 @@ async fn main() -> anyhow::Result<()> {
         Some(Commands::Skills { action }) => commands::skills::run(action),
         Some(Commands::Config { action }) => commands::config::run(action),
         Some(Commands::Cron { action }) => commands::cron::run(action),
-        Some(Commands::Agent { message, agent, model, api_key, base_url, stream, continue_session, session, fallback }) => {
+        Some(Commands::Agent { action: Some(action), .. }) => commands::agent::run_action(action).await,
+        Some(Commands::Agent {
+            action: None, message, agent, model, api_key, base_url, stream, continue_session, session, fallback,
+            max_rounds, max_tool_output_tokens, resume_turn, record,
+        }) => {
             commands::agent::run(commands::agent::AgentOptions {
                 message,
                 agent,
@@ -139,6 +158,10 @@ async fn main() -> anyhow::Result<()> {
                 continue_session,
                 session,
                 fallback,
+                max_rounds,
+                max_tool_output_tokens,
+                resume_turn,
+                record,
             })
             .await
         }
@@ -60,6 +60,118 @@ pub struct AgentDefaults {
     pub context_pruning: Option<serde_json::Value>,
     #[serde(default)]
     pub compaction: Option<serde_json::Value>,
+    #[serde(default)]
+    pub memory: Option<serde_json::Value>,
+}
+
+impl AgentDefaults {
+    /// Typed view of `compaction` (defaults when unset or malformed)
+    pub fn compaction_config(&self) -> CompactionConfig {
+        self.compaction
+            .as_ref()
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_default()
+    }
+
+    /// Typed view of `memory` (defaults when unset or malformed)
+    pub fn memory_config(&self) -> MemoryConfig {
+        self.memory
+            .as_ref()
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_default()
+    }
+}
+
+/// `agents.defaults.compaction`: summarize history that no longer fits the context budget
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CompactionConfig {
+    /// `"off"` keeps the old behaviour (older history is dropped); anything else summarizes
+    pub mode: Option<String>,
+    /// "provider/model" that writes summaries (default: the turn's own provider)
+    pub model: Option<String>,
+    /// History kept verbatim before compaction kicks in
+    pub max_history_messages: usize,
+    /// Token budget for history (default: a share of the model's context window)
+    pub max_history_tokens: Option<usize>,
+    /// Rough length cap for the summary, in words
+    pub summary_max_words: usize,
+}
+
+impl Default for CompactionConfig {
+    fn default() -> Self {
+        Self {
+            mode: None,
+            model: None,
+            max_history_messages: 40,
+            max_history_tokens: None,
+            summary_max_words: 400,
+        }
+    }
+}
+
+impl CompactionConfig {
+    pub fn enabled(&self) -> bool {
+        self.mode.as_deref() != Some("off")
+    }
+
+    /// History token budget for a model: the configured value, else a quarter of
+    /// its context window (the rest is left for the system prompt, tools, the
+    /// turn's own rounds and the reply), else 12K when the window is unknown
+    pub fn history_tokens(&self, context_window: Option<u64>) -> usize {
+        self.max_history_tokens
+            .or(context_window.map(|w| (w / 4) as usize))
+            .unwrap_or(12000)
+    }
+}
+
+/// `agents.defaults.memory`: semantic recall over notes and past conversations
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MemoryConfig {
+    /// `"file"` or `"postgres"` (default: Postgres when connected, else a file in the workspace)
+    pub store: Option<String>,
+    /// Embeddings endpoint for semantic ranking; pure BM25 when unset
+    pub embeddings: Option<EmbeddingsConfig>,
+    /// Memories recalled into the system prompt each turn (0 disables)
+    pub recall_top_k: usize,
+    /// Index each finished exchange as a `conversation` memory
+    pub index_conversations: bool,
+    /// Conversation memories kept per session; older ones are pruned
+    pub max_conversation_memories: usize,
+    /// Notes kept per session; older ones are pruned
+    pub max_notes: usize,
+    /// Newest memories ranked on each recall
+    pub recall_window: usize,
+    /// Share of the hybrid score given to BM25 when embeddings are available
+    pub keyword_weight: f32,
+}
+
+impl Default for MemoryConfig {
+    fn default() -> Self {
+        Self {
+            store: None,
+            embeddings: None,
+            recall_top_k: 5,
+            index_conversations: true,
+            max_conversation_memories: 200,
+            max_notes: 500,
+            recall_window: 1000,
+            keyword_weight: 0.4,
+        }
+    }
+}
+
+/// An OpenAI-compatible `/embeddings` endpoint: a configured provider's
+/// `baseUrl`/`apiKey`, or explicit ones
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EmbeddingsConfig {
+    /// Name under `models.providers` to take `baseUrl` and `apiKey` from
+    pub provider: Option<String>,
+    pub base_url: Option<String>,
+    pub api_key: Option<String>,
+    pub model: String,
 }
 
 #[derive(Debug, Default, Deserialize, Serialize)]
@@ -96,6 +208,41 @@ mod tests {
         assert_eq!(config.gateway.port, Some(3001));
     }
 
+    #[test]
+    fn test_compaction_config() {
+        let json = r#"{"agents": {"defaults": {"compaction": {"model": "ollama/qwen3:8b", "maxHistoryTokens": 8000}}}}"#;
+        let config: ManualConfig = serde_json::from_str(json).unwrap();
+        let compaction = config.agents.defaults.compaction_config();
+        assert!(compaction.enabled());
+        assert_eq!(compaction.model.as_deref(), Some("ollama/qwen3:8b"));
+        assert_eq!(compaction.history_tokens(Some(200_000)), 8000);
+        assert_eq!(compaction.max_history_messages, 40);
+
+        let off: ManualConfig =
+            serde_json::from_str(r#"{"agents": {"defaults": {"compaction": {"mode": "off"}}}}"#).unwrap();
+        let off = off.agents.defaults.compaction_config();
+        assert!(!off.enabled());
+        assert_eq!(off.history_tokens(Some(200_000)), 50_000);
+        assert_eq!(off.history_tokens(None), 12000);
+    }
+
+    #[test]
+    fn test_memory_config() {
+        let json = r#"{"agents": {"defaults": {"memory": {"recallTopK": 3, "embeddings": {"provider": "openai", "model": "text-embedding-3-small"}}}}}"#;
+        let config: ManualConfig = serde_json::from_str(json).unwrap();
+        let memory = config.agents.defaults.memory_config();
+        assert_eq!(memory.recall_top_k, 3);
+        assert!(memory.index_conversations);
+        let embeddings = memory.embeddings.unwrap();
+        assert_eq!(embeddings.provider.as_deref(), Some("openai"));
+        assert_eq!(embeddings.model, "text-embedding-3-small");
+
+        let unset: ManualConfig = serde_json::from_str("{}").unwrap();
+        let unset = unset.agents.defaults.memory_config();
+        assert_eq!(unset, MemoryConfig::default());
+        assert!(unset.embeddings.is_none());
+    }
+
     #[test]
     fn test_parse_empty_config() {
         let json = "{}";
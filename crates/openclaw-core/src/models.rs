@@ -20,6 +20,120 @@ pub struct ModelInfo {
     pub reasoning: bool,
     pub context_window: Option<u64>,
     pub local: bool,
+    /// Effective generation parameters (provider defaults merged with model overrides)
+    #[serde(default)]
+    pub params: GenerationParams,
+}
+
+/// Generation parameters for a provider or model (`params` in openclaw-manual.json).
+/// Set on a provider entry as defaults for all its models; a model's own `params`
+/// override individual fields.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerationParams {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop: Option<Vec<String>>,
+    /// "low" / "medium" / "high" — sent as `reasoning_effort` to OpenAI-compatible APIs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning_effort: Option<String>,
+    /// Provider-specific request fields passed through verbatim (e.g. `response_format`)
+    #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl GenerationParams {
+    /// Layer `overrides` on top of these params (set fields in `overrides` win)
+    pub fn merged_with(&self, overrides: &GenerationParams) -> GenerationParams {
+        let mut extra = self.extra.clone();
+        extra.extend(overrides.extra.clone());
+        GenerationParams {
+            temperature: overrides.temperature.or(self.temperature),
+            top_p: overrides.top_p.or(self.top_p),
+            max_tokens: overrides.max_tokens.or(self.max_tokens),
+            stop: overrides.stop.clone().or_else(|| self.stop.clone()),
+            reasoning_effort: overrides.reasoning_effort.clone().or_else(|| self.reasoning_effort.clone()),
+            extra,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == GenerationParams::default()
+    }
+}
+
+/// Resolve the generation params for a model from its provider's config entry.
+/// A model-level `maxTokens` is honored when `params.maxTokens` is not set.
+pub fn resolve_generation_params(provider: &serde_json::Value, model_id: &str) -> GenerationParams {
+    let parse = |v: Option<&serde_json::Value>| -> GenerationParams {
+        v.and_then(|p| serde_json::from_value(p.clone()).ok())
+            .unwrap_or_default()
+    };
+
+    let model = provider
+        .get("models")
+        .and_then(|m| m.as_array())
+        .and_then(|arr| arr.iter().find(|m| m.get("id").and_then(|v| v.as_str()) == Some(model_id)));
+
+    let mut params = parse(provider.get("params")).merged_with(&parse(model.and_then(|m| m.get("params"))));
+    if params.max_tokens.is_none() {
+        params.max_tokens = model
+            .and_then(|m| m.get("maxTokens"))
+            .and_then(|v| v.as_u64())
+            .map(|v| v as u32);
+    }
+    params
+}
+
+/// Well-known context windows, matched by longest model ID prefix
+const BUILTIN_CONTEXT_WINDOWS: &[(&str, u64)] = &[
+    ("claude-", 200_000),
+    ("gpt-4o", 128_000),
+    ("gpt-4.1", 1_047_576),
+    ("gpt-4-turbo", 128_000),
+    ("gpt-4", 8_192),
+    ("gpt-5", 400_000),
+    ("o1", 200_000),
+    ("o3", 200_000),
+    ("o4", 200_000),
+    ("gemini-2.5", 1_048_576),
+    ("gemini-2.0", 1_048_576),
+    ("kimi-k2", 262_144),
+];
+
+/// Context window for a "provider/model" label or bare model ID: the model's
+/// configured `contextWindow` first, then well-known defaults by model family
+pub fn context_window(config: &serde_json::Value, model: &str) -> Option<u64> {
+    let (provider_name, model_id) = match model.split_once('/') {
+        Some((p, m)) => (Some(p), m),
+        None => (None, model),
+    };
+    let configured = config
+        .get("models")
+        .and_then(|m| m.get("providers"))
+        .and_then(|p| p.as_object())
+        .and_then(|providers| {
+            providers
+                .iter()
+                .filter(|(name, _)| provider_name.is_none_or(|p| p == name.as_str()))
+                .filter_map(|(_, provider)| provider.get("models").and_then(|m| m.as_array()))
+                .flatten()
+                .find(|m| m.get("id").and_then(|v| v.as_str()) == Some(model_id))
+                .and_then(|m| m.get("contextWindow"))
+                .and_then(|v| v.as_u64())
+        });
+    configured.or_else(|| {
+        BUILTIN_CONTEXT_WINDOWS
+            .iter()
+            .filter(|(prefix, _)| model_id.starts_with(prefix))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, window)| *window)
+    })
 }
 
 /// Load all providers and their models from openclaw-manual.json
@@ -73,6 +187,7 @@ pub fn load_providers(config_path: &Path) -> Result<Vec<ProviderInfo>> {
                             reasoning,
                             context_window,
                             local: is_local,
+                            params: resolve_generation_params(provider, id),
                         })
                     })
                     .collect()
@@ -165,6 +280,39 @@ mod tests {
         std::fs::remove_file(&tmp).ok();
     }
 
+    #[test]
+    fn test_generation_params_model_overrides_provider() {
+        let provider = serde_json::json!({
+            "baseUrl": "https://api.moonshot.ai/v1",
+            "params": {"temperature": 0.8, "topP": 0.9, "extra": {"user": "aitan"}},
+            "models": [
+                {"id": "kimi-k2.5", "params": {"temperature": 0.1, "reasoningEffort": "high",
+                    "extra": {"response_format": {"type": "json_object"}}}},
+                {"id": "kimi-chat", "maxTokens": 2048}
+            ]
+        });
+
+        let coder = resolve_generation_params(&provider, "kimi-k2.5");
+        assert_eq!(coder.temperature, Some(0.1));
+        assert_eq!(coder.top_p, Some(0.9));
+        assert_eq!(coder.reasoning_effort.as_deref(), Some("high"));
+        assert_eq!(coder.extra.len(), 2);
+        assert!(coder.max_tokens.is_none());
+
+        let chat = resolve_generation_params(&provider, "kimi-chat");
+        assert_eq!(chat.temperature, Some(0.8));
+        assert_eq!(chat.max_tokens, Some(2048));
+
+        let unknown = resolve_generation_params(&provider, "nope");
+        assert_eq!(unknown.temperature, Some(0.8));
+    }
+
+    #[test]
+    fn test_generation_params_empty() {
+        let provider = serde_json::json!({"models": [{"id": "llama3.2:1b"}]});
+        assert!(resolve_generation_params(&provider, "llama3.2:1b").is_empty());
+    }
+
     #[test]
     fn test_parse_fallback_chain() {
         let json = r#"{
@@ -194,4 +342,17 @@ mod tests {
 
         std::fs::remove_file(&tmp).ok();
     }
+
+    #[test]
+    fn test_context_window() {
+        let config = serde_json::json!({
+            "models": {"providers": {"ollama": {"models": [{"id": "qwen3:8b", "contextWindow": 32768}]}}}
+        });
+        assert_eq!(context_window(&config, "ollama/qwen3:8b"), Some(32768));
+        assert_eq!(context_window(&config, "qwen3:8b"), Some(32768));
+        assert_eq!(context_window(&config, "anthropic/claude-sonnet-4-5"), Some(200_000));
+        assert_eq!(context_window(&config, "gpt-4o-mini"), Some(128_000));
+        assert_eq!(context_window(&config, "gpt-4-0613"), Some(8_192));
+        assert_eq!(context_window(&config, "mystery"), None);
+    }
 }
@@ -0,0 +1,109 @@
+use anyhow::Result;
+use sqlx::PgPool;
+
+/// Saved progress of an agent turn that has not produced its final answer yet
+#[derive(Debug, Clone, PartialEq)]
+pub struct TurnCheckpoint {
+    pub turn_id: String,
+    pub session_key: String,
+    pub agent_name: String,
+    pub user_message: String,
+    /// Images attached to the user message
+    pub image_urls: Vec<String>,
+    /// Messages the turn generated so far (JSON array of chat messages)
+    pub messages: serde_json::Value,
+    /// LLM rounds completed
+    pub rounds: i32,
+}
+
+type CheckpointRow = (String, String, String, String, Vec<String>, serde_json::Value, i32);
+
+fn from_row(row: CheckpointRow) -> TurnCheckpoint {
+    TurnCheckpoint {
+        turn_id: row.0,
+        session_key: row.1,
+        agent_name: row.2,
+        user_message: row.3,
+        image_urls: row.4,
+        messages: row.5,
+        rounds: row.6,
+    }
+}
+
+/// Insert or update a turn's checkpoint
+pub async fn save_checkpoint(pool: &PgPool, checkpoint: &TurnCheckpoint) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO turn_checkpoints (turn_id, session_key, agent_name, user_message, image_urls, messages, rounds)
+         VALUES ($1, $2, $3, $4, $5, $6, $7)
+         ON CONFLICT (turn_id) DO UPDATE SET
+            messages = EXCLUDED.messages,
+            rounds = EXCLUDED.rounds,
+            updated_at = now()"
+    )
+    .bind(&checkpoint.turn_id)
+    .bind(&checkpoint.session_key)
+    .bind(&checkpoint.agent_name)
+    .bind(&checkpoint.user_message)
+    .bind(&checkpoint.image_urls)
+    .bind(&checkpoint.messages)
+    .bind(checkpoint.rounds)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Load a checkpoint by turn ID
+pub async fn load_checkpoint(pool: &PgPool, turn_id: &str) -> Result<Option<TurnCheckpoint>> {
+    let row: Option<CheckpointRow> = sqlx::query_as(
+        "SELECT turn_id, session_key, agent_name, user_message, image_urls, messages, rounds
+         FROM turn_checkpoints WHERE turn_id = $1"
+    )
+    .bind(turn_id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.map(from_row))
+}
+
+/// The most recently updated checkpoint of a session
+pub async fn latest_checkpoint(pool: &PgPool, session_key: &str) -> Result<Option<TurnCheckpoint>> {
+    let row: Option<CheckpointRow> = sqlx::query_as(
+        "SELECT turn_id, session_key, agent_name, user_message, image_urls, messages, rounds
+         FROM turn_checkpoints WHERE session_key = $1
+         ORDER BY updated_at DESC LIMIT 1"
+    )
+    .bind(session_key)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.map(from_row))
+}
+
+/// Every checkpoint of a session, oldest first
+pub async fn session_checkpoints(pool: &PgPool, session_key: &str) -> Result<Vec<TurnCheckpoint>> {
+    let rows: Vec<CheckpointRow> = sqlx::query_as(
+        "SELECT turn_id, session_key, agent_name, user_message, image_urls, messages, rounds
+         FROM turn_checkpoints WHERE session_key = $1
+         ORDER BY created_at"
+    )
+    .bind(session_key)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().map(from_row).collect())
+}
+
+/// Delete a turn's checkpoint (the turn finished)
+pub async fn delete_checkpoint(pool: &PgPool, turn_id: &str) -> Result<()> {
+    sqlx::query("DELETE FROM turn_checkpoints WHERE turn_id = $1")
+        .bind(turn_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Delete every checkpoint of a session, returning how many were removed
+pub async fn delete_session_checkpoints(pool: &PgPool, session_key: &str) -> Result<u64> {
+    let result = sqlx::query("DELETE FROM turn_checkpoints WHERE session_key = $1")
+        .bind(session_key)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
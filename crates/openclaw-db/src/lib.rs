@@ -1,9 +1,12 @@
+pub mod checkpoints;
 pub mod context;
 pub mod cron;
 pub mod llm_log;
+pub mod memories;
 pub mod messages;
 pub mod metrics;
 pub mod sessions;
+pub mod spend;
 
 use anyhow::Result;
 use sqlx::postgres::PgPoolOptions;
@@ -19,7 +19,11 @@ pub async fn record_llm_call(
     usage_prompt_tokens: i32,
     usage_completion_tokens: i32,
     usage_total_tokens: i32,
+    usage_cache_read_tokens: i32,
+    usage_cache_write_tokens: i32,
     latency_ms: i32,
+    cost_micros: i64,
+    route: Option<&str>,
     error: Option<&str>,
 ) -> Result<()> {
     sqlx::query(
@@ -27,8 +31,9 @@ pub async fn record_llm_call(
             id, session_key, model, provider_attempt, messages_count,
             request_tokens_est, streaming, response_content, response_reasoning,
             response_tool_calls, tool_call_names, usage_prompt_tokens,
-            usage_completion_tokens, usage_total_tokens, latency_ms, error
-        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)"
+            usage_completion_tokens, usage_total_tokens, usage_cache_read_tokens,
+            usage_cache_write_tokens, latency_ms, cost_micros, route, error
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20)"
     )
     .bind(id)
     .bind(session_key)
@@ -44,7 +49,11 @@ pub async fn record_llm_call(
     .bind(usage_prompt_tokens)
     .bind(usage_completion_tokens)
     .bind(usage_total_tokens)
+    .bind(usage_cache_read_tokens)
+    .bind(usage_cache_write_tokens)
     .bind(latency_ms)
+    .bind(cost_micros)
+    .bind(route)
     .bind(error)
     .execute(pool)
     .await?;
@@ -0,0 +1,112 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+/// A note or conversation snippet stored for recall
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemoryRow {
+    pub id: String,
+    pub agent_name: String,
+    pub text: String,
+    /// `note` (stored by the agent) or `conversation` (indexed from a turn)
+    pub source: String,
+    pub tags: Vec<String>,
+    pub session_key: Option<String>,
+    pub embedding: Option<Vec<f32>>,
+    pub created_at: DateTime<Utc>,
+}
+
+type Row = (String, String, String, String, Vec<String>, Option<String>, Option<Vec<f32>>, DateTime<Utc>);
+
+fn from_row(row: Row) -> MemoryRow {
+    MemoryRow {
+        id: row.0,
+        agent_name: row.1,
+        text: row.2,
+        source: row.3,
+        tags: row.4,
+        session_key: row.5,
+        embedding: row.6,
+        created_at: row.7,
+    }
+}
+
+/// Store a memory (an existing ID is overwritten)
+pub async fn insert_memory(pool: &PgPool, memory: &MemoryRow) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO memories (id, agent_name, text, source, tags, session_key, embedding, created_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+         ON CONFLICT (id) DO UPDATE SET
+            text = EXCLUDED.text,
+            tags = EXCLUDED.tags,
+            embedding = EXCLUDED.embedding"
+    )
+    .bind(&memory.id)
+    .bind(&memory.agent_name)
+    .bind(&memory.text)
+    .bind(&memory.source)
+    .bind(&memory.tags)
+    .bind(&memory.session_key)
+    .bind(&memory.embedding)
+    .bind(memory.created_at)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// An agent's `limit` newest memories. With `session_key`, only that session's
+/// memories and the shared ones (stored without a session).
+pub async fn list_memories(
+    pool: &PgPool,
+    agent_name: &str,
+    session_key: Option<&str>,
+    limit: usize,
+) -> Result<Vec<MemoryRow>> {
+    let rows: Vec<Row> = sqlx::query_as(
+        "SELECT id, agent_name, text, source, tags, session_key, embedding, created_at
+         FROM memories
+         WHERE agent_name = $1 AND ($2::TEXT IS NULL OR session_key IS NULL OR session_key = $2)
+         ORDER BY created_at DESC
+         LIMIT $3"
+    )
+    .bind(agent_name)
+    .bind(session_key)
+    .bind(limit.min(i64::MAX as usize) as i64)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().map(from_row).collect())
+}
+
+/// Delete one of an agent's memories, only if it belongs to `session_key` when
+/// one is given; returns whether it was deleted
+pub async fn delete_memory(pool: &PgPool, agent_name: &str, session_key: Option<&str>, id: &str) -> Result<bool> {
+    let result = sqlx::query(
+        "DELETE FROM memories
+         WHERE agent_name = $1 AND id = $2 AND ($3::TEXT IS NULL OR session_key = $3)"
+    )
+    .bind(agent_name)
+    .bind(id)
+    .bind(session_key)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Keep only the newest `keep` memories of one source in a session; returns how many were deleted
+pub async fn prune_memories(pool: &PgPool, agent_name: &str, session_key: &str, source: &str, keep: usize) -> Result<u64> {
+    let result = sqlx::query(
+        "DELETE FROM memories WHERE id IN (
+            SELECT id FROM memories
+            WHERE agent_name = $1 AND session_key = $2 AND source = $3
+            ORDER BY created_at DESC
+            OFFSET $4
+         )"
+    )
+    .bind(agent_name)
+    .bind(session_key)
+    .bind(source)
+    .bind(keep as i64)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
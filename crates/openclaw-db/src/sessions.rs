@@ -11,6 +11,8 @@ pub struct SessionInfo {
     pub updated_at_ms: i64,
     pub message_count: i64,
     pub total_tokens: i64,
+    /// Running LLM cost in micro-dollars
+    pub total_cost_micros: i64,
 }
 
 /// A single message loaded from the database
@@ -30,6 +32,7 @@ pub struct DbStats {
     pub session_count: i64,
     pub message_count: i64,
     pub total_tokens: i64,
+    pub total_cost_micros: i64,
     pub oldest_ms: Option<i64>,
     pub newest_ms: Option<i64>,
 }
@@ -74,6 +77,52 @@ pub async fn add_tokens(pool: &PgPool, session_key: &str, tokens: i64) -> Result
     Ok(())
 }
 
+/// Add LLM cost (micro-dollars) to a session's running total
+pub async fn add_cost(pool: &PgPool, session_key: &str, cost_micros: i64) -> Result<()> {
+    sqlx::query(
+        "UPDATE sessions SET total_cost_micros = total_cost_micros + $1, updated_at = now() WHERE session_key = $2"
+    )
+    .bind(cost_micros)
+    .bind(session_key)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Rolling summary of the history compacted out of a session's context window
+#[derive(Debug, Clone, Default)]
+pub struct SessionSummary {
+    pub text: String,
+    /// How many of the session's oldest messages the summary covers
+    pub covered_messages: i64,
+}
+
+/// Load a session's conversation summary, if it has one
+pub async fn load_summary(pool: &PgPool, session_key: &str) -> Result<Option<SessionSummary>> {
+    let row: Option<(Option<String>, i32)> = sqlx::query_as(
+        "SELECT summary, summarized_messages FROM sessions WHERE session_key = $1"
+    )
+    .bind(session_key)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.and_then(|(text, covered)| {
+        text.map(|text| SessionSummary { text, covered_messages: covered as i64 })
+    }))
+}
+
+/// Replace a session's conversation summary
+pub async fn save_summary(pool: &PgPool, session_key: &str, text: &str, covered_messages: i64) -> Result<()> {
+    sqlx::query(
+        "UPDATE sessions SET summary = $1, summarized_messages = $2, updated_at = now() WHERE session_key = $3"
+    )
+    .bind(text)
+    .bind(covered_messages as i32)
+    .bind(session_key)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
 /// Get session ID by key
 pub async fn get_session_id(pool: &PgPool, session_key: &str) -> Result<Option<i64>> {
     let row: Option<(i64,)> = sqlx::query_as::<_, (i64,)>(
@@ -116,9 +165,9 @@ pub async fn load_messages(pool: &PgPool, session_key: &str) -> Result<Vec<Sessi
 
 /// List recent sessions for an agent
 pub async fn list_sessions(pool: &PgPool, agent_name: &str, limit: i64) -> Result<Vec<SessionInfo>> {
-    let rows = sqlx::query_as::<_, (String, String, String, chrono::DateTime<chrono::Utc>, chrono::DateTime<chrono::Utc>, i64, i64)>(
+    let rows = sqlx::query_as::<_, (String, String, String, chrono::DateTime<chrono::Utc>, chrono::DateTime<chrono::Utc>, i64, i64, i64)>(
         "SELECT s.session_key, s.agent_name, s.model, s.created_at, s.updated_at,
-                s.total_tokens, COUNT(m.id) as msg_count
+                s.total_tokens, s.total_cost_micros, COUNT(m.id) as msg_count
          FROM sessions s
          LEFT JOIN messages m ON m.session_id = s.id
          WHERE s.agent_name = $1
@@ -131,7 +180,7 @@ pub async fn list_sessions(pool: &PgPool, agent_name: &str, limit: i64) -> Resul
     .fetch_all(pool)
     .await?;
 
-    Ok(rows.into_iter().map(|(key, agent, model, created, updated, tokens, msgs)| {
+    Ok(rows.into_iter().map(|(key, agent, model, created, updated, tokens, cost, msgs)| {
         SessionInfo {
             session_key: key,
             agent_name: agent,
@@ -139,6 +188,7 @@ pub async fn list_sessions(pool: &PgPool, agent_name: &str, limit: i64) -> Resul
             created_at_ms: created.timestamp_millis(),
             updated_at_ms: updated.timestamp_millis(),
             total_tokens: tokens,
+            total_cost_micros: cost,
             message_count: msgs,
         }
     }).collect())
@@ -179,11 +229,12 @@ pub async fn delete_session(pool: &PgPool, session_key: &str) -> Result<usize> {
 
 /// Get database statistics for an agent
 pub async fn db_stats(pool: &PgPool, agent_name: &str) -> Result<DbStats> {
-    let row = sqlx::query_as::<_, (i64, i64, i64, Option<chrono::DateTime<chrono::Utc>>, Option<chrono::DateTime<chrono::Utc>>)>(
+    let row = sqlx::query_as::<_, (i64, i64, i64, i64, Option<chrono::DateTime<chrono::Utc>>, Option<chrono::DateTime<chrono::Utc>>)>(
         "SELECT
             COUNT(DISTINCT s.id) as session_count,
             COUNT(m.id) as message_count,
             COALESCE(SUM(DISTINCT s.total_tokens), 0) as total_tokens,
+            (SELECT COALESCE(SUM(total_cost_micros), 0)::BIGINT FROM sessions WHERE agent_name = $1) as total_cost_micros,
             MIN(s.created_at) as oldest,
             MAX(s.updated_at) as newest
          FROM sessions s
@@ -198,8 +249,9 @@ pub async fn db_stats(pool: &PgPool, agent_name: &str) -> Result<DbStats> {
         session_count: row.0,
         message_count: row.1,
         total_tokens: row.2,
-        oldest_ms: row.3.map(|dt| dt.timestamp_millis()),
-        newest_ms: row.4.map(|dt| dt.timestamp_millis()),
+        total_cost_micros: row.3,
+        oldest_ms: row.4.map(|dt| dt.timestamp_millis()),
+        newest_ms: row.5.map(|dt| dt.timestamp_millis()),
     })
 }
 
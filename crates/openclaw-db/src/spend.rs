@@ -0,0 +1,62 @@
+use anyhow::Result;
+use sqlx::PgPool;
+
+/// Token and cost usage for one quota scope, today and this calendar month (UTC)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SpendTotals {
+    pub day_tokens: i64,
+    pub day_cost_micros: i64,
+    pub month_tokens: i64,
+    pub month_cost_micros: i64,
+}
+
+/// Add a turn's tokens and cost (micro-dollars) to today's row for a scope ("user" / "session")
+pub async fn record_spend(
+    pool: &PgPool,
+    scope: &str,
+    scope_key: &str,
+    tokens: i64,
+    cost_micros: i64,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO spend_ledger (scope, scope_key, day, tokens, cost_micros)
+         VALUES ($1, $2, (now() AT TIME ZONE 'UTC')::date, $3, $4)
+         ON CONFLICT (scope, scope_key, day) DO UPDATE SET
+            tokens = spend_ledger.tokens + EXCLUDED.tokens,
+            cost_micros = spend_ledger.cost_micros + EXCLUDED.cost_micros"
+    )
+    .bind(scope)
+    .bind(scope_key)
+    .bind(tokens)
+    .bind(cost_micros)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Today's and this month's totals for a scope
+pub async fn spend_totals(pool: &PgPool, scope: &str, scope_key: &str) -> Result<SpendTotals> {
+    let row = sqlx::query_as::<_, (i64, i64, i64, i64)>(
+        "WITH today AS (SELECT (now() AT TIME ZONE 'UTC')::date AS d)
+         SELECT
+            COALESCE(SUM(tokens) FILTER (WHERE day = today.d), 0)::BIGINT,
+            COALESCE(SUM(cost_micros) FILTER (WHERE day = today.d), 0)::BIGINT,
+            COALESCE(SUM(tokens), 0)::BIGINT,
+            COALESCE(SUM(cost_micros), 0)::BIGINT
+         FROM today
+         LEFT JOIN spend_ledger ON scope = $1 AND scope_key = $2
+            AND day >= date_trunc('month', today.d)::date
+         GROUP BY today.d"
+    )
+    .bind(scope)
+    .bind(scope_key)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(SpendTotals {
+        day_tokens: row.0,
+        day_cost_micros: row.1,
+        month_tokens: row.2,
+        month_cost_micros: row.3,
+    })
+}
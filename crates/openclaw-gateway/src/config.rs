@@ -1,4 +1,11 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tracing::warn;
+use openclaw_agent::approval::ApprovalPolicy;
+use openclaw_agent::runtime::TurnLimits;
+use openclaw_agent::sandbox::{ExecBackend, ExecLimits, SandboxPolicy};
+use openclaw_agent::tool_policy::ToolPolicy;
 use openclaw_agent::tools::mcp_bridge::McpServerConfig;
 
 /// Gateway configuration
@@ -7,11 +14,38 @@ pub struct GatewayConfig {
     pub telegram: TelegramConfig,
     #[serde(default)]
     pub discord: Option<DiscordConfig>,
+    /// Default agent profile, used for anything the routing rules don't match
     pub agent: AgentConfig,
+    /// Further agent profiles by name (the key is the profile's name)
+    #[serde(default)]
+    pub agents: HashMap<String, AgentConfig>,
+    /// Which profile serves a Telegram chat, Discord channel/guild or webhook path
+    #[serde(default)]
+    pub routing: RoutingConfig,
     #[serde(default)]
     pub webhook: Option<WebhookConfig>,
     #[serde(default)]
     pub mcp_servers: Vec<McpServerConfig>,
+    #[serde(default)]
+    pub quotas: Option<QuotaConfig>,
+}
+
+/// Spend quotas (daily / monthly, UTC) per user ID and per session key
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QuotaConfig {
+    #[serde(default)]
+    pub per_user: QuotaLimits,
+    #[serde(default)]
+    pub per_session: QuotaLimits,
+}
+
+/// Token and cost limits for one quota scope; unset limits are unlimited
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct QuotaLimits {
+    pub daily_tokens: Option<u64>,
+    pub monthly_tokens: Option<u64>,
+    pub daily_cost_cents: Option<u64>,
+    pub monthly_cost_cents: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,14 +66,72 @@ pub struct TelegramConfig {
     pub allowed_user_ids: Vec<i64>,
 }
 
+/// Rules mapping incoming messages to agent profiles (names in `agents`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RoutingConfig {
+    /// Telegram chat ID → profile
+    #[serde(default)]
+    pub telegram_chats: HashMap<String, String>,
+    /// Discord channel ID → profile (checked before the guild)
+    #[serde(default)]
+    pub discord_channels: HashMap<String, String>,
+    /// Discord guild ID → profile
+    #[serde(default)]
+    pub discord_guilds: HashMap<String, String>,
+    /// `/webhook/<path>` → profile
+    #[serde(default)]
+    pub webhook_paths: HashMap<String, String>,
+}
+
+/// Where a message came from, for picking its agent profile
+#[derive(Debug, Clone, Copy)]
+pub enum Route<'a> {
+    Telegram { chat_id: i64 },
+    Discord { channel_id: &'a str, guild_id: Option<&'a str> },
+    Webhook { path: &'a str },
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentConfig {
+    #[serde(default = "default_agent_name")]
     pub name: String,
     #[serde(default = "default_true")]
     pub fallback: bool,
     pub model: Option<String>,
+    /// Explicit "provider/model" chain, tried in order (overrides `fallback` / `model`)
+    #[serde(default)]
+    pub models: Vec<String>,
+    /// Workspace directory (default `~/.openclaw/workspace-<name>`)
+    #[serde(default)]
+    pub workspace_dir: Option<String>,
+    /// Text appended to the workspace system prompt (persona overlay)
+    #[serde(default)]
+    pub system_prompt: Option<String>,
+    /// Tool allow/deny globs and call caps
+    #[serde(default)]
+    pub tools: ToolsConfig,
+    /// Record every turn's LLM calls as a replay cassette in this directory
+    /// (`openclaw agent replay <file>`)
+    #[serde(default)]
+    pub record_dir: Option<String>,
     #[serde(default)]
     pub sandbox: Option<SandboxConfig>,
+    /// Round cap, tool output limit and loop-detection thresholds for turns
+    #[serde(default)]
+    pub limits: TurnLimits,
+    /// Tool calls that need the user's go-ahead (Telegram / Discord buttons)
+    #[serde(default)]
+    pub approval: ApprovalPolicy,
+}
+
+/// Tool policy of an agent, with overrides by channel
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ToolsConfig {
+    #[serde(flatten)]
+    pub policy: ToolPolicy,
+    /// Overrides by channel: "telegram", "discord", "webhook", "cron"
+    #[serde(default)]
+    pub channels: HashMap<String, ToolPolicy>,
 }
 
 /// Optional sandbox configuration overrides
@@ -60,12 +152,133 @@ pub struct SandboxConfig {
     pub rate_limit_messages: Option<usize>,
     /// Rate limit: window in seconds
     pub rate_limit_window_secs: Option<u64>,
+    /// Directories tools may read from (empty = unrestricted)
+    #[serde(default)]
+    pub read_allow: Vec<String>,
+    /// Directories tools may write to (empty = unrestricted)
+    #[serde(default)]
+    pub write_allow: Vec<String>,
+    /// Whether web tools may reach the network (default true)
+    pub network_allowed: Option<bool>,
+    /// Where exec/process commands run: "host" (default) or "namespace"
+    #[serde(default)]
+    pub exec_backend: ExecBackend,
+    /// CPU, memory and process limits for exec/process commands
+    #[serde(default)]
+    pub exec_limits: ExecLimits,
+    /// Overrides by channel: "telegram", "discord", "webhook", "cron"
+    #[serde(default)]
+    pub channels: HashMap<String, SandboxOverride>,
+    /// Overrides by user ID, applied after the channel override
+    #[serde(default)]
+    pub users: HashMap<String, SandboxOverride>,
+}
+
+/// Per-channel or per-user changes to the sandbox; unset fields are inherited
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SandboxOverride {
+    /// Extra commands to block (added to the inherited list)
+    #[serde(default)]
+    pub blocked_commands: Vec<String>,
+    pub max_exec_timeout_secs: Option<u64>,
+    pub read_allow: Option<Vec<String>>,
+    pub write_allow: Option<Vec<String>>,
+    pub network_allowed: Option<bool>,
+    pub exec_backend: Option<ExecBackend>,
+    /// Replaces the inherited exec limits as a whole
+    pub exec_limits: Option<ExecLimits>,
+}
+
+impl SandboxConfig {
+    /// Tool sandbox for a turn on `channel` from `user_id`
+    pub fn policy_for(&self, channel: &str, user_id: &str) -> SandboxPolicy {
+        let mut policy = SandboxPolicy::from(self);
+        for ov in [self.channels.get(channel), self.users.get(user_id)].into_iter().flatten() {
+            ov.apply(&mut policy);
+        }
+        policy
+    }
+}
+
+impl From<&SandboxConfig> for SandboxPolicy {
+    fn from(config: &SandboxConfig) -> Self {
+        let mut policy = SandboxPolicy::default();
+        policy.command_blocklist.extend(config.blocked_commands.iter().cloned());
+        policy.read_allow = config.read_allow.clone();
+        policy.write_allow = config.write_allow.clone();
+        if let Some(secs) = config.max_exec_timeout_secs {
+            policy.max_exec_timeout_secs = secs;
+        }
+        if let Some(secs) = config.turn_timeout_secs {
+            policy.turn_timeout_secs = secs;
+        }
+        if let Some(allowed) = config.network_allowed {
+            policy.network_allowed = allowed;
+        }
+        policy.exec_backend = config.exec_backend;
+        policy.exec_limits = config.exec_limits.clone();
+        policy
+    }
+}
+
+impl SandboxOverride {
+    fn apply(&self, policy: &mut SandboxPolicy) {
+        policy.command_blocklist.extend(self.blocked_commands.iter().cloned());
+        if let Some(ref dirs) = self.read_allow {
+            policy.read_allow = dirs.clone();
+        }
+        if let Some(ref dirs) = self.write_allow {
+            policy.write_allow = dirs.clone();
+        }
+        if let Some(secs) = self.max_exec_timeout_secs {
+            policy.max_exec_timeout_secs = secs;
+        }
+        if let Some(allowed) = self.network_allowed {
+            policy.network_allowed = allowed;
+        }
+        if let Some(backend) = self.exec_backend {
+            policy.exec_backend = backend;
+        }
+        if let Some(ref limits) = self.exec_limits {
+            policy.exec_limits = limits.clone();
+        }
+    }
+}
+
+impl AgentConfig {
+    /// Sandbox policy for a turn; the built-in defaults when no sandbox is configured
+    pub fn sandbox_policy(&self, channel: &str, user_id: &str) -> SandboxPolicy {
+        self.sandbox
+            .as_ref()
+            .map(|sb| sb.policy_for(channel, user_id))
+            .unwrap_or_default()
+    }
+
+    /// Tool policy for a turn on `channel`
+    pub fn tool_policy(&self, channel: &str) -> ToolPolicy {
+        match self.tools.channels.get(channel) {
+            Some(over) => self.tools.policy.with_override(over),
+            None => self.tools.policy.clone(),
+        }
+    }
+
+    /// Workspace directory of this profile
+    pub fn workspace_dir(&self) -> PathBuf {
+        match self.workspace_dir {
+            Some(ref dir) => PathBuf::from(dir),
+            None => openclaw_agent::workspace::resolve_workspace_dir(&self.name),
+        }
+    }
 }
 
 fn default_true() -> bool {
     true
 }
 
+fn default_agent_name() -> String {
+    "main".to_string()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -122,6 +335,82 @@ mod tests {
         assert_eq!(sb.max_concurrent, Some(2));
     }
 
+    #[test]
+    fn test_sandbox_policy_overrides() {
+        let json = r#"{
+            "telegram": { "bot_token": "t", "allowed_user_ids": [] },
+            "agent": {
+                "name": "a",
+                "sandbox": {
+                    "blocked_commands": ["curl"],
+                    "max_exec_timeout_secs": 30,
+                    "write_allow": ["/srv/agent"],
+                    "exec_limits": { "cpu_secs": 20, "memory_mb": 1024 },
+                    "channels": {
+                        "discord": {
+                            "blocked_commands": ["git push"], "network_allowed": false, "exec_backend": "namespace",
+                            "exec_limits": { "cpu_secs": 5, "max_pids": 32 }
+                        }
+                    },
+                    "users": {
+                        "42": { "write_allow": [], "max_exec_timeout_secs": 300 }
+                    }
+                }
+            }
+        }"#;
+        let config: GatewayConfig = serde_json::from_str(json).unwrap();
+
+        let tg = config.agent.sandbox_policy("telegram", "7");
+        assert!(tg.is_command_blocked("curl https://example.com").is_some());
+        assert!(tg.is_command_blocked("shutdown now").is_some()); // defaults kept
+        assert!(tg.is_command_blocked("git push").is_none());
+        assert_eq!(tg.max_exec_timeout_secs, 30);
+        assert!(!tg.can_write("/tmp/x"));
+        assert!(tg.network_allowed);
+        assert_eq!(tg.exec_backend, ExecBackend::Host);
+        assert_eq!(tg.exec_limits.cpu_secs, Some(20));
+        assert_eq!(tg.exec_limits.memory_mb, Some(1024));
+
+        let dc = config.agent.sandbox_policy("discord", "7");
+        assert!(dc.is_command_blocked("git push origin main").is_some());
+        assert!(dc.is_command_blocked("curl x").is_some());
+        assert!(!dc.network_allowed);
+        assert_eq!(dc.exec_backend, ExecBackend::Namespace);
+        assert_eq!(dc.exec_limits.cpu_secs, Some(5));
+        assert_eq!(dc.exec_limits.max_pids, Some(32));
+        assert_eq!(dc.exec_limits.memory_mb, None); // replaced, not merged field by field
+
+        let admin = config.agent.sandbox_policy("discord", "42");
+        assert_eq!(admin.max_exec_timeout_secs, 300);
+        assert!(admin.can_write("/tmp/x"));
+        assert!(!admin.network_allowed); // channel override still applies
+
+        let unconfigured: GatewayConfig = serde_json::from_str(
+            r#"{ "telegram": { "bot_token": "t", "allowed_user_ids": [] }, "agent": { "name": "a" } }"#,
+        ).unwrap();
+        assert_eq!(unconfigured.agent.sandbox_policy("telegram", "7").max_exec_timeout_secs, 60);
+    }
+
+    #[test]
+    fn test_parse_turn_limits() {
+        let json = r#"{
+            "telegram": { "bot_token": "t", "allowed_user_ids": [] },
+            "agent": {
+                "name": "research",
+                "limits": {
+                    "max_rounds": 60,
+                    "loop_detection": { "warning_threshold": 12 }
+                }
+            }
+        }"#;
+        let config: GatewayConfig = serde_json::from_str(json).unwrap();
+        let limits = &config.agent.limits;
+        assert_eq!(limits.max_rounds, 60);
+        assert_eq!(limits.max_tool_output_tokens, 8000);
+        assert_eq!(limits.loop_detection.warning_threshold, 12);
+        assert_eq!(limits.loop_detection.critical_threshold, 15);
+    }
+
     #[test]
     fn test_parse_webhook_config() {
         let json = r#"{
@@ -134,6 +423,105 @@ mod tests {
         assert_eq!(wh.token, "secret-token-123");
     }
 
+    #[test]
+    fn test_parse_quota_config() {
+        let json = r#"{
+            "telegram": { "bot_token": "t", "allowed_user_ids": [] },
+            "agent": { "name": "a" },
+            "quotas": {
+                "per_user": { "daily_tokens": 500000, "monthly_cost_cents": 2000 },
+                "per_session": { "daily_cost_cents": 100 }
+            }
+        }"#;
+        let config: GatewayConfig = serde_json::from_str(json).unwrap();
+        let quotas = config.quotas.unwrap();
+        assert_eq!(quotas.per_user.daily_tokens, Some(500000));
+        assert_eq!(quotas.per_user.monthly_cost_cents, Some(2000));
+        assert!(quotas.per_user.daily_cost_cents.is_none());
+        assert_eq!(quotas.per_session.daily_cost_cents, Some(100));
+    }
+
+    #[test]
+    fn test_agent_profiles_and_routing() {
+        let json = r#"{
+            "telegram": { "bot_token": "t", "allowed_user_ids": [] },
+            "agent": { "name": "main" },
+            "agents": {
+                "ops": {
+                    "models": ["local/qwen3", "openai/gpt-4o"],
+                    "workspace_dir": "/srv/ops",
+                    "system_prompt": "You are the on-call assistant.",
+                    "tools": { "allow": ["exec", "read", "web_fetch"], "deny": ["web_fetch"] },
+                    "sandbox": { "network_allowed": false }
+                },
+                "research": { "fallback": false, "model": "gpt-4" }
+            },
+            "routing": {
+                "telegram_chats": { "-1001": "ops" },
+                "discord_channels": { "555": "research" },
+                "discord_guilds": { "900": "ops" },
+                "webhook_paths": { "alerts": "ops" }
+            }
+        }"#;
+        let config: GatewayConfig = serde_json::from_str(json).unwrap();
+
+        let ops = config.routed(Route::Telegram { chat_id: -1001 }).agent;
+        assert_eq!(ops.name, "ops");
+        assert_eq!(ops.models, vec!["local/qwen3", "openai/gpt-4o"]);
+        assert_eq!(ops.workspace_dir(), PathBuf::from("/srv/ops"));
+        assert_eq!(ops.system_prompt.as_deref(), Some("You are the on-call assistant."));
+        let ops_tools = ops.tool_policy("telegram");
+        assert!(ops_tools.permits("exec"));
+        assert!(!ops_tools.permits("web_fetch"));
+        assert!(!ops_tools.permits("write"));
+        assert!(!ops.sandbox_policy("telegram", "1").network_allowed);
+
+        assert_eq!(config.routed(Route::Telegram { chat_id: 7 }).agent.name, "main");
+        let channel = Route::Discord { channel_id: "555", guild_id: Some("900") };
+        assert_eq!(config.routed(channel).agent.name, "research");
+        let guild = Route::Discord { channel_id: "556", guild_id: Some("900") };
+        assert_eq!(config.routed(guild).agent.name, "ops");
+        assert_eq!(config.route_profile(Route::Webhook { path: "alerts" }), Some("ops"));
+        assert_eq!(config.route_profile(Route::Webhook { path: "research" }), Some("research"));
+        assert_eq!(config.route_profile(Route::Webhook { path: "nope" }), None);
+
+        let main = &config.agent;
+        assert!(main.tool_policy("telegram").permits("web_fetch"));
+        assert!(main.workspace_dir().ends_with(".openclaw/workspace"));
+    }
+
+    #[test]
+    fn test_tool_policy_channel_override() {
+        let json = r#"{
+            "telegram": { "bot_token": "t", "allowed_user_ids": [] },
+            "agent": {
+                "name": "a",
+                "tools": {
+                    "deny": ["claude_code"],
+                    "per_turn": { "web_fetch": 10 },
+                    "channels": {
+                        "discord": { "allow": ["read", "grep", "find", "list_dir", "mcp_*"] },
+                        "webhook": { "per_minute": { "exec": 5 } }
+                    }
+                }
+            }
+        }"#;
+        let config: GatewayConfig = serde_json::from_str(json).unwrap();
+
+        let tg = config.agent.tool_policy("telegram");
+        assert!(tg.permits("exec"));
+        assert!(!tg.permits("claude_code"));
+        assert_eq!(tg.per_turn["web_fetch"], 10);
+
+        let dc = config.agent.tool_policy("discord");
+        assert!(dc.permits("mcp_docs_search"));
+        assert!(!dc.permits("exec"));
+
+        let wh = config.agent.tool_policy("webhook");
+        assert_eq!(wh.per_minute["exec"], 5);
+        assert_eq!(wh.per_turn["web_fetch"], 10);
+    }
+
     #[test]
     fn test_webhook_config_optional() {
         let json = r#"{
@@ -145,7 +533,54 @@ mod tests {
     }
 }
 
+/// Config file path: `GATEWAY_CONFIG` or the system default
+pub fn config_path() -> String {
+    std::env::var("GATEWAY_CONFIG").unwrap_or_else(|_| "/etc/openclaw-gateway/config.json".to_string())
+}
+
 impl GatewayConfig {
+    /// Agent profile by name: the default `agent` or an entry of `agents`
+    pub fn profile(&self, name: &str) -> Option<AgentConfig> {
+        if name == self.agent.name {
+            return Some(self.agent.clone());
+        }
+        self.agents.get(name).map(|profile| AgentConfig {
+            name: name.to_string(),
+            ..profile.clone()
+        })
+    }
+
+    /// Name of the profile the routing rules pick for `route`, if any.
+    /// A webhook path that is itself a profile name routes to that profile.
+    pub fn route_profile(&self, route: Route<'_>) -> Option<&str> {
+        let routing = &self.routing;
+        let rule = match route {
+            Route::Telegram { chat_id } => routing.telegram_chats.get(&chat_id.to_string()),
+            Route::Discord { channel_id, guild_id } => routing
+                .discord_channels
+                .get(channel_id)
+                .or_else(|| guild_id.and_then(|g| routing.discord_guilds.get(g))),
+            Route::Webhook { path } => routing
+                .webhook_paths
+                .get(path)
+                .or_else(|| self.agents.get_key_value(path).map(|(name, _)| name)),
+        };
+        rule.map(String::as_str)
+    }
+
+    /// This config with `agent` replaced by the profile routed to `route`;
+    /// anything unrouted stays with the default agent
+    pub fn routed(&self, route: Route<'_>) -> GatewayConfig {
+        let mut config = self.clone();
+        if let Some(name) = self.route_profile(route) {
+            match self.profile(name) {
+                Some(agent) => config.agent = agent,
+                None => warn!("Routing rule names unknown agent profile '{}'", name),
+            }
+        }
+        config
+    }
+
     /// Load from environment variables
     pub fn from_env() -> anyhow::Result<Self> {
         let bot_token = std::env::var("TELEGRAM_BOT_TOKEN")
@@ -174,10 +609,20 @@ impl GatewayConfig {
                 name: agent_name,
                 fallback: use_fallback,
                 model,
+                models: Vec::new(),
+                workspace_dir: None,
+                system_prompt: None,
+                tools: ToolsConfig::default(),
+                record_dir: None,
                 sandbox: None,
+                limits: Default::default(),
+                approval: Default::default(),
             },
+            agents: HashMap::new(),
+            routing: RoutingConfig::default(),
             webhook: None,
             mcp_servers: Vec::new(),
+            quotas: None,
         })
     }
 
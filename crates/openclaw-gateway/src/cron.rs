@@ -4,7 +4,6 @@ use openclaw_agent::llm::fallback::FallbackProvider;
 use openclaw_agent::llm::LlmProvider;
 use openclaw_agent::runtime::{AgentTurnConfig, AgentTurnResult};
 use openclaw_agent::tools::ToolRegistry;
-use openclaw_agent::workspace;
 use openclaw_core::cron::{CronJob, CronSchedule};
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -289,7 +288,7 @@ fn is_dst_eastern(utc: &chrono::DateTime<Utc>) -> bool {
 }
 
 async fn run_cron_agent_turn(config: &GatewayConfig, message: &str) -> Result<AgentTurnResult> {
-    let workspace_dir = workspace::resolve_workspace_dir(&config.agent.name);
+    let workspace_dir = config.agent.workspace_dir();
     let ws_str = workspace_dir.to_string_lossy().to_string();
 
     let session_key = format!("cron:{}:{}", config.agent.name, uuid::Uuid::new_v4());
@@ -299,6 +298,10 @@ async fn run_cron_agent_turn(config: &GatewayConfig, message: &str) -> Result<Ag
         session_key,
         workspace_dir: ws_str,
         minimal_context: true,
+        sandbox: config.agent.sandbox_policy("cron", ""),
+        limits: config.agent.limits.clone(),
+        approval: config.agent.approval.clone(),
+        system_prompt_overlay: config.agent.system_prompt.clone(),
     ..AgentTurnConfig::default()
     };
 
@@ -307,7 +310,7 @@ async fn run_cron_agent_turn(config: &GatewayConfig, message: &str) -> Result<Ag
         Err(e) => return Err(e),
     };
 
-    let mut tools = ToolRegistry::with_defaults();
+    let mut tools = ToolRegistry::with_defaults().with_policy(config.agent.tool_policy("cron"));
     tools.load_plugins(&workspace_dir);
 
     let result = openclaw_agent::runtime::run_agent_turn(
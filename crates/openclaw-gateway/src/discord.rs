@@ -38,6 +38,46 @@ pub struct DiscordUser {
     pub bot: Option<bool>,
 }
 
+/// A button press on a message component
+#[derive(Debug, Clone, Deserialize)]
+pub struct DiscordInteraction {
+    pub id: String,
+    pub token: String,
+    #[serde(default)]
+    pub data: Option<Value>,
+    /// Set in guilds (the presser is `member.user`)
+    #[serde(default)]
+    pub member: Option<DiscordMember>,
+    /// Set in DMs
+    #[serde(default)]
+    pub user: Option<DiscordUser>,
+    #[serde(default)]
+    pub message: Option<DiscordMessage>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DiscordMember {
+    pub user: DiscordUser,
+}
+
+impl DiscordInteraction {
+    /// The user who pressed the button
+    pub fn user(&self) -> Option<&DiscordUser> {
+        self.member.as_ref().map(|m| &m.user).or(self.user.as_ref())
+    }
+
+    pub fn custom_id(&self) -> Option<&str> {
+        self.data.as_ref()?.get("custom_id")?.as_str()
+    }
+}
+
+/// What the Gateway connection yields
+#[derive(Debug, Clone)]
+pub enum DiscordEvent {
+    Message(DiscordMessage),
+    Interaction(Box<DiscordInteraction>),
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct DiscordAttachment {
     pub id: String,
@@ -157,6 +197,85 @@ impl DiscordBot {
         Ok(last_id)
     }
 
+    /// Send a message with one row of buttons (`(label, custom_id, danger)`), returns message ID
+    pub async fn send_message_with_buttons(
+        &self,
+        channel_id: &str,
+        content: &str,
+        buttons: &[(&str, String, bool)],
+    ) -> Result<String> {
+        let components: Vec<Value> = buttons
+            .iter()
+            // Style 3 = success (green), 4 = danger (red)
+            .map(|(label, custom_id, danger)| serde_json::json!({
+                "type": 2,
+                "style": if *danger { 4 } else { 3 },
+                "label": label,
+                "custom_id": custom_id,
+            }))
+            .collect();
+        let body = serde_json::json!({
+            "content": content,
+            "components": [{ "type": 1, "components": components }],
+        });
+
+        let resp = self
+            .client
+            .post(format!("{}/channels/{}/messages", self.api_base, channel_id))
+            .header("Authorization", format!("Bot {}", self.token))
+            .json(&body)
+            .send()
+            .await
+            .context("Failed to send Discord message")?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let err_body = resp.text().await.unwrap_or_default();
+            anyhow::bail!("Discord sendMessage failed ({}): {}", status, err_body);
+        }
+
+        let msg: Value = resp.json().await?;
+        Ok(msg["id"].as_str().unwrap_or("").to_string())
+    }
+
+    /// Answer a button press by replacing the message's content and removing its buttons
+    pub async fn update_interaction_message(&self, interaction: &DiscordInteraction, content: &str) -> Result<()> {
+        let body = serde_json::json!({
+            "type": 7, // UPDATE_MESSAGE
+            "data": { "content": content, "components": [] },
+        });
+        self.interaction_callback(interaction, &body).await
+    }
+
+    /// Answer a button press with a message only the presser sees, leaving the message as is
+    pub async fn reply_to_interaction_ephemeral(&self, interaction: &DiscordInteraction, content: &str) -> Result<()> {
+        let body = serde_json::json!({
+            "type": 4, // CHANNEL_MESSAGE_WITH_SOURCE
+            "data": { "content": content, "flags": 64 }, // EPHEMERAL
+        });
+        self.interaction_callback(interaction, &body).await
+    }
+
+    async fn interaction_callback(&self, interaction: &DiscordInteraction, body: &Value) -> Result<()> {
+        let resp = self
+            .client
+            .post(format!(
+                "{}/interactions/{}/{}/callback",
+                self.api_base, interaction.id, interaction.token
+            ))
+            .json(body)
+            .send()
+            .await
+            .context("Failed to answer Discord interaction")?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let err_body = resp.text().await.unwrap_or_default();
+            anyhow::bail!("Discord interaction callback failed ({}): {}", status, err_body);
+        }
+        Ok(())
+    }
+
     /// Send a reply to a specific message (with retry on 5xx)
     pub async fn send_reply(
         &self,
@@ -416,10 +535,10 @@ impl DiscordBot {
     }
 
     /// Connect to the Discord Gateway via WebSocket and stream messages.
-    /// Returns a receiver that yields DiscordMessage events.
+    /// Returns a receiver that yields messages and button presses.
     pub async fn connect_gateway(
         self: Arc<Self>,
-    ) -> Result<mpsc::UnboundedReceiver<DiscordMessage>> {
+    ) -> Result<mpsc::UnboundedReceiver<DiscordEvent>> {
         let (msg_tx, msg_rx) = mpsc::unbounded_channel();
 
         let gateway_url = self.get_gateway_url().await?;
@@ -448,7 +567,7 @@ struct ResumeState {
 async fn run_gateway_loop(
     gateway_url: &str,
     token: &str,
-    msg_tx: mpsc::UnboundedSender<DiscordMessage>,
+    msg_tx: mpsc::UnboundedSender<DiscordEvent>,
 ) -> Result<()> {
     let mut reconnect_delay = 1u64;
     let mut consecutive_failures = 0u32;
@@ -531,7 +650,7 @@ async fn run_gateway_loop(
 async fn run_gateway_session(
     gateway_url: &str,
     token: &str,
-    msg_tx: &mpsc::UnboundedSender<DiscordMessage>,
+    msg_tx: &mpsc::UnboundedSender<DiscordEvent>,
     resume_state: &mut Option<ResumeState>,
 ) -> Result<()> {
     let (ws_stream, _) = tokio_tungstenite::connect_async(gateway_url)
@@ -691,13 +810,24 @@ async fn run_gateway_session(
                                                             if discord_msg.author.bot.unwrap_or(false) {
                                                                 continue;
                                                             }
-                                                            let _ = msg_tx.send(discord_msg);
+                                                            let _ = msg_tx.send(DiscordEvent::Message(discord_msg));
                                                         }
                                                         Err(e) => {
                                                             debug!("Failed to parse MESSAGE_CREATE: {}", e);
                                                         }
                                                     }
                                                 }
+                                            } else if event_name == "INTERACTION_CREATE" {
+                                                if let Some(ref d) = payload.d {
+                                                    match serde_json::from_value::<DiscordInteraction>(d.clone()) {
+                                                        Ok(interaction) => {
+                                                            let _ = msg_tx.send(DiscordEvent::Interaction(Box::new(interaction)));
+                                                        }
+                                                        Err(e) => {
+                                                            debug!("Failed to parse INTERACTION_CREATE: {}", e);
+                                                        }
+                                                    }
+                                                }
                                             } else if event_name == "READY" {
                                                 if let Some(ref d) = payload.d {
                                                     let user = d["user"]["username"].as_str().unwrap_or("?");
@@ -3,20 +3,52 @@ use tokio::sync::mpsc;
 use tracing::{info, warn};
 
 use openclaw_agent::llm::fallback::FallbackProvider;
+use openclaw_agent::llm::health::CircuitState;
 use openclaw_agent::llm::streaming::StreamEvent;
-use openclaw_agent::llm::LlmProvider;
 use openclaw_agent::runtime::{self, AgentTurnConfig};
 use openclaw_agent::tools::ToolRegistry;
-use openclaw_agent::workspace;
 
 use crate::config::GatewayConfig;
-use crate::discord::{DiscordBot, DiscordMessage};
+use crate::discord::{DiscordBot, DiscordInteraction, DiscordMessage};
 
 /// Minimum chars between Discord message edits
 const EDIT_MIN_CHARS: usize = 60;
 /// Minimum ms between Discord message edits
 const EDIT_MIN_MS: u64 = 500;
 
+/// Handle a button press (tool approval buttons)
+pub async fn handle_discord_interaction(
+    bot: &DiscordBot,
+    interaction: &DiscordInteraction,
+    config: &GatewayConfig,
+) -> Result<()> {
+    let Some(user) = interaction.user() else {
+        return Ok(());
+    };
+    let Some((approved, id)) = interaction.custom_id().and_then(crate::handler_utils::parse_approval_data) else {
+        return Ok(());
+    };
+    if let Some(ref discord_config) = config.discord {
+        let user_id_num: i64 = user.id.parse().unwrap_or(0);
+        if !discord_config.allowed_user_ids.is_empty() && !discord_config.allowed_user_ids.contains(&user_id_num) {
+            warn!("Unauthorized Discord button press from {} ({})", user.id, user.username);
+            return Ok(());
+        }
+    }
+
+    let channel_id = interaction.message.as_ref().map(|m| m.channel_id.as_str()).unwrap_or_default();
+    let by = openclaw_agent::approval::Requester::new(&user.id, channel_id);
+    let resolution = openclaw_agent::approval::resolve(id, approved, &by);
+    info!("Approval {} {} by Discord user {}: {:?}", id, if approved { "approved" } else { "denied" }, user.id, resolution);
+    let outcome = crate::handler_utils::approval_outcome(approved, resolution, &user.username);
+    if resolution == openclaw_agent::approval::Resolution::NotRequester {
+        // Still pending: answer only the presser and keep the buttons
+        return bot.reply_to_interaction_ephemeral(interaction, &outcome).await;
+    }
+    let prompt = interaction.message.as_ref().map(|m| m.content.as_str()).unwrap_or_default();
+    bot.update_interaction_message(interaction, &format!("{}\n\n{}", prompt, outcome)).await
+}
+
 /// Handle an incoming Discord message
 pub async fn handle_discord_message(
     bot: &DiscordBot,
@@ -60,8 +92,9 @@ pub async fn handle_discord_message(
         if has_image { " [+image]" } else { "" }
     );
 
-    // ── Handle commands ──
-    if (text.starts_with('/') || text.starts_with('!')) && !has_image {
+    // ── Handle commands (/resume runs a turn, below) ──
+    let is_resume = matches!(text.trim(), "/resume" | "!resume");
+    if (text.starts_with('/') || text.starts_with('!')) && !has_image && !is_resume {
         return handle_command(bot, channel_id, &msg.id, user_id, text, config, msg).await;
     }
 
@@ -101,6 +134,43 @@ pub async fn handle_discord_message(
         clean_text.clone()
     };
 
+    // ── Spend quotas (refuse up front; the remainder bounds the turn) ──
+    let session_key = format!("dc:{}:{}:{}", config.agent.name, user_id, channel_id);
+    let quota_user_key = format!("dc:{}", user_id);
+
+    // ── /resume: continue the channel's interrupted turn from its checkpoint ──
+    let resume = if is_resume {
+        if crate::task_registry::has_active_task(&format!("dc:{}:{}", user_id, channel_id)) {
+            bot.send_reply(channel_id, &msg.id, "⏳ A turn is still running — wait for it or /cancel it first.").await?;
+            return Ok(());
+        }
+        match crate::handler_utils::latest_checkpoint(&session_key).await {
+            Some(checkpoint) => Some(checkpoint),
+            None => {
+                bot.send_reply(channel_id, &msg.id, "ℹ️ Nothing to resume in this channel.").await?;
+                return Ok(());
+            }
+        }
+    } else {
+        None
+    };
+    let user_text = match resume {
+        Some(ref checkpoint) => {
+            // The resumed turn sees the images the original message had
+            image_urls = checkpoint.image_urls.clone();
+            checkpoint.user_message.clone()
+        }
+        None => user_text,
+    };
+    let turn_budget = match crate::quota::check(config.quotas.as_ref(), &quota_user_key, &session_key).await {
+        Ok(budget) => budget,
+        Err(exceeded) => {
+            info!("Quota exceeded for Discord user {}", user_id);
+            bot.send_reply(channel_id, &msg.id, &exceeded.message()).await?;
+            return Ok(());
+        }
+    };
+
     // ── Send typing indicator + placeholder ──
     bot.send_typing(channel_id).await.ok();
     let placeholder_id = bot
@@ -130,7 +200,7 @@ pub async fn handle_discord_message(
     });
 
     // ── Resolve workspace ──
-    let workspace_dir = workspace::resolve_workspace_dir(&config.agent.name);
+    let workspace_dir = config.agent.workspace_dir();
     if !workspace_dir.exists() {
         bot.edit_message(channel_id, &placeholder_id, "❌ Workspace not found.")
             .await?;
@@ -138,49 +208,20 @@ pub async fn handle_discord_message(
     }
 
     // ── Resolve provider ──
-    let provider: Box<dyn LlmProvider> = if config.agent.fallback {
-        match FallbackProvider::from_config() {
-            Ok(fb) => Box::new(fb),
-            Err(e) => {
-                bot.edit_message(
-                    channel_id,
-                    &placeholder_id,
-                    &format!("❌ Provider error: {}", e),
-                )
-                .await?;
-                return Ok(());
-            }
-        }
-    } else if let Some(ref model) = config.agent.model {
-        match crate::handler_utils::resolve_single_provider(model) {
-            Ok(p) => Box::new(p),
-            Err(e) => {
-                bot.edit_message(
-                    channel_id,
-                    &placeholder_id,
-                    &format!("❌ Provider error: {}", e),
-                )
-                .await?;
-                return Ok(());
-            }
-        }
-    } else {
-        match FallbackProvider::from_config() {
-            Ok(fb) => Box::new(fb),
-            Err(e) => {
-                bot.edit_message(
-                    channel_id,
-                    &placeholder_id,
-                    &format!("❌ Provider error: {}", e),
-                )
-                .await?;
-                return Ok(());
-            }
+    let provider = match crate::handler_utils::resolve_agent_provider(&config.agent, &session_key) {
+        Ok(p) => p,
+        Err(e) => {
+            bot.edit_message(
+                channel_id,
+                &placeholder_id,
+                &format!("❌ Provider error: {}", e),
+            )
+            .await?;
+            return Ok(());
         }
     };
 
     // ── Session (Postgres) ──
-    let session_key = format!("dc:{}:{}:{}", config.agent.name, user_id, channel_id);
     let pool = match openclaw_db::pool() {
         Some(p) => p,
         None => {
@@ -194,7 +235,7 @@ pub async fn handle_discord_message(
     ).await?;
 
     // ── Tools + config (load plugins + MCP client tools) ──
-    let tools = crate::handler::build_tool_registry(&workspace_dir).await;
+    let tools = crate::handler::build_tool_registry(&config.agent, "discord").await;
     let dc_chat_id: i64 = channel_id.parse().unwrap_or(0);
     let task_chat_id = dc_chat_id;
     let task_query_fn: Option<openclaw_agent::tools::TaskQueryFn> = Some(std::sync::Arc::new(move |_cid| {
@@ -221,6 +262,13 @@ pub async fn handle_discord_message(
         chat_id: dc_chat_id,
         task_query_fn,
         task_cancel_fn,
+        budget: turn_budget,
+        sandbox: config.agent.sandbox_policy("discord", user_id),
+        limits: config.agent.limits.clone(),
+        approval: config.agent.approval.clone().with_requester(user_id, channel_id),
+        system_prompt_overlay: config.agent.system_prompt.clone(),
+        turn_id: Some(resume.as_ref().map(|c| c.turn_id.clone()).unwrap_or_else(runtime::new_turn_id)),
+        on_usage: Some(crate::quota::usage_hook(&quota_user_key, &session_key)),
     ..AgentTurnConfig::default()
     };
 
@@ -244,16 +292,27 @@ pub async fn handle_discord_message(
 
     let user_text_owned = user_text.clone();
     let agent_handle = tokio::spawn(async move {
-        let result = runtime::run_agent_turn_streaming(
-            provider.as_ref(),
-            &user_text_owned,
-            &agent_config,
-            &tools,
-            event_tx,
-            image_urls,
-            Some(cancel_token),
-        )
-        .await;
+        let result = match resume {
+            Some(checkpoint) => runtime::resume_agent_turn_streaming(
+                provider.as_ref(),
+                &checkpoint,
+                &agent_config,
+                &tools,
+                event_tx,
+                Some(cancel_token),
+            )
+            .await,
+            None => runtime::run_agent_turn_streaming(
+                provider.as_ref(),
+                &user_text_owned,
+                &agent_config,
+                &tools,
+                event_tx,
+                image_urls,
+                Some(cancel_token),
+            )
+            .await,
+        };
         crate::task_registry::unregister_task(&task_key_cleanup);
         result
     });
@@ -270,8 +329,10 @@ pub async fn handle_discord_message(
 
     let mut accumulated = String::new();
     let mut last_edit_len: usize = 0;
+    let mut round_start_len: usize = 0; // Where the current round's LLM output begins
     let mut last_edit_time = std::time::Instant::now();
     let mut tool_status = String::new();
+    let mut output_tail = crate::handler_utils::OutputTail::default();
 
     while let Some(event) = event_rx.recv().await {
         watchdog.touch(); // Signal activity to prevent idle timeout
@@ -315,6 +376,7 @@ pub async fn handle_discord_message(
             }
 
             StreamEvent::ToolExec { name, args_summary, .. } => {
+                output_tail.clear();
                 tool_status = if args_summary.is_empty() {
                     format!("⚙️ Running {}...", name)
                 } else {
@@ -331,6 +393,28 @@ pub async fn handle_discord_message(
                     .ok();
             }
 
+            StreamEvent::ToolOutputDelta { chunk, .. } => {
+                output_tail.push(&chunk);
+                if output_tail.text().is_empty()
+                    || (last_edit_time.elapsed().as_millis() as u64) < crate::handler_utils::LIVE_OUTPUT_EDIT_MS
+                {
+                    continue;
+                }
+                // A zero-width space keeps output from closing the code block early
+                let output = output_tail.text().replace("```", "`\u{200B}``");
+                let live = format!("{}\n```\n{}\n```", tool_status, output);
+                let display = if accumulated.is_empty() {
+                    live
+                } else {
+                    format!("{}\n\n{}", accumulated, live)
+                };
+                stream_bot
+                    .edit_message(&stream_channel, &stream_placeholder, &display)
+                    .await
+                    .ok();
+                last_edit_time = std::time::Instant::now();
+            }
+
             StreamEvent::ToolResult { name, success, .. } => {
                 let icon = if success { "✅" } else { "❌" };
                 tool_status = format!("{} {}", icon, name);
@@ -347,16 +431,55 @@ pub async fn handle_discord_message(
 
             StreamEvent::RoundStart { round } => {
                 tool_status = String::new();
-                accumulated.clear();
-                last_edit_len = 0;
+                output_tail.clear();
+                // Keep earlier rounds' text, separated from the new round's output
+                if !accumulated.is_empty() {
+                    accumulated.push_str("\n\n---\n");
+                }
+                last_edit_len = accumulated.len();
+                round_start_len = accumulated.len();
 
-                let display = format!("🔄 Round {}...", round);
+                let display = if accumulated.is_empty() {
+                    format!("🔄 Round {}...", round)
+                } else {
+                    format!("{}\n🔄 Round {}...", accumulated, round)
+                };
                 stream_bot
                     .edit_message(&stream_channel, &stream_placeholder, &display)
                     .await
                     .ok();
             }
 
+            StreamEvent::ProviderSwitched { from, to } => {
+                // The failed provider's partial output is discarded; the round restarts
+                accumulated.truncate(round_start_len);
+                last_edit_len = accumulated.len();
+                tool_status = format!("🔁 {} failed, switching to {}...", from, to);
+                let display = if accumulated.is_empty() {
+                    tool_status.clone()
+                } else {
+                    format!("{}\n\n{}", accumulated, tool_status)
+                };
+                stream_bot
+                    .edit_message(&stream_channel, &stream_placeholder, &display)
+                    .await
+                    .ok();
+            }
+
+            StreamEvent::ApprovalRequired { id, tool, args_summary, reason, timeout_secs } => {
+                tool_status = format!("⏸️ Waiting for approval: {}", tool);
+                let prompt = crate::handler_utils::approval_prompt(&tool, &args_summary, &reason, timeout_secs);
+                let buttons = [
+                    ("Approve", crate::handler_utils::approval_button_data(&id, true), false),
+                    ("Deny", crate::handler_utils::approval_button_data(&id, false), true),
+                ];
+                if let Err(e) = stream_bot.send_message_with_buttons(&stream_channel, &prompt, &buttons).await {
+                    warn!("Failed to send approval request: {}", e);
+                    openclaw_agent::approval::cancel(&id);
+                }
+                crate::handler_utils::keep_alive_while_pending(&watchdog, id);
+            }
+
             StreamEvent::Done => {
                 typing_token.cancel(); // Stop typing immediately, don't wait for agent_handle
                 break;
@@ -379,47 +502,53 @@ pub async fn handle_discord_message(
     typing_token.cancel();
     let elapsed = t_start.elapsed().as_millis();
 
-    // ── Persist messages to Postgres ──
+    // ── Persist messages to Postgres (an interrupted turn stays in its checkpoint; the next turn moves it into the history) ──
     {
         let sid = openclaw_db::sessions::upsert_session(
             pool, &session_key, &config.agent.name, &result.model_name,
             Some("discord"), Some(&user_id),
         ).await?;
 
-        let _ = openclaw_db::messages::record_message(
-            pool, sid, "user", Some(&user_text), None, None, None,
-        ).await;
-
-        for turn_msg in &result.turn_messages {
-            let role = match turn_msg.role {
-                openclaw_agent::llm::Role::Assistant => "assistant",
-                openclaw_agent::llm::Role::Tool => "tool",
-                openclaw_agent::llm::Role::User => "user",
-                openclaw_agent::llm::Role::System => "system",
-            };
-            let tc_json = turn_msg.tool_calls.as_ref()
-                .map(|tc| serde_json::to_value(tc).unwrap_or_default());
+        if !result.resumable {
             let _ = openclaw_db::messages::record_message(
-                pool, sid, role,
-                turn_msg.content.as_deref(),
-                turn_msg.reasoning_content.as_deref(),
-                tc_json.as_ref(),
-                turn_msg.tool_call_id.as_deref(),
+                pool, sid, "user", Some(&user_text), None, None, None,
             ).await;
-        }
 
-        if result.turn_messages.is_empty() && !result.response.is_empty() {
-            let _ = openclaw_db::messages::record_message(
-                pool, sid, "assistant", Some(&result.response), None, None, None,
-            ).await;
+            for turn_msg in &result.turn_messages {
+                let role = match turn_msg.role {
+                    openclaw_agent::llm::Role::Assistant => "assistant",
+                    openclaw_agent::llm::Role::Tool => "tool",
+                    openclaw_agent::llm::Role::User => "user",
+                    openclaw_agent::llm::Role::System => "system",
+                };
+                let tc_json = turn_msg.tool_calls.as_ref()
+                    .map(|tc| serde_json::to_value(tc).unwrap_or_default());
+                let _ = openclaw_db::messages::record_message(
+                    pool, sid, role,
+                    turn_msg.content.as_deref(),
+                    turn_msg.reasoning_content.as_deref(),
+                    tc_json.as_ref(),
+                    turn_msg.tool_call_id.as_deref(),
+                ).await;
+            }
+
+            if result.turn_messages.is_empty() && !result.response.is_empty() {
+                let _ = openclaw_db::messages::record_message(
+                    pool, sid, "assistant", Some(&result.response), None, None, None,
+                ).await;
+            }
         }
 
         let _ = openclaw_db::sessions::add_tokens(pool, &session_key,
             result.total_usage.total_tokens as i64).await;
+        let _ = openclaw_db::sessions::add_cost(pool, &session_key,
+            result.total_cost_micros as i64).await;
     }
 
     // ── Final edit with stats footer ──
-    let response = if !result.response.is_empty() {
+    let response = if result.resumable {
+        format!("{}\n\n{}", result.response, crate::handler_utils::RESUME_HINT)
+    } else if !result.response.is_empty() {
         result.response.clone()
     } else if let Some(ref reasoning) = result.reasoning {
         reasoning.clone()
@@ -428,11 +557,12 @@ pub async fn handle_discord_message(
     };
 
     let stats = format!(
-        "\n\n*{}ms · {} round(s) · {} tool(s) · {} tokens · {}*",
+        "\n\n*{}ms · {} round(s) · {} tool(s) · {} tokens{} · {}*",
         elapsed,
         result.total_rounds,
         result.tool_calls_made,
         result.total_usage.total_tokens,
+        crate::handler_utils::cost_suffix(result.total_cost_micros),
         result.model_name,
     );
 
@@ -497,10 +627,10 @@ async fn handle_command(
                 &[
                     ("Session", "`/new` `/clear` `/sessions` `/export`", false),
                     ("Info", "`/status` `/model` `/version` `/whoami` `/db`", false),
-                    ("Monitoring", "`/stats` `/ping` `/history [N]` `/doctor` `/logs [N]`", false),
-                    ("Control", "`/cancel` `/stop` `/voice` `/cron` `/tools` `/skills` `/config` `/runtime`", false),
+                    ("Monitoring", "`/stats` `/quota` `/ping` `/history [N]` `/doctor` `/logs [N]`", false),
+                    ("Control", "`/cancel` `/stop` `/resume` `/voice` `/cron` `/tools` `/plugins [reload]` `/skills` `/config` `/runtime`", false),
                     ("Orchestrator", "`/projects` `/orch_status [project]` `/cycle <project> <prompt>` `/approve <id>` `/workers`", false),
-                    ("Commands", "28", true),
+                    ("Commands", "30", true),
                 ],
             ).await?;
         }
@@ -533,6 +663,13 @@ async fn handle_command(
                 bot.send_reply(channel_id, reply_to, "ℹ️ No task is currently running.").await?;
             }
         }
+        "quota" => {
+            let session_key = format!("dc:{}:{}:{}", config.agent.name, user_id, channel_id);
+            let status = crate::quota::status(
+                config.quotas.as_ref(), &format!("dc:{}", user_id), &session_key,
+            ).await;
+            bot.send_reply(channel_id, reply_to, &status).await?;
+        }
         "stats" => {
             if let Some(m) = crate::metrics::global() {
                 let tg_req = m.telegram_requests.load(std::sync::atomic::Ordering::Relaxed);
@@ -549,16 +686,21 @@ async fn handle_command(
                 let tool_calls = m.tool_calls.load(std::sync::atomic::Ordering::Relaxed);
                 let err_rate = m.error_rate_pct();
                 let webhooks = m.webhook_requests.load(std::sync::atomic::Ordering::Relaxed);
-                let session_count: i64 = if let Some(p) = openclaw_db::pool() {
+                let (session_count, session_cost): (i64, u64) = if let Some(p) = openclaw_db::pool() {
                     openclaw_db::sessions::db_stats(p, &config.agent.name).await
-                        .map(|s| s.session_count).unwrap_or(0)
-                } else { 0 };
+                        .map(|s| (s.session_count, s.total_cost_micros.max(0) as u64)).unwrap_or((0, 0))
+                } else { (0, 0) };
                 let session_str = session_count.to_string();
                 let webhook_str = webhooks.to_string();
                 let llm_stats = openclaw_agent::llm_log::stats();
                 let llm_calls_str = llm_stats.total_recorded.to_string();
                 let llm_errors_str = llm_stats.errors.to_string();
                 let llm_avg_str = format!("{}ms", llm_stats.avg_latency_ms);
+                let llm_cost_str = format!(
+                    "{} since start / {} all sessions",
+                    openclaw_agent::llm::pricing::format_cost(llm_stats.total_cost_micros),
+                    openclaw_agent::llm::pricing::format_cost(session_cost),
+                );
                 bot.send_embed(
                     channel_id, Some(reply_to),
                     "📊 Gateway Stats",
@@ -581,6 +723,7 @@ async fn handle_command(
                         ("LLM Calls", &llm_calls_str, true),
                         ("LLM Errors", &llm_errors_str, true),
                         ("LLM Avg Latency", &llm_avg_str, true),
+                        ("LLM Cost", &llm_cost_str, false),
                     ],
                 ).await?;
             } else {
@@ -624,21 +767,55 @@ async fn handle_command(
             ).await?;
         }
         "tools" => {
-            let workspace_dir = workspace::resolve_workspace_dir(&config.agent.name);
-            let tools = crate::handler::build_tool_registry(&workspace_dir).await;
+            let tools = crate::handler::build_tool_registry(&config.agent, "discord").await;
             let names = tools.tool_names();
             let tool_list = names.iter()
                 .map(|n| format!("`{}`", n))
                 .collect::<Vec<_>>()
                 .join(" · ");
+            let plugins = openclaw_agent::tools::plugins::plugin_set(
+                &openclaw_agent::tools::script_plugin::plugins_dir(&config.agent.workspace_dir()),
+            );
+            let rejected = crate::reload::rejected_lines(&plugins.report).join("\n");
+            let mut fields = vec![("Tools", tool_list.as_str(), false)];
+            if !rejected.is_empty() {
+                fields.push(("Rejected plugins", rejected.as_str(), false));
+            }
             bot.send_embed(
                 channel_id, Some(reply_to),
                 "🔧 Tools",
                 &format!("{} tools available", names.len()),
                 0x5865F2,
-                &[("Tools", &tool_list, false)],
+                &fields,
             ).await?;
         }
+        "plugins" => {
+            if text.split_whitespace().nth(1) == Some("reload") {
+                let reply = match crate::reload::reload(&crate::config::config_path()).await {
+                    Ok(report) => crate::reload::report_text(&report),
+                    Err(e) => format!("❌ Reload failed: {:#}", e),
+                };
+                bot.send_message(channel_id, &reply).await?;
+            } else {
+                let plugins = openclaw_agent::tools::plugins::plugin_set(
+                    &openclaw_agent::tools::script_plugin::plugins_dir(&config.agent.workspace_dir()),
+                );
+                let report = &plugins.report;
+                let loaded = if report.loaded.is_empty() { "(none)".to_string() } else { report.loaded.join(", ") };
+                let rejected = crate::reload::rejected_lines(report).join("\n");
+                let mut fields = vec![("Loaded", loaded.as_str(), false)];
+                if !rejected.is_empty() {
+                    fields.push(("Rejected", rejected.as_str(), false));
+                }
+                bot.send_embed(
+                    channel_id, Some(reply_to),
+                    "🧩 Plugins",
+                    &format!("{} — use `!plugins reload` to reload plugins and MCP servers", report.summary()),
+                    0x5865F2,
+                    &fields,
+                ).await?;
+            }
+        }
         "runtime" => {
             let pid = std::process::id();
             let uptime_secs = crate::handler::BOOT_TIME.elapsed().as_secs();
@@ -692,7 +869,7 @@ async fn handle_command(
             ).await?;
         }
         "skills" => {
-            let workspace_dir = openclaw_agent::workspace::resolve_workspace_dir(&config.agent.name);
+            let workspace_dir = config.agent.workspace_dir();
             let skills_dir = workspace_dir.join("skills");
             let skills = openclaw_core::skills::list_skills(&skills_dir).unwrap_or_default();
             if skills.is_empty() {
@@ -871,10 +1048,22 @@ async fn handle_command(
 
             let msg_count = current.map(|s| s.message_count).unwrap_or(0);
             let tokens = current.map(|s| s.total_tokens).unwrap_or(0);
+            let cost = current.map(|s| s.total_cost_micros).unwrap_or(0).max(0) as u64;
 
             let model_info = if config.agent.fallback {
                 match FallbackProvider::from_config() {
-                    Ok(fb) => fb.provider_labels().join(" → "),
+                    Ok(fb) => fb
+                        .health()
+                        .iter()
+                        .map(|h| {
+                            if h.state == CircuitState::Closed {
+                                h.label.clone()
+                            } else {
+                                format!("{} ({})", h.label, h.state.as_str())
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                        .join(" → "),
                     Err(_) => "(error)".to_string(),
                 }
             } else {
@@ -892,29 +1081,45 @@ async fn handle_command(
                     ("Active Tasks", &crate::task_registry::active_count().to_string(), true),
                     ("Messages", &msg_count.to_string(), true),
                     ("Tokens", &tokens.to_string(), true),
+                    ("Cost", &openclaw_agent::llm::pricing::format_cost(cost), true),
                     ("Sessions", &sessions.len().to_string(), true),
                 ],
             ).await?;
         }
         "model" => {
+            let session_key = format!("dc:{}:{}:{}", config.agent.name, user_id, channel_id);
+            if let Some(tier) = text.split_whitespace().nth(1) {
+                let reply = crate::handler_utils::set_model_tier(&session_key, tier);
+                bot.send_reply(channel_id, reply_to, &reply).await?;
+                return Ok(());
+            }
             if config.agent.fallback {
                 match FallbackProvider::from_config() {
                     Ok(fb) => {
-                        let labels = fb.provider_labels();
+                        let health = fb.health();
                         let mut chain_desc = String::new();
-                        for (i, label) in labels.iter().enumerate() {
+                        for (i, h) in health.iter().enumerate() {
                             let marker = if i == 0 { "🥇" } else if i == 1 { "🥈" } else { "🥉" };
-                            chain_desc.push_str(&format!("{} `{}`\n", marker, label));
+                            chain_desc.push_str(&format!("{} `{}` — {}\n", marker, h.label, h.summary()));
                         }
+                        if let Some(routing) = crate::handler_utils::routing_summary(&session_key) {
+                            chain_desc.push('\n');
+                            chain_desc.push_str(&routing);
+                        }
+                        let breaker_desc = format!(
+                            ">{} failures, {}s cooldown",
+                            fb.breaker_config().max_consecutive_failures,
+                            fb.breaker_config().cooldown.as_secs()
+                        );
                         bot.send_embed(
                             channel_id, Some(reply_to),
                             "🔗 Fallback Chain",
                             &chain_desc,
                             0xF74C00, // Rust orange
                             &[
-                                ("Providers", &labels.len().to_string(), true),
-                                ("Mode", "First available", true),
-                                ("Circuit Breaker", ">3 failures", true),
+                                ("Providers", &health.len().to_string(), true),
+                                ("Mode", fb.order().as_str(), true),
+                                ("Circuit Breaker", &breaker_desc, true),
                             ],
                         ).await?;
                     }
@@ -1007,28 +1212,32 @@ async fn handle_command(
             } else {
                 bot.send_typing(channel_id).await.ok();
 
-                let provider: Box<dyn LlmProvider> = match FallbackProvider::from_config() {
-                    Ok(fb) => Box::new(fb),
+                let session_key = format!("dc:{}:{}:{}", config.agent.name, user_id, channel_id);
+                let provider = match crate::handler_utils::resolve_agent_provider(&config.agent, &session_key) {
+                    Ok(p) => p,
                     Err(e) => {
                         bot.send_reply(channel_id, reply_to, &format!("❌ Provider error: {}", e)).await?;
                         return Ok(());
                     }
                 };
 
-                let workspace_dir = workspace::resolve_workspace_dir(&config.agent.name);
-                let session_key = format!("dc:{}:{}:{}", config.agent.name, user_id, channel_id);
+                let workspace_dir = config.agent.workspace_dir();
                 let p = openclaw_db::pool().ok_or_else(|| anyhow::anyhow!("Database not available"))?;
                 openclaw_db::sessions::upsert_session(
                     p, &session_key, &config.agent.name, provider.name(),
                     Some("discord"), Some(&user_id),
                 ).await?;
 
-                let tools = crate::handler::build_tool_registry(&workspace_dir).await;
+                let tools = crate::handler::build_tool_registry(&config.agent, "discord").await;
                 let agent_config = AgentTurnConfig {
                     agent_name: config.agent.name.clone(),
                     session_key: session_key.clone(),
                     workspace_dir: workspace_dir.to_string_lossy().to_string(),
                     minimal_context: true,
+                    sandbox: config.agent.sandbox_policy("discord", user_id),
+                    limits: config.agent.limits.clone(),
+                    approval: config.agent.approval.clone(),
+                    system_prompt_overlay: config.agent.system_prompt.clone(),
                 ..AgentTurnConfig::default()
                 };
 
@@ -3,14 +3,13 @@ use tokio::sync::mpsc;
 use tracing::{info, warn};
 
 use openclaw_agent::llm::fallback::FallbackProvider;
+use openclaw_agent::llm::health::CircuitState;
 use openclaw_agent::llm::streaming::StreamEvent;
-use openclaw_agent::llm::{LlmProvider, Message};
 use openclaw_agent::runtime::{self, AgentTurnConfig};
 use openclaw_agent::tools::ToolRegistry;
-use openclaw_agent::workspace;
 
 use crate::config::GatewayConfig;
-use crate::telegram::{TelegramBot, TgMessage};
+use crate::telegram::{TelegramBot, TgCallbackQuery, TgMessage};
 
 /// Process boot time for /version uptime tracking
 pub static BOOT_TIME: std::sync::LazyLock<std::time::Instant> =
@@ -23,8 +22,8 @@ pub static BOOT_TIMESTAMP: std::sync::LazyLock<String> =
 /// Agent name, set once at startup via init_agent_name()
 static AGENT_NAME: std::sync::OnceLock<String> = std::sync::OnceLock::new();
 
-/// MCP server configs, set once at startup
-static MCP_CONFIGS: std::sync::OnceLock<Vec<openclaw_agent::tools::mcp_bridge::McpServerConfig>> = std::sync::OnceLock::new();
+/// MCP server configs, set at startup and on reload
+static MCP_CONFIGS: std::sync::RwLock<Vec<openclaw_agent::tools::mcp_bridge::McpServerConfig>> = std::sync::RwLock::new(Vec::new());
 
 /// Total tool count (built-in + plugins + MCP), set at startup
 static TOOL_COUNT: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
@@ -34,11 +33,11 @@ pub fn init_agent_name(name: &str) {
 }
 
 pub fn init_mcp_configs(configs: Vec<openclaw_agent::tools::mcp_bridge::McpServerConfig>) {
-    let _ = MCP_CONFIGS.set(configs);
+    *MCP_CONFIGS.write().unwrap() = configs;
 }
 
-pub fn mcp_configs() -> &'static [openclaw_agent::tools::mcp_bridge::McpServerConfig] {
-    MCP_CONFIGS.get().map(|v| v.as_slice()).unwrap_or(&[])
+pub fn mcp_configs() -> Vec<openclaw_agent::tools::mcp_bridge::McpServerConfig> {
+    MCP_CONFIGS.read().unwrap().clone()
 }
 
 pub fn agent_name() -> &'static str {
@@ -53,13 +52,14 @@ pub fn tool_count() -> usize {
     TOOL_COUNT.load(std::sync::atomic::Ordering::Relaxed)
 }
 
-/// Build a ToolRegistry with defaults + MCP client tools.
-pub async fn build_tool_registry(workspace_dir: &std::path::Path) -> ToolRegistry {
-    let mut tools = ToolRegistry::with_defaults();
-    tools.load_plugins(workspace_dir);
+/// Build a ToolRegistry with defaults + MCP client tools under the agent's
+/// tool policy for `channel`.
+pub async fn build_tool_registry(agent: &crate::config::AgentConfig, channel: &str) -> ToolRegistry {
+    let mut tools = ToolRegistry::with_defaults().with_policy(agent.tool_policy(channel));
+    tools.load_plugins(&agent.workspace_dir());
     let mcp = mcp_configs();
     if !mcp.is_empty() {
-        let count = tools.load_mcp_tools(mcp).await;
+        let count = tools.load_mcp_tools(&mcp).await;
         if count > 0 {
             info!("Loaded {} MCP client tool(s)", count);
         }
@@ -75,6 +75,43 @@ const EDIT_MS_CONVERSATIONAL: u64 = 150;
 const EDIT_CHARS_TECHNICAL: usize = 1;
 const EDIT_MS_TECHNICAL: u64 = 100;
 
+/// Handle an inline keyboard press (tool approval buttons)
+pub async fn handle_callback_query(
+    bot: &TelegramBot,
+    query: &TgCallbackQuery,
+    config: &GatewayConfig,
+) -> Result<()> {
+    let user_id = query.from.id;
+    if !config.telegram.allowed_user_ids.is_empty()
+        && !config.telegram.allowed_user_ids.contains(&user_id)
+    {
+        warn!("Unauthorized button press from {} ({})", query.from.first_name, user_id);
+        bot.answer_callback_query(&query.id, Some("Not authorized")).await?;
+        return Ok(());
+    }
+    let Some((approved, id)) = query.data.as_deref().and_then(crate::handler_utils::parse_approval_data) else {
+        bot.answer_callback_query(&query.id, None).await?;
+        return Ok(());
+    };
+
+    let chat_id = query.message.as_ref().map(|m| m.chat.id.to_string()).unwrap_or_default();
+    let by = openclaw_agent::approval::Requester::new(user_id, chat_id);
+    let resolution = openclaw_agent::approval::resolve(id, approved, &by);
+    info!("Approval {} {} by {} ({}): {:?}", id, if approved { "approved" } else { "denied" }, query.from.first_name, user_id, resolution);
+    let outcome = crate::handler_utils::approval_outcome(approved, resolution, &query.from.first_name);
+    bot.answer_callback_query(&query.id, Some(&outcome)).await?;
+    if resolution == openclaw_agent::approval::Resolution::NotRequester {
+        // Still pending: keep the buttons for the requester
+        return Ok(());
+    }
+    if let Some(ref msg) = query.message {
+        // Editing without a keyboard also removes the buttons
+        let text = format!("{}\n\n{}", msg.text.as_deref().unwrap_or_default(), outcome);
+        bot.edit_message(msg.chat.id, msg.message_id, &text).await.ok();
+    }
+    Ok(())
+}
+
 /// Handle an incoming Telegram message
 pub async fn handle_message(
     bot: &TelegramBot,
@@ -151,8 +188,9 @@ pub async fn handle_message(
         &text[..text.len().min(100)],
         if has_photo { " [+photo]" } else { "" });
 
-    // ── Handle commands ──
-    if text.starts_with('/') && !has_photo {
+    // ── Handle commands (/resume runs a turn, below) ──
+    let is_resume = text.trim() == "/resume";
+    if text.starts_with('/') && !has_photo && !is_resume {
         return handle_command(bot, chat_id, user_id, &text, config, msg).await;
     }
 
@@ -180,6 +218,43 @@ pub async fn handle_message(
         }
     }
 
+    // ── Spend quotas (refuse up front; the remainder bounds the turn) ──
+    let session_key = format!("tg:{}:{}:{}", config.agent.name, user_id, chat_id);
+    let quota_user_key = format!("tg:{}", user_id);
+
+    // ── /resume: continue the chat's interrupted turn from its checkpoint ──
+    let resume = if is_resume {
+        if crate::task_registry::has_active_task(&format!("tg:{}:{}", user_id, chat_id)) {
+            bot.send_message(chat_id, "⏳ A turn is still running — wait for it or /cancel it first.").await?;
+            return Ok(());
+        }
+        match crate::handler_utils::latest_checkpoint(&session_key).await {
+            Some(checkpoint) => Some(checkpoint),
+            None => {
+                bot.send_message(chat_id, "ℹ️ Nothing to resume in this chat.").await?;
+                return Ok(());
+            }
+        }
+    } else {
+        None
+    };
+    let text = match resume {
+        Some(ref checkpoint) => {
+            // The resumed turn sees the images the original message had
+            image_urls = checkpoint.image_urls.clone();
+            checkpoint.user_message.clone()
+        }
+        None => text,
+    };
+    let turn_budget = match crate::quota::check(config.quotas.as_ref(), &quota_user_key, &session_key).await {
+        Ok(budget) => budget,
+        Err(exceeded) => {
+            info!("Quota exceeded for {} ({})", user_name, user_id);
+            bot.send_message(chat_id, &exceeded.message()).await?;
+            return Ok(());
+        }
+    };
+
     // ── Send typing indicator + initial placeholder ──
     bot.send_typing(chat_id).await.ok();
     let placeholder_id = bot.send_message_with_id(chat_id, "🧠 ...").await?;
@@ -202,7 +277,7 @@ pub async fn handle_message(
     });
 
     // ── Resolve workspace ──
-    let workspace_dir = workspace::resolve_workspace_dir(&config.agent.name);
+    let workspace_dir = config.agent.workspace_dir();
     if !workspace_dir.exists() {
         bot.edit_message(chat_id, placeholder_id, "❌ Workspace not found.")
             .await?;
@@ -210,37 +285,16 @@ pub async fn handle_message(
     }
 
     // ── Resolve provider ──
-    let provider: Box<dyn LlmProvider> = if config.agent.fallback {
-        match FallbackProvider::from_config() {
-            Ok(fb) => Box::new(fb),
-            Err(e) => {
-                bot.edit_message(chat_id, placeholder_id, &format!("❌ Provider error: {}", e))
-                    .await?;
-                return Ok(());
-            }
-        }
-    } else if let Some(ref model) = config.agent.model {
-        match crate::handler_utils::resolve_single_provider(model) {
-            Ok(p) => Box::new(p),
-            Err(e) => {
-                bot.edit_message(chat_id, placeholder_id, &format!("❌ Provider error: {}", e))
-                    .await?;
-                return Ok(());
-            }
-        }
-    } else {
-        match FallbackProvider::from_config() {
-            Ok(fb) => Box::new(fb),
-            Err(e) => {
-                bot.edit_message(chat_id, placeholder_id, &format!("❌ Provider error: {}", e))
-                    .await?;
-                return Ok(());
-            }
+    let provider = match crate::handler_utils::resolve_agent_provider(&config.agent, &session_key) {
+        Ok(p) => p,
+        Err(e) => {
+            bot.edit_message(chat_id, placeholder_id, &format!("❌ Provider error: {}", e))
+                .await?;
+            return Ok(());
         }
     };
 
     // ── Session (Postgres) ──
-    let session_key = format!("tg:{}:{}:{}", config.agent.name, user_id, chat_id);
     let pool = match openclaw_db::pool() {
         Some(p) => p,
         None => {
@@ -256,7 +310,7 @@ pub async fn handle_message(
     ).await?;
 
     // ── Tools + config (load plugins + MCP client tools from workspace) ──
-    let tools = build_tool_registry(&workspace_dir).await;
+    let tools = build_tool_registry(&config.agent, "telegram").await;
     // If images are attached inline, remove the `image` tool to prevent the LLM
     // from trying to use it on hallucinated file paths instead of analyzing the
     // inline image directly via its native vision capability.
@@ -295,6 +349,14 @@ pub async fn handle_message(
         delegate_tx: Some(delegate_tx),
         task_query_fn,
         task_cancel_fn,
+        budget: turn_budget,
+        sandbox: config.agent.sandbox_policy("telegram", &user_id.to_string()),
+        limits: config.agent.limits.clone(),
+        approval: config.agent.approval.clone().with_requester(user_id, chat_id),
+        system_prompt_overlay: config.agent.system_prompt.clone(),
+        turn_id: Some(resume.as_ref().map(|c| c.turn_id.clone()).unwrap_or_else(runtime::new_turn_id)),
+        on_usage: Some(crate::quota::usage_hook(&quota_user_key, &session_key)),
+        turn_calls: None,
     };
 
     // ── Spawn delegate listener (background subagent tasks) ──
@@ -409,6 +471,22 @@ pub async fn handle_message(
                                 activity_log.push(format!("\u{1F504} *Round {}*", round));
                                 dirty = true;
                             }
+                            openclaw_agent::llm::streaming::StreamEvent::ApprovalRequired { id, tool, args_summary, reason, timeout_secs } => {
+                                let prompt = format!(
+                                    "Task #{}: {}",
+                                    activity_task_id,
+                                    crate::handler_utils::approval_prompt(&tool, &args_summary, &reason, timeout_secs),
+                                );
+                                let buttons = [
+                                    ("✅ Approve", crate::handler_utils::approval_button_data(&id, true)),
+                                    ("❌ Deny", crate::handler_utils::approval_button_data(&id, false)),
+                                ];
+                                if let Err(e) = activity_bot.send_message_with_buttons(activity_chat_id, &prompt, &buttons).await {
+                                    warn!("Failed to send approval request: {}", e);
+                                    openclaw_agent::approval::cancel(&id);
+                                }
+                                crate::handler_utils::keep_alive_while_pending(&subagent_watchdog, id);
+                            }
                             _ => {}
                         }
 
@@ -441,7 +519,11 @@ pub async fn handle_message(
                 });
 
                 let result = openclaw_agent::subagent::run_subagent_turn(
-                    &req.task, &req.agent_name, &req.workspace_dir, Some(cancel_token),
+                    &req.task, &req.agent_name, &req.workspace_dir,
+                    openclaw_agent::subagent::ParentPolicy {
+                        sandbox: req.sandbox, approval: req.approval, tools: req.tool_policy, turn_calls: req.turn_calls,
+                    },
+                    Some(cancel_token),
                     Some(fwd_tx),
                 ).await;
 
@@ -522,16 +604,27 @@ pub async fn handle_message(
 
     let user_text_for_persist = text.clone(); // Save before move into spawned task
     let agent_handle = tokio::spawn(async move {
-        let result = runtime::run_agent_turn_streaming(
-            provider.as_ref(),
-            &text,
-            &agent_config,
-            &tools,
-            event_tx,
-            image_urls,
-            Some(cancel_token),
-        )
-        .await;
+        let result = match resume {
+            Some(checkpoint) => runtime::resume_agent_turn_streaming(
+                provider.as_ref(),
+                &checkpoint,
+                &agent_config,
+                &tools,
+                event_tx,
+                Some(cancel_token),
+            )
+            .await,
+            None => runtime::run_agent_turn_streaming(
+                provider.as_ref(),
+                &text,
+                &agent_config,
+                &tools,
+                event_tx,
+                image_urls,
+                Some(cancel_token),
+            )
+            .await,
+        };
         crate::task_registry::unregister_task(&task_key_cleanup);
         result
     });
@@ -544,6 +637,8 @@ pub async fn handle_message(
     let mut tool_status = String::new();
     let mut used_tools = false; // Switches to technical streaming mode once tools are invoked
     let mut is_done = false;
+    let mut round_start_len: usize = 0; // Where the current round's LLM output begins
+    let mut output_tail = crate::handler_utils::OutputTail::default();
 
     while let Some(event) = event_rx.recv().await {
         watchdog.touch(); // Signal activity to prevent idle timeout
@@ -624,6 +719,7 @@ pub async fn handle_message(
                     };
                     accumulated.push_str(&line);
                 } else {
+                    output_tail.clear();
                     tool_status = if args_summary.is_empty() {
                         format!("⚙️ Running {}...", name)
                     } else {
@@ -643,6 +739,37 @@ pub async fn handle_message(
                     .ok();
             }
 
+            StreamEvent::ToolOutputDelta { chunk, .. } => {
+                output_tail.push(&chunk);
+                if output_tail.text().is_empty()
+                    || (last_edit_time.elapsed().as_millis() as u64) < crate::handler_utils::LIVE_OUTPUT_EDIT_MS
+                {
+                    continue;
+                }
+                let live_lines: Vec<String> = output_tail.text().lines().map(|l| format!("│ {}", l)).collect();
+                let live = format!("{}\n{}", tool_status, live_lines.join("\n"));
+                let display = if accumulated.is_empty() {
+                    live
+                } else {
+                    format!("{}\n\n{}", accumulated, live)
+                };
+                // Keep the end (the live output) when the message is too long for Telegram
+                let display = if display.len() > 4000 {
+                    let mut cut = display.len() - 3997;
+                    while !display.is_char_boundary(cut) {
+                        cut += 1;
+                    }
+                    format!("...{}", &display[cut..])
+                } else {
+                    display
+                };
+                stream_bot
+                    .edit_message(chat_id, placeholder_id, &display)
+                    .await
+                    .ok();
+                last_edit_time = std::time::Instant::now();
+            }
+
             StreamEvent::ToolResult { name, success, output_preview } => {
                 let icon = if success { "✅" } else { "❌" };
                 if name.starts_with("cc:") {
@@ -682,12 +809,14 @@ pub async fn handle_message(
 
             StreamEvent::RoundStart { round } => {
                 tool_status = String::new();
+                output_tail.clear();
                 // Keep tool-emitted content (e.g. claude_code progress) visible,
                 // but separate it from the new round's LLM output
                 if !accumulated.is_empty() {
                     accumulated.push_str("\n\n---\n");
                 }
                 last_edit_len = accumulated.len();
+                round_start_len = accumulated.len();
 
                 let display = if accumulated.is_empty() {
                     format!("🔄 Round {}...", round)
@@ -700,6 +829,36 @@ pub async fn handle_message(
                     .ok();
             }
 
+            StreamEvent::ProviderSwitched { from, to } => {
+                // The failed provider's partial output is discarded; the round restarts
+                accumulated.truncate(round_start_len);
+                last_edit_len = accumulated.len();
+                tool_status = format!("🔁 {} failed, switching to {}...", from, to);
+                let display = if accumulated.is_empty() {
+                    tool_status.clone()
+                } else {
+                    format!("{}\n{}", accumulated, tool_status)
+                };
+                stream_bot
+                    .edit_message(chat_id, placeholder_id, &display)
+                    .await
+                    .ok();
+            }
+
+            StreamEvent::ApprovalRequired { id, tool, args_summary, reason, timeout_secs } => {
+                tool_status = format!("⏸️ Waiting for approval: {}", tool);
+                let prompt = crate::handler_utils::approval_prompt(&tool, &args_summary, &reason, timeout_secs);
+                let buttons = [
+                    ("✅ Approve", crate::handler_utils::approval_button_data(&id, true)),
+                    ("❌ Deny", crate::handler_utils::approval_button_data(&id, false)),
+                ];
+                if let Err(e) = stream_bot.send_message_with_buttons(chat_id, &prompt, &buttons).await {
+                    warn!("Failed to send approval request: {}", e);
+                    openclaw_agent::approval::cancel(&id);
+                }
+                crate::handler_utils::keep_alive_while_pending(&watchdog, id);
+            }
+
             StreamEvent::Done => {
                 is_done = true;
                 typing_token.cancel(); // Stop typing immediately, don't wait for agent_handle
@@ -723,51 +882,57 @@ pub async fn handle_message(
     typing_token.cancel();
     let elapsed = t_start.elapsed().as_millis();
 
-    // ── Persist messages to Postgres ──
+    // ── Persist messages to Postgres (an interrupted turn stays in its checkpoint; the next turn moves it into the history) ──
     {
         let sid = openclaw_db::sessions::upsert_session(
             pool, &session_key, &config.agent.name, &result.model_name,
             Some("telegram"), Some(&user_id.to_string()),
         ).await?;
 
-        // Save the user message (use the processed text which includes the image hint
-        // for photos, not raw msg.text which may be empty for captionless photos)
-        let _ = openclaw_db::messages::record_message(
-            pool, sid, "user", Some(&user_text_for_persist), None, None, None,
-        ).await;
-
-        // Save ALL turn messages (tool calls, tool results, final assistant)
-        for turn_msg in &result.turn_messages {
-            let role = match turn_msg.role {
-                openclaw_agent::llm::Role::Assistant => "assistant",
-                openclaw_agent::llm::Role::Tool => "tool",
-                openclaw_agent::llm::Role::User => "user",
-                openclaw_agent::llm::Role::System => "system",
-            };
-            let tc_json = turn_msg.tool_calls.as_ref()
-                .map(|tc| serde_json::to_value(tc).unwrap_or_default());
+        if !result.resumable {
+            // Save the user message (use the processed text which includes the image hint
+            // for photos, not raw msg.text which may be empty for captionless photos)
             let _ = openclaw_db::messages::record_message(
-                pool, sid, role,
-                turn_msg.content.as_deref(),
-                turn_msg.reasoning_content.as_deref(),
-                tc_json.as_ref(),
-                turn_msg.tool_call_id.as_deref(),
+                pool, sid, "user", Some(&user_text_for_persist), None, None, None,
             ).await;
-        }
 
-        // Fallback: if no turn messages, save the response directly
-        if result.turn_messages.is_empty() && !result.response.is_empty() {
-            let _ = openclaw_db::messages::record_message(
-                pool, sid, "assistant", Some(&result.response), None, None, None,
-            ).await;
+            // Save ALL turn messages (tool calls, tool results, final assistant)
+            for turn_msg in &result.turn_messages {
+                let role = match turn_msg.role {
+                    openclaw_agent::llm::Role::Assistant => "assistant",
+                    openclaw_agent::llm::Role::Tool => "tool",
+                    openclaw_agent::llm::Role::User => "user",
+                    openclaw_agent::llm::Role::System => "system",
+                };
+                let tc_json = turn_msg.tool_calls.as_ref()
+                    .map(|tc| serde_json::to_value(tc).unwrap_or_default());
+                let _ = openclaw_db::messages::record_message(
+                    pool, sid, role,
+                    turn_msg.content.as_deref(),
+                    turn_msg.reasoning_content.as_deref(),
+                    tc_json.as_ref(),
+                    turn_msg.tool_call_id.as_deref(),
+                ).await;
+            }
+
+            // Fallback: if no turn messages, save the response directly
+            if result.turn_messages.is_empty() && !result.response.is_empty() {
+                let _ = openclaw_db::messages::record_message(
+                    pool, sid, "assistant", Some(&result.response), None, None, None,
+                ).await;
+            }
         }
 
         let _ = openclaw_db::sessions::add_tokens(pool, &session_key,
             result.total_usage.total_tokens as i64).await;
+        let _ = openclaw_db::sessions::add_cost(pool, &session_key,
+            result.total_cost_micros as i64).await;
     }
 
     // ── Final edit with stats footer ──
-    let response = if !result.response.is_empty() {
+    let response = if result.resumable {
+        format!("{}\n\n{}", result.response, crate::handler_utils::RESUME_HINT)
+    } else if !result.response.is_empty() {
         result.response.clone()
     } else if let Some(ref reasoning) = result.reasoning {
         reasoning.clone()
@@ -778,11 +943,12 @@ pub async fn handle_message(
     // Escape underscores in model name to prevent Telegram Markdown breakage
     let safe_model = result.model_name.replace('_', "\\_");
     let stats = format!(
-        "\n\n_{}ms · {} round(s) · {} tool(s) · {} tokens · {}_",
+        "\n\n_{}ms · {} round(s) · {} tool(s) · {} tokens{} · {}_",
         elapsed,
         result.total_rounds,
         result.tool_calls_made,
         result.total_usage.total_tokens,
+        crate::handler_utils::cost_suffix(result.total_cost_micros),
         safe_model,
     );
 
@@ -845,7 +1011,7 @@ async fn handle_command(
                 *Commands:*\n\
                 /new — start a new session\n\
                 /status — show bot status\n\
-                /model — show current model info\n\
+                /model [tier|auto] — show model info, pin a routing tier\n\
                 /sessions — list recent sessions\n\
                 /export — export current session as markdown\n\
                 /voice — get a voice response (TTS)\n\
@@ -855,10 +1021,13 @@ async fn handle_command(
                 /db — session database stats\n\
                 /version — build info and uptime\n\
                 /stats — gateway request stats\n\
+                /quota — your token and spend quotas\n\
                 /whoami — show your user info\n\
                 /cancel — stop the running task\n\
+                /resume — continue a turn that was cut off\n\
                 /cron — list and manage cron jobs\n\
                 /tools — list all built-in agent tools\n\
+                /plugins [reload] — plugin status, or reload plugins and MCP servers\n\
                 /skills — list available workspace skills\n\
                 /config — show gateway config (sanitized)\n\
                 /runtime — build and runtime info\n\
@@ -943,6 +1112,13 @@ async fn handle_command(
                 if is_allowed { "✅ Yes" } else { "❌ No" },
             )).await?;
         }
+        "/quota" => {
+            let session_key = format!("tg:{}:{}:{}", config.agent.name, user_id, chat_id);
+            let status = crate::quota::status(
+                config.quotas.as_ref(), &format!("tg:{}", user_id), &session_key,
+            ).await;
+            bot.send_message(chat_id, &status).await?;
+        }
         "/stats" => {
             if let Some(m) = crate::metrics::global() {
                 let tg_req = m.telegram_requests.load(std::sync::atomic::Ordering::Relaxed);
@@ -959,11 +1135,12 @@ async fn handle_command(
                 let tool_calls = m.tool_calls.load(std::sync::atomic::Ordering::Relaxed);
                 let err_rate = m.error_rate_pct();
                 let webhooks = m.webhook_requests.load(std::sync::atomic::Ordering::Relaxed);
-                let session_count = if let Some(p) = openclaw_db::pool() {
+                let (session_count, session_cost) = if let Some(p) = openclaw_db::pool() {
                     openclaw_db::sessions::db_stats(p, &config.agent.name).await
-                        .map(|s| s.session_count).unwrap_or(0)
-                } else { 0 };
+                        .map(|s| (s.session_count, s.total_cost_micros.max(0) as u64)).unwrap_or((0, 0))
+                } else { (0, 0) };
                 let llm_stats = openclaw_agent::llm_log::stats();
+                let format_cost = openclaw_agent::llm::pricing::format_cost;
                 bot.send_message(chat_id, &format!(
                     "📊 *Gateway Stats* ({} uptime)\n\n\
                     Telegram: {} requests, {} errors\n\
@@ -981,11 +1158,13 @@ async fn handle_command(
                     Error rate: {:.1}%\n\
                     LLM calls: {}\n\
                     LLM errors: {}\n\
-                    LLM avg latency: {}ms",
+                    LLM avg latency: {}ms\n\
+                    LLM cost: {} since start, {} all sessions",
                     uptime_str, tg_req, tg_err, dc_req, dc_err, webhooks, rl, completed,
                     turns, tool_calls, session_count, cancelled, timeouts,
                     crate::task_registry::active_count(), avg, err_rate,
                     llm_stats.total_recorded, llm_stats.errors, llm_stats.avg_latency_ms,
+                    format_cost(llm_stats.total_cost_micros), format_cost(session_cost),
                 )).await?;
             } else {
                 bot.send_message(chat_id, "📊 Metrics not available.").await?;
@@ -1013,15 +1192,42 @@ async fn handle_command(
             bot.send_message(chat_id, &msg_text).await?;
         }
         "/tools" => {
-            let workspace_dir = workspace::resolve_workspace_dir(&config.agent.name);
-            let tools = build_tool_registry(&workspace_dir).await;
+            let tools = build_tool_registry(&config.agent, "telegram").await;
             let names = tools.tool_names();
             let mut msg_text = format!("🔧 *Tools* ({} total)\n\n", names.len());
             for name in &names {
                 msg_text.push_str(&format!("• `{}`\n", name));
             }
+            let plugins = openclaw_agent::tools::plugins::plugin_set(
+                &openclaw_agent::tools::script_plugin::plugins_dir(&config.agent.workspace_dir()),
+            );
+            let rejected = crate::reload::rejected_lines(&plugins.report);
+            if !rejected.is_empty() {
+                msg_text.push_str(&format!("\n⚠️ *Rejected plugins*\n{}\n", rejected.join("\n")));
+            }
             bot.send_message(chat_id, &msg_text).await?;
         }
+        "/plugins" => {
+            if text.split_whitespace().nth(1) == Some("reload") {
+                let reply = match crate::reload::reload(&crate::config::config_path()).await {
+                    Ok(report) => crate::reload::report_text(&report),
+                    Err(e) => format!("❌ Reload failed: {:#}", e),
+                };
+                bot.send_message(chat_id, &reply).await?;
+            } else {
+                let plugins = openclaw_agent::tools::plugins::plugin_set(
+                    &openclaw_agent::tools::script_plugin::plugins_dir(&config.agent.workspace_dir()),
+                );
+                let report = &plugins.report;
+                let mut lines = vec![format!("🧩 *Plugins*: {}", report.summary())];
+                if !report.loaded.is_empty() {
+                    lines.push(format!("✅ {}", report.loaded.join(", ")));
+                }
+                lines.extend(crate::reload::rejected_lines(report));
+                lines.push("\nUse `/plugins reload` to reload plugins and MCP servers.".to_string());
+                bot.send_message(chat_id, &lines.join("\n")).await?;
+            }
+        }
         "/runtime" => {
             let pid = std::process::id();
             let uptime_secs = crate::handler::BOOT_TIME.elapsed().as_secs();
@@ -1078,7 +1284,7 @@ async fn handle_command(
             bot.send_message(chat_id, &lines.join("\n")).await?;
         }
         "/skills" => {
-            let workspace_dir = openclaw_agent::workspace::resolve_workspace_dir(&config.agent.name);
+            let workspace_dir = config.agent.workspace_dir();
             let skills_dir = workspace_dir.join("skills");
             let skills = openclaw_core::skills::list_skills(&skills_dir).unwrap_or_default();
             if skills.is_empty() {
@@ -1202,10 +1408,24 @@ async fn handle_command(
 
             let msg_count = current.map(|s| s.message_count).unwrap_or(0);
             let tokens = current.map(|s| s.total_tokens).unwrap_or(0);
+            let cost = current.map(|s| s.total_cost_micros).unwrap_or(0).max(0) as u64;
 
             let chain_info = if config.agent.fallback {
                 match FallbackProvider::from_config() {
-                    Ok(fb) => format!("Chain: {}", fb.provider_labels().join(" → ")),
+                    Ok(fb) => {
+                        let open: Vec<String> = fb
+                            .health()
+                            .into_iter()
+                            .filter(|h| h.state != CircuitState::Closed)
+                            .map(|h| format!("{} ({})", h.label, h.state.as_str()))
+                            .collect();
+                        let breakers = if open.is_empty() {
+                            "all closed".to_string()
+                        } else {
+                            open.join(", ")
+                        };
+                        format!("Chain: {}\nBreakers: {}", fb.provider_labels().join(" → "), breakers)
+                    }
                     Err(_) => "Chain: (error loading)".to_string(),
                 }
             } else {
@@ -1221,7 +1441,7 @@ async fn handle_command(
                     {}\n\
                     Session: `{}`\n\
                     Messages: {}\n\
-                    Tokens used: {}\n\
+                    Tokens used: {} ({})\n\
                     Total sessions: {}\n\
                     Active tasks: {}",
                     config.agent.name,
@@ -1230,6 +1450,7 @@ async fn handle_command(
                     session_key,
                     msg_count,
                     tokens,
+                    openclaw_agent::llm::pricing::format_cost(cost),
                     sessions.len(),
                     crate::task_registry::active_count(),
                 ),
@@ -1237,16 +1458,26 @@ async fn handle_command(
             .await?;
         }
         "/model" => {
-            let chain_info = if config.agent.fallback {
+            let session_key = format!("tg:{}:{}:{}", config.agent.name, user_id, chat_id);
+            if let Some(tier) = text.split_whitespace().nth(1) {
+                let reply = crate::handler_utils::set_model_tier(&session_key, tier);
+                bot.send_message(chat_id, &reply).await?;
+                return Ok(());
+            }
+            let mut chain_info = if config.agent.fallback {
                 match FallbackProvider::from_config() {
                     Ok(fb) => {
-                        let labels = fb.provider_labels();
                         let mut info = String::from("🔗 *Fallback Chain:*\n\n");
-                        for (i, label) in labels.iter().enumerate() {
+                        for (i, health) in fb.health().iter().enumerate() {
                             let marker = if i == 0 { "🥇" } else if i == 1 { "🥈" } else { "🥉" };
-                            info.push_str(&format!("{} `{}`\n", marker, label));
+                            info.push_str(&format!("{} `{}` — {}\n", marker, health.label, health.summary()));
                         }
-                        info.push_str("\nFirst available model is used. Circuit breaker at >3 failures.");
+                        info.push_str(&format!(
+                            "\nMode: {}. Circuit breaker at >{} failures, {}s cooldown.",
+                            fb.order().as_str(),
+                            fb.breaker_config().max_consecutive_failures,
+                            fb.breaker_config().cooldown.as_secs(),
+                        ));
                         info
                     }
                     Err(e) => format!("❌ Error: {}", e),
@@ -1254,6 +1485,10 @@ async fn handle_command(
             } else {
                 format!("🤖 *Model:* `{}`", config.agent.model.as_deref().unwrap_or("default"))
             };
+            if let Some(routing) = crate::handler_utils::routing_summary(&session_key) {
+                chain_info.push_str("\n\n");
+                chain_info.push_str(&routing);
+            }
             bot.send_message(chat_id, &chain_info).await?;
         }
         "/export" => {
@@ -1320,28 +1555,32 @@ async fn handle_command(
                 bot.send_typing(chat_id).await.ok();
 
                 // Get LLM response for the text
-                let provider: Box<dyn LlmProvider> = match FallbackProvider::from_config() {
-                    Ok(fb) => Box::new(fb),
+                let session_key = format!("tg:{}:{}:{}", config.agent.name, user_id, chat_id);
+                let provider = match crate::handler_utils::resolve_agent_provider(&config.agent, &session_key) {
+                    Ok(p) => p,
                     Err(e) => {
                         bot.send_message(chat_id, &format!("❌ Provider error: {}", e)).await?;
                         return Ok(());
                     }
                 };
 
-                let workspace_dir = workspace::resolve_workspace_dir(&config.agent.name);
-                let session_key = format!("tg:{}:{}:{}", config.agent.name, user_id, chat_id);
+                let workspace_dir = config.agent.workspace_dir();
                 let p = openclaw_db::pool().ok_or_else(|| anyhow::anyhow!("Database not available"))?;
                 openclaw_db::sessions::upsert_session(
                     p, &session_key, &config.agent.name, provider.name(),
                     Some("telegram"), Some(&user_id.to_string()),
                 ).await?;
 
-                let tools = build_tool_registry(&workspace_dir).await;
+                let tools = build_tool_registry(&config.agent, "telegram").await;
                 let agent_config = AgentTurnConfig {
                     agent_name: config.agent.name.clone(),
                     session_key: session_key.clone(),
                     workspace_dir: workspace_dir.to_string_lossy().to_string(),
                     minimal_context: true,
+                    sandbox: config.agent.sandbox_policy("telegram", &user_id.to_string()),
+                    limits: config.agent.limits.clone(),
+                    approval: config.agent.approval.clone(),
+                    system_prompt_overlay: config.agent.system_prompt.clone(),
                 ..AgentTurnConfig::default()
                 };
 
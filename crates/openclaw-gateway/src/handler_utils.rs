@@ -1,6 +1,7 @@
 //! Shared utilities for Telegram and Discord message handlers.
 
 use anyhow::Result;
+use openclaw_agent::approval::Resolution;
 use openclaw_agent::llm::OpenAiCompatibleProvider;
 
 /// Resolve a single LLM provider by model spec (e.g. "moonshot/kimi-k2.5")
@@ -25,7 +26,8 @@ pub fn resolve_single_provider(model_spec: &str) -> Result<OpenAiCompatibleProvi
                 for model in models {
                     if let Some(id) = model.get("id").and_then(|v| v.as_str()) {
                         if id == model_spec || format!("{}/{}", pname, id) == model_spec {
-                            return Ok(OpenAiCompatibleProvider::new(base_url, api_key, id));
+                            let params = openclaw_core::models::resolve_generation_params(provider, id);
+                            return Ok(OpenAiCompatibleProvider::new(base_url, api_key, id).with_params(params));
                         }
                     }
                 }
@@ -36,6 +38,94 @@ pub fn resolve_single_provider(model_spec: &str) -> Result<OpenAiCompatibleProvi
     anyhow::bail!("Model '{}' not found in any provider", model_spec)
 }
 
+/// LLM provider for a turn of `agent` in `session_key`: the profile's explicit
+/// model chain, its single model, or the configured (routed) fallback chain.
+/// Wrapped in a cassette recorder when the agent has a `record_dir`.
+pub fn resolve_agent_provider(
+    agent: &crate::config::AgentConfig,
+    session_key: &str,
+) -> Result<Box<dyn openclaw_agent::llm::LlmProvider>> {
+    let provider = resolve_model_chain(agent, session_key)?;
+    Ok(match agent.record_dir {
+        Some(ref dir) => Box::new(openclaw_agent::replay::RecordingProvider::new(
+            provider,
+            std::path::Path::new(dir).join(cassette_file_name(session_key)),
+        )),
+        None => provider,
+    })
+}
+
+fn resolve_model_chain(
+    agent: &crate::config::AgentConfig,
+    session_key: &str,
+) -> Result<Box<dyn openclaw_agent::llm::LlmProvider>> {
+    if !agent.models.is_empty() {
+        let config_path = openclaw_core::paths::manual_config_path();
+        let config: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&config_path)?)?;
+        let chain = openclaw_agent::llm::fallback::FallbackProvider::from_specs(&config, &agent.models)?;
+        return Ok(Box::new(chain));
+    }
+    match agent.model {
+        Some(ref model) if !agent.fallback => Ok(Box::new(resolve_single_provider(model)?)),
+        _ => openclaw_agent::llm::router::provider_for_chat(session_key),
+    }
+}
+
+/// `<session key>-<UTC time>.jsonl`, with the key made filename-safe
+fn cassette_file_name(session_key: &str) -> String {
+    let key: String = session_key
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    format!("{}-{}.jsonl", key, chrono::Utc::now().format("%Y%m%dT%H%M%S%.3f"))
+}
+
+/// Routing overview for `/model`: tiers, where each complexity goes, and this chat's pin.
+/// `None` when `models.routing` isn't configured.
+pub fn routing_summary(session_key: &str) -> Option<String> {
+    use openclaw_agent::llm::router::{chat_override, Complexity, RouterProvider};
+
+    let router = RouterProvider::from_config().ok().flatten()?;
+    let routes: Vec<String> = [Complexity::Simple, Complexity::Standard, Complexity::Complex]
+        .iter()
+        .map(|c| format!("{} → `{}`", c.as_str(), router.tier_for(*c)))
+        .collect();
+    let pin = match chat_override(session_key) {
+        Some(tier) => format!("pinned to `{}`", tier),
+        None => "automatic".to_string(),
+    };
+    Some(format!(
+        "🧭 Routing: {}\nTiers: {}\nClassifier: {}\nThis chat: {}\n\nUse /model <tier> to pin a tier, /model auto to route automatically.",
+        routes.join(", "),
+        router.tier_names().iter().map(|t| format!("`{}`", t)).collect::<Vec<_>>().join(", "),
+        if router.has_classifier() { "on" } else { "off" },
+        pin,
+    ))
+}
+
+/// Handle `/model <tier>` / `/model auto`: pin this chat to a routing tier or clear the pin
+pub fn set_model_tier(session_key: &str, tier: &str) -> String {
+    use openclaw_agent::llm::router::{set_chat_override, RouterProvider};
+
+    if tier == "auto" {
+        set_chat_override(session_key, None);
+        return "🧭 Routing is automatic for this chat again.".to_string();
+    }
+    match RouterProvider::from_config() {
+        Ok(Some(router)) if router.has_tier(tier) => {
+            set_chat_override(session_key, Some(tier));
+            format!("🧭 This chat now always uses the `{}` tier. /model auto to undo.", tier)
+        }
+        Ok(Some(router)) => format!(
+            "❌ Unknown tier `{}`. Tiers: {}",
+            tier,
+            router.tier_names().join(", ")
+        ),
+        Ok(None) => "❌ Model routing is not configured (models.routing).".to_string(),
+        Err(e) => format!("❌ Error: {}", e),
+    }
+}
+
 /// Toggle a cron job's enabled state by name (case-insensitive partial match)
 pub fn toggle_cron_job(path: &std::path::Path, name: &str, enable: bool) -> Result<String> {
     let content = std::fs::read_to_string(path)?;
@@ -88,6 +178,118 @@ pub fn format_duration(ms: i64) -> String {
     }
 }
 
+/// " · $0.0123" for the reply stats footer, or nothing when the turn had no known cost
+pub fn cost_suffix(cost_micros: u64) -> String {
+    if cost_micros == 0 {
+        String::new()
+    } else {
+        format!(" · {}", openclaw_agent::llm::pricing::format_cost(cost_micros))
+    }
+}
+
+/// Button payload (Telegram callback data / Discord custom_id) for an approval answer
+pub fn approval_button_data(id: &str, approve: bool) -> String {
+    format!("approval:{}:{}", if approve { "y" } else { "n" }, id)
+}
+
+/// Parse an approval button payload into (approved, request id)
+pub fn parse_approval_data(data: &str) -> Option<(bool, &str)> {
+    let (answer, id) = data.strip_prefix("approval:")?.split_once(':')?;
+    match answer {
+        "y" => Some((true, id)),
+        "n" => Some((false, id)),
+        _ => None,
+    }
+}
+
+/// Message asking the user to approve a paused tool call
+pub fn approval_prompt(tool: &str, args_summary: &str, reason: &str, timeout_secs: u64) -> String {
+    let call = if args_summary.is_empty() {
+        tool.to_string()
+    } else {
+        format!("{} — {}", tool, args_summary)
+    };
+    format!(
+        "⏸️ Approval needed: {}\nWhy: {}\n\nDenied automatically in {}s.",
+        call, reason, timeout_secs
+    )
+}
+
+/// Line added to the approval message once someone presses a button
+pub fn approval_outcome(approved: bool, resolution: Resolution, who: &str) -> String {
+    match (resolution, approved) {
+        (Resolution::Expired, _) => "⌛ This request has already expired.".to_string(),
+        (Resolution::NotRequester, _) => "🚫 Only the user who started this turn can answer it.".to_string(),
+        (Resolution::Answered, true) => format!("✅ Approved by {}", who),
+        (Resolution::Answered, false) => format!("❌ Denied by {}", who),
+    }
+}
+
+/// Keep a turn's idle watchdog from firing while it waits on the user
+pub fn keep_alive_while_pending(watchdog: &openclaw_agent::watchdog::ActivityWatchdog, approval_id: String) {
+    let watchdog = watchdog.clone();
+    tokio::spawn(async move {
+        while openclaw_agent::approval::is_pending(&approval_id) {
+            watchdog.touch();
+            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+        }
+        watchdog.touch();
+    });
+}
+
+/// Min interval between message edits for live tool output (a build can print thousands of lines)
+pub const LIVE_OUTPUT_EDIT_MS: u64 = 1500;
+
+/// Rolling tail of running tools' output, shown under the "⚙️ Running" indicator
+#[derive(Debug, Default)]
+pub struct OutputTail {
+    text: String,
+}
+
+impl OutputTail {
+    const MAX_LINES: usize = 8;
+    const MAX_CHARS: usize = 600;
+
+    pub fn push(&mut self, chunk: &str) {
+        self.text.push_str(&chunk.replace("\r\n", "\n"));
+        // A bare \r redraws the line (progress bars): keep what came after it
+        let lines: Vec<&str> = self.text.split('\n').map(|l| l.rsplit('\r').next().unwrap_or(l)).collect();
+        let mut tail = lines[lines.len().saturating_sub(Self::MAX_LINES)..].join("\n");
+        if tail.len() > Self::MAX_CHARS {
+            let mut cut = tail.len() - Self::MAX_CHARS;
+            while !tail.is_char_boundary(cut) {
+                cut += 1;
+            }
+            tail = tail[cut..].to_string();
+        }
+        self.text = tail;
+    }
+
+    pub fn clear(&mut self) {
+        self.text.clear();
+    }
+
+    /// The last lines of output, without surrounding blank lines
+    pub fn text(&self) -> &str {
+        self.text.trim_matches('\n')
+    }
+}
+
+/// Appended to the reply of a turn that stopped early and can be resumed
+pub const RESUME_HINT: &str = "⏸️ Progress saved — send /resume to continue this turn.";
+
+/// The session's interrupted turn, if any (see `runtime::resume_agent_turn_streaming`)
+pub async fn latest_checkpoint(session_key: &str) -> Option<openclaw_db::checkpoints::TurnCheckpoint> {
+    let pool = openclaw_db::pool()?;
+    match openclaw_db::checkpoints::latest_checkpoint(pool, session_key).await {
+        Ok(checkpoint) => checkpoint,
+        Err(e) => {
+            tracing::warn!("Failed to load checkpoint for {}: {}", session_key, e);
+            None
+        }
+    }
+}
+
 /// Split a long message into chunks at newline boundaries, respecting a max length per chunk
 pub fn split_message(text: &str, max_len: usize) -> Vec<String> {
     if text.len() <= max_len {
@@ -111,6 +313,28 @@ pub fn split_message(text: &str, max_len: usize) -> Vec<String> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_output_tail_keeps_last_lines() {
+        let mut tail = OutputTail::default();
+        for i in 1..=20 {
+            tail.push(&format!("line {}\n", i));
+        }
+        assert!(tail.text().starts_with("line 14"));
+        assert!(tail.text().ends_with("line 20"));
+
+        tail.clear();
+        tail.push("building 10%\rbuilding 55%");
+        tail.push("\rbuilding 100%\ndone");
+        assert_eq!(tail.text(), "building 100%\ndone");
+    }
+
+    #[test]
+    fn test_cassette_file_name_is_path_safe() {
+        let name = cassette_file_name("tg:main:42:-1001/x");
+        assert!(name.starts_with("tg_main_42_-1001_x-"));
+        assert!(name.ends_with(".jsonl"));
+    }
+
     #[test]
     fn test_format_duration_seconds() {
         assert_eq!(format_duration(5_000), "5s ago");
@@ -135,6 +359,30 @@ mod tests {
         assert_eq!(format_duration(172_800_000), "2d ago");
     }
 
+    #[test]
+    fn test_cost_suffix() {
+        assert_eq!(cost_suffix(0), "");
+        assert_eq!(cost_suffix(12_300), " · $0.0123");
+    }
+
+    #[test]
+    fn test_approval_button_data_round_trip() {
+        let data = approval_button_data("a1b2c3d4e5f6", true);
+        assert_eq!(parse_approval_data(&data), Some((true, "a1b2c3d4e5f6")));
+        let data = approval_button_data("a1b2c3d4e5f6", false);
+        assert_eq!(parse_approval_data(&data), Some((false, "a1b2c3d4e5f6")));
+        assert_eq!(parse_approval_data("approval:maybe:x"), None);
+        assert_eq!(parse_approval_data("other:y:x"), None);
+    }
+
+    #[test]
+    fn test_approval_outcome() {
+        assert_eq!(approval_outcome(true, Resolution::Answered, "Ann"), "✅ Approved by Ann");
+        assert_eq!(approval_outcome(false, Resolution::Answered, "Ann"), "❌ Denied by Ann");
+        assert!(approval_outcome(true, Resolution::Expired, "Ann").contains("expired"));
+        assert!(approval_outcome(true, Resolution::NotRequester, "Bob").contains("Only the user"));
+    }
+
     #[test]
     fn test_split_message_short() {
         let chunks = split_message("hello", 2000);
@@ -10,7 +10,9 @@ mod handler;
 mod handler_utils;
 mod orch_commands;
 mod metrics;
+mod quota;
 mod ratelimit;
+mod reload;
 mod subagent_registry;
 mod task_registry;
 mod telegram;
@@ -28,8 +30,7 @@ async fn main() -> anyhow::Result<()> {
         )
         .init();
 
-    let config_path = std::env::var("GATEWAY_CONFIG")
-        .unwrap_or_else(|_| "/etc/openclaw-gateway/config.json".to_string());
+    let config_path = config::config_path();
     let config = config::GatewayConfig::from_file_or_env(&config_path)?;
 
     // ── Startup banner ──
@@ -37,11 +38,16 @@ async fn main() -> anyhow::Result<()> {
     info!("║  openclaw-gateway v{}              ║", env!("CARGO_PKG_VERSION"));
     info!("╚══════════════════════════════════════════╝");
     info!("Agent: {} | Fallback: {}", config.agent.name, config.agent.fallback);
+    if !config.agents.is_empty() {
+        let mut names: Vec<&str> = config.agents.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        info!("Agent profiles: {}", names.join(", "));
+    }
     info!("Telegram allowed users: {:?}", config.telegram.allowed_user_ids);
     if let Some(ref dc) = config.discord {
         info!("Discord enabled | allowed users: {:?}", dc.allowed_user_ids);
     }
-    info!("Commands: 28 (/help /new /status /model /sessions /export /voice /ping /history /clear /db /version /stats /whoami /cancel /stop /cron /tools /skills /config /runtime /doctor /logs /projects /orch_status /cycle /approve /workers)");
+    info!("Commands: 30 (/help /new /status /model /sessions /export /voice /ping /history /clear /db /version /stats /quota /whoami /cancel /stop /cron /tools /plugins /skills /config /runtime /doctor /logs /projects /orch_status /cycle /approve /workers)");
 
     // ── Verify bot token ──
     let bot = telegram::TelegramBot::new(&config.telegram.bot_token);
@@ -102,11 +108,11 @@ async fn main() -> anyhow::Result<()> {
     // ── Start health check HTTP server (build tool registry with MCP tools) ──
     let start_time = std::time::Instant::now();
     let health_config = Arc::new(config.clone());
-    let workspace_dir = openclaw_agent::workspace::resolve_workspace_dir(&config.agent.name);
-    let startup_tools = handler::build_tool_registry(&workspace_dir).await;
+    let startup_tools = handler::build_tool_registry(&config.agent, "").await;
     let tool_names: Vec<String> = startup_tools.tool_names().iter().map(|s| s.to_string()).collect();
     handler::set_tool_count(tool_names.len());
     info!("Tools: {} (built-in + MCP client)", tool_names.len());
+    openclaw_agent::tools::plugins::spawn_watcher();
     info!("MCP server enabled (SSE: /sse, Messages: /messages)");
     let tool_names = Arc::new(tool_names);
     let app = Router::new()
@@ -159,6 +165,13 @@ async fn main() -> anyhow::Result<()> {
             }),
         )
         .route("/doctor/json", get(doctor_json_handler))
+        .route(
+            "/config/reload",
+            axum::routing::post({
+                let cfg = health_config.clone();
+                move |headers: axum::http::HeaderMap| config_reload_handler(cfg, headers)
+            }),
+        )
         .route("/logs", get(logs_handler))
         .route("/logs/{id}", get(log_detail_handler))
         .route(
@@ -166,7 +179,18 @@ async fn main() -> anyhow::Result<()> {
             axum::routing::post({
                 let cfg = health_config.clone();
                 move |headers: axum::http::HeaderMap, body: Json<serde_json::Value>| {
-                    webhook_handler(cfg, headers, body)
+                    webhook_handler(cfg, None, headers, body)
+                }
+            }),
+        )
+        .route(
+            "/webhook/{path}",
+            axum::routing::post({
+                let cfg = health_config.clone();
+                move |axum::extract::Path(path): axum::extract::Path<String>,
+                      headers: axum::http::HeaderMap,
+                      body: Json<serde_json::Value>| {
+                    webhook_handler(cfg, Some(path), headers, body)
                 }
             }),
         );
@@ -281,7 +305,22 @@ async fn main() -> anyhow::Result<()> {
             match discord_bot.clone().connect_gateway().await {
                 Ok(mut msg_rx) => {
                     info!("Discord Gateway connected, listening for messages...");
-                    while let Some(msg) = msg_rx.recv().await {
+                    while let Some(event) = msg_rx.recv().await {
+                        let msg = match event {
+                            discord::DiscordEvent::Message(msg) => msg,
+                            discord::DiscordEvent::Interaction(interaction) => {
+                                let bot = discord::DiscordBot::new(
+                                    &discord_config_clone.discord.as_ref().unwrap().bot_token,
+                                );
+                                let config_clone = discord_config_clone.clone();
+                                tokio::spawn(async move {
+                                    if let Err(e) = discord_handler::handle_discord_interaction(&bot, &interaction, &config_clone).await {
+                                        error!("Discord interaction error: {}", e);
+                                    }
+                                });
+                                continue;
+                            }
+                        };
                         let user_id: i64 = msg.author.id.parse().unwrap_or(0);
                         discord_metrics.record_discord_request();
 
@@ -324,7 +363,10 @@ async fn main() -> anyhow::Result<()> {
                         let bot_clone = discord::DiscordBot::new(
                             &discord_config_clone.discord.as_ref().unwrap().bot_token,
                         );
-                        let config_clone = discord_config_clone.clone();
+                        let config_clone = discord_config_clone.routed(config::Route::Discord {
+                            channel_id: &msg.channel_id,
+                            guild_id: msg.guild_id.as_deref(),
+                        });
                         let task_metrics = discord_metrics.clone();
 
                         tokio::spawn(async move {
@@ -394,6 +436,17 @@ async fn main() -> anyhow::Result<()> {
                         for update in updates {
                             offset = update.update_id + 1;
 
+                            if let Some(query) = update.callback_query {
+                                let cb_bot = telegram::TelegramBot::new(&config.telegram.bot_token);
+                                let cb_config = config.clone();
+                                tokio::spawn(async move {
+                                    if let Err(e) = handler::handle_callback_query(&cb_bot, &query, &cb_config).await {
+                                        error!("Callback query error: {}", e);
+                                    }
+                                });
+                                continue;
+                            }
+
                             if let Some(msg) = update.message {
                                 let user_id = msg.from.as_ref().map(|u| u.id).unwrap_or(0);
                                 gateway_metrics.record_telegram_request();
@@ -426,7 +479,7 @@ async fn main() -> anyhow::Result<()> {
                                 };
 
                                 let bot_clone = telegram::TelegramBot::new(&config.telegram.bot_token);
-                                let config_clone = config.clone();
+                                let config_clone = config.routed(config::Route::Telegram { chat_id: msg.chat.id });
                                 let tg_metrics = gateway_metrics.clone();
 
                                 tokio::spawn(async move {
@@ -589,8 +642,8 @@ async fn health_handler() -> axum::response::Response {
             "sessions_db_size": crate::doctor::human_bytes_pub(sessions_db_size),
             "skills": skills_count,
             "sessions": session_count,
-            "commands": 28,
-            "http_endpoint_count": 14,
+            "commands": 29,
+            "http_endpoint_count": 16,
             "tool_count": handler::tool_count(),
             "total_requests": total_requests,
             "total_errors": total_errors,
@@ -740,8 +793,53 @@ async fn metrics_json_handler(
     Json(m.to_json())
 }
 
+/// `POST /config/reload` revalidates plugins and reconnects changed MCP
+/// servers; needs the webhook bearer token and is disabled without a webhook
+async fn config_reload_handler(
+    config: Arc<config::GatewayConfig>,
+    headers: axum::http::HeaderMap,
+) -> axum::response::Response {
+    use axum::response::IntoResponse;
+
+    // Reloading loads plugins and reconnects MCP servers, so it is never open
+    let expected_token = match &config.webhook {
+        Some(wh) if !wh.token.is_empty() => &wh.token,
+        _ => {
+            return (
+                axum::http::StatusCode::SERVICE_UNAVAILABLE,
+                Json(serde_json::json!({"error": "webhook not configured", "error_code": "WEBHOOK_NOT_CONFIGURED"})),
+            ).into_response();
+        }
+    };
+    let token = headers
+        .get("authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .unwrap_or("");
+    if token != expected_token {
+        return (
+            axum::http::StatusCode::UNAUTHORIZED,
+            Json(serde_json::json!({"error": "invalid token", "error_code": "INVALID_TOKEN"})),
+        ).into_response();
+    }
+
+    match reload::reload(&config::config_path()).await {
+        Ok(report) => {
+            info!("Config reload: {} | MCP {}", report.plugins.iter().map(|p| p.summary()).collect::<Vec<_>>().join(", "), report.mcp.summary());
+            Json(serde_json::json!(report)).into_response()
+        }
+        Err(e) => (
+            axum::http::StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": format!("{:#}", e), "error_code": "RELOAD_FAILED"})),
+        ).into_response(),
+    }
+}
+
+/// `POST /webhook` runs a turn as the default agent; `POST /webhook/<path>`
+/// as the profile `routing.webhook_paths` (or a profile of that name) picks
 async fn webhook_handler(
     config: Arc<config::GatewayConfig>,
+    path: Option<String>,
     headers: axum::http::HeaderMap,
     Json(body): Json<serde_json::Value>,
 ) -> axum::response::Response {
@@ -773,6 +871,20 @@ async fn webhook_handler(
         ).into_response();
     }
 
+    let config = match path {
+        Some(ref path) => {
+            let route = config::Route::Webhook { path };
+            if config.route_profile(route).is_none() {
+                return (
+                    axum::http::StatusCode::NOT_FOUND,
+                    Json(serde_json::json!({"request_id": request_id, "error": format!("no agent profile for webhook path '{}'", path), "error_code": "UNKNOWN_WEBHOOK_PATH"})),
+                ).into_response();
+            }
+            config.routed(route)
+        }
+        None => config::GatewayConfig::clone(&config),
+    };
+
     // Extract message from body
     let message = match body.get("message").and_then(|m| m.as_str()) {
         Some(m) => m.to_string(),
@@ -795,7 +907,7 @@ async fn webhook_handler(
     }
 
     // Build provider
-    let provider = match openclaw_agent::llm::fallback::FallbackProvider::from_config() {
+    let provider = match handler_utils::resolve_agent_provider(&config.agent, &session_key) {
         Ok(p) => p,
         Err(e) => {
             return (
@@ -805,22 +917,26 @@ async fn webhook_handler(
         }
     };
 
-    let workspace_dir = openclaw_agent::workspace::resolve_workspace_dir(&config.agent.name);
+    let workspace_dir = config.agent.workspace_dir();
     let agent_config = AgentTurnConfig {
         agent_name: config.agent.name.clone(),
         session_key: session_key.clone(),
         workspace_dir: workspace_dir.to_string_lossy().to_string(),
         minimal_context: false,
+        sandbox: config.agent.sandbox_policy("webhook", ""),
+        limits: config.agent.limits.clone(),
+        approval: config.agent.approval.clone(),
+        system_prompt_overlay: config.agent.system_prompt.clone(),
     ..AgentTurnConfig::default()
     };
 
-    let tools = handler::build_tool_registry(&workspace_dir).await;
+    let tools = handler::build_tool_registry(&config.agent, "webhook").await;
     let (event_tx, _event_rx) = tokio::sync::mpsc::unbounded_channel();
 
     let t_start = std::time::Instant::now();
 
     let result = runtime::run_agent_turn_streaming(
-        &provider,
+        provider.as_ref(),
         &message,
         &agent_config,
         &tools,
@@ -925,10 +1041,10 @@ async fn status_handler(
 
     // Commands
     let tg_commands = ["help", "new", "status", "model", "sessions", "export", "voice", "ping",
-        "history", "clear", "db", "version", "stats", "whoami", "cancel", "stop", "cron", "tools", "skills", "config", "runtime", "doctor", "logs",
+        "history", "clear", "db", "version", "stats", "whoami", "cancel", "stop", "cron", "tools", "plugins", "skills", "config", "runtime", "doctor", "logs",
         "projects", "orch_status", "cycle", "approve", "workers"];
     let dc_commands = ["help", "new", "status", "model", "sessions", "export", "voice", "ping",
-        "history", "clear", "db", "version", "stats", "whoami", "cancel", "stop", "cron", "tools", "skills", "config", "runtime", "doctor", "logs",
+        "history", "clear", "db", "version", "stats", "whoami", "cancel", "stop", "cron", "tools", "plugins", "skills", "config", "runtime", "doctor", "logs",
         "projects", "orch_status", "cycle", "approve", "workers"];
 
     // Provider labels from fallback chain
@@ -955,8 +1071,8 @@ async fn status_handler(
         "webhook_configured": config.webhook.is_some(),
         "built": env!("BUILD_TIMESTAMP"),
         "boot_time": *handler::BOOT_TIMESTAMP,
-        "http_endpoints": ["/health", "/health/lite", "/version", "/ping", "/ready", "/status", "/metrics", "/metrics/json", "/metrics/summary", "/doctor", "/doctor/json", "/webhook", "/logs", "/logs/:id"],
-        "http_endpoint_count": 14,
+        "http_endpoints": ["/health", "/health/lite", "/version", "/ping", "/ready", "/status", "/metrics", "/metrics/json", "/metrics/summary", "/doctor", "/doctor/json", "/webhook", "/webhook/:path", "/logs", "/logs/:id", "/config/reload"],
+        "http_endpoint_count": 16,
         "commands": {
             "telegram": tg_commands,
             "discord": dc_commands,
@@ -1012,12 +1128,30 @@ mod tests {
             "MISSING_MESSAGE",
             "PROVIDER_INIT_FAILED",
             "AGENT_TURN_FAILED",
+            "UNKNOWN_WEBHOOK_PATH",
         ];
         for code in &codes {
             assert!(code.chars().all(|c| c.is_ascii_uppercase() || c == '_'),
                 "Error code '{}' should be UPPER_SNAKE_CASE", code);
         }
-        assert_eq!(codes.len(), 5, "Should have 5 webhook error codes");
+        assert_eq!(codes.len(), 6, "Should have 6 webhook error codes");
+    }
+
+    #[tokio::test]
+    async fn test_config_reload_requires_token() {
+        let config = |webhook: &str| -> Arc<config::GatewayConfig> {
+            let json = format!(r#"{{"telegram": {{"bot_token": "t", "allowed_user_ids": []}}, "agent": {{"name": "test"}}{}}}"#, webhook);
+            Arc::new(serde_json::from_str(&json).unwrap())
+        };
+        let no_webhook = config_reload_handler(config(""), axum::http::HeaderMap::new()).await;
+        assert_eq!(no_webhook.status(), axum::http::StatusCode::SERVICE_UNAVAILABLE);
+        let empty_token = config_reload_handler(config(r#", "webhook": {"token": ""}"#), axum::http::HeaderMap::new()).await;
+        assert_eq!(empty_token.status(), axum::http::StatusCode::SERVICE_UNAVAILABLE);
+
+        let mut headers = axum::http::HeaderMap::new();
+        headers.insert("authorization", "Bearer wrong".parse().unwrap());
+        let wrong = config_reload_handler(config(r#", "webhook": {"token": "secret"}"#), headers).await;
+        assert_eq!(wrong.status(), axum::http::StatusCode::UNAUTHORIZED);
     }
 
     #[test]
@@ -1029,15 +1163,15 @@ mod tests {
     }
 
     #[test]
-    fn test_command_arrays_have_28_entries() {
+    fn test_command_arrays_have_29_entries() {
         let tg = ["help", "new", "status", "model", "sessions", "export", "voice", "ping",
-            "history", "clear", "db", "version", "stats", "whoami", "cancel", "stop", "cron", "tools", "skills", "config", "runtime", "doctor", "logs",
+            "history", "clear", "db", "version", "stats", "whoami", "cancel", "stop", "cron", "tools", "plugins", "skills", "config", "runtime", "doctor", "logs",
         "projects", "orch_status", "cycle", "approve", "workers"];
         let dc = ["help", "new", "status", "model", "sessions", "export", "voice", "ping",
-            "history", "clear", "db", "version", "stats", "whoami", "cancel", "stop", "cron", "tools", "skills", "config", "runtime", "doctor", "logs",
+            "history", "clear", "db", "version", "stats", "whoami", "cancel", "stop", "cron", "tools", "plugins", "skills", "config", "runtime", "doctor", "logs",
         "projects", "orch_status", "cycle", "approve", "workers"];
-        assert_eq!(tg.len(), 28, "Telegram should have 28 commands");
-        assert_eq!(dc.len(), 28, "Discord should have 28 commands");
+        assert_eq!(tg.len(), 29, "Telegram should have 29 commands");
+        assert_eq!(dc.len(), 29, "Discord should have 29 commands");
         // Verify both arrays are identical
         assert_eq!(tg, dc, "Telegram and Discord command lists should match");
     }
@@ -1210,13 +1344,13 @@ mod tests {
 
     #[test]
     fn test_http_endpoints_count() {
-        let endpoints = ["/health", "/health/lite", "/version", "/ping", "/ready", "/status", "/metrics", "/metrics/json", "/metrics/summary", "/doctor", "/doctor/json", "/webhook", "/logs", "/logs/:id"];
-        assert_eq!(endpoints.len(), 14, "Should have 14 HTTP endpoints");
+        let endpoints = ["/health", "/health/lite", "/version", "/ping", "/ready", "/status", "/metrics", "/metrics/json", "/metrics/summary", "/doctor", "/doctor/json", "/webhook", "/webhook/:path", "/logs", "/logs/:id", "/config/reload"];
+        assert_eq!(endpoints.len(), 16, "Should have 16 HTTP endpoints");
         // Verify no duplicates
         let mut sorted = endpoints.to_vec();
         sorted.sort();
         sorted.dedup();
-        assert_eq!(sorted.len(), 14, "HTTP endpoints should have no duplicates");
+        assert_eq!(sorted.len(), 16, "HTTP endpoints should have no duplicates");
     }
 
     #[test]
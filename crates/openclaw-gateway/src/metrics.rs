@@ -299,6 +299,42 @@ impl GatewayMetrics {
         out.push_str("# TYPE openclaw_gateway_llm_log_avg_latency_ms gauge\n");
         out.push_str(&format!("openclaw_gateway_llm_log_avg_latency_ms {}\n", llm_stats.avg_latency_ms));
 
+        let provider_health = openclaw_agent::llm::health::HealthRegistry::global().snapshot_all();
+        out.push_str("# HELP openclaw_gateway_provider_circuit_state LLM provider circuit breaker (0=closed, 1=half-open, 2=open)\n");
+        out.push_str("# TYPE openclaw_gateway_provider_circuit_state gauge\n");
+        for h in &provider_health {
+            out.push_str(&format!("openclaw_gateway_provider_circuit_state{{provider=\"{}\"}} {}\n",
+                h.label, h.state.as_gauge()));
+        }
+        out.push_str("# HELP openclaw_gateway_provider_consecutive_failures LLM provider consecutive failures\n");
+        out.push_str("# TYPE openclaw_gateway_provider_consecutive_failures gauge\n");
+        for h in &provider_health {
+            out.push_str(&format!("openclaw_gateway_provider_consecutive_failures{{provider=\"{}\"}} {}\n",
+                h.label, h.consecutive_failures));
+        }
+        out.push_str("# HELP openclaw_gateway_provider_latency_ms LLM provider mean latency over the rolling window\n");
+        out.push_str("# TYPE openclaw_gateway_provider_latency_ms gauge\n");
+        for h in &provider_health {
+            if let Some(ms) = h.avg_latency_ms {
+                out.push_str(&format!("openclaw_gateway_provider_latency_ms{{provider=\"{}\"}} {}\n",
+                    h.label, ms));
+            }
+        }
+        out.push_str("# HELP openclaw_gateway_provider_error_rate LLM provider error rate over the rolling window\n");
+        out.push_str("# TYPE openclaw_gateway_provider_error_rate gauge\n");
+        for h in &provider_health {
+            out.push_str(&format!("openclaw_gateway_provider_error_rate{{provider=\"{}\"}} {:.3}\n",
+                h.label, h.error_rate));
+        }
+        out.push_str("# HELP openclaw_gateway_provider_calls_total LLM provider calls by outcome\n");
+        out.push_str("# TYPE openclaw_gateway_provider_calls_total counter\n");
+        for h in &provider_health {
+            out.push_str(&format!("openclaw_gateway_provider_calls_total{{provider=\"{}\",outcome=\"success\"}} {}\n",
+                h.label, h.total_successes));
+            out.push_str(&format!("openclaw_gateway_provider_calls_total{{provider=\"{}\",outcome=\"failure\"}} {}\n",
+                h.label, h.total_failures));
+        }
+
         out.push_str("# HELP openclaw_gateway_pid Process ID of the gateway\n");
         out.push_str("# TYPE openclaw_gateway_pid gauge\n");
         out.push_str(&format!("openclaw_gateway_pid {}\n", std::process::id()));
@@ -566,6 +602,26 @@ mod tests {
             "JSON metrics should contain process_rss_bytes");
     }
 
+    #[test]
+    fn test_prometheus_provider_health() {
+        use openclaw_agent::llm::health::{BreakerConfig, HealthRegistry};
+        let cfg = BreakerConfig::default();
+        let registry = HealthRegistry::global();
+        registry.record_success("metrics-test/model", &cfg, 250);
+        for _ in 0..4 {
+            registry.record_failure("metrics-test/down", &cfg, 10);
+        }
+
+        let prom = GatewayMetrics::new().to_prometheus();
+        assert!(prom.contains("# TYPE openclaw_gateway_provider_circuit_state gauge"));
+        assert!(prom.contains("openclaw_gateway_provider_circuit_state{provider=\"metrics-test/model\"} 0"));
+        assert!(prom.contains("openclaw_gateway_provider_circuit_state{provider=\"metrics-test/down\"} 2"));
+        assert!(prom.contains("openclaw_gateway_provider_latency_ms{provider=\"metrics-test/model\"} 250"));
+        assert!(prom.contains(
+            "openclaw_gateway_provider_calls_total{provider=\"metrics-test/down\",outcome=\"failure\"} 4"
+        ));
+    }
+
     #[test]
     fn test_prometheus_has_all_expected_metrics() {
         let m = GatewayMetrics::new();
@@ -0,0 +1,253 @@
+//! Per-user and per-session spend quotas.
+//!
+//! Usage is kept per UTC day in the Postgres `spend_ledger` table. Before a
+//! turn the gateway refuses if any quota is used up; otherwise the remaining
+//! allowance is passed to the runtime as a `TurnBudget`, which stops the
+//! turn between rounds once it is spent.
+
+use openclaw_agent::llm::pricing::format_cost;
+use openclaw_agent::runtime::{TurnBudget, UsageHook};
+use openclaw_db::spend::SpendTotals;
+use tracing::warn;
+
+use crate::config::{QuotaConfig, QuotaLimits};
+
+const MICROS_PER_CENT: u64 = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scope {
+    User,
+    Session,
+}
+
+impl Scope {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::User => "user",
+            Self::Session => "session",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Period {
+    Daily,
+    Monthly,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Tokens,
+    Cost,
+}
+
+/// A quota that is already used up
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuotaExceeded {
+    pub scope: Scope,
+    pub period: Period,
+    kind: Kind,
+    used: u64,
+    limit: u64,
+}
+
+impl QuotaExceeded {
+    /// Friendly refusal shown to the user instead of running the turn
+    pub fn message(&self) -> String {
+        let whose = match self.scope {
+            Scope::User => "Your",
+            Scope::Session => "This session's",
+        };
+        let period = match self.period {
+            Period::Daily => "daily",
+            Period::Monthly => "monthly",
+        };
+        let (what, used, limit) = match self.kind {
+            Kind::Tokens => ("token", format!("{} tokens", self.used), format!("{}", self.limit)),
+            Kind::Cost => ("spend", format_cost(self.used), format_cost(self.limit)),
+        };
+        let resets = match self.period {
+            Period::Daily => "at midnight UTC",
+            Period::Monthly => "on the 1st of next month (UTC)",
+        };
+        let hint = match self.scope {
+            Scope::Session => " You can start a fresh session with /new.",
+            Scope::User => "",
+        };
+        format!(
+            "💸 {} {} {} quota is used up ({} of {}). It resets {}.{}",
+            whose, period, what, used, limit, resets, hint
+        )
+    }
+}
+
+/// Check one scope's usage against its limits. Returns what is left as a turn budget.
+pub fn evaluate(scope: Scope, limits: &QuotaLimits, totals: &SpendTotals) -> Result<TurnBudget, QuotaExceeded> {
+    let cents = |c: Option<u64>| c.map(|c| c * MICROS_PER_CENT);
+    let checks = [
+        (Period::Daily, Kind::Tokens, limits.daily_tokens, totals.day_tokens),
+        (Period::Monthly, Kind::Tokens, limits.monthly_tokens, totals.month_tokens),
+        (Period::Daily, Kind::Cost, cents(limits.daily_cost_cents), totals.day_cost_micros),
+        (Period::Monthly, Kind::Cost, cents(limits.monthly_cost_cents), totals.month_cost_micros),
+    ];
+
+    let mut budget = TurnBudget::default();
+    for (period, kind, limit, used) in checks {
+        let Some(limit) = limit else { continue };
+        let used = used.max(0) as u64;
+        if used >= limit {
+            return Err(QuotaExceeded { scope, period, kind, used, limit });
+        }
+        let remaining = Some(limit - used);
+        budget = budget.min(match kind {
+            Kind::Tokens => TurnBudget { max_tokens: remaining, ..Default::default() },
+            Kind::Cost => TurnBudget { max_cost_micros: remaining, ..Default::default() },
+        });
+    }
+    Ok(budget)
+}
+
+/// Check the user's and session's quotas before a turn.
+/// `Ok(None)` means unlimited. Database errors fail open (logged, turn allowed).
+pub async fn check(
+    config: Option<&QuotaConfig>,
+    user_key: &str,
+    session_key: &str,
+) -> Result<Option<TurnBudget>, QuotaExceeded> {
+    let (Some(config), Some(pool)) = (config, openclaw_db::pool()) else {
+        return Ok(None);
+    };
+
+    let mut budget: Option<TurnBudget> = None;
+    for (scope, key, limits) in [
+        (Scope::User, user_key, &config.per_user),
+        (Scope::Session, session_key, &config.per_session),
+    ] {
+        if *limits == QuotaLimits::default() {
+            continue;
+        }
+        let totals = match openclaw_db::spend::spend_totals(pool, scope.as_str(), key).await {
+            Ok(t) => t,
+            Err(e) => {
+                warn!("Quota lookup failed for {} {}: {}", scope.as_str(), key, e);
+                continue;
+            }
+        };
+        let remaining = evaluate(scope, limits, &totals)?;
+        budget = Some(budget.map_or(remaining, |b| b.min(remaining)));
+    }
+    Ok(budget)
+}
+
+/// Add a finished turn's usage to the user's and session's ledgers
+pub async fn record(user_key: &str, session_key: &str, tokens: u64, cost_micros: u64) {
+    let Some(pool) = openclaw_db::pool() else { return };
+    for (scope, key) in [(Scope::User, user_key), (Scope::Session, session_key)] {
+        if let Err(e) =
+            openclaw_db::spend::record_spend(pool, scope.as_str(), key, tokens as i64, cost_micros as i64).await
+        {
+            warn!("Failed to record spend for {} {}: {}", scope.as_str(), key, e);
+        }
+    }
+}
+
+/// Turn hook that records every LLM call against the user's and session's quotas,
+/// including calls of turns that later fail or are cancelled
+pub fn usage_hook(user_key: &str, session_key: &str) -> UsageHook {
+    let (user_key, session_key) = (user_key.to_string(), session_key.to_string());
+    std::sync::Arc::new(move |tokens, cost_micros| {
+        let (user_key, session_key) = (user_key.clone(), session_key.clone());
+        tokio::spawn(async move { record(&user_key, &session_key, tokens, cost_micros).await });
+    })
+}
+
+/// Usage vs. limits for the `/quota` command
+pub async fn status(config: Option<&QuotaConfig>, user_key: &str, session_key: &str) -> String {
+    let Some(pool) = openclaw_db::pool() else {
+        return "💸 Quotas need the database, which is not available.".to_string();
+    };
+    let no_limits = QuotaLimits::default();
+    let mut out = String::from("💸 Spend quotas (UTC)\n");
+    for (scope, label, key) in [(Scope::User, "You", user_key), (Scope::Session, "This session", session_key)] {
+        let limits = config
+            .map(|c| match scope {
+                Scope::User => &c.per_user,
+                Scope::Session => &c.per_session,
+            })
+            .unwrap_or(&no_limits);
+        let totals = openclaw_db::spend::spend_totals(pool, scope.as_str(), key)
+            .await
+            .unwrap_or_default();
+        out.push_str(&format!("\n{}\n{}", label, describe(limits, &totals)));
+    }
+    out
+}
+
+fn describe(limits: &QuotaLimits, totals: &SpendTotals) -> String {
+    let of = |limit: Option<String>| limit.map(|l| format!(" / {}", l)).unwrap_or_default();
+    let cost_limit = |cents: Option<u64>| cents.map(|c| format_cost(c * MICROS_PER_CENT));
+    format!(
+        "Today: {}{} tokens · {}{}\nThis month: {}{} tokens · {}{}\n",
+        totals.day_tokens,
+        of(limits.daily_tokens.map(|t| t.to_string())),
+        format_cost(totals.day_cost_micros.max(0) as u64),
+        of(cost_limit(limits.daily_cost_cents)),
+        totals.month_tokens,
+        of(limits.monthly_tokens.map(|t| t.to_string())),
+        format_cost(totals.month_cost_micros.max(0) as u64),
+        of(cost_limit(limits.monthly_cost_cents)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn totals(day_tokens: i64, day_cost: i64, month_tokens: i64, month_cost: i64) -> SpendTotals {
+        SpendTotals {
+            day_tokens,
+            day_cost_micros: day_cost,
+            month_tokens,
+            month_cost_micros: month_cost,
+        }
+    }
+
+    #[test]
+    fn test_evaluate_remaining_budget() {
+        let limits = QuotaLimits {
+            daily_tokens: Some(100_000),
+            monthly_tokens: Some(1_000_000),
+            daily_cost_cents: Some(50),
+            monthly_cost_cents: None,
+        };
+        let budget = evaluate(Scope::User, &limits, &totals(40_000, 100_000, 950_000, 2_000_000)).unwrap();
+        // Monthly tokens are the tighter limit; daily cost has $0.40 left
+        assert_eq!(budget.max_tokens, Some(50_000));
+        assert_eq!(budget.max_cost_micros, Some(400_000));
+    }
+
+    #[test]
+    fn test_evaluate_exceeded() {
+        let limits = QuotaLimits { daily_cost_cents: Some(100), ..Default::default() };
+        let err = evaluate(Scope::Session, &limits, &totals(0, 1_250_000, 0, 1_250_000)).unwrap_err();
+        assert_eq!(err.period, Period::Daily);
+        let msg = err.message();
+        assert!(msg.contains("This session's daily spend quota"));
+        assert!(msg.contains("$1.25 of $1.00"));
+        assert!(msg.contains("/new"));
+    }
+
+    #[test]
+    fn test_evaluate_unlimited() {
+        let budget = evaluate(Scope::User, &QuotaLimits::default(), &totals(9, 9, 9, 9)).unwrap();
+        assert_eq!(budget, TurnBudget::default());
+    }
+
+    #[test]
+    fn test_describe() {
+        let limits = QuotaLimits { daily_tokens: Some(500), ..Default::default() };
+        let text = describe(&limits, &totals(120, 4200, 300, 8400));
+        assert!(text.contains("Today: 120 / 500 tokens · $0.0042\n"));
+        assert!(text.contains("This month: 300 tokens · $0.0084\n"));
+    }
+}
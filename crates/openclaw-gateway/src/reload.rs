@@ -0,0 +1,129 @@
+//! Reloading plugins and MCP servers without restarting the gateway.
+//!
+//! `POST /config/reload` and `/plugins reload` re-read the config file,
+//! revalidate the plugin manifests of every agent profile and reconnect the
+//! MCP servers whose config changed. Other config changes still need a restart.
+
+use anyhow::Result;
+use serde::Serialize;
+use std::path::PathBuf;
+
+use openclaw_agent::tools::mcp_bridge::{self, McpReloadReport};
+use openclaw_agent::tools::plugins::{self, PluginReport};
+use openclaw_agent::tools::script_plugin;
+
+use crate::config::GatewayConfig;
+
+#[derive(Debug, Serialize)]
+pub struct ReloadReport {
+    pub plugins: Vec<PluginReport>,
+    pub mcp: McpReloadReport,
+    /// Tools the default agent now offers
+    pub tool_count: usize,
+}
+
+/// Reload from the config file at `config_path`, loaded the same way as at
+/// startup (env overrides, env-only config without a file). A config that
+/// fails to load is an error and leaves everything as it was.
+pub async fn reload(config_path: &str) -> Result<ReloadReport> {
+    let config = GatewayConfig::from_file_or_env(config_path)
+        .map_err(|e| anyhow::anyhow!("cannot load config {}: {}", config_path, e))?;
+
+    let mcp = mcp_bridge::reload_mcp_pool(&config.mcp_servers).await;
+    crate::handler::init_mcp_configs(config.mcp_servers.clone());
+
+    let dirs = workspace_dirs(&config);
+    let plugins = tokio::task::spawn_blocking(move || {
+        dirs.iter()
+            .map(|dir| plugins::reload(&script_plugin::plugins_dir(dir)).report.clone())
+            .collect()
+    })
+    .await?;
+
+    let tools = crate::handler::build_tool_registry(&config.agent, "").await;
+    let tool_count = tools.tool_names().len();
+    crate::handler::set_tool_count(tool_count);
+    Ok(ReloadReport { plugins, mcp, tool_count })
+}
+
+/// Workspaces of the default agent and every profile, without duplicates
+fn workspace_dirs(config: &GatewayConfig) -> Vec<PathBuf> {
+    let mut names: Vec<&String> = config.agents.keys().collect();
+    names.sort();
+    let mut dirs = vec![config.agent.workspace_dir()];
+    for name in names {
+        if let Some(dir) = config.profile(name).map(|p| p.workspace_dir()) {
+            if !dirs.contains(&dir) {
+                dirs.push(dir);
+            }
+        }
+    }
+    dirs
+}
+
+/// Rejected plugins of a report as chat lines, empty when there are none
+pub fn rejected_lines(report: &PluginReport) -> Vec<String> {
+    report
+        .rejected
+        .iter()
+        .map(|r| format!("❌ `{}`: {}", r.file, r.problems.join("; ")))
+        .collect()
+}
+
+/// Chat-friendly summary of a reload
+pub fn report_text(report: &ReloadReport) -> String {
+    let mut lines = vec![format!("🔄 *Reloaded* ({} tools)", report.tool_count)];
+    for plugins in &report.plugins {
+        lines.push(format!("\n*Plugins* `{}`: {}", plugins.dir, plugins.summary()));
+        if !plugins.loaded.is_empty() {
+            lines.push(format!("✅ {}", plugins.loaded.join(", ")));
+        }
+        lines.extend(rejected_lines(plugins));
+    }
+    lines.push(format!("\n*MCP servers*: {}", report.mcp.summary()));
+    for (name, error) in &report.mcp.failed {
+        lines.push(format!("❌ `{}`: {}", name, error));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_reload_rejects_bad_config_file() {
+        let path = std::env::temp_dir().join(format!("openclaw-reload-{}.json", uuid::Uuid::new_v4()));
+        if std::env::var("TELEGRAM_BOT_TOKEN").is_err() {
+            // No file: falls back to the env config, which needs a bot token
+            let err = reload(path.to_str().unwrap()).await.unwrap_err();
+            assert!(err.to_string().contains("TELEGRAM_BOT_TOKEN not set"), "{}", err);
+        }
+
+        std::fs::write(&path, "{\"telegram\": ").unwrap();
+        let err = reload(path.to_str().unwrap()).await.unwrap_err();
+        assert!(err.to_string().contains("cannot load config"), "{}", err);
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_report_text() {
+        let report = ReloadReport {
+            plugins: vec![PluginReport {
+                dir: "/ws/.openclaw/plugins".to_string(),
+                loaded: vec!["greet".to_string()],
+                rejected: vec![plugins::RejectedPlugin {
+                    file: "exec.json".to_string(),
+                    problems: vec!["name 'exec' collides with a built-in tool".to_string()],
+                }],
+            }],
+            mcp: McpReloadReport::default(),
+            tool_count: 20,
+        };
+        let text = report_text(&report);
+        assert!(text.contains("1 plugin(s) loaded, 1 rejected"));
+        assert!(text.contains("✅ greet"));
+        assert!(text.contains("❌ `exec.json`: name 'exec' collides"));
+        assert!(text.contains("0 unchanged, 0 connected, 0 removed, 0 failed"));
+    }
+}
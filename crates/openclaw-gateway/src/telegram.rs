@@ -22,6 +22,17 @@ pub struct TgResponse<T> {
 pub struct TgUpdate {
     pub update_id: i64,
     pub message: Option<TgMessage>,
+    #[serde(default)]
+    pub callback_query: Option<TgCallbackQuery>,
+}
+
+/// A press on an inline keyboard button
+#[derive(Debug, Deserialize)]
+pub struct TgCallbackQuery {
+    pub id: String,
+    pub from: TgUser,
+    pub message: Option<TgMessage>,
+    pub data: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -134,7 +145,7 @@ impl TelegramBot {
     /// Long-poll for updates
     pub async fn get_updates(&self, offset: i64, timeout: u64) -> Result<Vec<TgUpdate>> {
         let url = format!(
-            "{}/getUpdates?offset={}&timeout={}&allowed_updates=[\"message\"]",
+            "{}/getUpdates?offset={}&timeout={}&allowed_updates=[\"message\",\"callback_query\"]",
             self.api_base, offset, timeout
         );
 
@@ -324,6 +335,55 @@ impl TelegramBot {
         Ok(())
     }
 
+    /// Send a message with one row of inline keyboard buttons (`(label, callback_data)`),
+    /// returning the message_id
+    pub async fn send_message_with_buttons(&self, chat_id: i64, text: &str, buttons: &[(&str, String)]) -> Result<i64> {
+        let keyboard: Vec<serde_json::Value> = buttons
+            .iter()
+            .map(|(label, data)| serde_json::json!({ "text": label, "callback_data": data }))
+            .collect();
+        let body = serde_json::json!({
+            "chat_id": chat_id,
+            "text": text,
+            "reply_markup": { "inline_keyboard": [keyboard] },
+        });
+
+        let resp_body: TgResponse<TgSentMessage> = self
+            .client
+            .post(format!("{}/sendMessage", self.api_base))
+            .json(&body)
+            .send()
+            .await
+            .context("Failed to send message")?
+            .json()
+            .await
+            .context("Failed to parse sendMessage response")?;
+
+        match resp_body.result {
+            Some(msg) => Ok(msg.message_id),
+            None => anyhow::bail!(
+                "sendMessage failed: {}",
+                resp_body.description.unwrap_or_default()
+            ),
+        }
+    }
+
+    /// Acknowledge a button press, optionally with a short toast
+    pub async fn answer_callback_query(&self, callback_query_id: &str, text: Option<&str>) -> Result<()> {
+        let mut body = serde_json::json!({ "callback_query_id": callback_query_id });
+        if let Some(text) = text {
+            body["text"] = serde_json::Value::String(text.to_string());
+        }
+
+        self.client
+            .post(format!("{}/answerCallbackQuery", self.api_base))
+            .json(&body)
+            .send()
+            .await?;
+
+        Ok(())
+    }
+
     /// Delete a message by ID
     pub async fn delete_message(&self, chat_id: i64, message_id: i64) -> Result<()> {
         let body = serde_json::json!({
@@ -0,0 +1,9 @@
+-- ============================================================
+-- PROMPT CACHE ACCOUNTING
+-- Migration: 003_llm_cache_usage.sql
+-- ============================================================
+
+-- Prompt tokens served from / written to the provider's prompt cache.
+-- usage_prompt_tokens already includes both.
+ALTER TABLE llm_calls ADD COLUMN IF NOT EXISTS usage_cache_read_tokens  INT NOT NULL DEFAULT 0;
+ALTER TABLE llm_calls ADD COLUMN IF NOT EXISTS usage_cache_write_tokens INT NOT NULL DEFAULT 0;
@@ -0,0 +1,11 @@
+-- ============================================================
+-- LLM COST ACCOUNTING
+-- Migration: 004_llm_cost.sql
+-- ============================================================
+
+-- Cost per call in micro-dollars (USD × 1e6), computed from the pricing
+-- table in openclaw-manual.json. Integer to keep money out of floats.
+ALTER TABLE llm_calls ADD COLUMN IF NOT EXISTS cost_micros BIGINT NOT NULL DEFAULT 0;
+
+-- Running cost per session, aggregated by the gateway after each turn.
+ALTER TABLE sessions ADD COLUMN IF NOT EXISTS total_cost_micros BIGINT NOT NULL DEFAULT 0;
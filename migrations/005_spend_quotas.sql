@@ -0,0 +1,16 @@
+-- ============================================================
+-- SPEND QUOTAS
+-- Migration: 005_spend_quotas.sql
+-- ============================================================
+
+-- Daily token / cost usage per quota scope. The gateway adds each turn's
+-- usage and sums today's row and the current month's rows to enforce
+-- per-user and per-session quotas. Days are UTC.
+CREATE TABLE IF NOT EXISTS spend_ledger (
+    scope        TEXT   NOT NULL,           -- 'user' | 'session'
+    scope_key    TEXT   NOT NULL,           -- e.g. 'tg:12345' or a session key
+    day          DATE   NOT NULL,
+    tokens       BIGINT NOT NULL DEFAULT 0,
+    cost_micros  BIGINT NOT NULL DEFAULT 0, -- USD × 1e6
+    PRIMARY KEY (scope, scope_key, day)
+);
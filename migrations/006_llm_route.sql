@@ -0,0 +1,8 @@
+-- ============================================================
+-- SEMANTIC MODEL ROUTING
+-- Migration: 006_llm_route.sql
+-- ============================================================
+
+-- Routing decision that picked the model for a call, e.g.
+-- "cheap (simple: short message)". NULL when routing is off.
+ALTER TABLE llm_calls ADD COLUMN IF NOT EXISTS route TEXT;
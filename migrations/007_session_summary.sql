@@ -0,0 +1,10 @@
+-- ============================================================
+-- CONTEXT COMPACTION
+-- Migration: 007_session_summary.sql
+-- ============================================================
+
+-- Rolling summary of the oldest messages once a session outgrows the
+-- history budget. summarized_messages counts how many messages (oldest
+-- first, by id) the summary covers; only later messages are sent verbatim.
+ALTER TABLE sessions ADD COLUMN IF NOT EXISTS summary TEXT;
+ALTER TABLE sessions ADD COLUMN IF NOT EXISTS summarized_messages INT NOT NULL DEFAULT 0;
@@ -0,0 +1,27 @@
+-- ============================================================
+-- TURN CHECKPOINTS
+-- Migration: 008_turn_checkpoints.sql
+-- ============================================================
+
+-- In-flight agent turns, saved after every round so a turn cut short by a
+-- gateway restart or timeout can be resumed. messages holds the turn's
+-- assistant tool calls and tool results so far (JSON array of chat
+-- messages, excluding the user message); image_urls are the images attached
+-- to the user message (data or http URLs), so a resumed turn sees the same
+-- input. The row is deleted once the turn produces its final answer. A new
+-- turn in the session supersedes it: the agent first moves the interrupted
+-- turn into the session history, then deletes the row.
+CREATE TABLE IF NOT EXISTS turn_checkpoints (
+    turn_id       TEXT PRIMARY KEY,
+    session_key   TEXT NOT NULL,
+    agent_name    TEXT NOT NULL,
+    user_message  TEXT NOT NULL,
+    image_urls    TEXT[] NOT NULL DEFAULT '{}',
+    messages      JSONB NOT NULL DEFAULT '[]',
+    rounds        INT  NOT NULL DEFAULT 0,
+    created_at    TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at    TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_turn_checkpoints_session
+    ON turn_checkpoints (session_key, updated_at DESC);
@@ -0,0 +1,26 @@
+-- ============================================================
+-- SEMANTIC MEMORY
+-- Migration: 009_memories.sql
+-- ============================================================
+
+-- Notes and conversation snippets an agent can recall later. Ranking (BM25
+-- over text, cosine over embedding) happens in the agent, so the table only
+-- stores records; embedding is NULL when no embeddings endpoint is configured.
+CREATE TABLE IF NOT EXISTS memories (
+    id            TEXT PRIMARY KEY,
+    agent_name    TEXT NOT NULL,
+    text          TEXT NOT NULL,
+    source        TEXT NOT NULL DEFAULT 'note',
+    tags          TEXT[] NOT NULL DEFAULT '{}',
+    session_key   TEXT,
+    embedding     REAL[],
+    created_at    TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_memories_agent
+    ON memories (agent_name, created_at DESC);
+
+-- Recall, forget and pruning look up an agent's memories by session, so each
+-- user only sees the notes and conversations of their own session.
+CREATE INDEX IF NOT EXISTS idx_memories_session
+    ON memories (agent_name, session_key, created_at DESC);